@@ -0,0 +1,18 @@
+// Edit diffs are only fetched once their revision list is expanded
+document.addEventListener("toggle", (event) => {
+    let details = event.target;
+    if (!details.open) {
+        return;
+    }
+
+    details.querySelectorAll("pre[data-diff-url]:empty").forEach((diff) => {
+        fetch(diff.dataset.diffUrl, {
+            method: 'GET',
+        }).then(response => {
+            response.text()
+                .then(body => diff.innerHTML = body)
+                .catch(reason => console.log(reason))
+        })
+            .catch(reason => console.log(reason));
+    });
+}, true);
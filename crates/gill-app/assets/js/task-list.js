@@ -0,0 +1,26 @@
+/* Let users allowed to edit a markdown source toggle its task list checkboxes,
+   the source textarea is updated and its edit form submitted */
+const enableTaskList = (rendered, source) => {
+    if (!rendered || !source) {
+        return;
+    }
+
+    rendered.querySelectorAll("input[data-task-offset]").forEach(checkbox => {
+        checkbox.disabled = false;
+        checkbox.addEventListener("change", () => {
+            let offset = parseInt(checkbox.dataset.taskOffset);
+            let content = source.value;
+            let state = content.charAt(offset);
+            if (state.length !== 1 || !"xX ".includes(state)) {
+                return;
+            }
+
+            source.value = content.substring(0, offset) + (checkbox.checked ? "x" : " ") + content.substring(offset + 1);
+            source.form.submit();
+        });
+    });
+}
+
+document.querySelectorAll("[data-task-source]").forEach(rendered => {
+    enableTaskList(rendered, document.getElementById(rendered.dataset.taskSource));
+});
@@ -0,0 +1,119 @@
+use activitystreams_kinds::collection::{OrderedCollectionPageType, OrderedCollectionType};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+/// Number of items served in a single collection page
+pub const COLLECTION_PAGE_SIZE: i64 = 20;
+
+#[derive(Deserialize, Debug)]
+pub struct CollectionQuery {
+    pub page: Option<i64>,
+}
+
+impl CollectionQuery {
+    /// Pages start at 1, anything below is treated as the first page
+    pub fn offset(page: i64) -> i64 {
+        (page.max(1) - 1) * COLLECTION_PAGE_SIZE
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderedCollection {
+    #[serde(rename = "type")]
+    pub kind: OrderedCollectionType,
+    pub id: Url,
+    pub total_items: i64,
+    pub first: Url,
+    pub last: Url,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderedCollectionPage<T> {
+    #[serde(rename = "type")]
+    pub kind: OrderedCollectionPageType,
+    pub id: Url,
+    pub part_of: Url,
+    pub total_items: i64,
+    pub ordered_items: Vec<T>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next: Option<Url>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prev: Option<Url>,
+}
+
+impl OrderedCollection {
+    pub fn new(id: Url, total_items: i64) -> Self {
+        let last_page = ((total_items + COLLECTION_PAGE_SIZE - 1) / COLLECTION_PAGE_SIZE).max(1);
+        Self {
+            kind: Default::default(),
+            first: page_url(&id, 1),
+            last: page_url(&id, last_page),
+            id,
+            total_items,
+        }
+    }
+}
+
+impl<T> OrderedCollectionPage<T> {
+    pub fn new(collection_id: Url, page: i64, total_items: i64, ordered_items: Vec<T>) -> Self {
+        let page = page.max(1);
+        let has_next = page * COLLECTION_PAGE_SIZE < total_items;
+        Self {
+            kind: Default::default(),
+            id: page_url(&collection_id, page),
+            next: has_next.then(|| page_url(&collection_id, page + 1)),
+            prev: (page > 1).then(|| page_url(&collection_id, page - 1)),
+            part_of: collection_id,
+            total_items,
+            ordered_items,
+        }
+    }
+}
+
+fn page_url(collection_id: &Url, page: i64) -> Url {
+    let mut url = collection_id.clone();
+    url.query_pairs_mut()
+        .clear()
+        .append_pair("page", &page.to_string());
+    url
+}
+
+#[cfg(test)]
+mod test {
+    use crate::apub::collection::{OrderedCollection, OrderedCollectionPage};
+    use speculoos::prelude::*;
+    use url::Url;
+
+    #[test]
+    fn should_link_collection_pages() -> anyhow::Result<()> {
+        let id = Url::parse("https://gill.example/users/okno/outbox")?;
+
+        let collection = OrderedCollection::new(id.clone(), 45);
+        let page = OrderedCollectionPage::new(id, 2, 45, vec![1, 2, 3]);
+
+        assert_that!(collection.first.as_str())
+            .is_equal_to("https://gill.example/users/okno/outbox?page=1");
+        assert_that!(collection.last.as_str())
+            .is_equal_to("https://gill.example/users/okno/outbox?page=3");
+        assert_that!(page.prev.map(String::from)).is_equal_to(Some(
+            "https://gill.example/users/okno/outbox?page=1".to_string(),
+        ));
+        assert_that!(page.next.map(String::from)).is_equal_to(Some(
+            "https://gill.example/users/okno/outbox?page=3".to_string(),
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn should_not_link_past_last_page() -> anyhow::Result<()> {
+        let id = Url::parse("https://gill.example/users/okno/followers")?;
+
+        let page = OrderedCollectionPage::new(id, 1, 3, vec![1, 2, 3]);
+
+        assert_that!(page.next).is_none();
+        assert_that!(page.prev).is_none();
+        Ok(())
+    }
+}
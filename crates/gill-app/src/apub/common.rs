@@ -1,14 +1,21 @@
+use crate::apub::signature::signed_post;
+use crate::domain::activity::Activity as OutboxActivity;
 use crate::domain::apub::inbox_for_url;
+use crate::domain::delivery::Delivery;
+use crate::domain::user::User;
 use crate::error::{AppError, AppResult};
 use crate::instance::InstanceHandle;
-use activitypub_federation::core::activity_queue::send_activity;
 use activitypub_federation::core::signatures::PublicKey;
 use activitypub_federation::deser::context::WithContext;
 use activitypub_federation::traits::ActivityHandler;
-use activitypub_federation::LocalInstance;
+use activitystreams_kinds::kind;
+use activitystreams_kinds::object::TombstoneType;
+use anyhow::anyhow;
 use async_session::async_trait;
+use gill_markdown::Reference;
 use gill_settings::SETTINGS;
-use serde::{Deserialize, Serialize};
+use reqwest::Client;
+use serde::{Deserialize, Deserializer, Serialize};
 use sqlx::PgPool;
 use tracing::info;
 use url::{ParseError, Url};
@@ -21,45 +28,79 @@ pub trait GillApubObject {
 
     fn local_id(&self) -> i32;
 
+    fn actor_id(&self) -> Url;
+
     fn public_key_with_owner(&self) -> Result<PublicKey, ParseError>;
 
     fn private_key(&self) -> Option<String>;
 
+    /// Publish an activity: it is recorded in the actor outbox, then queued for delivery to `recipients`
     async fn send<Activity>(
         &self,
         activity: Activity,
         recipients: Vec<Url>,
-        local_instance: &LocalInstance,
+        instance: &InstanceHandle,
     ) -> Result<(), <Activity as ActivityHandler>::Error>
     where
         Activity: ActivityHandler + Serialize + Send + Sync,
         <Activity as ActivityHandler>::Error:
             From<anyhow::Error> + From<serde_json::Error> + From<AppError> + From<ParseError>,
     {
-        let activity = WithContext::new_default(activity);
+        OutboxActivity::save(&activity, instance.database()).await?;
+        self.deliver(activity, recipients, instance.database())
+            .await
+    }
 
+    /// Queue an activity for delivery to `recipients` without recording it in the actor outbox.
+    /// The delivery worker signs it with this object keys and posts it to each inbox.
+    async fn deliver<Activity>(
+        &self,
+        activity: Activity,
+        recipients: Vec<Url>,
+        db: &PgPool,
+    ) -> Result<(), <Activity as ActivityHandler>::Error>
+    where
+        Activity: ActivityHandler + Serialize + Send + Sync,
+        <Activity as ActivityHandler>::Error:
+            From<anyhow::Error> + From<serde_json::Error> + From<AppError> + From<ParseError>,
+    {
         info!(
-            "Sending activity {} to {:?}",
+            "Queueing activity {} for {:?}",
             activity.id(),
             recipients.iter().map(|r| r.to_string())
         );
 
-        send_activity(
-            activity,
-            self.public_key_with_owner()?,
-            self.private_key().expect("has private key"),
-            recipients,
-            local_instance,
-        )
-        .await?;
+        let payload = serde_json::to_value(&activity)?;
+        Delivery::enqueue(&payload, &self.actor_id(), &recipients, db).await?;
+        Ok(())
+    }
 
+    /// Sign and post an activity to `inbox` right away, this is used by the delivery worker
+    async fn post<Activity>(
+        &self,
+        activity: Activity,
+        inbox: &Url,
+        client: &Client,
+    ) -> Result<(), <Activity as ActivityHandler>::Error>
+    where
+        Activity: ActivityHandler + Serialize + Send + Sync,
+        <Activity as ActivityHandler>::Error:
+            From<anyhow::Error> + From<serde_json::Error> + From<AppError> + From<ParseError>,
+    {
+        let activity = serde_json::to_string(&WithContext::new_default(activity))?;
+        let private_key = self
+            .private_key()
+            .ok_or_else(|| anyhow!("{} has no private key", self.actor_id()))?;
+        // Same key id as the main key published with `public_key_with_owner`
+        let key_id = format!("{}#main-key", self.actor_id());
+
+        signed_post(client, inbox, activity, &key_id, &private_key).await?;
         Ok(())
     }
 
     async fn forward_activity<Activity>(
         &self,
         activity: Activity,
-        local_instance: &LocalInstance,
         db: &PgPool,
     ) -> Result<(), <Activity as ActivityHandler>::Error>
     where
@@ -85,7 +126,7 @@ pub trait GillApubObject {
                 .collect();
 
             info!("forwarding activity to local inboxes");
-            self.send(activity, recipients, local_instance).await?
+            self.deliver(activity, recipients, db).await?
         }
 
         Ok(())
@@ -105,9 +146,76 @@ pub fn is_local(url: &Url) -> bool {
     }
 }
 
+/// Other forges send `published` with a timezone offset while gill stores naive UTC datetimes
+pub fn deserialize_datetime<'de, D>(deserializer: D) -> Result<chrono::NaiveDateTime, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = String::deserialize(deserializer)?;
+    chrono::DateTime::parse_from_rfc3339(&value)
+        .map(|datetime| datetime.naive_utc())
+        .or_else(|_| value.parse::<chrono::NaiveDateTime>())
+        .map_err(serde::de::Error::custom)
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Source {
     pub content: String,
     pub media_type: String,
 }
+
+kind!(MentionType, Mention);
+
+/// A user mentioned in an object content
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Mention {
+    #[serde(rename = "type")]
+    pub kind: MentionType,
+    pub href: Url,
+    pub name: String,
+}
+
+/// Users mentioned in `content`, mentions of users unknown to this instance are left out
+pub async fn mentioned_users(content: &str, db: &PgPool) -> AppResult<Vec<User>> {
+    let mut users: Vec<User> = vec![];
+    for reference in gill_markdown::references(content) {
+        if let Reference::Mention { username, domain } = reference {
+            if let Some(user) = User::by_mention(&username, domain.as_deref(), db).await? {
+                if !users.contains(&user) {
+                    users.push(user);
+                }
+            }
+        }
+    }
+
+    Ok(users)
+}
+
+/// `Mention` tags for the users mentioned in `content`
+pub async fn mention_tags(content: &str, db: &PgPool) -> AppResult<Vec<Mention>> {
+    let tags = mentioned_users(content, db)
+        .await?
+        .into_iter()
+        .map(|user| Mention {
+            kind: Default::default(),
+            name: format!("@{}@{}", user.username, user.domain),
+            href: user.activity_pub_id.into(),
+        })
+        .collect();
+
+    Ok(tags)
+}
+
+/// Placeholder for a deleted object
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Tombstone {
+    #[serde(rename = "type")]
+    pub kind: TombstoneType,
+    pub id: Url,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub former_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deleted: Option<chrono::NaiveDateTime>,
+}
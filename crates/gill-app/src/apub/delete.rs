@@ -0,0 +1,98 @@
+use crate::error::AppError;
+use crate::instance::InstanceHandle;
+
+use activitypub_federation::deser::helpers::deserialize_one_or_many;
+
+use crate::apub::common::{is_local, GillActivity, Tombstone};
+
+use crate::domain::issue::comment::IssueComment;
+use crate::domain::issue::Issue;
+use crate::domain::repository::Repository;
+use crate::domain::user::User;
+
+use activitypub_federation::{core::object_id::ObjectId, data::Data, traits::ActivityHandler};
+use activitystreams_kinds::activity::DeleteType;
+use axum::async_trait;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use url::Url;
+
+/// Sent when an object is deleted by its author or the repository owner, the deleted object
+/// is replaced by a `Tombstone` whose `formerType` tells what kind of object it was.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Delete {
+    pub(crate) id: Url,
+    #[serde(rename = "type")]
+    pub(crate) kind: DeleteType,
+    pub(crate) actor: ObjectId<User>,
+    #[serde(deserialize_with = "deserialize_one_or_many")]
+    pub(crate) to: Vec<Url>,
+    pub(crate) object: Tombstone,
+}
+
+impl GillActivity for Delete {
+    fn forward_addresses(&self) -> Vec<&Url> {
+        self.to.iter().filter(|url| is_local(url)).collect()
+    }
+}
+
+#[async_trait]
+impl ActivityHandler for Delete {
+    type DataType = InstanceHandle;
+    type Error = AppError;
+
+    fn id(&self) -> &Url {
+        &self.id
+    }
+
+    fn actor(&self) -> &Url {
+        self.actor.inner()
+    }
+
+    async fn receive(
+        self,
+        data: &Data<InstanceHandle>,
+        _request_counter: &mut i32,
+    ) -> Result<(), Self::Error> {
+        let db = data.database();
+        match self.object.former_type.as_deref() {
+            Some("Ticket") => self.delete_ticket(db).await,
+            // Comments are the only other objects deleted remotely, not every
+            // implementation sets a `formerType`
+            _ => self.delete_note(db).await,
+        }
+    }
+}
+
+impl Delete {
+    async fn delete_ticket(&self, db: &PgPool) -> Result<(), AppError> {
+        let id = self.object.id.as_str();
+        let Some(issue) = Issue::by_activity_pub_id_optional(id, db).await? else {
+            // Never received
+            return Ok(());
+        };
+
+        let repository = Repository::by_id(issue.repository_id, db).await?;
+        if !issue.is_managed_by(self.actor.inner(), &repository) {
+            return Err(AppError::Unauthorized);
+        }
+
+        issue.mark_deleted(db).await
+    }
+
+    async fn delete_note(&self, db: &PgPool) -> Result<(), AppError> {
+        let id = self.object.id.as_str();
+        let Some(comment) = IssueComment::by_activity_pub_id_optional(id, db).await? else {
+            // Never received
+            return Ok(());
+        };
+
+        let repository = Repository::by_id(comment.repository_id, db).await?;
+        if !comment.is_managed_by(self.actor.inner(), &repository) {
+            return Err(AppError::Unauthorized);
+        }
+
+        comment.mark_deleted(db).await
+    }
+}
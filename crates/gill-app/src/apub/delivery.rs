@@ -0,0 +1,142 @@
+use crate::apub::common::GillApubObject;
+use crate::domain::delivery::Delivery;
+use crate::domain::federation::FederationPolicy;
+use crate::domain::repository::Repository;
+use crate::domain::user::User;
+use crate::error::{AppError, AppResult};
+use crate::instance::InstanceHandle;
+use activitypub_federation::data::Data;
+use activitypub_federation::traits::ActivityHandler;
+use anyhow::anyhow;
+use axum::async_trait;
+use reqwest::Client;
+use serde::Serialize;
+use sqlx::PgPool;
+use std::time::Duration;
+use tracing::{debug, error};
+use url::Url;
+
+const DELIVERY_BATCH_SIZE: i64 = 20;
+const DELIVERY_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// An activity read back from the delivery queue, serialized as it was queued
+#[derive(Serialize, Debug)]
+struct QueuedActivity {
+    #[serde(skip)]
+    id: Url,
+    #[serde(skip)]
+    actor: Url,
+    #[serde(flatten)]
+    payload: serde_json::Map<String, serde_json::Value>,
+}
+
+impl TryFrom<&Delivery> for QueuedActivity {
+    type Error = anyhow::Error;
+
+    fn try_from(delivery: &Delivery) -> Result<Self, Self::Error> {
+        let payload = delivery
+            .payload
+            .as_object()
+            .cloned()
+            .ok_or_else(|| anyhow!("activity payload is not an object"))?;
+
+        let id = payload
+            .get("id")
+            .and_then(serde_json::Value::as_str)
+            .ok_or_else(|| anyhow!("activity has no 'id' field"))?;
+
+        Ok(QueuedActivity {
+            id: Url::parse(id)?,
+            actor: delivery.sender.clone(),
+            payload,
+        })
+    }
+}
+
+#[async_trait]
+impl ActivityHandler for QueuedActivity {
+    type DataType = InstanceHandle;
+    type Error = AppError;
+
+    fn id(&self) -> &Url {
+        &self.id
+    }
+
+    fn actor(&self) -> &Url {
+        &self.actor
+    }
+
+    async fn receive(
+        self,
+        _data: &Data<Self::DataType>,
+        _request_counter: &mut i32,
+    ) -> Result<(), Self::Error> {
+        Err(anyhow!("queued activities are outgoing only").into())
+    }
+}
+
+/// Post queued activities to remote inboxes until the instance shuts down
+pub async fn run_delivery_worker(instance: InstanceHandle) {
+    let client = Client::new();
+    loop {
+        match deliver_due(&instance, &client).await {
+            Ok(0) => tokio::time::sleep(DELIVERY_POLL_INTERVAL).await,
+            Ok(count) => debug!("Processed {count} queued deliveries"),
+            Err(err) => {
+                error!("Failed to process delivery queue: {err:?}");
+                tokio::time::sleep(DELIVERY_POLL_INTERVAL).await
+            }
+        }
+    }
+}
+
+async fn deliver_due(instance: &InstanceHandle, client: &Client) -> AppResult<usize> {
+    let db = instance.database();
+    let deliveries = Delivery::claim_due(DELIVERY_BATCH_SIZE, db).await?;
+
+    for delivery in &deliveries {
+        if let Some(open_until) = delivery.circuit_open_until(db).await? {
+            debug!(
+                "Circuit open for {}, postponing delivery {}",
+                delivery.domain, delivery.id
+            );
+            delivery.postpone(open_until, db).await?;
+            continue;
+        }
+
+        match post(delivery, instance.database(), client).await {
+            Ok(()) => delivery.delivered(db).await?,
+            Err(err) => {
+                error!(
+                    "Failed to deliver {} to {}: {err:?}",
+                    delivery.activity_id, delivery.inbox
+                );
+                delivery.failed(&format!("{err:?}"), db).await?
+            }
+        }
+    }
+
+    Ok(deliveries.len())
+}
+
+async fn post(delivery: &Delivery, db: &PgPool, client: &Client) -> AppResult<()> {
+    let activity = QueuedActivity::try_from(delivery)?;
+    let inbox = &delivery.inbox;
+    let sender = delivery.sender.as_str();
+
+    if !FederationPolicy::is_allowed(inbox, db).await? {
+        debug!(
+            "{} is not allowed to federate, dropping delivery {}",
+            delivery.domain, delivery.id
+        );
+        return Ok(());
+    }
+
+    if let Some(user) = User::by_activity_pub_id_optional(sender, db).await? {
+        user.post(activity, inbox, client).await
+    } else if let Some(repository) = Repository::by_activity_pub_id_optional(sender, db).await? {
+        repository.post(activity, inbox, client).await
+    } else {
+        Err(anyhow!("unknown delivery sender {sender}").into())
+    }
+}
@@ -6,14 +6,15 @@ use activitypub_federation::core::axum::{verify_request_payload, DigestVerified}
 
 use activitypub_federation::data::Data;
 use activitypub_federation::deser::context::WithContext;
-use activitypub_federation::traits::ApubObject;
+use activitypub_federation::traits::{ActivityHandler, ApubObject};
 
-use axum::extract::{OriginalUri, Path, State};
-use axum::response::IntoResponse;
+use axum::extract::{OriginalUri, Path, Query, State};
+use axum::response::{IntoResponse, Response};
 use axum::routing::{get, post};
 use axum::{body, middleware, Extension, Json, Router};
 use http::{HeaderMap, Method};
 
+use crate::apub::release::ApubRelease;
 use crate::apub::ticket::ApubTicket;
 
 use repository::{ApubRepository, RepositoryAcceptedActivities};
@@ -24,16 +25,31 @@ use tower_http::ServiceBuilderExt;
 
 use uuid::Uuid;
 
+use crate::apub::collection::{
+    CollectionQuery, OrderedCollection, OrderedCollectionPage, COLLECTION_PAGE_SIZE,
+};
 use crate::apub::common::GillApubObject;
 use crate::apub::ticket::comment::ApubIssueComment;
+use crate::domain::activity::Activity;
+use crate::domain::federation::FederationPolicy;
 use crate::domain::issue::comment::IssueComment;
 use crate::domain::issue::Issue;
+use crate::domain::release::Release;
 use crate::domain::repository::Repository;
 use crate::domain::user::User;
+use crate::error::AppError;
+use serde::Serialize;
+use sqlx::PgPool;
+use url::Url;
 use user::{ApubUser, PersonAcceptedActivities};
 
+pub mod collection;
 pub mod common;
+pub mod delete;
+pub mod delivery;
+pub mod release;
 pub mod repository;
+pub mod signature;
 pub mod ticket;
 pub mod user;
 
@@ -45,9 +61,36 @@ pub fn router(instance: Arc<Instance>) -> Router {
             "/users/:user/repositories/:repository/issues/:number",
             get(issue),
         )
+        .route(
+            "/users/:user/repositories/:repository/issues/:number/history",
+            get(issue_history),
+        )
+        .route(
+            "/users/:user/repositories/:repository/issues/:number/dependencies",
+            get(issue_dependencies),
+        )
+        .route(
+            "/users/:user/repositories/:repository/issues/:number/dependants",
+            get(issue_dependants),
+        )
         .route(
             "/users/:user/repositories/:repository/issues/:number/comments/:uuid",
             get(comment),
+        )
+        .route(
+            "/users/:user/repositories/:repository/releases/:tag",
+            get(release),
+        )
+        .route("/users/:user/outbox", get(user_outbox))
+        .route("/users/:user/followers", get(user_followers))
+        .route("/users/:user/following", get(user_following))
+        .route(
+            "/users/:user/repositories/:repository/outbox",
+            get(repository_outbox),
+        )
+        .route(
+            "/users/:user/repositories/:repository/followers",
+            get(repository_followers),
         );
 
     let private = Router::new()
@@ -100,6 +143,46 @@ async fn issue(
     Ok(ApubJson(ticket))
 }
 
+async fn issue_history(
+    State(data): State<InstanceHandle>,
+    Path((user, repository, issue_number)): Path<(String, String, i32)>,
+    Query(query): Query<CollectionQuery>,
+) -> AppResult<Response> {
+    let db = data.database();
+    let repository = local_repository(&user, &repository, db).await?;
+    let issue = repository.issue_by_number(issue_number, db).await?;
+    let history = issue.history_events(db).await?;
+    Ok(in_memory_collection(issue.history, query, history))
+}
+
+async fn issue_dependencies(
+    State(data): State<InstanceHandle>,
+    Path((user, repository, issue_number)): Path<(String, String, i32)>,
+    Query(query): Query<CollectionQuery>,
+) -> AppResult<Response> {
+    let db = data.database();
+    let repository = local_repository(&user, &repository, db).await?;
+    let issue = repository.issue_by_number(issue_number, db).await?;
+    let dependencies = issue.dependency_ids(db).await?;
+    Ok(in_memory_collection(
+        issue.dependencies,
+        query,
+        dependencies,
+    ))
+}
+
+async fn issue_dependants(
+    State(data): State<InstanceHandle>,
+    Path((user, repository, issue_number)): Path<(String, String, i32)>,
+    Query(query): Query<CollectionQuery>,
+) -> AppResult<Response> {
+    let db = data.database();
+    let repository = local_repository(&user, &repository, db).await?;
+    let issue = repository.issue_by_number(issue_number, db).await?;
+    let dependants = issue.dependant_ids(db).await?;
+    Ok(in_memory_collection(issue.dependants, query, dependants))
+}
+
 async fn comment(
     State(data): State<InstanceHandle>,
     Path((user, repository, issue_number, uuid)): Path<(String, String, i32, Uuid)>,
@@ -112,6 +195,180 @@ async fn comment(
     Ok(ApubJson(comment))
 }
 
+async fn release(
+    State(data): State<InstanceHandle>,
+    Path((user, repository, tag)): Path<(String, String, String)>,
+) -> AppResult<ApubJson<WithContext<ApubRelease>>> {
+    let object_id = Release::activity_pub_id_from_namespace(&user, &repository, &tag)?;
+    let release = object_id.dereference_local(&data).await?;
+    let release = release.into_apub(&data).await;
+    let release = WithContext::new_default(release?);
+    Ok(ApubJson(release))
+}
+
+async fn user_outbox(
+    State(data): State<InstanceHandle>,
+    Path(user): Path<String>,
+    Query(query): Query<CollectionQuery>,
+) -> AppResult<Response> {
+    let user = local_user(&user, data.database()).await?;
+    let actor = Url::from(user.activity_pub_id);
+    outbox(user.outbox_url, &actor, query, data.database()).await
+}
+
+async fn user_followers(
+    State(data): State<InstanceHandle>,
+    Path(user): Path<String>,
+    Query(query): Query<CollectionQuery>,
+) -> AppResult<Response> {
+    let db = data.database();
+    let user = local_user(&user, db).await?;
+    let total_items = user.count_followers(db).await?;
+    let Some(page) = query.page else {
+        return Ok(collection(user.followers_url, total_items));
+    };
+
+    let followers = user
+        .get_followers(COLLECTION_PAGE_SIZE, CollectionQuery::offset(page), db)
+        .await?
+        .into_iter()
+        .map(|follower| Url::from(follower.activity_pub_id))
+        .collect();
+
+    Ok(collection_page(
+        user.followers_url,
+        page,
+        total_items,
+        followers,
+    ))
+}
+
+async fn user_following(
+    State(data): State<InstanceHandle>,
+    Path(user): Path<String>,
+    Query(query): Query<CollectionQuery>,
+) -> AppResult<Response> {
+    let db = data.database();
+    let user = local_user(&user, db).await?;
+    let following_url = user.following_url()?;
+    let total_items = user.count_following(db).await?;
+    let Some(page) = query.page else {
+        return Ok(collection(following_url, total_items));
+    };
+
+    let following = user
+        .get_following(COLLECTION_PAGE_SIZE, CollectionQuery::offset(page), db)
+        .await?;
+
+    Ok(collection_page(following_url, page, total_items, following))
+}
+
+async fn repository_outbox(
+    State(data): State<InstanceHandle>,
+    Path((user, repository)): Path<(String, String)>,
+    Query(query): Query<CollectionQuery>,
+) -> AppResult<Response> {
+    let repository = local_repository(&user, &repository, data.database()).await?;
+    let actor = Url::from(repository.activity_pub_id);
+    outbox(repository.outbox_url, &actor, query, data.database()).await
+}
+
+async fn repository_followers(
+    State(data): State<InstanceHandle>,
+    Path((user, repository)): Path<(String, String)>,
+    Query(query): Query<CollectionQuery>,
+) -> AppResult<Response> {
+    let db = data.database();
+    let repository = local_repository(&user, &repository, db).await?;
+    let total_items = repository.count_watchers(db).await?;
+    let Some(page) = query.page else {
+        return Ok(collection(repository.followers_url, total_items));
+    };
+
+    let watchers = repository
+        .get_watchers(COLLECTION_PAGE_SIZE, CollectionQuery::offset(page), db)
+        .await?
+        .into_iter()
+        .map(|watcher| Url::from(watcher.activity_pub_id))
+        .collect();
+
+    Ok(collection_page(
+        repository.followers_url,
+        page,
+        total_items,
+        watchers,
+    ))
+}
+
+async fn local_user(username: &str, db: &PgPool) -> AppResult<User> {
+    let user = User::by_name(username, db).await?;
+    if user.is_local {
+        Ok(user)
+    } else {
+        Err(AppError::NotFound)
+    }
+}
+
+async fn local_repository(owner: &str, name: &str, db: &PgPool) -> AppResult<Repository> {
+    let repository = Repository::by_namespace(owner, name, db).await?;
+    if repository.is_local {
+        Ok(repository)
+    } else {
+        Err(AppError::NotFound)
+    }
+}
+
+async fn outbox(
+    outbox_url: Url,
+    actor: &Url,
+    query: CollectionQuery,
+    db: &PgPool,
+) -> AppResult<Response> {
+    let total_items = Activity::count_public_by_actor(actor, db).await?;
+    let Some(page) = query.page else {
+        return Ok(collection(outbox_url, total_items));
+    };
+
+    let activities = Activity::list_public_by_actor(
+        actor,
+        COLLECTION_PAGE_SIZE,
+        CollectionQuery::offset(page),
+        db,
+    )
+    .await?
+    .into_iter()
+    .map(|activity| activity.payload)
+    .collect();
+
+    Ok(collection_page(outbox_url, page, total_items, activities))
+}
+
+fn collection(id: Url, total_items: i64) -> Response {
+    let collection = OrderedCollection::new(id, total_items);
+    ApubJson(WithContext::new_default(collection)).into_response()
+}
+
+/// Collection or page of `items`, for collections small enough to be loaded at once
+fn in_memory_collection<T: Serialize>(id: Url, query: CollectionQuery, items: Vec<T>) -> Response {
+    let total_items = items.len() as i64;
+    let Some(page) = query.page else {
+        return collection(id, total_items);
+    };
+
+    let items = items
+        .into_iter()
+        .skip(CollectionQuery::offset(page) as usize)
+        .take(COLLECTION_PAGE_SIZE as usize)
+        .collect();
+
+    collection_page(id, page, total_items, items)
+}
+
+fn collection_page<T: Serialize>(id: Url, page: i64, total_items: i64, items: Vec<T>) -> Response {
+    let page = OrderedCollectionPage::new(id, page, total_items, items);
+    ApubJson(WithContext::new_default(page)).into_response()
+}
+
 async fn user_inbox(
     headers: HeaderMap,
     method: Method,
@@ -122,11 +379,21 @@ async fn user_inbox(
     Json(activity): Json<WithContext<PersonAcceptedActivities>>,
 ) -> impl IntoResponse {
     let db = data.database();
+    FederationPolicy::check_actor(activity.inner().actor(), db).await?;
     let user = User::by_name(&user, db).await?;
     let user_activity = activity.inner().clone();
 
     match activity.inner() {
-        PersonAcceptedActivities::Follow(_) | PersonAcceptedActivities::CreateIssueComment(_) => {
+        PersonAcceptedActivities::Follow(_)
+        | PersonAcceptedActivities::UndoFollow(_)
+        | PersonAcceptedActivities::CreateIssueComment(_)
+        | PersonAcceptedActivities::UpdateIssue(_)
+        | PersonAcceptedActivities::ResolveIssue(_)
+        | PersonAcceptedActivities::ReopenIssue(_)
+        | PersonAcceptedActivities::UpdateIssueComment(_)
+        | PersonAcceptedActivities::Delete(_)
+        | PersonAcceptedActivities::ReactToIssue(_)
+        | PersonAcceptedActivities::UndoReactToIssue(_) => {
             receive_activity::<WithContext<PersonAcceptedActivities>, User, InstanceHandle>(
                 digest_verified,
                 activity,
@@ -156,10 +423,21 @@ async fn user_inbox(
             let issue = ticket.result.dereference_local(&data).await?;
             issue.add_subscriber(user.id, db).await?;
         }
+        PersonAcceptedActivities::CreateRelease(_) => {
+            receive_activity::<WithContext<PersonAcceptedActivities>, Repository, InstanceHandle>(
+                digest_verified,
+                activity,
+                &data.clone().local_instance,
+                &Data::new(data.clone()),
+                headers,
+                method,
+                uri,
+            )
+            .await?;
+        }
     };
 
-    user.forward_activity(user_activity, &data.local_instance, data.database())
-        .await
+    user.forward_activity(user_activity, data.database()).await
 }
 
 async fn repository_inbox(
@@ -171,6 +449,7 @@ async fn repository_inbox(
     Extension(digest_verified): Extension<DigestVerified>,
     Json(activity): Json<WithContext<RepositoryAcceptedActivities>>,
 ) -> impl IntoResponse {
+    FederationPolicy::check_actor(activity.inner().actor(), data.database()).await?;
     let repository = Repository::by_namespace(&user, &repository, data.database()).await?;
     let repository_activity = activity.inner().clone();
 
@@ -186,6 +465,6 @@ async fn repository_inbox(
     .await?;
 
     repository
-        .forward_activity(repository_activity, &data.local_instance, data.database())
+        .forward_activity(repository_activity, data.database())
         .await
 }
@@ -0,0 +1,61 @@
+use crate::apub::common::GillActivity;
+use crate::apub::release::ApubRelease;
+use crate::domain::release::Release;
+use crate::domain::repository::Repository;
+use crate::error::AppError;
+use crate::instance::InstanceHandle;
+use activitypub_federation::deser::helpers::deserialize_one_or_many;
+use activitypub_federation::traits::ApubObject;
+use activitypub_federation::{core::object_id::ObjectId, data::Data, traits::ActivityHandler};
+use activitystreams_kinds::activity::CreateType;
+use axum::async_trait;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+/// Announce a new release to the repository followers
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateRelease {
+    pub(crate) actor: ObjectId<Repository>,
+    #[serde(deserialize_with = "deserialize_one_or_many")]
+    pub(crate) to: Vec<Url>,
+    pub(crate) object: ApubRelease,
+    #[serde(rename = "type")]
+    pub(crate) kind: CreateType,
+    pub(crate) id: Url,
+}
+
+impl GillActivity for CreateRelease {
+    fn forward_addresses(&self) -> Vec<&Url> {
+        vec![]
+    }
+}
+
+#[async_trait]
+impl ActivityHandler for CreateRelease {
+    type DataType = InstanceHandle;
+    type Error = AppError;
+
+    fn id(&self) -> &Url {
+        &self.id
+    }
+
+    fn actor(&self) -> &Url {
+        self.actor.inner()
+    }
+
+    async fn receive(
+        self,
+        instance: &Data<InstanceHandle>,
+        request_counter: &mut i32,
+    ) -> Result<(), Self::Error> {
+        // Only the repository itself can announce its releases
+        if self.object.context.inner() != self.actor.inner() {
+            return Err(AppError::Unauthorized);
+        }
+
+        Release::from_apub(self.object, instance, request_counter).await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,111 @@
+use crate::apub::common::{GillApubObject, Source};
+use crate::domain::release::{NewRelease, Release};
+use crate::domain::repository::Repository;
+use crate::domain::user::User;
+use crate::error::AppError;
+use crate::instance::InstanceHandle;
+use activitypub_federation::core::object_id::ObjectId;
+use activitypub_federation::traits::ApubObject;
+use activitystreams_kinds::kind;
+use async_session::async_trait;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+pub mod create;
+
+kind!(ReleaseType, Release);
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApubRelease {
+    #[serde(rename = "type")]
+    pub kind: ReleaseType,
+    pub id: ObjectId<Release>,
+    pub attributed_to: ObjectId<User>,
+    pub context: ObjectId<Repository>,
+    pub tag: String,
+    pub name: String,
+    pub media_type: String,
+    pub content: String,
+    pub source: Source,
+    pub published: chrono::NaiveDateTime,
+}
+
+#[async_trait]
+impl ApubObject for Release {
+    type DataType = InstanceHandle;
+    type ApubType = ApubRelease;
+    type DbType = Release;
+    type Error = AppError;
+
+    async fn read_from_apub_id(
+        object_id: Url,
+        data: &Self::DataType,
+    ) -> Result<Option<Self>, Self::Error>
+    where
+        Self: Sized,
+    {
+        let db = data.database();
+        Release::by_activity_pub_id_optional(object_id.as_ref(), db).await
+    }
+
+    async fn into_apub(self, data: &Self::DataType) -> Result<Self::ApubType, Self::Error> {
+        let repository = Repository::by_id(self.repository_id, data.database()).await?;
+
+        Ok(ApubRelease {
+            kind: Default::default(),
+            id: self.activity_pub_id.into(),
+            attributed_to: self.attributed_to.into(),
+            context: repository.activity_pub_id.into(),
+            tag: self.tag,
+            name: self.title,
+            media_type: self.media_type.clone(),
+            content: self.content.clone(),
+            source: Source {
+                content: self.content,
+                media_type: self.media_type,
+            },
+            published: self.published,
+        })
+    }
+
+    async fn from_apub(
+        release: Self::ApubType,
+        data: &Self::DataType,
+        request_counter: &mut i32,
+    ) -> Result<Self, Self::Error>
+    where
+        Self: Sized,
+    {
+        let db = data.database();
+        let existing =
+            Release::by_activity_pub_id_optional(release.id.inner().as_str(), db).await?;
+        if let Some(existing) = existing {
+            return Ok(existing);
+        }
+
+        let user = release
+            .attributed_to
+            .dereference(data, &data.local_instance, request_counter)
+            .await?;
+
+        let repository = release
+            .context
+            .dereference(data, &data.local_instance, request_counter)
+            .await?;
+
+        NewRelease {
+            repository_id: repository.local_id(),
+            tag: release.tag,
+            activity_pub_id: release.id.into(),
+            title: release.name,
+            content: release.source.content,
+            media_type: release.source.media_type,
+            attributed_to: user.activity_pub_id,
+            published: release.published,
+            is_local: false,
+        }
+        .save(db)
+        .await
+    }
+}
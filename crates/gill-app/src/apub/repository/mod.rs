@@ -11,15 +11,20 @@ use fork::Fork;
 use gill_settings::SETTINGS;
 use serde::{Deserialize, Serialize};
 
+use crate::apub::delete::Delete;
 use crate::apub::ticket::comment::create::CreateTicketComment;
+use crate::apub::ticket::comment::update::UpdateTicketComment;
 use crate::apub::ticket::offer::OfferTicket;
+use crate::apub::ticket::reaction::{ReactToTicket, UndoReactToTicket};
+use crate::apub::ticket::resolve::{ResolveTicket, UndoResolveTicket};
+use crate::apub::ticket::update::UpdateTicket;
 use crate::domain::repository::create::CreateRepository;
 use crate::domain::repository::Repository;
 use crate::domain::user::User;
-use star::Star;
+use star::{Star, UndoStar};
 use std::str::FromStr;
 use url::{ParseError, Url};
-use watch::Watch;
+use watch::{UndoWatch, Watch};
 
 pub mod fork;
 pub mod star;
@@ -33,10 +38,19 @@ kind!(RepositoryType, Repository);
 #[enum_delegate::implement(ActivityHandler)]
 pub enum RepositoryAcceptedActivities {
     Watch(Watch),
+    UndoWatch(UndoWatch),
     Star(Star),
+    UndoStar(UndoStar),
     Fork(Fork),
     OfferIssue(OfferTicket),
     CreateIssueComment(CreateTicketComment),
+    UpdateIssue(UpdateTicket),
+    ResolveIssue(ResolveTicket),
+    ReopenIssue(UndoResolveTicket),
+    UpdateIssueComment(UpdateTicketComment),
+    Delete(Delete),
+    ReactToIssue(ReactToTicket),
+    UndoReactToIssue(UndoReactToTicket),
 }
 
 impl GillActivity for RepositoryAcceptedActivities {
@@ -46,6 +60,17 @@ impl GillActivity for RepositoryAcceptedActivities {
             RepositoryAcceptedActivities::CreateIssueComment(create_comment) => {
                 create_comment.forward_addresses()
             }
+            RepositoryAcceptedActivities::UpdateIssue(activity) => activity.forward_addresses(),
+            RepositoryAcceptedActivities::ResolveIssue(activity) => activity.forward_addresses(),
+            RepositoryAcceptedActivities::ReopenIssue(activity) => activity.forward_addresses(),
+            RepositoryAcceptedActivities::UpdateIssueComment(activity) => {
+                activity.forward_addresses()
+            }
+            RepositoryAcceptedActivities::Delete(activity) => activity.forward_addresses(),
+            RepositoryAcceptedActivities::ReactToIssue(activity) => activity.forward_addresses(),
+            RepositoryAcceptedActivities::UndoReactToIssue(activity) => {
+                activity.forward_addresses()
+            }
             _ => vec![],
         }
     }
@@ -95,6 +120,10 @@ impl GillApubObject for Repository {
         self.id
     }
 
+    fn actor_id(&self) -> Url {
+        self.activity_pub_id.clone().into()
+    }
+
     fn public_key_with_owner(&self) -> Result<PublicKey, ParseError> {
         Ok(PublicKey::new_main_key(
             self.activity_pub_id.clone().into(),
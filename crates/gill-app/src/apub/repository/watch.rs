@@ -6,7 +6,7 @@ use crate::instance::InstanceHandle;
 use activitypub_federation::core::object_id::ObjectId;
 use activitypub_federation::data::Data;
 use activitypub_federation::traits::ActivityHandler;
-use activitystreams_kinds::activity::FollowType;
+use activitystreams_kinds::activity::{FollowType, UndoType};
 use axum::async_trait;
 use serde::{Deserialize, Serialize};
 use url::Url;
@@ -67,3 +67,66 @@ impl ActivityHandler for Watch {
         Ok(())
     }
 }
+
+/// Stop watching a repository
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct UndoWatch {
+    id: Url,
+    pub actor: ObjectId<User>,
+    pub object: Watch,
+    r#type: UndoType,
+}
+
+impl GillActivity for UndoWatch {
+    fn forward_addresses(&self) -> Vec<&Url> {
+        vec![]
+    }
+}
+
+impl UndoWatch {
+    pub fn new(object: Watch, id: Url) -> UndoWatch {
+        UndoWatch {
+            id,
+            actor: object.user.clone(),
+            object,
+            r#type: Default::default(),
+        }
+    }
+}
+
+#[async_trait]
+impl ActivityHandler for UndoWatch {
+    type DataType = InstanceHandle;
+    type Error = AppError;
+
+    fn id(&self) -> &Url {
+        &self.id
+    }
+
+    fn actor(&self) -> &Url {
+        self.actor.inner()
+    }
+
+    async fn receive(
+        self,
+        data: &Data<Self::DataType>,
+        _request_counter: &mut i32,
+    ) -> Result<(), Self::Error> {
+        // Only the author of the original activity can undo it
+        if self.object.user.inner() != self.actor.inner() {
+            return Err(AppError::Unauthorized);
+        }
+
+        let user = ObjectId::<User>::new(self.object.user)
+            .dereference_local(data)
+            .await?;
+
+        let repository = ObjectId::<Repository>::new(self.object.repository)
+            .dereference_local(data)
+            .await?;
+
+        repository.remove_watcher(&user, data).await?;
+        Ok(())
+    }
+}
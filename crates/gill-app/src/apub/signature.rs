@@ -0,0 +1,140 @@
+use anyhow::anyhow;
+use base64::engine::general_purpose;
+use base64::Engine;
+use openssl::hash::MessageDigest;
+use openssl::pkey::PKey;
+use openssl::sign::Signer;
+use reqwest::Client;
+use std::time::Duration;
+use url::Url;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Post a serialized activity to `inbox` with an http signature made with `private_key`.
+/// Only a 2xx answer is a successful delivery.
+pub async fn signed_post(
+    client: &Client,
+    inbox: &Url,
+    activity: String,
+    key_id: &str,
+    private_key: &str,
+) -> anyhow::Result<()> {
+    let date = chrono::Utc::now()
+        .format("%a, %d %b %Y %H:%M:%S GMT")
+        .to_string();
+    let headers = SignedHeaders::new(inbox, &activity, &date, key_id, private_key)?;
+
+    client
+        .post(inbox.as_str())
+        .timeout(REQUEST_TIMEOUT)
+        .header("Content-Type", "application/activity+json")
+        .header("Host", &headers.host)
+        .header("Date", &headers.date)
+        .header("Digest", &headers.digest)
+        .header("Signature", &headers.signature)
+        .body(activity)
+        .send()
+        .await?
+        .error_for_status()?;
+
+    Ok(())
+}
+
+/// Headers covered by the signature, in the draft-cavage `rsa-sha256` flavor most
+/// ActivityPub servers expect
+#[derive(Debug)]
+struct SignedHeaders {
+    host: String,
+    date: String,
+    digest: String,
+    signature: String,
+}
+
+impl SignedHeaders {
+    fn new(
+        inbox: &Url,
+        body: &str,
+        date: &str,
+        key_id: &str,
+        private_key: &str,
+    ) -> anyhow::Result<Self> {
+        let host = match (inbox.host_str(), inbox.port()) {
+            (Some(host), Some(port)) => format!("{host}:{port}"),
+            (Some(host), None) => host.to_string(),
+            (None, _) => return Err(anyhow!("Inbox {inbox} has no host")),
+        };
+
+        let request_target = match inbox.query() {
+            Some(query) => format!("post {}?{query}", inbox.path()),
+            None => format!("post {}", inbox.path()),
+        };
+
+        let digest = openssl::sha::sha256(body.as_bytes());
+        let digest = format!("SHA-256={}", general_purpose::STANDARD.encode(digest));
+        let signing_string = format!(
+            "(request-target): {request_target}\nhost: {host}\ndate: {date}\ndigest: {digest}"
+        );
+
+        let private_key = PKey::private_key_from_pem(private_key.as_bytes())?;
+        let mut signer = Signer::new(MessageDigest::sha256(), &private_key)?;
+        signer.update(signing_string.as_bytes())?;
+        let signature = general_purpose::STANDARD.encode(signer.sign_to_vec()?);
+        let signature = format!(
+            "keyId=\"{key_id}\",algorithm=\"rsa-sha256\",headers=\"(request-target) host date digest\",signature=\"{signature}\""
+        );
+
+        Ok(SignedHeaders {
+            host,
+            date: date.to_string(),
+            digest,
+            signature,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::apub::signature::SignedHeaders;
+    use activitypub_federation::core::signatures::generate_actor_keypair;
+    use base64::engine::general_purpose;
+    use base64::Engine;
+    use openssl::hash::MessageDigest;
+    use openssl::pkey::PKey;
+    use openssl::sign::Verifier;
+    use speculoos::prelude::*;
+    use url::Url;
+
+    #[test]
+    fn should_sign_request_target_host_date_and_digest() -> anyhow::Result<()> {
+        let keys = generate_actor_keypair()?;
+        let inbox = Url::parse("http://localhost:3000/users/alice/inbox")?;
+        let date = "Sun, 06 Nov 1994 08:49:37 GMT";
+        let key_id = "https://gill.example.org/users/bob#main-key";
+
+        let headers = SignedHeaders::new(&inbox, "{}", date, key_id, &keys.private_key)?;
+
+        assert_that!(headers.host).is_equal_to("localhost:3000".to_string());
+        assert_that!(headers.digest)
+            .is_equal_to("SHA-256=RBNvo1WzZ4oRRq0W9+hknpT7T8If536DEMBg9hyq/4o=".to_string());
+        assert_that!(headers.signature).starts_with(
+            "keyId=\"https://gill.example.org/users/bob#main-key\",algorithm=\"rsa-sha256\",headers=\"(request-target) host date digest\",signature=\"",
+        );
+
+        let signature = headers
+            .signature
+            .rsplit_once("signature=\"")
+            .and_then(|(_, signature)| signature.strip_suffix('"'))
+            .expect("signature field");
+        let signature = general_purpose::STANDARD.decode(signature)?;
+        let signing_string = format!(
+            "(request-target): post /users/alice/inbox\nhost: localhost:3000\ndate: {date}\ndigest: {}",
+            headers.digest
+        );
+
+        let public_key = PKey::public_key_from_pem(keys.public_key.as_bytes())?;
+        let mut verifier = Verifier::new(MessageDigest::sha256(), &public_key)?;
+        verifier.update(signing_string.as_bytes())?;
+        assert_that!(verifier.verify(&signature)?).is_true();
+        Ok(())
+    }
+}
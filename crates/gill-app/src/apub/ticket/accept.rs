@@ -4,6 +4,7 @@ use crate::instance::InstanceHandle;
 use activitypub_federation::deser::helpers::deserialize_one_or_many;
 
 use crate::domain::issue::Issue;
+use crate::domain::user::activity::UserActivityKind;
 
 use crate::apub::common::{is_local, GillActivity};
 use crate::domain::repository::Repository;
@@ -66,7 +67,11 @@ impl ActivityHandler for AcceptTicket {
             .dereference(data, &data.local_instance, request_counter)
             .await?;
 
-        issue.save(data.database()).await?;
+        let db = data.database();
+        let issue = issue.save(db).await?;
+        issue
+            .record_activity(issue.opened_by, UserActivityKind::OpenIssue, db)
+            .await;
 
         Ok(())
     }
@@ -8,6 +8,7 @@ use activitypub_federation::deser::helpers::deserialize_one_or_many;
 use crate::apub::common::{is_local, GillActivity, GillApubObject};
 
 use crate::domain::issue::Issue;
+use crate::domain::user::activity::UserActivityKind;
 use crate::domain::user::User;
 
 use activitypub_federation::{core::object_id::ObjectId, data::Data, traits::ActivityHandler};
@@ -76,6 +77,24 @@ impl ActivityHandler for CreateTicketComment {
         issue
             .add_subscriber(user.local_id(), instance.database())
             .await?;
+        issue
+            .record_activity(
+                user.local_id(),
+                UserActivityKind::CommentIssue,
+                instance.database(),
+            )
+            .await;
+        issue
+            .notify(
+                user.local_id(),
+                "commented",
+                Some(&comment.content),
+                instance.database(),
+            )
+            .await;
+        issue
+            .record_references(user.local_id(), &comment.content, instance.database())
+            .await;
 
         Ok(())
     }
@@ -1,4 +1,4 @@
-use crate::apub::common::{GillApubObject, Source};
+use crate::apub::common::{deserialize_datetime, mention_tags, GillApubObject, Mention, Source};
 
 use crate::error::AppError;
 use crate::instance::InstanceHandle;
@@ -17,6 +17,7 @@ use url::Url;
 use uuid::Uuid;
 
 pub mod create;
+pub mod update;
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -30,7 +31,10 @@ pub struct ApubIssueComment {
     pub media_type: String,
     pub content: String,
     pub source: Source,
+    #[serde(deserialize_with = "deserialize_datetime")]
     pub published: chrono::NaiveDateTime,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tag: Vec<Mention>,
 }
 
 #[async_trait]
@@ -51,7 +55,8 @@ impl ApubObject for IssueComment {
         IssueComment::by_activity_pub_id_optional(object_id.as_ref(), db).await
     }
 
-    async fn into_apub(self, _: &Self::DataType) -> Result<Self::ApubType, Self::Error> {
+    async fn into_apub(self, data: &Self::DataType) -> Result<Self::ApubType, Self::Error> {
+        let tag = mention_tags(&self.content, data.database()).await?;
         Ok(ApubIssueComment {
             kind: NoteType::Note,
             id: self.activity_pub_id.into(),
@@ -65,6 +70,7 @@ impl ApubObject for IssueComment {
                 media_type: self.media_type,
             },
             published: self.published,
+            tag,
         })
     }
 
@@ -98,6 +104,7 @@ impl ApubObject for IssueComment {
             context: comment.context.into(),
             in_reply_to: comment.attributed_to.into(),
             published: comment.published,
+            deleted_at: None,
         })
     }
 }
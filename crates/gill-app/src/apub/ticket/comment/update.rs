@@ -0,0 +1,75 @@
+use crate::error::AppError;
+use crate::instance::InstanceHandle;
+
+use crate::apub::ticket::comment::ApubIssueComment;
+
+use activitypub_federation::deser::helpers::deserialize_one_or_many;
+
+use crate::apub::common::{is_local, GillActivity};
+
+use crate::domain::issue::comment::IssueComment;
+use crate::domain::repository::Repository;
+use crate::domain::user::User;
+
+use activitypub_federation::{core::object_id::ObjectId, data::Data, traits::ActivityHandler};
+use activitystreams_kinds::activity::UpdateType;
+use axum::async_trait;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+/// Sent when a comment is edited by its author or the repository owner
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateTicketComment {
+    pub(crate) id: Url,
+    #[serde(rename = "type")]
+    pub(crate) kind: UpdateType,
+    pub(crate) actor: ObjectId<User>,
+    #[serde(deserialize_with = "deserialize_one_or_many")]
+    pub(crate) to: Vec<Url>,
+    pub(crate) object: ApubIssueComment,
+}
+
+impl GillActivity for UpdateTicketComment {
+    fn forward_addresses(&self) -> Vec<&Url> {
+        self.to.iter().filter(|url| is_local(url)).collect()
+    }
+}
+
+#[async_trait]
+impl ActivityHandler for UpdateTicketComment {
+    type DataType = InstanceHandle;
+    type Error = AppError;
+
+    fn id(&self) -> &Url {
+        &self.id
+    }
+
+    fn actor(&self) -> &Url {
+        self.actor.inner()
+    }
+
+    async fn receive(
+        self,
+        data: &Data<InstanceHandle>,
+        _request_counter: &mut i32,
+    ) -> Result<(), Self::Error> {
+        let db = data.database();
+        let comment =
+            IssueComment::by_activity_pub_id_optional(self.object.id.inner().as_str(), db).await?;
+        let Some(comment) = comment.filter(|comment| comment.deleted_at.is_none()) else {
+            // We never received this comment or it was deleted, nothing to update
+            return Ok(());
+        };
+
+        let repository = Repository::by_id(comment.repository_id, db).await?;
+        if !comment.is_managed_by(self.actor.inner(), &repository) {
+            return Err(AppError::Unauthorized);
+        }
+
+        let editor = self.actor.dereference_local(data).await?;
+        comment
+            .update_content(editor.id, &self.object.content, db)
+            .await
+    }
+}
@@ -0,0 +1,97 @@
+use crate::domain::issue::reference::IssueReference;
+use crate::domain::issue::Issue;
+use crate::domain::timeline::{HistoryItem, ItemEvent, ItemEventKind};
+use crate::error::AppResult;
+use gill_settings::SETTINGS;
+use serde::Serialize;
+use sqlx::PgPool;
+use url::Url;
+
+/// An entry of a ticket `history` collection. Comments are served by `replies`, the history
+/// lists what happened to the ticket itself using the closest ActivityStreams activity type.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApubTicketEvent {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub actor: Url,
+    pub object: Url,
+    pub summary: String,
+    /// The issue, pull request or commit a reference comes from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub origin: Option<Url>,
+    pub published: chrono::NaiveDateTime,
+}
+
+impl Issue {
+    /// Events and references of the issue, oldest first
+    pub async fn history_events(&self, db: &PgPool) -> AppResult<Vec<ApubTicketEvent>> {
+        HistoryItem::for_item(self.repository_id, self.number, db)
+            .await?
+            .into_iter()
+            .map(|item| match item {
+                HistoryItem::Event(event) => self.event_to_apub(event),
+                HistoryItem::Reference(reference) => self.reference_to_apub(reference),
+            })
+            .collect()
+    }
+
+    fn event_to_apub(&self, event: ItemEvent) -> AppResult<ApubTicketEvent> {
+        let kind = match event.kind {
+            ItemEventKind::Opened => "Create",
+            ItemEventKind::Closed => "Resolve",
+            ItemEventKind::Reopened => "Undo",
+            ItemEventKind::Renamed => "Update",
+            ItemEventKind::Labeled => "Add",
+            ItemEventKind::Merged => "Apply",
+            ItemEventKind::ForcePushed => "Push",
+        };
+
+        let actor = &event.actor_name;
+        let summary = match event.kind {
+            ItemEventKind::Opened => format!("{actor} opened the issue"),
+            ItemEventKind::Closed => format!("{actor} closed the issue"),
+            ItemEventKind::Reopened => format!("{actor} reopened the issue"),
+            ItemEventKind::Renamed => format!(
+                "{actor} changed the title to {}",
+                event.new_value.as_deref().unwrap_or_default()
+            ),
+            ItemEventKind::Labeled => format!(
+                "{actor} added the label {}",
+                event.new_value.as_deref().unwrap_or_default()
+            ),
+            ItemEventKind::Merged => format!("{actor} merged the pull request"),
+            ItemEventKind::ForcePushed => format!("{actor} force-pushed {}", event.pushed_range()),
+        };
+
+        Ok(ApubTicketEvent {
+            kind: kind.to_string(),
+            actor: Url::parse(&event.actor_activity_pub_id)?,
+            object: self.activity_pub_id.clone().into(),
+            summary,
+            origin: None,
+            published: event.created_at,
+        })
+    }
+
+    fn reference_to_apub(&self, reference: IssueReference) -> AppResult<ApubTicketEvent> {
+        let action = if reference.closes {
+            "closed the issue in"
+        } else {
+            "referenced the issue from"
+        };
+
+        Ok(ApubTicketEvent {
+            kind: "Announce".to_string(),
+            actor: Url::parse(&reference.actor_activity_pub_id)?,
+            object: self.activity_pub_id.clone().into(),
+            summary: format!(
+                "{} {action} {}",
+                reference.actor_name,
+                reference.source_label()
+            ),
+            origin: Some(SETTINGS.domain_url()?.join(&reference.source_url())?),
+            published: reference.created_at,
+        })
+    }
+}
@@ -1,4 +1,4 @@
-use crate::apub::common::{GillApubObject, Source};
+use crate::apub::common::{deserialize_datetime, mention_tags, GillApubObject, Mention, Source};
 use crate::error::AppError;
 use crate::instance::InstanceHandle;
 use activitypub_federation::core::object_id::ObjectId;
@@ -19,7 +19,11 @@ use url::Url;
 
 pub mod accept;
 pub mod comment;
+pub mod history;
 pub mod offer;
+pub mod reaction;
+pub mod resolve;
+pub mod update;
 
 kind!(TicketType, Ticket);
 
@@ -33,6 +37,7 @@ pub struct ApubTicket {
     pub attributed_to: ObjectId<User>,
     pub summary: String,
     pub source: Source,
+    #[serde(deserialize_with = "deserialize_datetime")]
     pub published: chrono::NaiveDateTime,
     pub followers: Url,
     pub team: Url,
@@ -43,6 +48,8 @@ pub struct ApubTicket {
     pub is_resolved: bool,
     pub resolved_by: Option<Url>,
     pub resolved: Option<chrono::NaiveDateTime>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tag: Vec<Mention>,
 }
 
 #[async_trait]
@@ -63,7 +70,8 @@ impl ApubObject for Issue {
         Issue::by_activity_pub_id_optional(object_id.as_ref(), db).await
     }
 
-    async fn into_apub(self, _: &Self::DataType) -> Result<Self::ApubType, Self::Error> {
+    async fn into_apub(self, data: &Self::DataType) -> Result<Self::ApubType, Self::Error> {
+        let tag = mention_tags(&self.content, data.database()).await?;
         Ok(ApubTicket {
             kind: Default::default(),
             id: self.activity_pub_id.into(),
@@ -84,6 +92,7 @@ impl ApubObject for Issue {
             is_resolved: self.state == IssueState::Closed,
             resolved_by: self.resolved_by.map(Into::into),
             resolved: self.resolved,
+            tag,
         })
     }
 
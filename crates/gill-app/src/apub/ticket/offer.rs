@@ -8,6 +8,7 @@ use crate::apub::common::{is_local, GillActivity, GillApubObject, Source};
 use crate::apub::ticket::accept::AcceptTicket;
 use crate::domain::issue::Issue;
 use crate::domain::repository::Repository;
+use crate::domain::user::activity::UserActivityKind;
 use crate::domain::user::User;
 use activitypub_federation::traits::{Actor, ApubObject};
 use activitypub_federation::{core::object_id::ObjectId, data::Data, traits::ActivityHandler};
@@ -78,6 +79,13 @@ impl ActivityHandler for OfferTicket {
         let issue = Issue::from_apub(ticket, context, request_counter).await?;
         let issue = issue.save(db).await?;
         issue.add_subscriber(author.id, db).await?;
+        issue
+            .record_activity(author.id, UserActivityKind::OpenIssue, db)
+            .await;
+        issue
+            .notify(author.id, "opened", Some(&issue.content), db)
+            .await;
+        issue.record_references(author.id, &issue.content, db).await;
 
         let hostname = &SETTINGS.domain;
         let id = Url::parse(&format!(
@@ -99,9 +107,7 @@ impl ActivityHandler for OfferTicket {
             result,
         };
 
-        repository
-            .send(accept, recipient, &context.local_instance)
-            .await
+        repository.send(accept, recipient, context).await
     }
 }
 
@@ -147,6 +153,7 @@ impl ApubTicket {
             is_resolved: false,
             resolved_by: None,
             resolved: None,
+            tag: vec![],
         })
     }
 }
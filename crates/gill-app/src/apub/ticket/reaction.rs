@@ -0,0 +1,145 @@
+use crate::error::AppError;
+use crate::instance::InstanceHandle;
+
+use activitypub_federation::deser::helpers::deserialize_one_or_many;
+
+use crate::apub::common::{is_local, GillActivity};
+use crate::domain::reaction::{Reaction, ReactionEmoji};
+use crate::domain::user::User;
+
+use activitypub_federation::{core::object_id::ObjectId, data::Data, traits::ActivityHandler};
+use activitystreams_kinds::activity::UndoType;
+use axum::async_trait;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+/// `Like` is what Mastodon and Lemmy send for favourites and upvotes,
+/// `EmojiReact` carries any emoji in its `content`
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, Eq, PartialEq)]
+pub enum ReactionType {
+    Like,
+    EmojiReact,
+}
+
+/// A reaction to a ticket or a ticket comment
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReactToTicket {
+    pub(crate) id: Url,
+    #[serde(rename = "type")]
+    pub(crate) kind: ReactionType,
+    pub(crate) actor: ObjectId<User>,
+    /// Mastodon likes are only addressed to the liked object author
+    #[serde(default, deserialize_with = "deserialize_one_or_many")]
+    pub(crate) to: Vec<Url>,
+    pub(crate) object: Url,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) content: Option<String>,
+}
+
+/// Remove a reaction
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UndoReactToTicket {
+    pub(crate) id: Url,
+    #[serde(rename = "type")]
+    pub(crate) kind: UndoType,
+    pub(crate) actor: ObjectId<User>,
+    #[serde(default, deserialize_with = "deserialize_one_or_many")]
+    pub(crate) to: Vec<Url>,
+    pub(crate) object: ReactToTicket,
+}
+
+impl GillActivity for ReactToTicket {
+    fn forward_addresses(&self) -> Vec<&Url> {
+        self.to.iter().filter(|url| is_local(url)).collect()
+    }
+}
+
+impl GillActivity for UndoReactToTicket {
+    fn forward_addresses(&self) -> Vec<&Url> {
+        self.to.iter().filter(|url| is_local(url)).collect()
+    }
+}
+
+impl ReactToTicket {
+    /// Likes without a supported emoji count as a thumbs up,
+    /// other emoji reactions are ignored
+    pub fn emoji(&self) -> Option<ReactionEmoji> {
+        let emoji = self
+            .content
+            .as_deref()
+            .and_then(ReactionEmoji::from_content);
+        match self.kind {
+            ReactionType::Like => Some(emoji.unwrap_or(ReactionEmoji::ThumbsUp)),
+            ReactionType::EmojiReact => emoji,
+        }
+    }
+}
+
+#[async_trait]
+impl ActivityHandler for ReactToTicket {
+    type DataType = InstanceHandle;
+    type Error = AppError;
+
+    fn id(&self) -> &Url {
+        &self.id
+    }
+
+    fn actor(&self) -> &Url {
+        self.actor.inner()
+    }
+
+    async fn receive(
+        self,
+        data: &Data<InstanceHandle>,
+        request_counter: &mut i32,
+    ) -> Result<(), Self::Error> {
+        let emoji = match self.emoji() {
+            Some(emoji) => emoji,
+            None => return Ok(()),
+        };
+
+        let db = data.database();
+        let reaction = match Reaction::to_ticket_object(&self.object, emoji, db).await? {
+            Some(reaction) => reaction,
+            // Not one of our tickets or comments
+            None => return Ok(()),
+        };
+
+        let user = self
+            .actor
+            .dereference(data, data.local_instance(), request_counter)
+            .await?;
+
+        reaction.save(user.id, self.id.as_str(), db).await
+    }
+}
+
+#[async_trait]
+impl ActivityHandler for UndoReactToTicket {
+    type DataType = InstanceHandle;
+    type Error = AppError;
+
+    fn id(&self) -> &Url {
+        &self.id
+    }
+
+    fn actor(&self) -> &Url {
+        self.actor.inner()
+    }
+
+    async fn receive(
+        self,
+        data: &Data<InstanceHandle>,
+        _request_counter: &mut i32,
+    ) -> Result<(), Self::Error> {
+        // Only the author of the original activity can undo it
+        if self.object.actor.inner() != self.actor.inner() {
+            return Err(AppError::Unauthorized);
+        }
+
+        let user = self.actor.dereference_local(data).await?;
+        Reaction::remove_activity(self.object.id.as_str(), user.id, data.database()).await
+    }
+}
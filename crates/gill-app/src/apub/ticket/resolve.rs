@@ -0,0 +1,156 @@
+use crate::error::AppError;
+use crate::instance::InstanceHandle;
+
+use activitypub_federation::deser::helpers::deserialize_one_or_many;
+
+use crate::apub::common::{is_local, GillActivity};
+use crate::domain::issue::Issue;
+use crate::domain::repository::Repository;
+use crate::domain::timeline::ItemEventKind;
+use crate::domain::user::activity::UserActivityKind;
+use crate::domain::user::User;
+
+use activitypub_federation::{core::object_id::ObjectId, data::Data, traits::ActivityHandler};
+use activitystreams_kinds::activity::UndoType;
+use activitystreams_kinds::kind;
+use axum::async_trait;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+kind!(ResolveType, Resolve);
+
+/// ForgeFed `Resolve` activity, sent when a ticket is closed
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolveTicket {
+    pub(crate) id: Url,
+    #[serde(rename = "type")]
+    pub(crate) kind: ResolveType,
+    pub(crate) actor: ObjectId<User>,
+    #[serde(deserialize_with = "deserialize_one_or_many")]
+    pub(crate) to: Vec<Url>,
+    pub(crate) object: ObjectId<Issue>,
+}
+
+/// Undo a `Resolve` activity, sent when a ticket is reopened
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UndoResolveTicket {
+    pub(crate) id: Url,
+    #[serde(rename = "type")]
+    pub(crate) kind: UndoType,
+    pub(crate) actor: ObjectId<User>,
+    #[serde(deserialize_with = "deserialize_one_or_many")]
+    pub(crate) to: Vec<Url>,
+    pub(crate) object: ResolveTicket,
+}
+
+impl GillActivity for ResolveTicket {
+    fn forward_addresses(&self) -> Vec<&Url> {
+        self.to.iter().filter(|url| is_local(url)).collect()
+    }
+}
+
+impl GillActivity for UndoResolveTicket {
+    fn forward_addresses(&self) -> Vec<&Url> {
+        self.to.iter().filter(|url| is_local(url)).collect()
+    }
+}
+
+/// Only the ticket author and the repository owner can close or reopen a ticket,
+/// tickets we never received are ignored
+async fn resolvable_issue(
+    issue: &ObjectId<Issue>,
+    actor: &Url,
+    data: &Data<InstanceHandle>,
+) -> Result<Option<Issue>, AppError> {
+    let db = data.database();
+    let Some(issue) = Issue::by_activity_pub_id_optional(issue.inner().as_str(), db).await? else {
+        return Ok(None);
+    };
+
+    let repository = Repository::by_id(issue.repository_id, db).await?;
+    if !issue.is_managed_by(actor, &repository) {
+        return Err(AppError::Unauthorized);
+    }
+
+    Ok(Some(issue))
+}
+
+#[async_trait]
+impl ActivityHandler for ResolveTicket {
+    type DataType = InstanceHandle;
+    type Error = AppError;
+
+    fn id(&self) -> &Url {
+        &self.id
+    }
+
+    fn actor(&self) -> &Url {
+        self.actor.inner()
+    }
+
+    async fn receive(
+        self,
+        data: &Data<InstanceHandle>,
+        _request_counter: &mut i32,
+    ) -> Result<(), Self::Error> {
+        let issue = match resolvable_issue(&self.object, self.actor.inner(), data).await? {
+            Some(issue) => issue,
+            None => return Ok(()),
+        };
+
+        let db = data.database();
+        issue
+            .mark_resolved(self.actor.inner(), Some(&self.id), db)
+            .await?;
+        let actor = self.actor.dereference_local(data).await?;
+        issue
+            .record_event(actor.id, ItemEventKind::Closed, db)
+            .await;
+        issue
+            .record_activity(actor.id, UserActivityKind::CloseIssue, db)
+            .await;
+        issue.notify(actor.id, "closed", None, db).await;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ActivityHandler for UndoResolveTicket {
+    type DataType = InstanceHandle;
+    type Error = AppError;
+
+    fn id(&self) -> &Url {
+        &self.id
+    }
+
+    fn actor(&self) -> &Url {
+        self.actor.inner()
+    }
+
+    async fn receive(
+        self,
+        data: &Data<InstanceHandle>,
+        _request_counter: &mut i32,
+    ) -> Result<(), Self::Error> {
+        let issue = match resolvable_issue(&self.object.object, self.actor.inner(), data).await? {
+            Some(issue) => issue,
+            None => return Ok(()),
+        };
+
+        let db = data.database();
+        issue.mark_reopened(db).await?;
+        let actor = self.actor.dereference_local(data).await?;
+        issue
+            .record_event(actor.id, ItemEventKind::Reopened, db)
+            .await;
+        issue
+            .record_activity(actor.id, UserActivityKind::ReopenIssue, db)
+            .await;
+        issue.notify(actor.id, "reopened", None, db).await;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,77 @@
+use crate::error::AppError;
+use crate::instance::InstanceHandle;
+
+use activitypub_federation::deser::helpers::deserialize_one_or_many;
+
+use crate::apub::common::{is_local, GillActivity};
+use crate::apub::ticket::ApubTicket;
+use crate::domain::issue::Issue;
+use crate::domain::repository::Repository;
+use crate::domain::user::User;
+
+use activitypub_federation::{core::object_id::ObjectId, data::Data, traits::ActivityHandler};
+use activitystreams_kinds::activity::UpdateType;
+use axum::async_trait;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+/// Sent when a ticket title or content is edited by its author or the repository owner
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateTicket {
+    pub(crate) id: Url,
+    #[serde(rename = "type")]
+    pub(crate) kind: UpdateType,
+    pub(crate) actor: ObjectId<User>,
+    #[serde(deserialize_with = "deserialize_one_or_many")]
+    pub(crate) to: Vec<Url>,
+    pub(crate) object: ApubTicket,
+}
+
+impl GillActivity for UpdateTicket {
+    fn forward_addresses(&self) -> Vec<&Url> {
+        self.to.iter().filter(|url| is_local(url)).collect()
+    }
+}
+
+#[async_trait]
+impl ActivityHandler for UpdateTicket {
+    type DataType = InstanceHandle;
+    type Error = AppError;
+
+    fn id(&self) -> &Url {
+        &self.id
+    }
+
+    fn actor(&self) -> &Url {
+        self.actor.inner()
+    }
+
+    async fn receive(
+        self,
+        data: &Data<InstanceHandle>,
+        _request_counter: &mut i32,
+    ) -> Result<(), Self::Error> {
+        let db = data.database();
+        let issue = Issue::by_activity_pub_id_optional(self.object.id.inner().as_str(), db).await?;
+        let Some(issue) = issue else {
+            // We never received this ticket, nothing to update
+            return Ok(());
+        };
+
+        let repository = Repository::by_id(issue.repository_id, db).await?;
+        if !issue.is_managed_by(self.actor.inner(), &repository) {
+            return Err(AppError::Unauthorized);
+        }
+
+        let editor = self.actor.dereference_local(data).await?;
+        issue
+            .update_content(
+                editor.id,
+                &self.object.summary,
+                &self.object.source.content,
+                db,
+            )
+            .await
+    }
+}
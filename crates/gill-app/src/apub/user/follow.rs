@@ -4,7 +4,7 @@ use crate::instance::InstanceHandle;
 use crate::apub::common::{GillActivity, GillApubObject};
 use crate::domain::user::User;
 use activitypub_federation::{core::object_id::ObjectId, data::Data, traits::ActivityHandler};
-use activitystreams_kinds::activity::FollowType;
+use activitystreams_kinds::activity::{FollowType, UndoType};
 use axum::async_trait;
 use serde::{Deserialize, Serialize};
 use url::Url;
@@ -13,7 +13,10 @@ use url::Url;
 #[serde(rename_all = "camelCase")]
 pub struct Follow {
     id: Url,
+    /// Other implementations use the plain ActivityStreams `actor` and `object` properties
+    #[serde(alias = "actor")]
     pub follower: ObjectId<User>,
+    #[serde(alias = "object")]
     pub followed: ObjectId<User>,
     r#type: FollowType,
 }
@@ -67,3 +70,68 @@ impl ActivityHandler for Follow {
         Ok(())
     }
 }
+
+/// Stop following a user
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct UndoFollow {
+    id: Url,
+    pub actor: ObjectId<User>,
+    pub object: Follow,
+    r#type: UndoType,
+}
+
+impl GillActivity for UndoFollow {
+    fn forward_addresses(&self) -> Vec<&Url> {
+        vec![]
+    }
+}
+
+impl UndoFollow {
+    pub fn new(object: Follow, id: Url) -> UndoFollow {
+        UndoFollow {
+            id,
+            actor: object.follower.clone(),
+            object,
+            r#type: Default::default(),
+        }
+    }
+}
+
+#[async_trait]
+impl ActivityHandler for UndoFollow {
+    type DataType = InstanceHandle;
+    type Error = AppError;
+
+    fn id(&self) -> &Url {
+        &self.id
+    }
+
+    fn actor(&self) -> &Url {
+        self.actor.inner()
+    }
+
+    async fn receive(
+        self,
+        data: &Data<Self::DataType>,
+        _request_counter: &mut i32,
+    ) -> Result<(), Self::Error> {
+        // Only the author of the original activity can undo it
+        if self.object.follower.inner() != self.actor.inner() {
+            return Err(AppError::Unauthorized);
+        }
+
+        let followed = ObjectId::<User>::new(self.object.followed)
+            .dereference_local(data)
+            .await?;
+
+        let follower = ObjectId::<User>::new(self.object.follower)
+            .dereference_local(data)
+            .await?;
+
+        followed
+            .remove_follower(follower.local_id(), data.database())
+            .await?;
+        Ok(())
+    }
+}
@@ -1,7 +1,13 @@
 use crate::apub::common::{GillActivity, GillApubObject};
+use crate::apub::release::create::CreateRelease;
 
+use crate::apub::delete::Delete;
 use crate::apub::ticket::accept::AcceptTicket;
 use crate::apub::ticket::comment::create::CreateTicketComment;
+use crate::apub::ticket::comment::update::UpdateTicketComment;
+use crate::apub::ticket::reaction::{ReactToTicket, UndoReactToTicket};
+use crate::apub::ticket::resolve::{ResolveTicket, UndoResolveTicket};
+use crate::apub::ticket::update::UpdateTicket;
 
 use crate::domain::id::ActivityPubId;
 use crate::domain::user::create::CreateUser;
@@ -13,8 +19,9 @@ use activitypub_federation::core::signatures::PublicKey;
 use activitypub_federation::data::Data;
 use activitypub_federation::traits::{ActivityHandler, Actor, ApubObject};
 use activitystreams_kinds::actor::PersonType;
+use anyhow::anyhow;
 use async_session::async_trait;
-use follow::Follow;
+use follow::{Follow, UndoFollow};
 
 use serde::{Deserialize, Serialize};
 
@@ -28,16 +35,34 @@ pub mod follow;
 #[enum_delegate::implement(ActivityHandler)]
 pub enum PersonAcceptedActivities {
     Follow(Follow),
+    UndoFollow(UndoFollow),
     AcceptTicket(AcceptTicket),
     CreateIssueComment(CreateTicketComment),
+    CreateRelease(CreateRelease),
+    UpdateIssue(UpdateTicket),
+    ResolveIssue(ResolveTicket),
+    ReopenIssue(UndoResolveTicket),
+    UpdateIssueComment(UpdateTicketComment),
+    Delete(Delete),
+    ReactToIssue(ReactToTicket),
+    UndoReactToIssue(UndoReactToTicket),
 }
 
 impl GillActivity for PersonAcceptedActivities {
     fn forward_addresses(&self) -> Vec<&Url> {
         match self {
             PersonAcceptedActivities::Follow(activity) => activity.forward_addresses(),
+            PersonAcceptedActivities::UndoFollow(activity) => activity.forward_addresses(),
             PersonAcceptedActivities::AcceptTicket(activity) => activity.forward_addresses(),
             PersonAcceptedActivities::CreateIssueComment(activity) => activity.forward_addresses(),
+            PersonAcceptedActivities::CreateRelease(activity) => activity.forward_addresses(),
+            PersonAcceptedActivities::UpdateIssue(activity) => activity.forward_addresses(),
+            PersonAcceptedActivities::ResolveIssue(activity) => activity.forward_addresses(),
+            PersonAcceptedActivities::ReopenIssue(activity) => activity.forward_addresses(),
+            PersonAcceptedActivities::UpdateIssueComment(activity) => activity.forward_addresses(),
+            PersonAcceptedActivities::Delete(activity) => activity.forward_addresses(),
+            PersonAcceptedActivities::ReactToIssue(activity) => activity.forward_addresses(),
+            PersonAcceptedActivities::UndoReactToIssue(activity) => activity.forward_addresses(),
         }
     }
 }
@@ -49,11 +74,16 @@ pub struct ApubUser {
     pub kind: PersonType,
     pub id: ObjectId<User>,
     pub email: Option<String>,
+    #[serde(alias = "preferredUsername")]
     pub username: String,
     pub outbox: Url,
     pub inbox: Url,
-    pub domain: String,
+    /// Only sent by gill instances, derived from the actor id otherwise
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub domain: Option<String>,
     pub followers: Url,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub following: Option<Url>,
     pub public_key: PublicKey,
 }
 
@@ -79,6 +109,10 @@ impl GillApubObject for User {
         self.id
     }
 
+    fn actor_id(&self) -> Url {
+        self.activity_pub_id.clone().into()
+    }
+
     fn public_key_with_owner(&self) -> Result<PublicKey, ParseError> {
         Ok(PublicKey::new_main_key(
             self.activity_pub_id.clone().into(),
@@ -110,6 +144,7 @@ impl ApubObject for User {
 
     async fn into_apub(self, _data: &Self::DataType) -> Result<Self::ApubType, Self::Error> {
         let public_key = self.public_key_with_owner()?;
+        let following = Some(self.following_url()?);
         Ok(ApubUser {
             kind: Default::default(),
             id: self.activity_pub_id.into(),
@@ -117,9 +152,10 @@ impl ApubObject for User {
             email: self.email,
             username: self.username,
             outbox: self.outbox_url,
-            domain: self.domain,
+            domain: Some(self.domain),
             inbox: self.inbox_url,
             followers: self.followers_url,
+            following,
         })
     }
 
@@ -134,6 +170,19 @@ impl ApubObject for User {
         if let Some(user) = user {
             Ok(user)
         } else {
+            let domain = match apub.domain {
+                Some(domain) => domain,
+                None => {
+                    let host = id
+                        .host_str()
+                        .ok_or_else(|| anyhow!("Actor id {id} has no domain"))?;
+                    match id.port() {
+                        Some(port) => format!("{host}:{port}"),
+                        None => host.to_string(),
+                    }
+                }
+            };
+
             let user = CreateUser {
                 username: apub.username,
                 email: apub.email,
@@ -142,7 +191,7 @@ impl ApubObject for User {
                 activity_pub_id: ActivityPubId::from(id),
                 outbox_url: apub.outbox,
                 inbox_url: apub.inbox,
-                domain: apub.domain,
+                domain,
                 followers_url: apub.followers,
                 is_local: false,
             };
@@ -0,0 +1,140 @@
+use crate::error::AppResult;
+use activitypub_federation::traits::ActivityHandler;
+use activitystreams_kinds::public;
+use anyhow::anyhow;
+use gill_db::activity::{Activity as ActivityEntity, CreateActivity};
+use gill_db::Insert;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use sqlx::PgPool;
+use url::Url;
+
+/// An activity published by a local actor
+#[derive(Debug, Clone)]
+pub struct Activity {
+    pub id: i32,
+    pub activity_pub_id: Url,
+    pub actor: Url,
+    pub kind: String,
+    pub payload: serde_json::Value,
+    pub published: chrono::NaiveDateTime,
+}
+
+impl TryFrom<ActivityEntity> for Activity {
+    type Error = url::ParseError;
+
+    fn try_from(activity: ActivityEntity) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: activity.id,
+            activity_pub_id: Url::parse(&activity.activity_pub_id)?,
+            actor: Url::parse(&activity.actor)?,
+            kind: activity.kind,
+            payload: activity.payload,
+            published: activity.published,
+        })
+    }
+}
+
+impl Activity {
+    /// Persist an outgoing activity so it can be replayed from its actor outbox
+    pub async fn save<A>(activity: &A, db: &PgPool) -> AppResult<Activity>
+    where
+        A: ActivityHandler + Serialize,
+    {
+        let payload = serde_json::to_value(activity)?;
+        let kind = payload
+            .get("type")
+            .and_then(serde_json::Value::as_str)
+            .map(ToString::to_string)
+            .ok_or_else(|| anyhow!("activity has no 'type' field"))?;
+
+        let activity = CreateActivity {
+            activity_pub_id: activity.id().to_string(),
+            actor: activity.actor().to_string(),
+            kind,
+            public: is_public(&payload),
+            payload,
+        };
+
+        let activity = activity.insert(db).await?;
+        Activity::try_from(activity).map_err(Into::into)
+    }
+
+    /// Last `kind` activity sent by `actor` whose `object_field` is `object`, undo activities
+    /// embed the activity they revert
+    pub async fn last_sent<A>(
+        actor: &Url,
+        kind: &str,
+        object_field: &str,
+        object: &Url,
+        db: &PgPool,
+    ) -> AppResult<Option<A>>
+    where
+        A: DeserializeOwned,
+    {
+        let activity =
+            ActivityEntity::last_sent(actor.as_str(), kind, object_field, object.as_str(), db)
+                .await?;
+
+        activity
+            .map(|activity| serde_json::from_value(activity.payload))
+            .transpose()
+            .map_err(Into::into)
+    }
+
+    /// Publicly addressed activities of `actor`, these are the ones served in its outbox
+    pub async fn list_public_by_actor(
+        actor: &Url,
+        limit: i64,
+        offset: i64,
+        db: &PgPool,
+    ) -> AppResult<Vec<Activity>> {
+        let activities =
+            ActivityEntity::list_public_by_actor(actor.as_str(), limit, offset, db).await?;
+        let activities = activities
+            .into_iter()
+            .map(Activity::try_from)
+            .filter_map(Result::ok)
+            .collect();
+
+        Ok(activities)
+    }
+
+    pub async fn count_public_by_actor(actor: &Url, db: &PgPool) -> AppResult<i64> {
+        ActivityEntity::count_public_by_actor(actor.as_str(), db)
+            .await
+            .map_err(Into::into)
+    }
+}
+
+/// Whether `to` or `cc` names the public collection, in any of its accepted forms
+fn is_public(payload: &serde_json::Value) -> bool {
+    let public = public().to_string();
+    let is_public = |value: &serde_json::Value| matches!(value.as_str(), Some(address) if address == public || address == "as:Public" || address == "Public");
+
+    ["to", "cc"].iter().any(|field| match payload.get(field) {
+        Some(serde_json::Value::Array(addresses)) => addresses.iter().any(is_public),
+        Some(address) => is_public(address),
+        None => false,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::is_public;
+    use serde_json::json;
+    use speculoos::prelude::*;
+
+    #[test]
+    fn should_only_treat_publicly_addressed_activities_as_public() {
+        let public = json!({ "to": ["https://www.w3.org/ns/activitystreams#Public"] });
+        let public_cc = json!({ "to": "https://gill.test/users/alice", "cc": "as:Public" });
+        let followers = json!({ "to": ["https://gill.test/users/alice/followers"] });
+        let direct = json!({ "to": "https://gill.test/users/bob" });
+
+        assert_that!(is_public(&public)).is_true();
+        assert_that!(is_public(&public_cc)).is_true();
+        assert_that!(is_public(&followers)).is_false();
+        assert_that!(is_public(&direct)).is_false();
+    }
+}
@@ -4,7 +4,6 @@ use crate::domain::user::User;
 use crate::error::AppResult;
 use gill_git::commits::OwnedCommit;
 use gill_git::diffs::Diff;
-use gill_git::GitRepository;
 use sqlx::PgPool;
 
 #[derive(Debug, Clone)]
@@ -49,7 +48,7 @@ impl Repository {
         branch: &str,
         db: &PgPool,
     ) -> AppResult<Vec<Commit>> {
-        let repo = GitRepository::open(owner, name)?;
+        let repo = Repository::open_git(owner, name, db).await?;
         let git_commits = repo.history(branch)?;
         let mut commits = vec![];
 
@@ -68,7 +67,7 @@ impl Repository {
         pull_request: &PullRequest,
         db: &PgPool,
     ) -> AppResult<Vec<Commit>> {
-        let repo = GitRepository::open(owner, name)?;
+        let repo = Repository::open_git(owner, name, db).await?;
         let git_commits =
             repo.list_commits_between_ref(&pull_request.base, &pull_request.compare)?;
         let mut commits = vec![];
@@ -88,7 +87,7 @@ impl Repository {
         sha: &str,
         db: &PgPool,
     ) -> AppResult<(Commit, Vec<Diff>)> {
-        let repo = GitRepository::open(owner, name)?;
+        let repo = Repository::open_git(owner, name, db).await?;
         let git_commits = repo.find_commit(sha)?;
         let commit = Commit::from_git_commit(git_commits, db).await?;
         let diff = repo.commit_diff(sha)?;
@@ -0,0 +1,277 @@
+use crate::domain::user::User;
+use crate::error::{AppError, AppResult};
+use anyhow::anyhow;
+use chrono::{Duration, NaiveDateTime, Utc};
+use gill_db::delivery::{
+    Delivery as DeliveryEntity, DeliveryDomain, DeliveryState as DeliveryStateEntity,
+};
+use sqlx::PgPool;
+use url::Url;
+
+/// A delivery is dead lettered after this many failed attempts
+pub const MAX_DELIVERY_ATTEMPTS: i32 = 10;
+/// Consecutive failures after which we stop delivering to a domain for a while
+pub const CIRCUIT_BREAKER_THRESHOLD: i32 = 5;
+const CIRCUIT_BREAKER_OPEN_MINUTES: i64 = 15;
+const BASE_BACKOFF_SECONDS: i64 = 30;
+const MAX_BACKOFF_SECONDS: i64 = 6 * 60 * 60;
+/// Time a claimed delivery stays hidden from other workers
+const CLAIM_LEASE_SECONDS: f64 = 5.0 * 60.0;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum DeliveryState {
+    Pending,
+    Failed,
+}
+
+/// An outgoing activity queued for a remote inbox
+#[derive(Debug, Clone)]
+pub struct Delivery {
+    pub id: i32,
+    pub activity_id: String,
+    pub sender: Url,
+    pub inbox: Url,
+    pub domain: String,
+    pub payload: serde_json::Value,
+    pub state: DeliveryState,
+    pub attempts: i32,
+    pub last_error: Option<String>,
+    pub next_attempt_at: NaiveDateTime,
+    pub created_at: NaiveDateTime,
+}
+
+impl From<DeliveryStateEntity> for DeliveryState {
+    fn from(state: DeliveryStateEntity) -> Self {
+        match state {
+            DeliveryStateEntity::Pending => DeliveryState::Pending,
+            DeliveryStateEntity::Failed => DeliveryState::Failed,
+        }
+    }
+}
+
+impl From<DeliveryState> for DeliveryStateEntity {
+    fn from(state: DeliveryState) -> Self {
+        match state {
+            DeliveryState::Pending => DeliveryStateEntity::Pending,
+            DeliveryState::Failed => DeliveryStateEntity::Failed,
+        }
+    }
+}
+
+impl TryFrom<DeliveryEntity> for Delivery {
+    type Error = url::ParseError;
+
+    fn try_from(delivery: DeliveryEntity) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: delivery.id,
+            activity_id: delivery.activity_id,
+            sender: Url::parse(&delivery.sender)?,
+            inbox: Url::parse(&delivery.inbox)?,
+            domain: delivery.domain,
+            payload: delivery.payload,
+            state: delivery.state.into(),
+            attempts: delivery.attempts,
+            last_error: delivery.last_error,
+            next_attempt_at: delivery.next_attempt_at,
+            created_at: delivery.created_at,
+        })
+    }
+}
+
+/// Delay before the next attempt once a delivery failed `attempts` times
+pub fn backoff(attempts: i32) -> Duration {
+    let exponent = attempts.clamp(1, 20) as u32 - 1;
+    let seconds = BASE_BACKOFF_SECONDS.saturating_mul(2_i64.pow(exponent));
+    Duration::seconds(seconds.min(MAX_BACKOFF_SECONDS))
+}
+
+impl Delivery {
+    /// Queue `activity` for each recipient inbox, it will be signed with `sender` keys
+    pub async fn enqueue(
+        activity: &serde_json::Value,
+        sender: &Url,
+        recipients: &[Url],
+        db: &PgPool,
+    ) -> AppResult<()> {
+        let activity_id = activity
+            .get("id")
+            .and_then(serde_json::Value::as_str)
+            .ok_or_else(|| anyhow!("activity has no 'id' field"))?;
+
+        let mut inboxes = vec![];
+        let mut domains = vec![];
+        for inbox in recipients {
+            let Some(domain) = inbox.domain() else {
+                continue;
+            };
+
+            if !inboxes.contains(&inbox.to_string()) {
+                inboxes.push(inbox.to_string());
+                domains.push(domain.to_string());
+            }
+        }
+
+        if inboxes.is_empty() {
+            return Ok(());
+        }
+
+        DeliveryEntity::enqueue(
+            activity_id,
+            sender.as_str(),
+            activity,
+            &inboxes,
+            &domains,
+            db,
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    /// Claim the deliveries due for an attempt
+    pub async fn claim_due(limit: i64, db: &PgPool) -> AppResult<Vec<Delivery>> {
+        let deliveries = DeliveryEntity::claim_due(limit, CLAIM_LEASE_SECONDS, db).await?;
+        let mut claimed = vec![];
+        for delivery in deliveries {
+            let id = delivery.id;
+            match Delivery::try_from(delivery) {
+                Ok(delivery) => claimed.push(delivery),
+                Err(err) => DeliveryEntity::fail(id, &err.to_string(), db).await?,
+            }
+        }
+
+        Ok(claimed)
+    }
+
+    /// When the circuit breaker of the delivery domain is open, the time it closes
+    pub async fn circuit_open_until(&self, db: &PgPool) -> AppResult<Option<NaiveDateTime>> {
+        let domain = DeliveryDomain::by_domain(&self.domain, db).await?;
+        let now = Utc::now().naive_utc();
+        Ok(domain
+            .and_then(|domain| domain.open_until)
+            .filter(|open_until| *open_until > now))
+    }
+
+    pub async fn postpone(&self, until: NaiveDateTime, db: &PgPool) -> AppResult<()> {
+        DeliveryEntity::postpone(self.id, until, db).await?;
+        Ok(())
+    }
+
+    pub async fn delivered(&self, db: &PgPool) -> AppResult<()> {
+        DeliveryEntity::delete(self.id, db).await?;
+        DeliveryDomain::reset(&self.domain, db).await?;
+        Ok(())
+    }
+
+    /// Schedule a new attempt with exponential backoff, or dead letter the delivery
+    /// once it ran out of attempts. Too many consecutive failures on the same domain
+    /// open its circuit breaker.
+    pub async fn failed(&self, error: &str, db: &PgPool) -> AppResult<()> {
+        let attempts = self.attempts + 1;
+        if attempts >= MAX_DELIVERY_ATTEMPTS {
+            DeliveryEntity::fail(self.id, error, db).await?;
+        } else {
+            let next_attempt_at = Utc::now().naive_utc() + backoff(attempts);
+            DeliveryEntity::reschedule(self.id, error, next_attempt_at, db).await?;
+        }
+
+        let failures = DeliveryDomain::record_failure(&self.domain, db).await?;
+        if failures >= CIRCUIT_BREAKER_THRESHOLD {
+            let open_until =
+                Utc::now().naive_utc() + Duration::minutes(CIRCUIT_BREAKER_OPEN_MINUTES);
+            DeliveryDomain::open(&self.domain, open_until, db).await?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn list(
+        user: &User,
+        state: DeliveryState,
+        limit: i64,
+        offset: i64,
+        db: &PgPool,
+    ) -> AppResult<Vec<Delivery>> {
+        if !user.is_admin() {
+            return Err(AppError::Unauthorized);
+        }
+
+        let deliveries = DeliveryEntity::list(state.into(), limit, offset, db).await?;
+        let deliveries = deliveries
+            .into_iter()
+            .map(Delivery::try_from)
+            .filter_map(Result::ok)
+            .collect();
+
+        Ok(deliveries)
+    }
+
+    pub async fn count(user: &User, state: DeliveryState, db: &PgPool) -> AppResult<i64> {
+        if !user.is_admin() {
+            return Err(AppError::Unauthorized);
+        }
+
+        DeliveryEntity::count(state.into(), db)
+            .await
+            .map_err(Into::into)
+    }
+
+    /// Put a dead lettered delivery back in the queue
+    pub async fn retry(user: &User, id: i32, db: &PgPool) -> AppResult<()> {
+        if !user.is_admin() {
+            return Err(AppError::Unauthorized);
+        }
+
+        DeliveryEntity::retry(id, db).await?;
+        Ok(())
+    }
+}
+
+/// A remote domain deliveries are currently failing for
+#[derive(Debug)]
+pub struct FailingDomain {
+    pub domain: String,
+    pub consecutive_failures: i32,
+    pub open_until: Option<NaiveDateTime>,
+}
+
+impl FailingDomain {
+    pub async fn list(user: &User, db: &PgPool) -> AppResult<Vec<FailingDomain>> {
+        if !user.is_admin() {
+            return Err(AppError::Unauthorized);
+        }
+
+        let now = Utc::now().naive_utc();
+        let domains = DeliveryDomain::list_failing(db)
+            .await?
+            .into_iter()
+            .map(|domain| FailingDomain {
+                domain: domain.domain,
+                consecutive_failures: domain.consecutive_failures,
+                open_until: domain.open_until.filter(|open_until| *open_until > now),
+            })
+            .collect();
+
+        Ok(domains)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::domain::delivery::{backoff, MAX_BACKOFF_SECONDS};
+    use chrono::Duration;
+    use speculoos::prelude::*;
+
+    #[test]
+    fn backoff_should_double_on_each_attempt() {
+        assert_that!(backoff(1)).is_equal_to(Duration::seconds(30));
+        assert_that!(backoff(2)).is_equal_to(Duration::seconds(60));
+        assert_that!(backoff(5)).is_equal_to(Duration::seconds(480));
+    }
+
+    #[test]
+    fn backoff_should_be_capped() {
+        assert_that!(backoff(15)).is_equal_to(Duration::seconds(MAX_BACKOFF_SECONDS));
+        assert_that!(backoff(i32::MAX)).is_equal_to(Duration::seconds(MAX_BACKOFF_SECONDS));
+    }
+}
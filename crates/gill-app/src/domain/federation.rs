@@ -0,0 +1,220 @@
+use crate::apub::common::is_local;
+use crate::domain::user::User;
+use crate::error::{AppError, AppResult};
+use gill_db::federation::{
+    federation_mode, set_federation_mode, DomainPolicy as DomainPolicyEntity, DomainRule,
+    FederationMode as FederationModeEntity, KnownInstance as KnownInstanceEntity,
+};
+use gill_settings::SETTINGS;
+use sqlx::PgPool;
+use url::Url;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum FederationMode {
+    Blocklist,
+    Allowlist,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum DomainPolicy {
+    Allowed,
+    Blocked,
+    Silenced,
+}
+
+#[derive(Debug)]
+pub struct KnownInstance {
+    pub domain: String,
+    pub user_count: i64,
+    pub repository_count: i64,
+    pub policy: Option<DomainPolicy>,
+    pub reason: Option<String>,
+}
+
+impl From<FederationModeEntity> for FederationMode {
+    fn from(mode: FederationModeEntity) -> Self {
+        match mode {
+            FederationModeEntity::Blocklist => FederationMode::Blocklist,
+            FederationModeEntity::Allowlist => FederationMode::Allowlist,
+        }
+    }
+}
+
+impl From<FederationMode> for FederationModeEntity {
+    fn from(mode: FederationMode) -> Self {
+        match mode {
+            FederationMode::Blocklist => FederationModeEntity::Blocklist,
+            FederationMode::Allowlist => FederationModeEntity::Allowlist,
+        }
+    }
+}
+
+impl From<DomainPolicyEntity> for DomainPolicy {
+    fn from(policy: DomainPolicyEntity) -> Self {
+        match policy {
+            DomainPolicyEntity::Allowed => DomainPolicy::Allowed,
+            DomainPolicyEntity::Blocked => DomainPolicy::Blocked,
+            DomainPolicyEntity::Silenced => DomainPolicy::Silenced,
+        }
+    }
+}
+
+impl From<DomainPolicy> for DomainPolicyEntity {
+    fn from(policy: DomainPolicy) -> Self {
+        match policy {
+            DomainPolicy::Allowed => DomainPolicyEntity::Allowed,
+            DomainPolicy::Blocked => DomainPolicyEntity::Blocked,
+            DomainPolicy::Silenced => DomainPolicyEntity::Silenced,
+        }
+    }
+}
+
+/// Instance wide federation policy, managed by the instance admins
+pub struct FederationPolicy;
+
+impl FederationPolicy {
+    /// Whether we accept to federate with `url`, local urls are always allowed.
+    /// A rule on a domain also applies to its subdomains, unless they have their own rule.
+    pub async fn is_allowed(url: &Url, db: &PgPool) -> AppResult<bool> {
+        if is_local(url) {
+            return Ok(true);
+        }
+
+        let Some(domain) = url.domain() else {
+            return Ok(false);
+        };
+
+        let mode = federation_mode(db).await?;
+        let policy = DomainRule::matching(domain, db)
+            .await?
+            .map(|rule| DomainPolicy::from(rule.policy));
+
+        Ok(is_allowed(mode.into(), policy))
+    }
+
+    /// Reject activities sent by actors living on a domain we do not federate with
+    pub async fn check_actor(actor: &Url, db: &PgPool) -> AppResult<()> {
+        if Self::is_allowed(actor, db).await? {
+            Ok(())
+        } else {
+            tracing::warn!("Rejected activity from blocked actor {actor}");
+            Err(AppError::Unauthorized)
+        }
+    }
+
+    pub async fn mode(db: &PgPool) -> AppResult<FederationMode> {
+        let mode = federation_mode(db).await?;
+        Ok(mode.into())
+    }
+
+    pub async fn set_mode(user: &User, mode: FederationMode, db: &PgPool) -> AppResult<()> {
+        ensure_admin(user)?;
+        set_federation_mode(mode.into(), db).await?;
+        Ok(())
+    }
+
+    pub async fn set_domain_policy(
+        user: &User,
+        domain: &str,
+        policy: DomainPolicy,
+        reason: Option<&str>,
+        db: &PgPool,
+    ) -> AppResult<()> {
+        ensure_admin(user)?;
+        let domain = domain.trim().to_lowercase();
+        if domain.is_empty() || domain == SETTINGS.domain {
+            return Err(anyhow::anyhow!("Invalid domain '{domain}'").into());
+        }
+
+        DomainRule::upsert(&domain, policy.into(), reason, db).await?;
+        Ok(())
+    }
+
+    pub async fn remove_domain_policy(user: &User, domain: &str, db: &PgPool) -> AppResult<()> {
+        ensure_admin(user)?;
+        DomainRule::delete(domain, db).await?;
+        Ok(())
+    }
+
+    pub async fn known_instances(db: &PgPool) -> AppResult<Vec<KnownInstance>> {
+        let rules = DomainRule::list(db).await?;
+        let instances = KnownInstanceEntity::list(db)
+            .await?
+            .into_iter()
+            .map(|instance| {
+                let reason = rules
+                    .iter()
+                    .find(|rule| rule.domain == instance.domain)
+                    .and_then(|rule| rule.reason.clone());
+
+                KnownInstance {
+                    domain: instance.domain,
+                    user_count: instance.user_count,
+                    repository_count: instance.repository_count,
+                    policy: instance.policy.map(DomainPolicy::from),
+                    reason,
+                }
+            })
+            .collect();
+
+        Ok(instances)
+    }
+}
+
+fn ensure_admin(user: &User) -> AppResult<()> {
+    if user.is_admin() {
+        Ok(())
+    } else {
+        Err(AppError::Unauthorized)
+    }
+}
+
+fn is_allowed(mode: FederationMode, policy: Option<DomainPolicy>) -> bool {
+    match (mode, policy) {
+        (_, Some(DomainPolicy::Blocked)) => false,
+        (_, Some(DomainPolicy::Allowed | DomainPolicy::Silenced)) => true,
+        (FederationMode::Blocklist, None) => true,
+        (FederationMode::Allowlist, None) => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::domain::federation::{is_allowed, DomainPolicy, FederationMode};
+    use speculoos::prelude::*;
+
+    #[test]
+    fn blocklist_should_only_reject_blocked_domains() {
+        assert_that!(is_allowed(FederationMode::Blocklist, None)).is_true();
+        assert_that!(is_allowed(
+            FederationMode::Blocklist,
+            Some(DomainPolicy::Silenced)
+        ))
+        .is_true();
+        assert_that!(is_allowed(
+            FederationMode::Blocklist,
+            Some(DomainPolicy::Blocked)
+        ))
+        .is_false();
+    }
+
+    #[test]
+    fn allowlist_should_only_accept_listed_domains() {
+        assert_that!(is_allowed(FederationMode::Allowlist, None)).is_false();
+        assert_that!(is_allowed(
+            FederationMode::Allowlist,
+            Some(DomainPolicy::Allowed)
+        ))
+        .is_true();
+        assert_that!(is_allowed(
+            FederationMode::Allowlist,
+            Some(DomainPolicy::Silenced)
+        ))
+        .is_true();
+        assert_that!(is_allowed(
+            FederationMode::Allowlist,
+            Some(DomainPolicy::Blocked)
+        ))
+        .is_false();
+    }
+}
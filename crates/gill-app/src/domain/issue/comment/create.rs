@@ -1,14 +1,14 @@
 use crate::apub::common::GillApubObject;
 use crate::apub::ticket::comment::create::CreateTicketComment;
 use crate::domain::issue::comment::IssueComment;
-use std::collections::HashSet;
 
 use crate::domain::id::ActivityPubId;
 use crate::domain::repository::Repository;
+use crate::domain::user::activity::UserActivityKind;
 use crate::domain::user::User;
 use crate::error::AppResult;
 use crate::instance::InstanceHandle;
-use activitypub_federation::traits::{Actor, ApubObject};
+use activitypub_federation::traits::ApubObject;
 use chrono::Utc;
 use gill_settings::SETTINGS;
 use tracing::debug;
@@ -52,34 +52,30 @@ impl CreateIssueCommentCommand<'_> {
             context: issue.activity_pub_id.clone(),
             in_reply_to: issue.activity_pub_id.clone().into(),
             published: Utc::now().naive_utc(),
+            deleted_at: None,
         };
 
         let comment = comment.save(db).await?;
+        issue.add_subscriber(author.id, db).await?;
+
+        issue
+            .record_activity(author.id, UserActivityKind::CommentIssue, db)
+            .await;
+        issue
+            .notify(author.id, "commented", Some(&comment.content), db)
+            .await;
+        issue
+            .record_references(author.id, &comment.content, db)
+            .await;
         let hostname = instance.local_instance().hostname();
         let id = format!("https://{hostname}/activity/{uuid}", uuid = Uuid::new_v4());
 
-        let subscribers = issue.get_subscribers_inbox(i64::MAX, 0, db).await?;
-        let subscribers: Vec<Url> = subscribers
-            .into_iter()
-            .filter_map(|inbox| Url::parse(&inbox).ok())
-            .collect();
-
-        let mut recipients = HashSet::new();
-        let repository_followers = repository.followers(instance).await?;
-        let user_followers = author.followers(instance).await?;
-        recipients.insert(repository.shared_inbox_or_inbox());
-        recipients.extend(repository_followers);
-        recipients.extend(subscribers);
-        recipients.extend(user_followers);
+        let mut audience = issue.audience(&repository, &author, instance).await?;
+        audience.add_mentions(&comment.content, db).await?;
 
         let create_event = CreateTicketComment {
             actor: author.activity_pub_id.clone().into(),
-            to: vec![
-                repository.activity_pub_id.into(),
-                repository.followers_url,
-                issue.followers_url,
-                author.followers_url.clone(),
-            ],
+            to: audience.to,
             object: comment.into_apub(instance).await?,
             kind: Default::default(),
             id: Url::parse(&id)?,
@@ -87,18 +83,15 @@ impl CreateIssueCommentCommand<'_> {
 
         debug!(
             "Sending CreateComment event to {:#?}",
-            recipients
+            audience
+                .inboxes
                 .iter()
                 .map(ToString::to_string)
                 .collect::<Vec<String>>()
         );
 
         author
-            .send(
-                create_event,
-                recipients.into_iter().collect(),
-                &instance.local_instance,
-            )
+            .send(create_event, audience.inboxes, instance)
             .await?;
 
         Ok(())
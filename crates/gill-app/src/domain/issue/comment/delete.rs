@@ -0,0 +1,43 @@
+use crate::apub::common::{GillApubObject, Tombstone};
+use crate::apub::delete::Delete;
+use crate::domain::issue::comment::IssueComment;
+use crate::domain::repository::Repository;
+use crate::domain::user::User;
+use crate::error::{AppError, AppResult};
+use crate::instance::InstanceHandle;
+use chrono::Utc;
+use url::Url;
+use uuid::Uuid;
+
+impl IssueComment {
+    /// Replace the comment with a tombstone and send a `Delete` activity to the issue audience
+    pub async fn delete_by(&self, user: &User, instance: &InstanceHandle) -> AppResult<()> {
+        let db = instance.database();
+        let repository = Repository::by_id(self.repository_id, db).await?;
+        if !self.is_managed_by(&user.activity_pub_id.clone().into(), &repository) {
+            return Err(AppError::Unauthorized);
+        }
+
+        let issue = repository.issue_by_number(self.number, db).await?;
+        self.mark_deleted(db).await?;
+
+        let audience = issue.audience(&repository, user, instance).await?;
+        let hostname = instance.local_instance().hostname();
+        let id = format!("https://{hostname}/activity/{uuid}", uuid = Uuid::new_v4());
+
+        let delete = Delete {
+            id: Url::parse(&id)?,
+            kind: Default::default(),
+            actor: user.activity_pub_id.clone().into(),
+            to: audience.to,
+            object: Tombstone {
+                kind: Default::default(),
+                id: self.activity_pub_id.clone().into(),
+                former_type: Some("Note".to_string()),
+                deleted: Some(Utc::now().naive_utc()),
+            },
+        };
+
+        user.send(delete, audience.inboxes, instance).await
+    }
+}
@@ -1,3 +1,4 @@
+use chrono::NaiveDateTime;
 use gill_db::repository::issue::comment::IssueCommentDigest as IssueCommentDigestEntity;
 use uuid::Uuid;
 
@@ -7,6 +8,8 @@ pub struct IssueCommentDigest {
     pub repository_id: i32,
     pub created_by: String,
     pub content: String,
+    pub published: NaiveDateTime,
+    pub deleted_at: Option<NaiveDateTime>,
 }
 
 impl From<IssueCommentDigestEntity> for IssueCommentDigest {
@@ -16,6 +19,8 @@ impl From<IssueCommentDigestEntity> for IssueCommentDigest {
             repository_id: comment.repository_id,
             created_by: comment.created_by,
             content: comment.content,
+            published: comment.published,
+            deleted_at: comment.deleted_at,
         }
     }
 }
@@ -1,5 +1,7 @@
 use crate::domain::id::ActivityPubId;
 use crate::domain::issue::Issue;
+use crate::domain::repository::Repository;
+use crate::domain::revision::RecordRevisionCommand;
 use crate::domain::user::User;
 use crate::error::{AppError, AppResult};
 use activitypub_federation::core::object_id::ObjectId;
@@ -12,9 +14,11 @@ use url::{ParseError, Url};
 use uuid::Uuid;
 
 pub mod create;
+pub mod delete;
 pub mod digest;
+pub mod update;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct IssueComment {
     pub id: Uuid,
     pub activity_pub_id: ActivityPubId<IssueComment>,
@@ -27,6 +31,7 @@ pub struct IssueComment {
     pub context: ActivityPubId<Issue>,
     pub in_reply_to: Url,
     pub published: chrono::NaiveDateTime,
+    pub deleted_at: Option<chrono::NaiveDateTime>,
 }
 
 impl TryFrom<IssueCommentEntity> for IssueComment {
@@ -45,6 +50,7 @@ impl TryFrom<IssueCommentEntity> for IssueComment {
             context: ActivityPubId::try_from(comment.context.clone())?,
             in_reply_to: Url::parse(&comment.in_reply_to)?,
             published: comment.published,
+            deleted_at: comment.deleted_at,
         })
     }
 }
@@ -63,6 +69,7 @@ impl From<&IssueComment> for IssueCommentEntity {
             context: val.context.to_string(),
             in_reply_to: val.in_reply_to.to_string(),
             published: val.published,
+            deleted_at: val.deleted_at,
         }
     }
 }
@@ -102,4 +109,46 @@ impl IssueComment {
         let entity = entity.insert(db).await?;
         IssueComment::try_from(entity).map_err(Into::into)
     }
+
+    pub async fn by_id(id: Uuid, db: &PgPool) -> AppResult<IssueComment> {
+        let entity = IssueCommentEntity::by_id(id, db).await?;
+        IssueComment::try_from(entity).map_err(Into::into)
+    }
+
+    /// Comments can be edited and deleted by their author and by the repository owner
+    pub fn is_managed_by(&self, actor: &Url, repository: &Repository) -> bool {
+        let actor = actor.as_str();
+        self.attributed_to.to_string() == actor || repository.attributed_to.to_string() == actor
+    }
+
+    /// Update the comment content and keep the previous one as a revision
+    pub async fn update_content(
+        &self,
+        editor_id: i32,
+        content: &str,
+        db: &PgPool,
+    ) -> AppResult<()> {
+        let entity: IssueCommentEntity = self.into();
+        entity.update_content(content, db).await?;
+        RecordRevisionCommand {
+            repository_id: self.repository_id,
+            number: self.number,
+            issue_comment_id: Some(self.id),
+            pull_request_comment_id: None,
+            old_title: None,
+            new_title: None,
+            old_content: &self.content,
+            new_content: content,
+            edited_by: editor_id,
+        }
+        .execute(db)
+        .await;
+
+        Ok(())
+    }
+
+    pub async fn mark_deleted(&self, db: &PgPool) -> AppResult<()> {
+        let entity: IssueCommentEntity = self.into();
+        entity.mark_deleted(db).await.map_err(Into::into)
+    }
 }
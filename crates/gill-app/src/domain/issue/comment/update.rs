@@ -0,0 +1,53 @@
+use crate::apub::common::GillApubObject;
+use crate::apub::ticket::comment::update::UpdateTicketComment;
+use crate::domain::issue::comment::IssueComment;
+use crate::domain::repository::Repository;
+use crate::domain::user::User;
+use crate::error::{AppError, AppResult};
+use crate::instance::InstanceHandle;
+use activitypub_federation::traits::ApubObject;
+use url::Url;
+use uuid::Uuid;
+
+impl IssueComment {
+    /// Edit the comment content, the comment author and the repository owner can do this
+    pub async fn edit(
+        &self,
+        user: &User,
+        content: &str,
+        instance: &InstanceHandle,
+    ) -> AppResult<()> {
+        if self.deleted_at.is_some() {
+            return Err(AppError::NotFound);
+        }
+
+        let db = instance.database();
+        let repository = Repository::by_id(self.repository_id, db).await?;
+        if !self.is_managed_by(&user.activity_pub_id.clone().into(), &repository) {
+            return Err(AppError::Unauthorized);
+        }
+
+        let content = content.escape_default().to_string();
+        self.update_content(user.id, &content, db).await?;
+
+        let issue = repository.issue_by_number(self.number, db).await?;
+        let audience = issue.audience(&repository, user, instance).await?;
+        let hostname = instance.local_instance().hostname();
+        let id = format!("https://{hostname}/activity/{uuid}", uuid = Uuid::new_v4());
+
+        let comment = IssueComment {
+            content,
+            ..self.clone()
+        };
+
+        let update = UpdateTicketComment {
+            id: Url::parse(&id)?,
+            kind: Default::default(),
+            actor: user.activity_pub_id.clone().into(),
+            to: audience.to,
+            object: comment.into_apub(instance).await?,
+        };
+
+        user.send(update, audience.inboxes, instance).await
+    }
+}
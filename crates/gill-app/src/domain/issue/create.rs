@@ -4,6 +4,7 @@ use crate::apub::ticket::offer::{ApubTicketOffer, OfferTicket};
 use crate::domain::id::ActivityPubId;
 use crate::domain::issue::{Issue, IssueState};
 use crate::domain::repository::Repository;
+use crate::domain::user::activity::UserActivityKind;
 use crate::domain::user::User;
 use crate::error::AppResult;
 use crate::instance::InstanceHandle;
@@ -18,6 +19,7 @@ use uuid::Uuid;
 pub struct CreateIssueCommand {
     title: String,
     content: String,
+    template: Option<String>,
 }
 
 impl From<CreateIssueForm> for CreateIssueCommand {
@@ -25,6 +27,7 @@ impl From<CreateIssueForm> for CreateIssueCommand {
         Self {
             title: form.title,
             content: form.content,
+            template: form.template.filter(|template| !template.is_empty()),
         }
     }
 }
@@ -82,6 +85,21 @@ impl CreateIssueCommand {
             };
 
             let issue = new_issue.save(db).await?;
+            issue
+                .record_activity(user.id, UserActivityKind::OpenIssue, db)
+                .await;
+            issue
+                .notify(user.id, "opened", Some(&issue.content), db)
+                .await;
+            issue.record_references(user.id, &issue.content, db).await;
+
+            // Apply the default labels of the template the issue was started from
+            if let Some(template) = &self.template {
+                if let Some(template) = repo.issue_template(owner, template, db).await? {
+                    repo.add_labels(issue.number, &template.labels, user.id, db)
+                        .await?;
+                }
+            }
 
             // Add the author to the list of subscriber
             issue.add_subscriber(user.local_id(), db).await?;
@@ -111,8 +129,7 @@ impl CreateIssueCommand {
                 result: ticket.id,
             };
 
-            repo.send(accept_ticket, recipient, &instance.local_instance)
-                .await?;
+            repo.send(accept_ticket, recipient, instance).await?;
 
             Ok(())
         } else {
@@ -143,8 +160,7 @@ impl CreateIssueCommand {
                 target: repository_activity_pub_id.clone().into(),
             };
 
-            user.send(offer, vec![repository.inbox_url], &instance.local_instance)
-                .await
+            user.send(offer, vec![repository.inbox_url], instance).await
         }
     }
 }
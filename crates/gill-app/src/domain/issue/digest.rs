@@ -1,6 +1,7 @@
 use crate::domain::issue::comment::digest::IssueCommentDigest;
 use crate::domain::issue::IssueState;
 use crate::error::AppResult;
+use chrono::NaiveDateTime;
 use gill_db::repository::issue::IssueDigest as IssueDigestEntity;
 use sqlx::PgPool;
 use std::cmp::Ordering;
@@ -13,6 +14,7 @@ pub struct IssueDigest {
     pub title: String,
     pub content: String,
     pub state: IssueState,
+    pub deleted_at: Option<NaiveDateTime>,
 }
 
 impl From<IssueDigestEntity> for IssueDigest {
@@ -24,6 +26,7 @@ impl From<IssueDigestEntity> for IssueDigest {
             title: issue.title,
             content: issue.content,
             state: issue.state.into(),
+            deleted_at: issue.deleted_at,
         }
     }
 }
@@ -37,6 +40,7 @@ impl From<IssueDigest> for IssueDigestEntity {
             title: val.title,
             content: val.content,
             state: val.state.into(),
+            deleted_at: val.deleted_at,
         }
     }
 }
@@ -0,0 +1,294 @@
+use crate::domain::issue::reference::resolve_repository_id;
+use crate::domain::issue::{Issue, IssueState};
+use crate::domain::repository::Repository;
+use crate::domain::user::User;
+use crate::error::{AppError, AppResult};
+use crate::instance::InstanceHandle;
+use activitypub_federation::core::object_id::ObjectId;
+use gill_db::repository::issue::link::{
+    CreateIssueLink, IssueLink as IssueLinkEntity, IssueLinkKind,
+};
+use gill_db::Insert;
+use gill_markdown::Reference;
+use sqlx::PgPool;
+use url::Url;
+
+/// How a linked issue relates to the issue it is displayed on
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum IssueRelation {
+    Blocks,
+    BlockedBy,
+    RelatesTo,
+    Duplicates,
+    DuplicatedBy,
+}
+
+/// An issue linked to another one, possibly from another repository or instance
+#[derive(Debug, Clone)]
+pub struct IssueLink {
+    pub id: i32,
+    pub relation: IssueRelation,
+    pub owner: String,
+    pub repository: String,
+    pub number: i32,
+    pub title: String,
+    pub state: IssueState,
+    pub activity_pub_id: String,
+}
+
+impl IssueRelation {
+    /// Relations offered when linking an issue, `DuplicatedBy` is declared from the duplicate
+    pub const CHOICES: [IssueRelation; 4] = [
+        IssueRelation::Blocks,
+        IssueRelation::BlockedBy,
+        IssueRelation::RelatesTo,
+        IssueRelation::Duplicates,
+    ];
+
+    fn of(kind: IssueLinkKind, incoming: bool) -> Self {
+        match (kind, incoming) {
+            (IssueLinkKind::Blocks, false) => IssueRelation::Blocks,
+            (IssueLinkKind::Blocks, true) => IssueRelation::BlockedBy,
+            (IssueLinkKind::RelatesTo, _) => IssueRelation::RelatesTo,
+            (IssueLinkKind::Duplicates, false) => IssueRelation::Duplicates,
+            (IssueLinkKind::Duplicates, true) => IssueRelation::DuplicatedBy,
+        }
+    }
+
+    /// Name used in link forms
+    pub fn name(&self) -> &'static str {
+        match self {
+            IssueRelation::Blocks => "blocks",
+            IssueRelation::BlockedBy => "blocked_by",
+            IssueRelation::RelatesTo => "relates_to",
+            IssueRelation::Duplicates => "duplicates",
+            IssueRelation::DuplicatedBy => "duplicated_by",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<IssueRelation> {
+        IssueRelation::CHOICES
+            .into_iter()
+            .find(|relation| relation.name() == name)
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            IssueRelation::Blocks => "Blocks",
+            IssueRelation::BlockedBy => "Blocked by",
+            IssueRelation::RelatesTo => "Relates to",
+            IssueRelation::Duplicates => "Duplicates",
+            IssueRelation::DuplicatedBy => "Duplicated by",
+        }
+    }
+}
+
+impl From<IssueLinkEntity> for IssueLink {
+    fn from(link: IssueLinkEntity) -> Self {
+        Self {
+            id: link.id,
+            relation: IssueRelation::of(link.kind, link.incoming),
+            owner: link.owner,
+            repository: link.repository,
+            number: link.number,
+            title: link.title,
+            state: link.state.into(),
+            activity_pub_id: link.activity_pub_id,
+        }
+    }
+}
+
+impl IssueLink {
+    /// Links from and to issue `number` of `repository_id`
+    pub async fn for_issue(
+        repository_id: i32,
+        number: i32,
+        db: &PgPool,
+    ) -> AppResult<Vec<IssueLink>> {
+        let links = IssueLinkEntity::for_issue(repository_id, number, db).await?;
+        Ok(links.into_iter().map(IssueLink::from).collect())
+    }
+
+    pub fn url(&self) -> String {
+        format!("/{}/{}/issues/{}", self.owner, self.repository, self.number)
+    }
+
+    /// e.g. `owner/repository#12`
+    pub fn label(&self) -> String {
+        format!("{}/{}#{}", self.owner, self.repository, self.number)
+    }
+
+    /// The linked issue is still open and blocks the one the link is displayed on
+    pub fn is_open_dependency(&self) -> bool {
+        self.relation == IssueRelation::BlockedBy && self.state == IssueState::Open
+    }
+}
+
+impl Issue {
+    pub async fn links(&self, db: &PgPool) -> AppResult<Vec<IssueLink>> {
+        IssueLink::for_issue(self.repository_id, self.number, db).await
+    }
+
+    /// Open issues blocking this one
+    pub async fn open_dependencies(&self, db: &PgPool) -> AppResult<Vec<IssueLink>> {
+        let links = self.links(db).await?;
+        Ok(links
+            .into_iter()
+            .filter(IssueLink::is_open_dependency)
+            .collect())
+    }
+
+    /// Ids of the issues blocking this one, served as the ticket `dependencies` collection
+    pub async fn dependency_ids(&self, db: &PgPool) -> AppResult<Vec<Url>> {
+        self.linked_ids(IssueRelation::BlockedBy, db).await
+    }
+
+    /// Ids of the issues this one blocks, served as the ticket `dependants` collection
+    pub async fn dependant_ids(&self, db: &PgPool) -> AppResult<Vec<Url>> {
+        self.linked_ids(IssueRelation::Blocks, db).await
+    }
+
+    async fn linked_ids(&self, relation: IssueRelation, db: &PgPool) -> AppResult<Vec<Url>> {
+        let mut ids = vec![];
+        for link in self.links(db).await? {
+            if link.relation == relation {
+                ids.push(Url::parse(&link.activity_pub_id)?);
+            }
+        }
+
+        Ok(ids)
+    }
+
+    /// Link `other` to this issue on behalf of `user`, who must be allowed to manage this issue.
+    /// Dependencies and duplicates change how the other end can be resolved, `user` must be
+    /// allowed to manage it as well.
+    pub async fn link(
+        &self,
+        user: &User,
+        relation: IssueRelation,
+        other: &Issue,
+        db: &PgPool,
+    ) -> AppResult<()> {
+        let actor: Url = user.activity_pub_id.clone().into();
+        let repository = Repository::by_id(self.repository_id, db).await?;
+        if !self.is_managed_by(&actor, &repository) {
+            return Err(AppError::Unauthorized);
+        }
+
+        if relation != IssueRelation::RelatesTo {
+            let other_repository = Repository::by_id(other.repository_id, db).await?;
+            if !other.is_managed_by(&actor, &other_repository) {
+                return Err(AppError::Unauthorized);
+            }
+        }
+
+        let (source, target) = match relation {
+            IssueRelation::BlockedBy | IssueRelation::DuplicatedBy => (other, self),
+            _ => (self, other),
+        };
+
+        if (source.repository_id, source.number) == (target.repository_id, target.number) {
+            return Err(AppError::NotFound);
+        }
+
+        let kind = match relation {
+            IssueRelation::Blocks | IssueRelation::BlockedBy => IssueLinkKind::Blocks,
+            IssueRelation::RelatesTo => IssueLinkKind::RelatesTo,
+            IssueRelation::Duplicates | IssueRelation::DuplicatedBy => IssueLinkKind::Duplicates,
+        };
+
+        CreateIssueLink {
+            repository_id: source.repository_id,
+            number: source.number,
+            target_repository_id: target.repository_id,
+            target_number: target.number,
+            kind,
+            actor_id: user.id,
+        }
+        .insert(db)
+        .await
+        .map_err(Into::into)
+    }
+
+    /// Remove link `link_id` from this issue on behalf of `user`
+    pub async fn unlink(&self, user: &User, link_id: i32, db: &PgPool) -> AppResult<()> {
+        let repository = Repository::by_id(self.repository_id, db).await?;
+        let actor: Url = user.activity_pub_id.clone().into();
+        if !self.is_managed_by(&actor, &repository) {
+            return Err(AppError::Unauthorized);
+        }
+
+        IssueLinkEntity::delete(link_id, self.repository_id, self.number, db)
+            .await
+            .map_err(Into::into)
+    }
+}
+
+impl Repository {
+    /// The issue `reference` points to: `#12`, `owner/repository#12` or the url of a ticket,
+    /// unknown tickets are fetched from their instance
+    pub async fn find_linked_issue(
+        &self,
+        reference: &str,
+        instance: &InstanceHandle,
+    ) -> AppResult<Issue> {
+        let db = instance.database();
+        let reference = reference.trim();
+        if let Ok(url) = Url::parse(reference) {
+            if let Some(issue) = Issue::by_activity_pub_id_optional(url.as_str(), db).await? {
+                return Ok(issue);
+            }
+
+            let issue = ObjectId::<Issue>::new(url)
+                .dereference(instance, instance.local_instance(), &mut 0)
+                .await?;
+            return issue.save(db).await;
+        }
+
+        let item = gill_markdown::references(reference)
+            .into_iter()
+            .find_map(|reference| match reference {
+                Reference::Item { namespace, number } => Some((namespace, number)),
+                _ => None,
+            });
+
+        let Some((namespace, number)) = item else {
+            return Err(AppError::NotFound);
+        };
+
+        let repository_id = resolve_repository_id(self.id, namespace, db)
+            .await
+            .ok_or(AppError::NotFound)?;
+
+        Repository::by_id(repository_id, db)
+            .await?
+            .issue_by_number(number, db)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::domain::issue::link::IssueRelation;
+    use gill_db::repository::issue::link::IssueLinkKind;
+    use speculoos::prelude::*;
+
+    #[test]
+    fn should_see_link_from_both_ends() {
+        assert_that!(IssueRelation::of(IssueLinkKind::Blocks, false))
+            .is_equal_to(IssueRelation::Blocks);
+        assert_that!(IssueRelation::of(IssueLinkKind::Blocks, true))
+            .is_equal_to(IssueRelation::BlockedBy);
+        assert_that!(IssueRelation::of(IssueLinkKind::RelatesTo, true))
+            .is_equal_to(IssueRelation::RelatesTo);
+        assert_that!(IssueRelation::of(IssueLinkKind::Duplicates, true))
+            .is_equal_to(IssueRelation::DuplicatedBy);
+    }
+
+    #[test]
+    fn should_only_offer_declarable_relations() {
+        assert_that!(IssueRelation::from_name("blocked_by"))
+            .is_equal_to(Some(IssueRelation::BlockedBy));
+        assert_that!(IssueRelation::from_name("duplicated_by")).is_none();
+    }
+}
@@ -1,19 +1,33 @@
+use crate::apub::common::{mentioned_users, GillApubObject};
 use crate::domain::id::ActivityPubId;
+use crate::domain::notification::{mentions, NotificationSubject, NotifyCommand};
 use crate::domain::repository::Repository;
+use crate::domain::revision::RecordRevisionCommand;
+use crate::domain::timeline::{ItemEventKind, RecordEventCommand};
+use crate::domain::user::activity::{UserActivity, UserActivityKind};
 use crate::domain::user::User;
 use crate::error::{AppError, AppResult};
+use crate::instance::InstanceHandle;
+use activitypub_federation::traits::Actor;
 
 use chrono::NaiveDateTime;
 use gill_db::repository::issue::{Issue as IssueEntity, IssueState as IssueStateEntity};
 use gill_db::Insert;
+use gill_settings::SETTINGS;
 
 use sqlx::PgPool;
+use tracing::error;
 
 use url::Url;
 
 pub mod comment;
 pub mod create;
+pub mod delete;
 pub mod digest;
+pub mod link;
+pub mod reference;
+pub mod resolve;
+pub mod update;
 
 #[derive(Debug, Clone)]
 pub struct Issue {
@@ -39,6 +53,14 @@ pub struct Issue {
     pub is_local: bool,
 }
 
+/// Addresses of an issue activity: `to` is the activity audience and
+/// `inboxes` the inboxes it gets delivered to
+#[derive(Debug)]
+pub struct IssueAudience {
+    pub to: Vec<Url>,
+    pub inboxes: Vec<Url>,
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum IssueState {
     Open,
@@ -81,11 +103,11 @@ impl TryFrom<IssueEntity> for Issue {
             opened_by: issue.opened_by,
             title: issue.title,
             content: issue.content,
-            state: IssueState::Open,
+            state: issue.state.into(),
             context: ActivityPubId::try_from(issue.context)?,
             attributed_to: ActivityPubId::try_from(issue.attributed_to)?,
             media_type: issue.media_type,
-            published: Default::default(),
+            published: issue.published,
             followers_url: Url::parse(&issue.followers_url)?,
             team: Url::parse(&issue.team)?,
             replies: Url::parse(&issue.replies)?,
@@ -173,10 +195,24 @@ impl Issue {
         }
     }
 
+    /// Insert the issue and record its opening in its history
     pub async fn save(self, db: &PgPool) -> AppResult<Self> {
         let entity: IssueEntity = self.into();
         let entity = entity.insert(db).await?;
-        Issue::try_from(entity).map_err(Into::into)
+        let issue = Issue::try_from(entity)?;
+        RecordEventCommand {
+            repository_id: issue.repository_id,
+            number: issue.number,
+            actor_id: issue.opened_by,
+            kind: ItemEventKind::Opened,
+            old_value: None,
+            new_value: None,
+            created_at: Some(issue.published),
+        }
+        .execute(db)
+        .await;
+
+        Ok(issue)
     }
 
     pub async fn has_subscriber(&self, subscriber_id: i32, db: &PgPool) -> AppResult<bool> {
@@ -212,4 +248,179 @@ impl Issue {
             .await
             .map_err(Into::into)
     }
+
+    /// Whether `actor` is allowed to manage this issue: close, reopen, edit or delete it
+    pub fn is_managed_by(&self, actor: &Url, repository: &Repository) -> bool {
+        let actor = actor.as_str();
+        self.attributed_to.to_string() == actor || repository.attributed_to.to_string() == actor
+    }
+
+    /// Update the issue title and content on behalf of `editor_id`, the previous version is
+    /// kept as a revision and title changes are recorded in the issue history
+    pub async fn update_content(
+        &self,
+        editor_id: i32,
+        title: &str,
+        content: &str,
+        db: &PgPool,
+    ) -> AppResult<()> {
+        let entity: IssueEntity = self.into();
+        entity.update_content(title, content, db).await?;
+        RecordRevisionCommand {
+            repository_id: self.repository_id,
+            number: self.number,
+            issue_comment_id: None,
+            pull_request_comment_id: None,
+            old_title: Some(&self.title),
+            new_title: Some(title),
+            old_content: &self.content,
+            new_content: content,
+            edited_by: editor_id,
+        }
+        .execute(db)
+        .await;
+
+        if title != self.title {
+            RecordEventCommand {
+                repository_id: self.repository_id,
+                number: self.number,
+                actor_id: editor_id,
+                kind: ItemEventKind::Renamed,
+                old_value: Some(self.title.clone()),
+                new_value: Some(title.to_string()),
+                created_at: None,
+            }
+            .execute(db)
+            .await;
+        }
+
+        Ok(())
+    }
+
+    pub async fn mark_deleted(&self, db: &PgPool) -> AppResult<()> {
+        let entity: IssueEntity = self.into();
+        entity.mark_deleted(db).await.map_err(Into::into)
+    }
+
+    /// Close the issue, `resolve_id` is the `Resolve` activity closing it if any
+    pub async fn mark_resolved(
+        &self,
+        resolved_by: &Url,
+        resolve_id: Option<&Url>,
+        db: &PgPool,
+    ) -> AppResult<()> {
+        let entity: IssueEntity = self.into();
+        entity
+            .resolve(resolved_by.as_str(), resolve_id.map(Url::as_str), db)
+            .await
+            .map_err(Into::into)
+    }
+
+    /// Id and actor of the `Resolve` activity which closed the issue
+    pub async fn resolve_activity_id(&self, db: &PgPool) -> AppResult<Option<(Url, Url)>> {
+        let entity: IssueEntity = self.into();
+        let Some((id, actor)) = entity.resolve_activity(db).await? else {
+            return Ok(None);
+        };
+
+        Ok(Some((Url::parse(&id)?, Url::parse(&actor)?)))
+    }
+
+    pub async fn mark_reopened(&self, db: &PgPool) -> AppResult<()> {
+        let entity: IssueEntity = self.into();
+        entity.reopen(db).await.map_err(Into::into)
+    }
+
+    /// Notify the subscribers, watchers and mentioned users of an action of `actor_id`,
+    /// mentioned users are subscribed to the issue along the way
+    pub async fn notify(&self, actor_id: i32, action: &str, content: Option<&str>, db: &PgPool) {
+        let mentioned = content
+            .map(|content| mentions(content, &SETTINGS.domain))
+            .unwrap_or_default();
+        for username in mentioned {
+            let subscribed = match User::by_mention(&username, None, db).await {
+                Ok(Some(user)) => self.add_subscriber(user.id, db).await,
+                Ok(None) => Ok(()),
+                Err(err) => Err(err),
+            };
+
+            if let Err(err) = subscribed {
+                error!(
+                    "Failed to subscribe {username} to issue #{} of repository {}: {err:?}",
+                    self.number, self.repository_id
+                );
+            }
+        }
+
+        NotifyCommand {
+            actor_id,
+            repository_id: self.repository_id,
+            subject: NotificationSubject::Issue,
+            number: self.number,
+            title: &self.title,
+            action,
+            content,
+        }
+        .execute(db)
+        .await
+    }
+
+    /// Log an action of `actor_id` on this issue to the dashboard feeds
+    pub async fn record_activity(&self, actor_id: i32, kind: UserActivityKind, db: &PgPool) {
+        UserActivity::record(
+            actor_id,
+            self.repository_id,
+            kind,
+            Some(self.number),
+            Some(self.title.clone()),
+            db,
+        )
+        .await
+    }
+
+    /// The repository, its followers, the issue subscribers and the actor followers
+    pub async fn audience(
+        &self,
+        repository: &Repository,
+        actor: &User,
+        instance: &InstanceHandle,
+    ) -> AppResult<IssueAudience> {
+        let db = instance.database();
+        let subscribers = self.get_subscribers_inbox(i64::MAX, 0, db).await?;
+        let subscribers = subscribers
+            .into_iter()
+            .filter_map(|inbox| Url::parse(&inbox).ok());
+
+        let mut inboxes = vec![repository.shared_inbox_or_inbox()];
+        inboxes.extend(repository.followers(instance).await?);
+        inboxes.extend(subscribers);
+        inboxes.extend(actor.followers(instance).await?);
+        inboxes.sort();
+        inboxes.dedup();
+
+        let to = vec![
+            repository.activity_pub_id.clone().into(),
+            repository.followers_url.clone(),
+            self.followers_url.clone(),
+            actor.followers_url.clone(),
+        ];
+
+        Ok(IssueAudience { to, inboxes })
+    }
+}
+
+impl IssueAudience {
+    /// Address the remote users mentioned in `content` as well
+    pub async fn add_mentions(&mut self, content: &str, db: &PgPool) -> AppResult<()> {
+        for user in mentioned_users(content, db).await? {
+            if !user.is_local {
+                self.to.push(user.activity_pub_id.clone().into());
+                self.inboxes.push(user.shared_inbox_or_inbox());
+            }
+        }
+
+        self.inboxes.sort();
+        self.inboxes.dedup();
+        Ok(())
+    }
 }
@@ -0,0 +1,199 @@
+use crate::domain::issue::Issue;
+use crate::domain::repository::Repository;
+use crate::error::AppResult;
+use chrono::NaiveDateTime;
+use gill_db::repository::issue::reference::{
+    CreateIssueReference, IssueReference as IssueReferenceEntity,
+    ReferenceSource as ReferenceSourceEntity,
+};
+use gill_db::Insert;
+use gill_markdown::Reference;
+use sqlx::PgPool;
+use tracing::error;
+
+/// Where an issue is referenced from
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ReferenceSource {
+    Issue(i32),
+    PullRequest(i32),
+    Commit(String),
+}
+
+/// An issue, a pull request or a commit referencing an issue with `#number` or
+/// `owner/repository#number`
+#[derive(Debug, Clone)]
+pub struct IssueReference {
+    pub source: ReferenceSource,
+    /// Title of the source issue or pull request
+    pub source_title: Option<String>,
+    pub source_owner: String,
+    pub source_repository: String,
+    pub actor_name: String,
+    pub actor_activity_pub_id: String,
+    /// The reference closed the issue
+    pub closes: bool,
+    pub created_at: NaiveDateTime,
+}
+
+impl From<IssueReferenceEntity> for IssueReference {
+    fn from(reference: IssueReferenceEntity) -> Self {
+        let number = reference.source_number.unwrap_or_default();
+        let source = match reference.source {
+            ReferenceSourceEntity::Issue => ReferenceSource::Issue(number),
+            ReferenceSourceEntity::PullRequest => ReferenceSource::PullRequest(number),
+            ReferenceSourceEntity::Commit => {
+                ReferenceSource::Commit(reference.source_sha.unwrap_or_default())
+            }
+        };
+
+        Self {
+            source,
+            source_title: reference.source_title,
+            source_owner: reference.source_owner,
+            source_repository: reference.source_repository,
+            actor_name: reference.actor_name,
+            actor_activity_pub_id: reference.actor_activity_pub_id,
+            closes: reference.closes,
+            created_at: reference.created_at,
+        }
+    }
+}
+
+impl IssueReference {
+    /// Path of the issue, pull request or commit the reference comes from
+    pub fn source_url(&self) -> String {
+        let namespace = format!("/{}/{}", self.source_owner, self.source_repository);
+        match &self.source {
+            ReferenceSource::Issue(number) => format!("{namespace}/issues/{number}"),
+            ReferenceSource::PullRequest(number) => format!("{namespace}/pulls/{number}"),
+            ReferenceSource::Commit(sha) => format!("{namespace}/commit/{sha}"),
+        }
+    }
+
+    /// Short label of the source, e.g. `owner/repository#12` or a short commit sha
+    pub fn source_label(&self) -> String {
+        match &self.source {
+            ReferenceSource::Issue(number) | ReferenceSource::PullRequest(number) => {
+                format!("{}/{}#{number}", self.source_owner, self.source_repository)
+            }
+            ReferenceSource::Commit(sha) => sha.chars().take(7).collect(),
+        }
+    }
+
+    /// Record the issues referenced in `content`, written by `actor_id` in `source`
+    /// of `source_repository_id`. Like notifications, a failure is reported but never
+    /// fails the action.
+    pub async fn record_all(
+        source_repository_id: i32,
+        source: &ReferenceSource,
+        actor_id: i32,
+        content: &str,
+        db: &PgPool,
+    ) {
+        for reference in gill_markdown::references(content) {
+            let Reference::Item { namespace, number } = reference else {
+                continue;
+            };
+
+            let Some(repository_id) =
+                resolve_repository_id(source_repository_id, namespace, db).await
+            else {
+                continue;
+            };
+
+            IssueReference::record(
+                source_repository_id,
+                source,
+                actor_id,
+                (repository_id, number),
+                false,
+                db,
+            )
+            .await;
+        }
+    }
+
+    /// Record a reference to issue `number` of `repository_id`
+    pub async fn record(
+        source_repository_id: i32,
+        source: &ReferenceSource,
+        actor_id: i32,
+        (repository_id, number): (i32, i32),
+        closes: bool,
+        db: &PgPool,
+    ) {
+        let (source, source_number, source_sha) = match source {
+            ReferenceSource::Issue(source_number) => {
+                // An issue mentioning its own number is not a reference
+                if repository_id == source_repository_id && number == *source_number {
+                    return;
+                }
+
+                (ReferenceSourceEntity::Issue, Some(*source_number), None)
+            }
+            ReferenceSource::PullRequest(source_number) => (
+                ReferenceSourceEntity::PullRequest,
+                Some(*source_number),
+                None,
+            ),
+            ReferenceSource::Commit(sha) => {
+                (ReferenceSourceEntity::Commit, None, Some(sha.clone()))
+            }
+        };
+
+        let reference = CreateIssueReference {
+            repository_id,
+            number,
+            source_repository_id,
+            source_number,
+            source_sha,
+            source,
+            actor_id,
+            closes,
+        };
+
+        if let Err(err) = reference.insert(db).await {
+            error!("Failed to record reference to #{number} of repository {repository_id}: {err}");
+        }
+    }
+
+    /// Issues, pull requests and commits referencing issue `number` of `repository_id`
+    pub async fn for_issue(
+        repository_id: i32,
+        number: i32,
+        db: &PgPool,
+    ) -> AppResult<Vec<IssueReference>> {
+        let references = IssueReferenceEntity::for_issue(repository_id, number, db)
+            .await?
+            .into_iter()
+            .map(IssueReference::from)
+            .collect();
+
+        Ok(references)
+    }
+}
+
+/// Id of the repository an `owner/repository#number` reference points to, references
+/// without a namespace point to the source repository
+pub(crate) async fn resolve_repository_id(
+    source_repository_id: i32,
+    namespace: Option<(String, String)>,
+    db: &PgPool,
+) -> Option<i32> {
+    match namespace {
+        None => Some(source_repository_id),
+        // Not found when the repository is unknown to this instance
+        Some((owner, name)) => Repository::by_namespace(&owner, &name, db)
+            .await
+            .ok()
+            .map(|repository| repository.id),
+    }
+}
+
+impl Issue {
+    /// Record the issues referenced from this issue in `content`
+    pub async fn record_references(&self, actor_id: i32, content: &str, db: &PgPool) {
+        let source = ReferenceSource::Issue(self.number);
+        IssueReference::record_all(self.repository_id, &source, actor_id, content, db).await
+    }
+}
@@ -0,0 +1,168 @@
+use crate::apub::common::GillApubObject;
+use crate::apub::ticket::resolve::{ResolveTicket, UndoResolveTicket};
+use crate::domain::issue::reference::{resolve_repository_id, IssueReference, ReferenceSource};
+use crate::domain::issue::{Issue, IssueState};
+use crate::domain::repository::Repository;
+use crate::domain::timeline::ItemEventKind;
+use crate::domain::user::activity::UserActivityKind;
+use crate::domain::user::User;
+use crate::error::{AppError, AppResult};
+use crate::instance::InstanceHandle;
+use activitypub_federation::core::object_id::ObjectId;
+use gill_markdown::Reference;
+use tracing::debug;
+use url::Url;
+use uuid::Uuid;
+
+impl Issue {
+    /// Close the issue and send a `Resolve` activity to its audience. Issues blocked by
+    /// open issues are only closed when `force` is set.
+    pub async fn close(
+        &self,
+        user: &User,
+        force: bool,
+        instance: &InstanceHandle,
+    ) -> AppResult<()> {
+        let db = instance.database();
+        let repository = Repository::by_id(self.repository_id, db).await?;
+        let actor: Url = user.activity_pub_id.clone().into();
+        if !self.is_managed_by(&actor, &repository) {
+            return Err(AppError::Unauthorized);
+        }
+
+        if !force && !self.open_dependencies(db).await?.is_empty() {
+            let reason = format!("Issue #{} is blocked by open issues", self.number);
+            return Err(AppError::Conflict(reason));
+        }
+
+        let audience = self.audience(&repository, user, instance).await?;
+        let resolve = self.resolve_activity(user, audience.to, instance)?;
+        self.mark_resolved(&actor, Some(&resolve.id), db).await?;
+        self.record_event(user.id, ItemEventKind::Closed, db).await;
+        self.record_activity(user.id, UserActivityKind::CloseIssue, db)
+            .await;
+        self.notify(user.id, "closed", None, db).await;
+        user.send(resolve, audience.inboxes, instance).await
+    }
+
+    /// Reopen the issue and send an `Undo` of the `Resolve` activity to its audience
+    pub async fn reopen(&self, user: &User, instance: &InstanceHandle) -> AppResult<()> {
+        let db = instance.database();
+        let repository = Repository::by_id(self.repository_id, db).await?;
+        let actor: Url = user.activity_pub_id.clone().into();
+        if !self.is_managed_by(&actor, &repository) {
+            return Err(AppError::Unauthorized);
+        }
+
+        // Read before reopening, this forgets the activity which closed the issue
+        let resolve_id = self.resolve_activity_id(db).await?;
+        self.mark_reopened(db).await?;
+        self.record_event(user.id, ItemEventKind::Reopened, db)
+            .await;
+        self.record_activity(user.id, UserActivityKind::ReopenIssue, db)
+            .await;
+        self.notify(user.id, "reopened", None, db).await;
+        let audience = self.audience(&repository, user, instance).await?;
+        let resolve = match resolve_id {
+            Some((id, actor)) => ResolveTicket {
+                id,
+                kind: Default::default(),
+                actor: ObjectId::new(actor),
+                to: audience.to.clone(),
+                object: self.activity_pub_id.clone().into(),
+            },
+            // Imported issues, and the ones closed before activities were kept, have none
+            None => self.resolve_activity(user, audience.to.clone(), instance)?,
+        };
+
+        let hostname = instance.local_instance().hostname();
+        let id = format!("https://{hostname}/activity/{uuid}", uuid = Uuid::new_v4());
+        let undo = UndoResolveTicket {
+            id: Url::parse(&id)?,
+            kind: Default::default(),
+            actor: user.activity_pub_id.clone().into(),
+            to: audience.to,
+            object: resolve,
+        };
+
+        user.send(undo, audience.inboxes, instance).await
+    }
+
+    fn resolve_activity(
+        &self,
+        user: &User,
+        to: Vec<Url>,
+        instance: &InstanceHandle,
+    ) -> AppResult<ResolveTicket> {
+        let hostname = instance.local_instance().hostname();
+        let id = format!("https://{hostname}/activity/{uuid}", uuid = Uuid::new_v4());
+        Ok(ResolveTicket {
+            id: Url::parse(&id)?,
+            kind: Default::default(),
+            actor: user.activity_pub_id.clone().into(),
+            to,
+            object: self.activity_pub_id.clone().into(),
+        })
+    }
+}
+
+impl Repository {
+    /// Close issue `number` on behalf of `user`, closing an already closed issue does nothing
+    pub async fn close_issue(
+        &self,
+        number: i32,
+        user: &User,
+        instance: &InstanceHandle,
+    ) -> AppResult<()> {
+        let db = instance.database();
+        let digest = self.get_issue_digest(number, db).await?;
+        if digest.state == IssueState::Closed {
+            return Ok(());
+        }
+
+        self.issue_by_number(number, db)
+            .await?
+            // Closing keywords take precedence over dependencies
+            .close(user, true, instance)
+            .await
+    }
+
+    /// Close the issues `text` says are fixed, e.g. "fixes #12" or "closes owner/repository#3".
+    /// `source` is the commit or pull request of this repository `text` comes from. Issues
+    /// `user` is not allowed to manage are only referenced.
+    pub async fn close_referenced_issues(
+        &self,
+        text: &str,
+        source: &ReferenceSource,
+        user: &User,
+        instance: &InstanceHandle,
+    ) {
+        let db = instance.database();
+        IssueReference::record_all(self.id, source, user.id, text, db).await;
+
+        for reference in gill_markdown::closing_references(text) {
+            let Reference::Item { namespace, number } = reference else {
+                continue;
+            };
+
+            let Some(repository_id) = resolve_repository_id(self.id, namespace, db).await else {
+                continue;
+            };
+
+            let closed = match Repository::by_id(repository_id, db).await {
+                Ok(repository) => repository.close_issue(number, user, instance).await,
+                Err(err) => Err(err),
+            };
+
+            match closed {
+                Ok(()) => {
+                    let issue = (repository_id, number);
+                    IssueReference::record(self.id, source, user.id, issue, true, db).await
+                }
+                Err(err) => {
+                    debug!("Not closing #{number} of repository {repository_id}: {err:?}")
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use crate::apub::common::GillApubObject;
+use crate::apub::ticket::update::UpdateTicket;
+use crate::domain::issue::Issue;
+use crate::domain::repository::Repository;
+use crate::domain::user::User;
+use crate::error::{AppError, AppResult};
+use crate::instance::InstanceHandle;
+use activitypub_federation::traits::ApubObject;
+use url::Url;
+use uuid::Uuid;
+
+impl Issue {
+    /// Edit the issue title and content, the issue author and the repository owner can do this
+    pub async fn edit(
+        &self,
+        user: &User,
+        title: &str,
+        content: &str,
+        instance: &InstanceHandle,
+    ) -> AppResult<()> {
+        let db = instance.database();
+        let repository = Repository::by_id(self.repository_id, db).await?;
+        if !self.is_managed_by(&user.activity_pub_id.clone().into(), &repository) {
+            return Err(AppError::Unauthorized);
+        }
+
+        let content = content.escape_default().to_string();
+        self.update_content(user.id, title, &content, db).await?;
+
+        let issue = Issue::by_activity_pub_id_optional(&self.activity_pub_id.to_string(), db)
+            .await?
+            .ok_or(AppError::NotFound)?;
+        let audience = issue.audience(&repository, user, instance).await?;
+        let hostname = instance.local_instance().hostname();
+        let id = format!("https://{hostname}/activity/{uuid}", uuid = Uuid::new_v4());
+
+        let update = UpdateTicket {
+            id: Url::parse(&id)?,
+            kind: Default::default(),
+            actor: user.activity_pub_id.clone().into(),
+            to: audience.to,
+            object: issue.into_apub(instance).await?,
+        };
+
+        user.send(update, audience.inboxes, instance).await
+    }
+}
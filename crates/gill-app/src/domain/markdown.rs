@@ -0,0 +1,64 @@
+//! Issue, pull request and comment contents are stored escaped with `str::escape_default`
+
+/// Render stored `content` to sanitized html, relative links point to the repository
+pub fn render(content: &str, owner: &str, repository: &str) -> String {
+    gill_markdown::render(&unescape(content), owner, repository)
+}
+
+/// Revert `str::escape_default`
+pub fn unescape(content: &str) -> String {
+    let mut unescaped = String::with_capacity(content.len());
+    let mut chars = content.chars();
+    while let Some(char) = chars.next() {
+        if char != '\\' {
+            unescaped.push(char);
+            continue;
+        }
+
+        match chars.next() {
+            Some('n') => unescaped.push('\n'),
+            Some('r') => unescaped.push('\r'),
+            Some('t') => unescaped.push('\t'),
+            Some('u') => {
+                let code: String = chars
+                    .by_ref()
+                    .skip_while(|char| *char == '{')
+                    .take_while(|char| *char != '}')
+                    .collect();
+                let char = u32::from_str_radix(&code, 16)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .unwrap_or(char::REPLACEMENT_CHARACTER);
+                unescaped.push(char);
+            }
+            Some(char) => unescaped.push(char),
+            None => unescaped.push('\\'),
+        }
+    }
+
+    unescaped
+}
+
+#[cfg(test)]
+mod test {
+    use crate::domain::markdown::{render, unescape};
+    use speculoos::prelude::*;
+
+    #[test]
+    fn should_unescape_stored_content() {
+        let content = "Hello \"world\" 🦀\nbye\\";
+        let escaped = content.escape_default().to_string();
+
+        assert_that!(unescape(&escaped)).is_equal_to(content.to_string());
+    }
+
+    #[test]
+    fn should_render_stored_content() {
+        let content = "- [ ] todo\n\n<script>alert(\"hey\")</script>".escape_default();
+
+        let html = render(&content.to_string(), "alice", "gill");
+
+        assert_that!(html).contains("data-task-offset");
+        assert_that!(html).does_not_contain("<script>");
+    }
+}
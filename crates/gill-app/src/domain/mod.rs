@@ -1,7 +1,17 @@
+pub mod activity;
 pub mod apub;
 pub mod commit;
+pub mod delivery;
+pub mod federation;
 pub mod id;
 pub mod issue;
+pub mod markdown;
+pub mod notification;
 pub mod pull_request;
+pub mod reaction;
+pub mod release;
 pub mod repository;
+pub mod revision;
+pub mod secret;
+pub mod timeline;
 pub mod user;
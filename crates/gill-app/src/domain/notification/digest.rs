@@ -0,0 +1,134 @@
+use crate::domain::notification::mailer::{Email, Mailer};
+use crate::domain::notification::Notification;
+use crate::error::AppResult;
+use chrono::Utc;
+use gill_db::user::notification::{DigestRecipient, Notification as NotificationEntity};
+use gill_settings::SETTINGS;
+use sqlx::PgPool;
+use std::collections::HashSet;
+use std::time::Duration;
+use tracing::{debug, error};
+
+/// Notifications are gathered for a while before being emailed
+const DIGEST_INTERVAL: Duration = Duration::from_secs(15 * 60);
+const DIGEST_BATCH_SIZE: i64 = 50;
+const MAX_DIGEST_BACKOFF_SECONDS: i64 = 24 * 60 * 60;
+
+/// Email unread notifications to the users who enabled digests until the instance shuts down
+pub async fn run_digest_worker(db: PgPool, mailer: Mailer) {
+    loop {
+        tokio::time::sleep(DIGEST_INTERVAL).await;
+        match send_digests(&mailer, &db).await {
+            Ok(0) => {}
+            Ok(count) => debug!("Sent {count} notification digests"),
+            Err(err) => error!("Failed to send notification digests: {err:?}"),
+        }
+    }
+}
+
+/// Send one digest to each user with pending notifications, returns the number of digests sent
+pub async fn send_digests(mailer: &Mailer, db: &PgPool) -> AppResult<usize> {
+    let mut handled = HashSet::new();
+    let mut sent = 0;
+
+    loop {
+        let recipients = NotificationEntity::digest_recipients(DIGEST_BATCH_SIZE, db).await?;
+        // Recipients are only listed again if notifications arrived while sending
+        let recipients: Vec<DigestRecipient> = recipients
+            .into_iter()
+            .filter(|recipient| handled.insert(recipient.user_id))
+            .collect();
+
+        if recipients.is_empty() {
+            return Ok(sent);
+        }
+
+        for recipient in recipients {
+            if send_digest(&recipient, mailer, db).await? {
+                sent += 1;
+            }
+        }
+    }
+}
+
+// Returns whether the digest was sent, failed recipients are retried with a backoff
+async fn send_digest(recipient: &DigestRecipient, mailer: &Mailer, db: &PgPool) -> AppResult<bool> {
+    let pending = NotificationEntity::pending_digest(recipient.user_id, db).await?;
+    if pending.is_empty() {
+        return Ok(false);
+    }
+
+    let ids: Vec<i32> = pending.iter().map(|notification| notification.id).collect();
+    let notifications: Vec<Notification> = pending.into_iter().map(Notification::from).collect();
+
+    match mailer.send(digest_email(recipient, &notifications)).await {
+        Ok(()) => {
+            NotificationEntity::mark_emailed(&ids, db).await?;
+            NotificationEntity::digest_sent(recipient.user_id, db).await?;
+            Ok(true)
+        }
+        // Leave them pending, they will be part of the next digest
+        Err(err) => {
+            error!("Failed to email digest to {}: {err:?}", recipient.username);
+            let retry_at = Utc::now().naive_utc() + digest_backoff(recipient.digest_failures + 1);
+            NotificationEntity::digest_failed(recipient.user_id, retry_at, db).await?;
+            Ok(false)
+        }
+    }
+}
+
+/// Delay before retrying a digest which failed `failures` times in a row
+fn digest_backoff(failures: i32) -> chrono::Duration {
+    let exponent = failures.clamp(1, 20) as u32 - 1;
+    let seconds = (DIGEST_INTERVAL.as_secs() as i64).saturating_mul(2_i64.pow(exponent));
+    chrono::Duration::seconds(seconds.min(MAX_DIGEST_BACKOFF_SECONDS))
+}
+
+fn digest_email(recipient: &DigestRecipient, notifications: &[Notification]) -> Email {
+    let base_url = format!("{}://{}", SETTINGS.protocol(), SETTINGS.domain);
+    let mut body = format!(
+        "Hi {}, here is what happened since your last digest:\n\n",
+        recipient.username
+    );
+
+    for notification in notifications {
+        let actor = if notification.actor_is_local {
+            notification.actor_name.clone()
+        } else {
+            format!("{}@{}", notification.actor_name, notification.actor_domain)
+        };
+
+        body.push_str(&format!(
+            "- {actor} {} {}/{}#{} {}\n  {base_url}{}\n",
+            notification.action,
+            notification.repository_owner,
+            notification.repository_name,
+            notification.number,
+            notification.title,
+            notification.url(),
+        ));
+    }
+
+    body.push_str(&format!(
+        "\nManage your notifications at {base_url}/notifications\n"
+    ));
+
+    Email {
+        to: recipient.email.clone(),
+        subject: format!("[gill] {} new notifications", notifications.len()),
+        body,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::domain::notification::digest::digest_backoff;
+    use speculoos::prelude::*;
+
+    #[test]
+    fn digest_backoff_should_double_up_to_a_day() {
+        assert_that!(digest_backoff(1).num_minutes()).is_equal_to(15);
+        assert_that!(digest_backoff(2).num_minutes()).is_equal_to(30);
+        assert_that!(digest_backoff(10).num_hours()).is_equal_to(24);
+    }
+}
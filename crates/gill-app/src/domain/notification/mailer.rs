@@ -0,0 +1,69 @@
+use crate::error::AppResult;
+use gill_settings::{SmtpSettings, SETTINGS};
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use tracing::info;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Email {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+#[derive(Clone)]
+pub enum Mailer {
+    Smtp(Box<SmtpMailer>),
+    /// Only logs emails, used when no SMTP server is configured
+    Log,
+}
+
+#[derive(Clone)]
+pub struct SmtpMailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl Mailer {
+    /// An SMTP mailer when the instance has a mail server configured, a logging one otherwise
+    pub fn from_settings() -> AppResult<Mailer> {
+        match &SETTINGS.smtp {
+            Some(smtp) => Mailer::smtp(smtp),
+            None => Ok(Mailer::Log),
+        }
+    }
+
+    pub fn smtp(settings: &SmtpSettings) -> AppResult<Mailer> {
+        let mut transport = AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&settings.host)?
+            .port(settings.port);
+
+        if let (Some(user), Some(password)) = (&settings.user, &settings.password) {
+            transport = transport.credentials(Credentials::new(user.clone(), password.clone()));
+        }
+
+        Ok(Mailer::Smtp(Box::new(SmtpMailer {
+            transport: transport.build(),
+            from: settings.from.parse()?,
+        })))
+    }
+
+    pub async fn send(&self, email: Email) -> AppResult<()> {
+        match self {
+            Mailer::Smtp(smtp) => {
+                let message = Message::builder()
+                    .from(smtp.from.clone())
+                    .to(email.to.parse()?)
+                    .subject(email.subject)
+                    .body(email.body)?;
+
+                smtp.transport.send(message).await?;
+            }
+            Mailer::Log => {
+                info!("Email to {}: {}\n{}", email.to, email.subject, email.body);
+            }
+        }
+
+        Ok(())
+    }
+}
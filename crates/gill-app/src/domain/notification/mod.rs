@@ -0,0 +1,296 @@
+use crate::domain::user::User;
+use crate::error::{AppError, AppResult};
+use chrono::NaiveDateTime;
+use gill_db::user::notification::{
+    CreateNotifications, Notification as NotificationEntity,
+    NotificationLevel as NotificationLevelEntity, NotificationReason as NotificationReasonEntity,
+    NotificationSettings as NotificationSettingsEntity,
+    NotificationSubject as NotificationSubjectEntity,
+};
+use gill_markdown::Reference;
+use gill_settings::SETTINGS;
+use sqlx::PgPool;
+use tracing::error;
+
+pub mod digest;
+pub mod mailer;
+
+/// What a user wants to be notified about
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum NotificationLevel {
+    /// Everything happening on watched repositories
+    Watching,
+    /// Issues and pull requests the user opened, commented or subscribed to
+    Participating,
+    MentionsOnly,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum NotificationReason {
+    Watching,
+    Participating,
+    Mention,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum NotificationSubject {
+    Issue,
+    PullRequest,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct NotificationSettings {
+    pub level: NotificationLevel,
+    pub email_digest: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct Notification {
+    pub id: i32,
+    pub reason: NotificationReason,
+    pub subject: NotificationSubject,
+    pub number: i32,
+    pub title: String,
+    pub action: String,
+    pub created_at: NaiveDateTime,
+    pub read: bool,
+    pub actor_name: String,
+    pub actor_domain: String,
+    pub actor_is_local: bool,
+    pub repository_owner: String,
+    pub repository_name: String,
+}
+
+/// An action on an issue or a pull request to notify
+pub struct NotifyCommand<'a> {
+    pub actor_id: i32,
+    pub repository_id: i32,
+    pub subject: NotificationSubject,
+    pub number: i32,
+    pub title: &'a str,
+    /// Past tense verb describing the action, e.g. "commented"
+    pub action: &'a str,
+    /// Markdown content of the action, searched for mentions
+    pub content: Option<&'a str>,
+}
+
+impl From<NotificationLevelEntity> for NotificationLevel {
+    fn from(level: NotificationLevelEntity) -> Self {
+        match level {
+            NotificationLevelEntity::Watching => NotificationLevel::Watching,
+            NotificationLevelEntity::Participating => NotificationLevel::Participating,
+            NotificationLevelEntity::MentionsOnly => NotificationLevel::MentionsOnly,
+        }
+    }
+}
+
+impl From<NotificationLevel> for NotificationLevelEntity {
+    fn from(level: NotificationLevel) -> Self {
+        match level {
+            NotificationLevel::Watching => NotificationLevelEntity::Watching,
+            NotificationLevel::Participating => NotificationLevelEntity::Participating,
+            NotificationLevel::MentionsOnly => NotificationLevelEntity::MentionsOnly,
+        }
+    }
+}
+
+impl From<NotificationReasonEntity> for NotificationReason {
+    fn from(reason: NotificationReasonEntity) -> Self {
+        match reason {
+            NotificationReasonEntity::Watching => NotificationReason::Watching,
+            NotificationReasonEntity::Participating => NotificationReason::Participating,
+            NotificationReasonEntity::Mention => NotificationReason::Mention,
+        }
+    }
+}
+
+impl From<NotificationSubjectEntity> for NotificationSubject {
+    fn from(subject: NotificationSubjectEntity) -> Self {
+        match subject {
+            NotificationSubjectEntity::Issue => NotificationSubject::Issue,
+            NotificationSubjectEntity::PullRequest => NotificationSubject::PullRequest,
+        }
+    }
+}
+
+impl From<NotificationSubject> for NotificationSubjectEntity {
+    fn from(subject: NotificationSubject) -> Self {
+        match subject {
+            NotificationSubject::Issue => NotificationSubjectEntity::Issue,
+            NotificationSubject::PullRequest => NotificationSubjectEntity::PullRequest,
+        }
+    }
+}
+
+impl From<NotificationEntity> for Notification {
+    fn from(notification: NotificationEntity) -> Self {
+        Self {
+            id: notification.id,
+            reason: notification.reason.into(),
+            subject: notification.subject.into(),
+            number: notification.number,
+            title: notification.title,
+            action: notification.action,
+            created_at: notification.created_at,
+            read: notification.read_at.is_some(),
+            actor_name: notification.actor_name,
+            actor_domain: notification.actor_domain,
+            actor_is_local: notification.actor_is_local,
+            repository_owner: notification.repository_owner,
+            repository_name: notification.repository_name,
+        }
+    }
+}
+
+impl Notification {
+    /// Path of the issue or pull request the notification is about
+    pub fn url(&self) -> String {
+        let collection = match self.subject {
+            NotificationSubject::Issue => "issues",
+            NotificationSubject::PullRequest => "pulls",
+        };
+
+        format!(
+            "/{}/{}/{collection}/{}",
+            self.repository_owner, self.repository_name, self.number
+        )
+    }
+
+    pub async fn list(
+        user: &User,
+        limit: i64,
+        offset: i64,
+        db: &PgPool,
+    ) -> AppResult<Vec<Notification>> {
+        let notifications = NotificationEntity::list(user.id, limit, offset, db)
+            .await?
+            .into_iter()
+            .map(Notification::from)
+            .collect();
+
+        Ok(notifications)
+    }
+
+    pub async fn unread_count(user: &User, db: &PgPool) -> AppResult<i64> {
+        NotificationEntity::unread_count(user.id, db)
+            .await
+            .map_err(Into::into)
+    }
+
+    /// Mark the notification as read and return it, only its recipient can open it
+    pub async fn open(id: i32, user: &User, db: &PgPool) -> AppResult<Notification> {
+        if !NotificationEntity::mark_read(id, user.id, db).await? {
+            return Err(AppError::NotFound);
+        }
+
+        let notification = NotificationEntity::by_id(id, user.id, db).await?;
+        Ok(notification.into())
+    }
+
+    pub async fn mark_all_read(user: &User, db: &PgPool) -> AppResult<()> {
+        NotificationEntity::mark_all_read(user.id, db)
+            .await
+            .map_err(Into::into)
+    }
+}
+
+impl NotificationSettings {
+    pub async fn for_user(user: &User, db: &PgPool) -> AppResult<NotificationSettings> {
+        let settings = NotificationSettingsEntity::for_user(user.id, db).await?;
+        Ok(NotificationSettings {
+            level: settings.level.into(),
+            email_digest: settings.email_digest,
+        })
+    }
+
+    pub async fn save(&self, user: &User, db: &PgPool) -> AppResult<()> {
+        let settings = NotificationSettingsEntity {
+            user_id: user.id,
+            level: self.level.into(),
+            email_digest: self.email_digest,
+        };
+
+        settings.save(db).await.map_err(Into::into)
+    }
+}
+
+impl NotifyCommand<'_> {
+    /// Notify the local users concerned by this action, errors are only logged
+    /// since a missed notification should not undo the action.
+    pub async fn execute(self, db: &PgPool) {
+        let notifications = CreateNotifications {
+            actor_id: self.actor_id,
+            repository_id: self.repository_id,
+            subject: self.subject.into(),
+            number: self.number,
+            title: self.title.to_string(),
+            action: self.action.to_string(),
+            mentioned: self
+                .content
+                .map(|content| mentions(content, &SETTINGS.domain))
+                .unwrap_or_default(),
+        };
+
+        if let Err(err) = notifications.fan_out(db).await {
+            error!(
+                "Failed to notify {:?} #{} of repository {}: {err}",
+                self.subject, self.number, self.repository_id
+            );
+        }
+    }
+}
+
+/// Local usernames mentioned with `@username` or `@username@local_domain` in `content`.
+/// Mentions of remote users, email addresses and code are left out.
+pub fn mentions(content: &str, local_domain: &str) -> Vec<String> {
+    let mut mentions: Vec<String> = vec![];
+    for reference in gill_markdown::references(content) {
+        let Reference::Mention { username, domain } = reference else {
+            continue;
+        };
+
+        let is_local = domain.map_or(true, |domain| domain == local_domain);
+        if is_local && !mentions.contains(&username) {
+            mentions.push(username);
+        }
+    }
+
+    mentions
+}
+
+#[cfg(test)]
+mod test {
+    use crate::domain::notification::mentions;
+    use speculoos::prelude::*;
+
+    #[test]
+    fn should_find_local_mentions() {
+        let mentions = mentions(
+            "@alice could you review this? cc @bob-the_builder, @alice@gill.example.org",
+            "gill.example.org",
+        );
+
+        assert_that!(mentions)
+            .is_equal_to(vec!["alice".to_string(), "bob-the_builder".to_string()]);
+    }
+
+    #[test]
+    fn should_ignore_emails_and_remote_mentions() {
+        let mentions = mentions(
+            "mail alice@example.org or ping @bob@remote.org, not @",
+            "gill.example.org",
+        );
+
+        assert_that!(mentions).is_empty();
+    }
+
+    #[test]
+    fn should_ignore_mentions_in_code() {
+        let mentions = mentions(
+            "run `ssh git@host` as @alice\n\n```\n@bob\n```",
+            "gill.example.org",
+        );
+
+        assert_that!(mentions).is_equal_to(vec!["alice".to_string()]);
+    }
+}
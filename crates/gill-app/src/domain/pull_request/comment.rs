@@ -1,3 +1,4 @@
+use chrono::NaiveDateTime;
 use gill_db::repository::pull_request::comment::PullRequestComment as CommentEntity;
 
 #[derive(Debug, Clone)]
@@ -6,6 +7,8 @@ pub struct PullRequestComment {
     pub repository_id: i32,
     pub created_by: String,
     pub content: String,
+    pub created_at: NaiveDateTime,
+    pub deleted_at: Option<NaiveDateTime>,
 }
 
 impl From<CommentEntity> for PullRequestComment {
@@ -15,6 +18,8 @@ impl From<CommentEntity> for PullRequestComment {
             repository_id: comment.repository_id,
             created_by: comment.created_by,
             content: comment.content,
+            created_at: comment.created_at,
+            deleted_at: comment.deleted_at,
         }
     }
 }
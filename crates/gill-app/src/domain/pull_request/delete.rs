@@ -0,0 +1,44 @@
+use crate::domain::pull_request::PullRequest;
+use crate::domain::repository::Repository;
+use crate::domain::user::User;
+use crate::error::{AppError, AppResult};
+use gill_db::repository::pull_request::PullRequest as PullRequestEntity;
+use sqlx::PgPool;
+
+impl PullRequest {
+    /// Replace the pull request with a tombstone, comments and history are kept
+    /// so the pull request number still resolves
+    pub async fn delete_by(
+        &self,
+        user: &User,
+        repository: &Repository,
+        db: &PgPool,
+    ) -> AppResult<()> {
+        if !PullRequest::is_managed_by(user, &self.opened_by, repository) {
+            return Err(AppError::Unauthorized);
+        }
+
+        let entity: PullRequestEntity = self.clone().into();
+        entity.mark_deleted(db).await.map_err(Into::into)
+    }
+
+    /// Replace comment `comment_id` with a tombstone
+    pub async fn delete_comment_by(
+        &self,
+        user: &User,
+        repository: &Repository,
+        comment_id: i32,
+        db: &PgPool,
+    ) -> AppResult<()> {
+        let entity: PullRequestEntity = self.clone().into();
+        let comment = entity.get_comment(comment_id, db).await?;
+        if !PullRequest::is_managed_by(user, &comment.created_by, repository) {
+            return Err(AppError::Unauthorized);
+        }
+
+        entity
+            .mark_comment_deleted(comment_id, db)
+            .await
+            .map_err(Into::into)
+    }
+}
@@ -1,5 +1,9 @@
+use crate::domain::issue::reference::{IssueReference, ReferenceSource};
+use crate::domain::notification::{NotificationSubject, NotifyCommand};
 use crate::domain::pull_request::comment::PullRequestComment;
+use crate::domain::user::activity::{UserActivity, UserActivityKind};
 use crate::error::AppResult;
+use chrono::NaiveDateTime;
 use std::cmp::Ordering;
 
 use gill_db::repository::pull_request::{
@@ -10,6 +14,8 @@ use gill_git::GitRepository;
 use sqlx::PgPool;
 
 pub mod comment;
+pub mod delete;
+pub mod update;
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct PullRequest {
@@ -21,6 +27,7 @@ pub struct PullRequest {
     pub base: String,
     pub compare: String,
     pub state: PullRequestState,
+    pub deleted_at: Option<NaiveDateTime>,
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
@@ -61,6 +68,7 @@ impl From<PullRequest> for PullRequestEntity {
             base: val.base,
             compare: val.compare,
             state: val.state.into(),
+            deleted_at: val.deleted_at,
         }
     }
 }
@@ -76,6 +84,7 @@ impl From<PullRequestEntity> for PullRequest {
             base: pull_request.base,
             compare: pull_request.compare,
             state: pull_request.state.into(),
+            deleted_at: pull_request.deleted_at,
         }
     }
 }
@@ -100,10 +109,36 @@ impl PullRequest {
     pub async fn comment(&self, comment: &str, user_id: i32, db: &PgPool) -> AppResult<()> {
         let entity: PullRequestEntity = self.clone().into();
         let comment = comment.escape_default().to_string();
-        entity
-            .comment(&comment, user_id, db)
-            .await
-            .map_err(Into::into)
+        entity.comment(&comment, user_id, db).await?;
+        self.notify(user_id, "commented", Some(&comment), db).await;
+        let source = ReferenceSource::PullRequest(self.number);
+        IssueReference::record_all(self.repository_id, &source, user_id, &comment, db).await;
+        UserActivity::record(
+            user_id,
+            self.repository_id,
+            UserActivityKind::CommentPullRequest,
+            Some(self.number),
+            Some(self.title.clone()),
+            db,
+        )
+        .await;
+
+        Ok(())
+    }
+
+    /// Notify the participants, watchers and mentioned users of an action of `actor_id`
+    pub async fn notify(&self, actor_id: i32, action: &str, content: Option<&str>, db: &PgPool) {
+        NotifyCommand {
+            actor_id,
+            repository_id: self.repository_id,
+            subject: NotificationSubject::PullRequest,
+            number: self.number,
+            title: &self.title,
+            action,
+            content,
+        }
+        .execute(db)
+        .await
     }
 
     pub async fn get_comments(&self, db: &PgPool) -> AppResult<Vec<PullRequestComment>> {
@@ -127,4 +162,16 @@ impl PullRequest {
         let diff = repo.diff(&self.base, &self.compare)?;
         Ok(diff)
     }
+
+    pub fn get_patch(&self, owner: &str, name: &str) -> AppResult<Vec<u8>> {
+        let repo = GitRepository::open(owner, name)?;
+        let patch = repo.format_patch(&self.base, &self.compare)?;
+        Ok(patch)
+    }
+
+    pub fn get_raw_diff(&self, owner: &str, name: &str) -> AppResult<Vec<u8>> {
+        let repo = GitRepository::open(owner, name)?;
+        let diff = repo.raw_diff_from_merge_base(&self.base, &self.compare)?;
+        Ok(diff)
+    }
 }
@@ -0,0 +1,106 @@
+use crate::domain::pull_request::PullRequest;
+use crate::domain::repository::Repository;
+use crate::domain::revision::RecordRevisionCommand;
+use crate::domain::timeline::{ItemEventKind, RecordEventCommand};
+use crate::domain::user::User;
+use crate::error::{AppError, AppResult};
+use gill_db::repository::pull_request::PullRequest as PullRequestEntity;
+use sqlx::PgPool;
+
+impl PullRequest {
+    /// Whether `user` is allowed to edit or delete this pull request and its comments
+    /// written by `author`: the author and the repository owner are
+    pub fn is_managed_by(user: &User, author: &str, repository: &Repository) -> bool {
+        user.username == author || repository.attributed_to == user.activity_pub_id
+    }
+
+    /// Edit the pull request title and description, the previous version is kept as a revision
+    pub async fn edit(
+        &self,
+        user: &User,
+        repository: &Repository,
+        title: &str,
+        description: &str,
+        db: &PgPool,
+    ) -> AppResult<()> {
+        if self.deleted_at.is_some() {
+            return Err(AppError::NotFound);
+        }
+
+        if !PullRequest::is_managed_by(user, &self.opened_by, repository) {
+            return Err(AppError::Unauthorized);
+        }
+
+        let description = description.escape_default().to_string();
+        let entity: PullRequestEntity = self.clone().into();
+        entity.update_content(title, Some(&description), db).await?;
+
+        RecordRevisionCommand {
+            repository_id: self.repository_id,
+            number: self.number,
+            issue_comment_id: None,
+            pull_request_comment_id: None,
+            old_title: Some(&self.title),
+            new_title: Some(title),
+            old_content: self.description.as_deref().unwrap_or_default(),
+            new_content: &description,
+            edited_by: user.id,
+        }
+        .execute(db)
+        .await;
+
+        if title != self.title {
+            RecordEventCommand {
+                repository_id: self.repository_id,
+                number: self.number,
+                actor_id: user.id,
+                kind: ItemEventKind::Renamed,
+                old_value: Some(self.title.clone()),
+                new_value: Some(title.to_string()),
+                created_at: None,
+            }
+            .execute(db)
+            .await;
+        }
+
+        Ok(())
+    }
+
+    /// Edit comment `comment_id`, the previous content is kept as a revision
+    pub async fn edit_comment(
+        &self,
+        user: &User,
+        repository: &Repository,
+        comment_id: i32,
+        content: &str,
+        db: &PgPool,
+    ) -> AppResult<()> {
+        let entity: PullRequestEntity = self.clone().into();
+        let comment = entity.get_comment(comment_id, db).await?;
+        if comment.deleted_at.is_some() {
+            return Err(AppError::NotFound);
+        }
+
+        if !PullRequest::is_managed_by(user, &comment.created_by, repository) {
+            return Err(AppError::Unauthorized);
+        }
+
+        let content = content.escape_default().to_string();
+        entity.update_comment(comment_id, &content, db).await?;
+        RecordRevisionCommand {
+            repository_id: self.repository_id,
+            number: self.number,
+            issue_comment_id: None,
+            pull_request_comment_id: Some(comment_id),
+            old_title: None,
+            new_title: None,
+            old_content: &comment.content,
+            new_content: &content,
+            edited_by: user.id,
+        }
+        .execute(db)
+        .await;
+
+        Ok(())
+    }
+}
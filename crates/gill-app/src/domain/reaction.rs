@@ -0,0 +1,330 @@
+use crate::apub::common::GillApubObject;
+use crate::apub::ticket::reaction::{ReactToTicket, ReactionType, UndoReactToTicket};
+use crate::domain::issue::comment::IssueComment;
+use crate::domain::issue::Issue;
+use crate::domain::repository::Repository;
+use crate::domain::user::User;
+use crate::error::{AppError, AppResult};
+use crate::instance::InstanceHandle;
+use gill_db::repository::reaction::{
+    CreateReaction, Reaction as ReactionEntity, ReactionDigest as ReactionDigestEntity,
+};
+use gill_db::Insert;
+use sqlx::PgPool;
+use std::collections::HashMap;
+use url::Url;
+use uuid::Uuid;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum ReactionEmoji {
+    ThumbsUp,
+    ThumbsDown,
+    Heart,
+    Tada,
+    Confused,
+    Eyes,
+}
+
+/// What a reaction is attached to, pull requests and their comments are not federated
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ReactionTarget {
+    Issue,
+    IssueComment(Uuid),
+    PullRequest,
+    PullRequestComment(i32),
+}
+
+/// A reaction to issue or pull request `number` of `repository_id`, or to one of their comments
+#[derive(Debug, Clone)]
+pub struct Reaction {
+    pub repository_id: i32,
+    pub number: i32,
+    pub target: ReactionTarget,
+    pub emoji: ReactionEmoji,
+}
+
+/// Reactions with the same emoji, `usernames` is the who-reacted list
+#[derive(Debug, Clone)]
+pub struct ReactionCount {
+    pub emoji: ReactionEmoji,
+    pub count: i64,
+    pub usernames: Vec<String>,
+}
+
+/// Reactions to an issue or a pull request and to its comments
+#[derive(Debug, Default)]
+pub struct ItemReactions {
+    pub item: Vec<ReactionCount>,
+    issue_comments: HashMap<Uuid, Vec<ReactionCount>>,
+    pull_request_comments: HashMap<i32, Vec<ReactionCount>>,
+}
+
+impl ReactionEmoji {
+    pub const ALL: [ReactionEmoji; 6] = [
+        ReactionEmoji::ThumbsUp,
+        ReactionEmoji::ThumbsDown,
+        ReactionEmoji::Heart,
+        ReactionEmoji::Tada,
+        ReactionEmoji::Confused,
+        ReactionEmoji::Eyes,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReactionEmoji::ThumbsUp => "👍",
+            ReactionEmoji::ThumbsDown => "👎",
+            ReactionEmoji::Heart => "❤️",
+            ReactionEmoji::Tada => "🎉",
+            ReactionEmoji::Confused => "😕",
+            ReactionEmoji::Eyes => "👀",
+        }
+    }
+
+    /// Name used in reaction urls
+    pub fn name(&self) -> &'static str {
+        match self {
+            ReactionEmoji::ThumbsUp => "+1",
+            ReactionEmoji::ThumbsDown => "-1",
+            ReactionEmoji::Heart => "heart",
+            ReactionEmoji::Tada => "hooray",
+            ReactionEmoji::Confused => "confused",
+            ReactionEmoji::Eyes => "eyes",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<ReactionEmoji> {
+        ReactionEmoji::ALL
+            .into_iter()
+            .find(|emoji| emoji.name() == name)
+    }
+
+    /// Parse the content of an `EmojiReact` activity, some instances drop the emoji
+    /// variation selector
+    pub fn from_content(content: &str) -> Option<ReactionEmoji> {
+        let without_selector = |emoji: &str| emoji.trim().replace('\u{fe0f}', "");
+        let content = without_selector(content);
+        ReactionEmoji::ALL
+            .into_iter()
+            .find(|emoji| without_selector(emoji.as_str()) == content)
+    }
+}
+
+impl From<ReactionDigestEntity> for ReactionCount {
+    fn from(reaction: ReactionDigestEntity) -> Self {
+        Self {
+            emoji: ReactionEmoji::from_content(&reaction.emoji).unwrap_or(ReactionEmoji::ThumbsUp),
+            count: reaction.count,
+            usernames: reaction.usernames,
+        }
+    }
+}
+
+impl ReactionCount {
+    pub fn reacted_by(&self, username: Option<&str>) -> bool {
+        match username {
+            Some(username) => self.usernames.iter().any(|name| name == username),
+            None => false,
+        }
+    }
+
+    /// e.g. "alice, bob reacted with 👍"
+    pub fn tooltip(&self) -> String {
+        format!(
+            "{} reacted with {}",
+            self.usernames.join(", "),
+            self.emoji.as_str()
+        )
+    }
+}
+
+impl Reaction {
+    /// A reaction to one of our tickets or ticket comments, `None` if `object` is neither
+    pub async fn to_ticket_object(
+        object: &Url,
+        emoji: ReactionEmoji,
+        db: &PgPool,
+    ) -> AppResult<Option<Reaction>> {
+        if let Some(issue) = Issue::by_activity_pub_id_optional(object.as_str(), db).await? {
+            return Ok(Some(Reaction {
+                repository_id: issue.repository_id,
+                number: issue.number,
+                target: ReactionTarget::Issue,
+                emoji,
+            }));
+        }
+
+        let comment = IssueComment::by_activity_pub_id_optional(object.as_str(), db).await?;
+        Ok(comment.map(|comment| Reaction {
+            repository_id: comment.repository_id,
+            number: comment.number,
+            target: ReactionTarget::IssueComment(comment.id),
+            emoji,
+        }))
+    }
+
+    fn entity(&self, user_id: i32, activity_pub_id: &str) -> CreateReaction {
+        let (issue_comment_id, pull_request_comment_id) = match self.target {
+            ReactionTarget::IssueComment(id) => (Some(id), None),
+            ReactionTarget::PullRequestComment(id) => (None, Some(id)),
+            ReactionTarget::Issue | ReactionTarget::PullRequest => (None, None),
+        };
+
+        CreateReaction {
+            repository_id: self.repository_id,
+            number: self.number,
+            issue_comment_id,
+            pull_request_comment_id,
+            user_id,
+            emoji: self.emoji.as_str().to_string(),
+            activity_pub_id: activity_pub_id.to_string(),
+        }
+    }
+
+    /// Record the reaction, reacting twice with the same emoji is a no-op
+    pub async fn save(&self, user_id: i32, activity_pub_id: &str, db: &PgPool) -> AppResult<()> {
+        self.entity(user_id, activity_pub_id)
+            .insert(db)
+            .await
+            .map_err(Into::into)
+    }
+
+    /// Remove the reaction made by `activity_pub_id` if `user_id` made it
+    pub async fn remove_activity(
+        activity_pub_id: &str,
+        user_id: i32,
+        db: &PgPool,
+    ) -> AppResult<()> {
+        match ReactionEntity::by_activity_pub_id(activity_pub_id, db).await? {
+            Some(reaction) if reaction.user_id == user_id => {
+                reaction.delete(db).await.map_err(Into::into)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Add the reaction of `user`, or remove it if they already reacted with this emoji.
+    /// Reactions to issues and issue comments are sent to the issue audience.
+    pub async fn toggle(
+        &self,
+        user: &User,
+        repository: &Repository,
+        instance: &InstanceHandle,
+    ) -> AppResult<()> {
+        let db = instance.database();
+        // The ticket or ticket comment the reaction is sent for
+        let federated: Option<(Issue, Url)> = match self.target {
+            ReactionTarget::Issue => {
+                let issue = repository.issue_by_number(self.number, db).await?;
+                let object = issue.activity_pub_id.clone().into();
+                Some((issue, object))
+            }
+            ReactionTarget::IssueComment(id) => {
+                let comment = IssueComment::by_id(id, db).await?;
+                if comment.repository_id != self.repository_id || comment.number != self.number {
+                    return Err(AppError::NotFound);
+                }
+
+                let issue = repository.issue_by_number(self.number, db).await?;
+                Some((issue, comment.activity_pub_id.into()))
+            }
+            ReactionTarget::PullRequest | ReactionTarget::PullRequestComment(_) => None,
+        };
+
+        let hostname = instance.local_instance().hostname();
+        let id = format!("https://{hostname}/activity/{uuid}", uuid = Uuid::new_v4());
+        let reaction = self.entity(user.id, &id);
+        let existing = reaction.existing(db).await?;
+        match &existing {
+            Some(existing) => existing.delete(db).await?,
+            None => reaction.insert(db).await?,
+        };
+
+        let Some((issue, object)) = federated else {
+            return Ok(());
+        };
+
+        let audience = issue.audience(repository, user, instance).await?;
+        let kind = match self.emoji {
+            ReactionEmoji::ThumbsUp => ReactionType::Like,
+            _ => ReactionType::EmojiReact,
+        };
+
+        let react = ReactToTicket {
+            id: Url::parse(&id)?,
+            kind,
+            actor: user.activity_pub_id.clone().into(),
+            to: audience.to.clone(),
+            object,
+            content: Some(self.emoji.as_str().to_string()),
+        };
+
+        match existing {
+            None => user.send(react, audience.inboxes, instance).await,
+            Some(existing) => {
+                let undo = UndoReactToTicket {
+                    id: Url::parse(&id)?,
+                    kind: Default::default(),
+                    actor: user.activity_pub_id.clone().into(),
+                    to: audience.to,
+                    object: ReactToTicket {
+                        id: Url::parse(&existing.activity_pub_id)?,
+                        ..react
+                    },
+                };
+
+                user.send(undo, audience.inboxes, instance).await
+            }
+        }
+    }
+}
+
+impl ItemReactions {
+    pub async fn for_item(repository_id: i32, number: i32, db: &PgPool) -> AppResult<Self> {
+        let reactions = ReactionDigestEntity::for_item(repository_id, number, db).await?;
+        let mut item_reactions = ItemReactions::default();
+        for reaction in reactions {
+            match (reaction.issue_comment_id, reaction.pull_request_comment_id) {
+                (Some(id), _) => item_reactions
+                    .issue_comments
+                    .entry(id)
+                    .or_default()
+                    .push(reaction.into()),
+                (None, Some(id)) => item_reactions
+                    .pull_request_comments
+                    .entry(id)
+                    .or_default()
+                    .push(reaction.into()),
+                (None, None) => item_reactions.item.push(reaction.into()),
+            }
+        }
+
+        Ok(item_reactions)
+    }
+
+    pub fn of_issue_comment(&self, id: &Uuid) -> &[ReactionCount] {
+        self.issue_comments
+            .get(id)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    pub fn of_pull_request_comment(&self, id: &i32) -> &[ReactionCount] {
+        self.pull_request_comments
+            .get(id)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::domain::reaction::ReactionEmoji;
+    use speculoos::prelude::*;
+
+    #[test]
+    fn should_parse_emoji_react_content() {
+        assert_that!(ReactionEmoji::from_content("❤")).is_equal_to(Some(ReactionEmoji::Heart));
+        assert_that!(ReactionEmoji::from_content(" 👀 ")).is_equal_to(Some(ReactionEmoji::Eyes));
+        assert_that!(ReactionEmoji::from_content("🦀")).is_none();
+    }
+}
@@ -0,0 +1,146 @@
+use crate::apub::common::GillApubObject;
+use crate::apub::release::create::CreateRelease;
+use crate::domain::id::ActivityPubId;
+use crate::domain::release::{NewRelease, Release};
+use crate::domain::repository::Repository;
+use crate::domain::user::User;
+use crate::error::{AppError, AppResult};
+use crate::instance::InstanceHandle;
+use activitypub_federation::traits::ApubObject;
+use chrono::Utc;
+use gill_git::GitRepository;
+use std::fs;
+use std::path::PathBuf;
+use tracing::{debug, warn};
+use url::Url;
+use uuid::Uuid;
+
+pub struct CreateReleaseCommand<'a> {
+    pub owner: &'a str,
+    pub repository: &'a str,
+    pub tag: &'a str,
+    pub title: &'a str,
+    pub content: &'a str,
+    pub assets: Vec<ReleaseAssetUpload>,
+}
+
+/// An uploaded asset, streamed to a staging file until its release is saved
+pub struct ReleaseAssetUpload {
+    pub filename: String,
+    pub content_type: String,
+    pub path: PathBuf,
+    pub size: i64,
+}
+
+impl ReleaseAssetUpload {
+    /// Create an empty upload staged in the repository release assets directory,
+    /// so it can be moved in place once the release is saved
+    pub fn staged(
+        owner: &str,
+        repository: &str,
+        filename: String,
+        content_type: String,
+    ) -> AppResult<Self> {
+        let repo = GitRepository::open(owner, repository)?;
+        let dir = repo.release_assets_path().join(".uploads");
+        fs::create_dir_all(&dir)?;
+
+        Ok(Self {
+            filename,
+            content_type,
+            path: dir.join(Uuid::new_v4().to_string()),
+            size: 0,
+        })
+    }
+}
+
+impl Drop for ReleaseAssetUpload {
+    fn drop(&mut self) {
+        // Uploads which were not moved next to a saved release are discarded
+        if self.path.exists() {
+            if let Err(err) = fs::remove_file(&self.path) {
+                warn!("Failed to remove staged upload {:?}: {err}", self.path);
+            }
+        }
+    }
+}
+
+impl CreateReleaseCommand<'_> {
+    pub async fn execute(self, author: &User, instance: &InstanceHandle) -> AppResult<Release> {
+        let db = instance.database();
+        let repository = Repository::by_namespace(self.owner, self.repository, db).await?;
+
+        if repository.attributed_to != author.activity_pub_id {
+            return Err(AppError::Unauthorized);
+        }
+
+        // A release can only be created for a tag that was pushed to the repository
+        let tag = repository.get_tag(self.tag, db).await?;
+        let activity_pub_id: ActivityPubId<Release> =
+            Release::activity_pub_id_from_namespace(self.owner, self.repository, &tag.name)?.into();
+
+        let release = NewRelease {
+            repository_id: repository.id,
+            tag: tag.name,
+            activity_pub_id,
+            title: self.title.to_string(),
+            content: self.content.to_string(),
+            media_type: "text/markdown".to_string(),
+            attributed_to: author.activity_pub_id.clone(),
+            published: Utc::now().naive_utc(),
+            is_local: true,
+        };
+
+        let mut tx = db.begin().await?;
+        let release = release.save_in(&mut tx).await?;
+
+        let saved: AppResult<()> = async {
+            for asset in &self.assets {
+                release
+                    .add_asset(self.owner, self.repository, asset, &mut tx)
+                    .await?;
+            }
+
+            tx.commit().await?;
+            Ok(())
+        }
+        .await;
+
+        // The transaction was rolled back, remove the assets already moved in place
+        if let Err(err) = saved {
+            let dir = release.assets_dir(self.owner, self.repository)?;
+            if dir.exists() {
+                fs::remove_dir_all(dir)?;
+            }
+
+            return Err(err);
+        }
+
+        let recipients = repository.followers(instance).await?;
+        if recipients.is_empty() {
+            return Ok(release);
+        }
+
+        let hostname = instance.local_instance().hostname();
+        let id = format!("https://{hostname}/activity/{uuid}", uuid = Uuid::new_v4());
+        let create_event = CreateRelease {
+            actor: repository.activity_pub_id.clone().into(),
+            to: vec![repository.followers_url.clone()],
+            object: release.clone().into_apub(instance).await?,
+            kind: Default::default(),
+            id: Url::parse(&id)?,
+        };
+
+        debug!(
+            "Sending CreateRelease event to {:#?}",
+            recipients
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<String>>()
+        );
+
+        repository.send(create_event, recipients, instance).await?;
+
+        Ok(release)
+    }
+}
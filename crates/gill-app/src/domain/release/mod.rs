@@ -0,0 +1,267 @@
+use crate::domain::id::ActivityPubId;
+use crate::domain::release::create::ReleaseAssetUpload;
+use crate::domain::repository::Repository;
+use crate::domain::user::User;
+use crate::error::{AppError, AppResult};
+use activitypub_federation::core::object_id::ObjectId;
+use gill_db::repository::release::{
+    CreateRelease as CreateReleaseEntity, Release as ReleaseEntity,
+    ReleaseAsset as ReleaseAssetEntity,
+};
+use gill_db::repository::Repository as RepositoryEntity;
+use gill_db::Insert;
+use gill_git::GitRepository;
+use gill_settings::SETTINGS;
+use sqlx::{PgPool, Postgres, Transaction};
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+use url::{ParseError, Url};
+
+pub mod create;
+
+#[derive(Debug, Clone)]
+pub struct Release {
+    pub id: i32,
+    pub repository_id: i32,
+    pub tag: String,
+    pub activity_pub_id: ActivityPubId<Release>,
+    pub title: String,
+    pub content: String,
+    pub media_type: String,
+    pub attributed_to: ActivityPubId<User>,
+    pub published: chrono::NaiveDateTime,
+    pub is_local: bool,
+}
+
+/// A release which has not been persisted yet
+#[derive(Debug)]
+pub struct NewRelease {
+    pub repository_id: i32,
+    pub tag: String,
+    pub activity_pub_id: ActivityPubId<Release>,
+    pub title: String,
+    pub content: String,
+    pub media_type: String,
+    pub attributed_to: ActivityPubId<User>,
+    pub published: chrono::NaiveDateTime,
+    pub is_local: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct ReleaseAsset {
+    pub id: i32,
+    pub release_id: i32,
+    pub filename: String,
+    pub content_type: String,
+    pub size: i64,
+}
+
+impl TryFrom<ReleaseEntity> for Release {
+    type Error = ParseError;
+
+    fn try_from(release: ReleaseEntity) -> Result<Self, ParseError> {
+        Ok(Self {
+            id: release.id,
+            repository_id: release.repository_id,
+            tag: release.tag,
+            activity_pub_id: ActivityPubId::try_from(release.activity_pub_id)?,
+            title: release.title,
+            content: release.content,
+            media_type: release.media_type,
+            attributed_to: ActivityPubId::try_from(release.attributed_to)?,
+            published: release.published,
+            is_local: release.is_local,
+        })
+    }
+}
+
+impl From<&Release> for ReleaseEntity {
+    fn from(val: &Release) -> Self {
+        ReleaseEntity {
+            id: val.id,
+            repository_id: val.repository_id,
+            tag: val.tag.clone(),
+            activity_pub_id: val.activity_pub_id.to_string(),
+            title: val.title.clone(),
+            content: val.content.clone(),
+            media_type: val.media_type.clone(),
+            attributed_to: val.attributed_to.to_string(),
+            published: val.published,
+            is_local: val.is_local,
+        }
+    }
+}
+
+impl From<NewRelease> for CreateReleaseEntity {
+    fn from(val: NewRelease) -> Self {
+        CreateReleaseEntity {
+            repository_id: val.repository_id,
+            tag: val.tag,
+            activity_pub_id: val.activity_pub_id.to_string(),
+            title: val.title,
+            content: val.content,
+            media_type: val.media_type,
+            attributed_to: val.attributed_to.to_string(),
+            published: val.published,
+            is_local: val.is_local,
+        }
+    }
+}
+
+impl From<ReleaseAssetEntity> for ReleaseAsset {
+    fn from(asset: ReleaseAssetEntity) -> Self {
+        Self {
+            id: asset.id,
+            release_id: asset.release_id,
+            filename: asset.filename,
+            content_type: asset.content_type,
+            size: asset.size,
+        }
+    }
+}
+
+impl NewRelease {
+    pub async fn save(self, db: &PgPool) -> AppResult<Release> {
+        let entity: CreateReleaseEntity = self.into();
+        let entity = entity.insert(db).await?;
+        Release::try_from(entity).map_err(Into::into)
+    }
+
+    pub async fn save_in(self, tx: &mut Transaction<'_, Postgres>) -> AppResult<Release> {
+        let entity: CreateReleaseEntity = self.into();
+        let entity = entity.insert_in(&mut *tx).await?;
+        Release::try_from(entity).map_err(Into::into)
+    }
+}
+
+impl Release {
+    pub async fn by_activity_pub_id_optional(
+        activity_pub_id: &str,
+        db: &PgPool,
+    ) -> AppResult<Option<Release>> {
+        let entity = ReleaseEntity::by_activity_pub_id(activity_pub_id, db).await;
+        match entity {
+            Ok(entity) => {
+                let release = Release::try_from(entity)?;
+                Ok(Some(release))
+            }
+            Err(sqlx::Error::RowNotFound) => Ok(None),
+            Err(err) => Err(AppError::from(err)),
+        }
+    }
+
+    pub fn activity_pub_id_from_namespace(
+        user: &str,
+        repository: &str,
+        tag: &str,
+    ) -> AppResult<ObjectId<Self>> {
+        let domain = &SETTINGS.domain;
+        let scheme = if SETTINGS.debug { "http" } else { "https" };
+        let mut url = Url::from_str(&format!(
+            "{scheme}://{domain}/users/{user}/repositories/{repository}/releases"
+        ))?;
+
+        // Tag names may contain slashes, push it as a single percent encoded segment
+        url.path_segments_mut()
+            .map_err(|_| AppError::Internal(anyhow::anyhow!("Invalid release url for {tag}")))?
+            .push(tag);
+
+        Ok(ObjectId::new(url))
+    }
+
+    pub async fn list_assets(&self, db: &PgPool) -> AppResult<Vec<ReleaseAsset>> {
+        let entity: ReleaseEntity = self.into();
+        let assets = entity.list_assets(db).await?;
+        Ok(assets.into_iter().map(ReleaseAsset::from).collect())
+    }
+
+    pub async fn get_asset(&self, filename: &str, db: &PgPool) -> AppResult<ReleaseAsset> {
+        let entity: ReleaseEntity = self.into();
+        let asset = entity.get_asset(filename, db).await?;
+        Ok(ReleaseAsset::from(asset))
+    }
+
+    /// Move a staged upload next to this release assets and register it, the row is only
+    /// visible once the surrounding transaction commits.
+    pub async fn add_asset(
+        &self,
+        owner: &str,
+        repository: &str,
+        upload: &ReleaseAssetUpload,
+        tx: &mut Transaction<'_, Postgres>,
+    ) -> AppResult<ReleaseAsset> {
+        // Only keep the file name, we don't want the client to choose where the asset is written
+        let filename = PathBuf::from(&upload.filename)
+            .file_name()
+            .map(|filename| filename.to_string_lossy().to_string())
+            .ok_or_else(|| anyhow::anyhow!("Invalid asset filename: {}", upload.filename))?;
+
+        let entity: ReleaseEntity = self.into();
+        let asset = entity
+            .add_asset(&filename, &upload.content_type, upload.size, &mut *tx)
+            .await?;
+
+        let path = self.asset_path(owner, repository, &filename)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::rename(&upload.path, &path)?;
+
+        Ok(ReleaseAsset::from(asset))
+    }
+
+    pub fn read_asset(
+        &self,
+        owner: &str,
+        repository: &str,
+        asset: &ReleaseAsset,
+    ) -> AppResult<Vec<u8>> {
+        let path = self.asset_path(owner, repository, &asset.filename)?;
+        fs::read(path).map_err(Into::into)
+    }
+
+    fn asset_path(&self, owner: &str, repository: &str, filename: &str) -> AppResult<PathBuf> {
+        Ok(self.assets_dir(owner, repository)?.join(filename))
+    }
+
+    fn assets_dir(&self, owner: &str, repository: &str) -> AppResult<PathBuf> {
+        let repo = GitRepository::open(owner, repository)?;
+        Ok(repo.release_assets_path().join(self.id.to_string()))
+    }
+}
+
+impl Repository {
+    pub async fn list_releases(
+        &self,
+        limit: i64,
+        offset: i64,
+        db: &PgPool,
+    ) -> AppResult<Vec<Release>> {
+        let entity: RepositoryEntity = self.into();
+        let releases = entity.list_releases(limit, offset, db).await?;
+        let releases = releases
+            .into_iter()
+            .map(Release::try_from)
+            .filter_map(Result::ok)
+            .collect();
+
+        Ok(releases)
+    }
+
+    pub async fn get_release(&self, tag: &str, db: &PgPool) -> AppResult<Release> {
+        let entity: RepositoryEntity = self.into();
+        let release = entity.get_release(tag, db).await?;
+        Release::try_from(release).map_err(Into::into)
+    }
+
+    pub async fn get_latest_release(&self, db: &PgPool) -> AppResult<Option<Release>> {
+        let entity: RepositoryEntity = self.into();
+        let release = entity.get_latest_release(db).await?;
+        release
+            .map(Release::try_from)
+            .transpose()
+            .map_err(Into::into)
+    }
+}
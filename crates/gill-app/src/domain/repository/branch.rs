@@ -1,4 +1,11 @@
+use crate::domain::repository::Repository;
+use crate::domain::user::User;
+use crate::error::{AppError, AppResult};
+use anyhow::anyhow;
 use gill_db::repository::branch::Branch as BranchEntity;
+use gill_db::repository::Repository as RepositoryEntity;
+use gill_git::GitRepository;
+use sqlx::PgPool;
 
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub struct Branch {
@@ -16,3 +23,132 @@ impl From<BranchEntity> for Branch {
         }
     }
 }
+
+impl Repository {
+    /// Create `name` from an existing branch, tag or commit sha
+    pub async fn create_branch(
+        &self,
+        user: &User,
+        owner: &str,
+        name: &str,
+        from: &str,
+        db: &PgPool,
+    ) -> AppResult<()> {
+        if self.attributed_to != user.activity_pub_id {
+            return Err(AppError::Unauthorized);
+        };
+
+        self.ensure_not_mirror(db).await?;
+
+        let git_repository = GitRepository::open(owner, &self.name)?;
+        git_repository.create_branch(name, from)?;
+        let entity: RepositoryEntity = self.into();
+        entity.create_branch(name, db).await?;
+        Ok(())
+    }
+
+    /// Delete a branch which has been merged into the default branch and is not
+    /// the base or compare branch of an open pull request
+    pub async fn delete_branch(
+        &self,
+        user: &User,
+        owner: &str,
+        name: &str,
+        db: &PgPool,
+    ) -> AppResult<()> {
+        if self.attributed_to != user.activity_pub_id {
+            return Err(AppError::Unauthorized);
+        };
+
+        self.ensure_not_mirror(db).await?;
+
+        let default_branch = self
+            .get_default_branch(db)
+            .await
+            .ok_or_else(|| anyhow!("{} has no default branch", self.name))?;
+
+        if default_branch.name == name {
+            return Err(anyhow!("Cannot delete the default branch").into());
+        }
+
+        let git_repository = GitRepository::open(owner, &self.name)?;
+        if !git_repository.is_merged(&default_branch.name, name)? {
+            return Err(anyhow!("{name} is not merged into {}", default_branch.name).into());
+        }
+
+        // Kept to restore the branch if the deletion cannot be committed
+        let head = git_repository.branch_head(name)?;
+        let mut tx = db.begin().await?;
+        let entity: RepositoryEntity = self.into();
+        if !entity.delete_branch(name, &mut tx).await? {
+            return Err(anyhow!("{name} is used by an open pull request").into());
+        }
+
+        git_repository.delete_branch(name)?;
+        if let Err(err) = tx.commit().await {
+            git_repository.create_branch(name, &head.id)?;
+            return Err(err.into());
+        }
+
+        Ok(())
+    }
+
+    /// Rename a branch, open pull requests are retargeted to the new name
+    pub async fn rename_branch(
+        &self,
+        user: &User,
+        owner: &str,
+        name: &str,
+        new_name: &str,
+        db: &PgPool,
+    ) -> AppResult<()> {
+        if self.attributed_to != user.activity_pub_id {
+            return Err(AppError::Unauthorized);
+        };
+
+        self.ensure_not_mirror(db).await?;
+
+        let git_repository = GitRepository::open(owner, &self.name)?;
+        let mut tx = db.begin().await?;
+        let entity: RepositoryEntity = self.into();
+        entity.rename_branch(name, new_name, &mut tx).await?;
+        git_repository.rename_branch(name, new_name)?;
+        if let Err(err) = tx.commit().await {
+            git_repository.rename_branch(new_name, name)?;
+            return Err(err.into());
+        }
+
+        // HEAD is not updated by `git branch -m` in a bare repository
+        let default_branch = self.get_default_branch(db).await;
+        if matches!(default_branch, Some(branch) if branch.name == new_name) {
+            git_repository.set_head(new_name)?;
+        }
+
+        Ok(())
+    }
+
+    /// Change the default branch, HEAD of the bare repository follows
+    pub async fn change_default_branch(
+        &self,
+        user: &User,
+        owner: &str,
+        name: &str,
+        db: &PgPool,
+    ) -> AppResult<()> {
+        if self.attributed_to != user.activity_pub_id {
+            return Err(AppError::Unauthorized);
+        };
+
+        self.ensure_not_mirror(db).await?;
+
+        let git_repository = GitRepository::open(owner, &self.name)?;
+        if git_repository.branch_head(name).is_err() {
+            return Err(AppError::NotFound);
+        }
+
+        git_repository.set_head(name)?;
+        let entity: RepositoryEntity = self.into();
+        entity.set_default_branch(name, db).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,253 @@
+use crate::error::AppResult;
+use gill_git::traversal::TreeEntry;
+use gill_git::GitRepository;
+use gill_markdown::Location;
+
+/// README file names by priority, lowercased. Only markdown is rendered, reStructuredText
+/// and Org READMEs are still picked up but displayed as plain text.
+const README_FILES: [&str; 6] = [
+    "readme.md",
+    "readme.markdown",
+    "readme.rst",
+    "readme.org",
+    "readme.txt",
+    "readme",
+];
+
+/// License file names, `LICENSE-MIT` and `LICENSE.md` are licenses as well
+const LICENSE_FILES: [&str; 4] = ["LICENSE", "LICENCE", "COPYING", "UNLICENSE"];
+
+/// SPDX identifiers and phrases of the license text identifying them, most specific first.
+/// GPL variants all mention the GPL and BSD-3-Clause contains the BSD-2-Clause text.
+const KNOWN_LICENSES: [(&str, &[&str]); 15] = [
+    (
+        "AGPL-3.0",
+        &["gnu affero general public license", "version 3"],
+    ),
+    (
+        "LGPL-3.0",
+        &["gnu lesser general public license", "version 3"],
+    ),
+    (
+        "LGPL-2.1",
+        &["gnu lesser general public license", "version 2.1"],
+    ),
+    ("GPL-3.0", &["gnu general public license", "version 3"]),
+    ("GPL-2.0", &["gnu general public license", "version 2"]),
+    ("Apache-2.0", &["apache license", "version 2.0"]),
+    ("MPL-2.0", &["mozilla public license version 2.0"]),
+    ("EUPL-1.2", &["european union public licence", "v. 1.2"]),
+    ("BSL-1.0", &["boost software license - version 1.0"]),
+    (
+        "Unlicense",
+        &["this is free and unencumbered software released into the public domain"],
+    ),
+    ("CC0-1.0", &["cc0 1.0 universal"]),
+    (
+        "ISC",
+        &["permission to use, copy, modify, and/or distribute this software for any purpose"],
+    ),
+    (
+        "BSD-3-Clause",
+        &[
+            "redistribution and use in source and binary forms",
+            "neither the name",
+        ],
+    ),
+    (
+        "BSD-2-Clause",
+        &["redistribution and use in source and binary forms"],
+    ),
+    ("MIT", &["permission is hereby granted, free of charge"]),
+];
+
+/// The README displayed below a directory listing
+#[derive(Debug)]
+pub struct Readme {
+    pub filename: String,
+    pub content: ReadmeContent,
+}
+
+#[derive(Debug)]
+pub enum ReadmeContent {
+    /// Rendered markdown
+    Html(String),
+    /// reStructuredText, Org and text files are displayed as is
+    PlainText(String),
+}
+
+/// Community files found at the root of a branch, linked from the repository sidebar
+#[derive(Debug, Default)]
+pub struct RepositoryDocs {
+    pub licenses: Vec<License>,
+    pub contributing: Option<String>,
+    pub security: Option<String>,
+    pub code_of_conduct: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct License {
+    pub filename: String,
+    /// `None` when the license text was not recognized
+    pub spdx_id: Option<String>,
+}
+
+impl Readme {
+    /// The README of `tree`, markdown links are resolved from `location`
+    pub fn find(
+        tree: &TreeEntry,
+        repository: &GitRepository,
+        location: Location,
+    ) -> AppResult<Option<Readme>> {
+        let readme = tree
+            .blobs
+            .iter()
+            .filter_map(|blob| {
+                let filename = blob.filename();
+                readme_priority(&filename).map(|priority| (priority, filename, blob))
+            })
+            .min_by_key(|(priority, ..)| *priority);
+
+        let Some((_, filename, blob)) = readme else {
+            return Ok(None);
+        };
+
+        let content = repository.blob_str(blob)?;
+        let content = if is_markdown(&filename) {
+            ReadmeContent::Html(gill_markdown::render_at(&content, location))
+        } else {
+            ReadmeContent::PlainText(content)
+        };
+
+        Ok(Some(Readme { filename, content }))
+    }
+}
+
+impl RepositoryDocs {
+    /// Licenses, contributing guide, security policy and code of conduct of `tree`
+    pub fn find(tree: &TreeEntry, repository: &GitRepository) -> AppResult<RepositoryDocs> {
+        let mut docs = RepositoryDocs::default();
+        for blob in tree.blobs.iter() {
+            let filename = blob.filename();
+            let stem = filename
+                .split('.')
+                .next()
+                .unwrap_or_default()
+                .to_uppercase();
+
+            match stem.as_str() {
+                "CONTRIBUTING" => {
+                    docs.contributing.get_or_insert(filename);
+                }
+                "SECURITY" => {
+                    docs.security.get_or_insert(filename);
+                }
+                "CODE_OF_CONDUCT" => {
+                    docs.code_of_conduct.get_or_insert(filename);
+                }
+                stem if is_license(stem) => {
+                    let content = repository.blob_str(blob)?;
+                    docs.licenses.push(License {
+                        filename,
+                        spdx_id: spdx_id(&content),
+                    });
+                }
+                _ => {}
+            }
+        }
+
+        docs.licenses.sort_by(|a, b| a.filename.cmp(&b.filename));
+        Ok(docs)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.licenses.is_empty()
+            && self.contributing.is_none()
+            && self.security.is_none()
+            && self.code_of_conduct.is_none()
+    }
+}
+
+impl License {
+    /// e.g. "MIT", or the license file name when it was not recognized
+    pub fn name(&self) -> &str {
+        self.spdx_id.as_deref().unwrap_or(&self.filename)
+    }
+}
+
+pub fn is_markdown(filename: &str) -> bool {
+    let filename = filename.to_lowercase();
+    filename.ends_with(".md") || filename.ends_with(".markdown")
+}
+
+fn readme_priority(filename: &str) -> Option<usize> {
+    let filename = filename.to_lowercase();
+    README_FILES.iter().position(|readme| *readme == filename)
+}
+
+fn is_license(stem: &str) -> bool {
+    LICENSE_FILES
+        .iter()
+        .any(|license| match stem.strip_prefix(license) {
+            Some(rest) => rest.is_empty() || rest.starts_with(['-', '_']),
+            None => false,
+        })
+}
+
+/// SPDX identifier of the license in `content`, from its `SPDX-License-Identifier` tag or
+/// from well known phrases of the license text
+fn spdx_id(content: &str) -> Option<String> {
+    let tag = content
+        .lines()
+        .find_map(|line| line.split_once("SPDX-License-Identifier:"))
+        .map(|(_, id)| id.trim().trim_end_matches("*/").trim().to_string())
+        .filter(|id| !id.is_empty());
+
+    if tag.is_some() {
+        return tag;
+    }
+
+    let text = content
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase();
+
+    KNOWN_LICENSES
+        .iter()
+        .find(|(_, phrases)| phrases.iter().all(|phrase| text.contains(phrase)))
+        .map(|(id, _)| id.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::domain::repository::docs::{is_license, readme_priority, spdx_id};
+    use speculoos::prelude::*;
+
+    #[test]
+    fn should_detect_spdx_id_from_license_text() {
+        let mit = "MIT License\n\nCopyright (c) 2023 Alice\n\nPermission is hereby granted, free of charge, to any person\nobtaining a copy of this software";
+        let agpl = "GNU AFFERO GENERAL PUBLIC LICENSE\n   Version 3, 19 November 2007\n\n the GNU General Public License";
+
+        assert_that!(spdx_id(mit)).is_equal_to(Some("MIT".to_string()));
+        assert_that!(spdx_id(agpl)).is_equal_to(Some("AGPL-3.0".to_string()));
+        assert_that!(spdx_id("All rights reserved")).is_none();
+    }
+
+    #[test]
+    fn should_prefer_spdx_tag() {
+        let content = "/* SPDX-License-Identifier: MIT OR Apache-2.0 */\nPermission is hereby granted, free of charge";
+
+        assert_that!(spdx_id(content)).is_equal_to(Some("MIT OR Apache-2.0".to_string()));
+    }
+
+    #[test]
+    fn should_find_readme_and_license_files() {
+        assert_that!(readme_priority("README.md")).is_less_than(readme_priority("readme.rst"));
+        assert_that!(readme_priority("README.rst")).is_less_than(readme_priority("README"));
+        assert_that!(readme_priority("READ_ME.md")).is_none();
+        assert_that!(is_license("LICENSE")).is_true();
+        assert_that!(is_license("LICENSE-APACHE")).is_true();
+        assert_that!(is_license("LICENSES")).is_false();
+    }
+}
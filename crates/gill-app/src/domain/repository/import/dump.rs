@@ -0,0 +1,584 @@
+use anyhow::anyhow;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::str::FromStr;
+use url::Url;
+
+pub(crate) const DEFAULT_LABEL_COLOR: &str = "#cbd5e1";
+/// Login used by forges for deleted accounts
+const GHOST_USER: &str = "ghost";
+
+/// Format of the issue tracker API dump given to the importer
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum DumpFormat {
+    GitHub,
+    Gitea,
+    GitLab,
+}
+
+impl FromStr for DumpFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(format: &str) -> Result<Self, Self::Err> {
+        match format {
+            "github" => Ok(DumpFormat::GitHub),
+            "gitea" => Ok(DumpFormat::Gitea),
+            "gitlab" => Ok(DumpFormat::GitLab),
+            other => Err(anyhow!("Unknown dump format: {other}")),
+        }
+    }
+}
+
+/// Issue tracker content to import, independent of the forge it comes from
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ImportDump {
+    /// Base url of the source forge, authors without a local account get a placeholder
+    /// user pointing to their profile there
+    pub source_url: Option<Url>,
+    pub labels: Vec<ImportedLabel>,
+    pub issues: Vec<ImportedIssue>,
+    pub pull_requests: Vec<ImportedPullRequest>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ImportedLabel {
+    pub name: String,
+    /// Hex color, '#' prefixed
+    pub color: String,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ImportedIssue {
+    pub number: i32,
+    pub title: String,
+    pub body: String,
+    pub author: String,
+    pub closed: bool,
+    pub labels: Vec<String>,
+    pub created_at: Option<NaiveDateTime>,
+    pub comments: Vec<ImportedComment>,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub enum ImportedPullRequestState {
+    Open,
+    Closed,
+    Merged,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ImportedPullRequest {
+    pub number: i32,
+    pub title: String,
+    pub body: Option<String>,
+    pub author: String,
+    pub state: ImportedPullRequestState,
+    pub base: String,
+    pub head: String,
+    pub labels: Vec<String>,
+    pub comments: Vec<ImportedComment>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ImportedComment {
+    pub author: String,
+    pub body: String,
+    pub created_at: Option<NaiveDateTime>,
+}
+
+impl ImportDump {
+    pub fn parse(format: DumpFormat, source_url: Option<Url>, json: &[u8]) -> anyhow::Result<Self> {
+        let mut dump = match format {
+            DumpFormat::GitHub | DumpFormat::Gitea => {
+                let dump: GitHubDump = serde_json::from_slice(json)?;
+                ImportDump::from(dump)
+            }
+            DumpFormat::GitLab => {
+                let dump: GitLabDump = serde_json::from_slice(json)?;
+                ImportDump::from(dump)
+            }
+        };
+
+        dump.source_url = source_url;
+        Ok(dump)
+    }
+
+    /// Number of labels, issues and pull requests to import
+    pub fn len(&self) -> usize {
+        self.labels.len() + self.issues.len() + self.pull_requests.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Every login appearing as an issue, pull request or comment author
+    pub fn authors(&self) -> BTreeSet<&str> {
+        let issues = self.issues.iter().flat_map(|issue| {
+            std::iter::once(issue.author.as_str())
+                .chain(issue.comments.iter().map(|comment| comment.author.as_str()))
+        });
+
+        let pull_requests = self.pull_requests.iter().flat_map(|pull_request| {
+            std::iter::once(pull_request.author.as_str()).chain(
+                pull_request
+                    .comments
+                    .iter()
+                    .map(|comment| comment.author.as_str()),
+            )
+        });
+
+        issues.chain(pull_requests).collect()
+    }
+}
+
+// Forges return colors with or without the leading '#'
+fn normalize_color(color: &str) -> String {
+    let hex = color.trim_start_matches('#');
+    if hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        format!("#{}", hex.to_ascii_lowercase())
+    } else {
+        DEFAULT_LABEL_COLOR.to_string()
+    }
+}
+
+fn naive(date: Option<DateTime<Utc>>) -> Option<NaiveDateTime> {
+    date.map(|date| date.naive_utc())
+}
+
+/// GitHub REST API responses, Gitea serves the same shape
+#[derive(Deserialize, Debug)]
+struct GitHubDump {
+    #[serde(default)]
+    labels: Vec<GitHubLabel>,
+    /// `/issues` lists pull requests as well, they are recognized by their `pull_request` field
+    #[serde(default)]
+    issues: Vec<GitHubIssue>,
+    #[serde(default)]
+    pull_requests: Vec<GitHubPullRequest>,
+    /// Issue and pull request comments from `/issues/comments`
+    #[serde(default)]
+    comments: Vec<GitHubComment>,
+}
+
+#[derive(Deserialize, Debug)]
+struct GitHubUser {
+    login: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct GitHubLabel {
+    name: String,
+    #[serde(default)]
+    color: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct GitHubIssue {
+    number: i32,
+    title: String,
+    body: Option<String>,
+    state: String,
+    user: Option<GitHubUser>,
+    #[serde(default)]
+    labels: Vec<GitHubLabel>,
+    created_at: Option<DateTime<Utc>>,
+    pull_request: Option<serde_json::Value>,
+}
+
+#[derive(Deserialize, Debug)]
+struct GitHubRef {
+    #[serde(rename = "ref")]
+    name: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct GitHubPullRequest {
+    number: i32,
+    title: String,
+    body: Option<String>,
+    state: String,
+    user: Option<GitHubUser>,
+    #[serde(default)]
+    labels: Vec<GitHubLabel>,
+    base: GitHubRef,
+    head: GitHubRef,
+    merged_at: Option<DateTime<Utc>>,
+    /// Gitea only
+    #[serde(default)]
+    merged: bool,
+}
+
+#[derive(Deserialize, Debug)]
+struct GitHubComment {
+    #[serde(default)]
+    issue_url: String,
+    /// Gitea fills this one instead of `issue_url` for pull request comments
+    #[serde(default)]
+    pull_request_url: String,
+    body: Option<String>,
+    user: Option<GitHubUser>,
+    created_at: Option<DateTime<Utc>>,
+}
+
+impl GitHubComment {
+    fn number(&self) -> Option<i32> {
+        [&self.issue_url, &self.pull_request_url]
+            .into_iter()
+            .find_map(|url| url.rsplit('/').next()?.parse().ok())
+    }
+}
+
+fn login(user: Option<GitHubUser>) -> String {
+    user.map(|user| user.login)
+        .unwrap_or_else(|| GHOST_USER.to_string())
+}
+
+impl From<GitHubDump> for ImportDump {
+    fn from(dump: GitHubDump) -> Self {
+        let mut sorted_comments = dump.comments;
+        sorted_comments.sort_by_key(|comment| comment.created_at);
+        let mut comments: HashMap<i32, Vec<ImportedComment>> = HashMap::new();
+        for comment in sorted_comments {
+            if let Some(number) = comment.number() {
+                comments.entry(number).or_default().push(ImportedComment {
+                    body: comment.body.unwrap_or_default(),
+                    created_at: naive(comment.created_at),
+                    author: login(comment.user),
+                });
+            }
+        }
+
+        let issues = dump
+            .issues
+            .into_iter()
+            .filter(|issue| issue.pull_request.is_none())
+            .map(|issue| ImportedIssue {
+                number: issue.number,
+                title: issue.title,
+                body: issue.body.unwrap_or_default(),
+                author: login(issue.user),
+                closed: issue.state == "closed",
+                labels: issue.labels.into_iter().map(|label| label.name).collect(),
+                created_at: naive(issue.created_at),
+                comments: comments.remove(&issue.number).unwrap_or_default(),
+            })
+            .collect();
+
+        let pull_requests = dump
+            .pull_requests
+            .into_iter()
+            .map(|pull_request| {
+                let state = if pull_request.merged || pull_request.merged_at.is_some() {
+                    ImportedPullRequestState::Merged
+                } else if pull_request.state == "closed" {
+                    ImportedPullRequestState::Closed
+                } else {
+                    ImportedPullRequestState::Open
+                };
+
+                ImportedPullRequest {
+                    number: pull_request.number,
+                    title: pull_request.title,
+                    body: pull_request.body,
+                    author: login(pull_request.user),
+                    state,
+                    base: pull_request.base.name,
+                    head: pull_request.head.name,
+                    labels: pull_request
+                        .labels
+                        .into_iter()
+                        .map(|label| label.name)
+                        .collect(),
+                    comments: comments.remove(&pull_request.number).unwrap_or_default(),
+                }
+            })
+            .collect();
+
+        let labels = dump
+            .labels
+            .into_iter()
+            .map(|label| ImportedLabel {
+                color: normalize_color(&label.color),
+                name: label.name,
+            })
+            .collect();
+
+        ImportDump {
+            source_url: None,
+            labels,
+            issues,
+            pull_requests,
+        }
+    }
+}
+
+/// GitLab REST API responses, with the notes of each issue and merge request inlined
+#[derive(Deserialize, Debug)]
+struct GitLabDump {
+    #[serde(default)]
+    labels: Vec<GitHubLabel>,
+    #[serde(default)]
+    issues: Vec<GitLabIssue>,
+    #[serde(default)]
+    merge_requests: Vec<GitLabMergeRequest>,
+}
+
+#[derive(Deserialize, Debug)]
+struct GitLabUser {
+    username: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct GitLabIssue {
+    iid: i32,
+    title: String,
+    description: Option<String>,
+    state: String,
+    author: Option<GitLabUser>,
+    #[serde(default)]
+    labels: Vec<String>,
+    created_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    notes: Vec<GitLabNote>,
+}
+
+#[derive(Deserialize, Debug)]
+struct GitLabMergeRequest {
+    iid: i32,
+    title: String,
+    description: Option<String>,
+    state: String,
+    author: Option<GitLabUser>,
+    #[serde(default)]
+    labels: Vec<String>,
+    source_branch: String,
+    target_branch: String,
+    #[serde(default)]
+    notes: Vec<GitLabNote>,
+}
+
+#[derive(Deserialize, Debug)]
+struct GitLabNote {
+    body: String,
+    author: Option<GitLabUser>,
+    created_at: Option<DateTime<Utc>>,
+    /// Notes generated by GitLab such as "changed the description"
+    #[serde(default)]
+    system: bool,
+}
+
+fn username(user: Option<GitLabUser>) -> String {
+    user.map(|user| user.username)
+        .unwrap_or_else(|| GHOST_USER.to_string())
+}
+
+fn gitlab_comments(mut notes: Vec<GitLabNote>) -> Vec<ImportedComment> {
+    notes.sort_by_key(|note| note.created_at);
+    notes
+        .into_iter()
+        .filter(|note| !note.system)
+        .map(|note| ImportedComment {
+            author: username(note.author),
+            body: note.body,
+            created_at: naive(note.created_at),
+        })
+        .collect()
+}
+
+impl From<GitLabDump> for ImportDump {
+    fn from(dump: GitLabDump) -> Self {
+        // Merge requests are numbered separately from issues on GitLab, while they share
+        // their numbering with issues here. They are numbered after the last issue.
+        let offset = dump.issues.iter().map(|issue| issue.iid).max().unwrap_or(0);
+
+        let issues = dump
+            .issues
+            .into_iter()
+            .map(|issue| ImportedIssue {
+                number: issue.iid,
+                title: issue.title,
+                body: issue.description.unwrap_or_default(),
+                author: username(issue.author),
+                closed: issue.state == "closed",
+                labels: issue.labels,
+                created_at: naive(issue.created_at),
+                comments: gitlab_comments(issue.notes),
+            })
+            .collect();
+
+        let pull_requests = dump
+            .merge_requests
+            .into_iter()
+            .map(|merge_request| {
+                let state = match merge_request.state.as_str() {
+                    "merged" => ImportedPullRequestState::Merged,
+                    "closed" | "locked" => ImportedPullRequestState::Closed,
+                    _ => ImportedPullRequestState::Open,
+                };
+
+                ImportedPullRequest {
+                    number: offset + merge_request.iid,
+                    title: merge_request.title,
+                    body: merge_request.description,
+                    author: username(merge_request.author),
+                    state,
+                    base: merge_request.target_branch,
+                    head: merge_request.source_branch,
+                    labels: merge_request.labels,
+                    comments: gitlab_comments(merge_request.notes),
+                }
+            })
+            .collect();
+
+        let labels = dump
+            .labels
+            .into_iter()
+            .map(|label| ImportedLabel {
+                color: normalize_color(&label.color),
+                name: label.name,
+            })
+            .collect();
+
+        ImportDump {
+            source_url: None,
+            labels,
+            issues,
+            pull_requests,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::domain::repository::import::dump::{
+        DumpFormat, ImportDump, ImportedPullRequestState,
+    };
+    use speculoos::prelude::*;
+
+    const GITHUB_DUMP: &str = r##"{
+        "labels": [{"name": "bug", "color": "D73A4A"}, {"name": "weird", "color": "nope"}],
+        "issues": [
+            {
+                "number": 1,
+                "title": "Crash on start",
+                "body": "It crashes",
+                "state": "closed",
+                "user": {"login": "octocat"},
+                "labels": [{"name": "bug", "color": "d73a4a"}],
+                "created_at": "2022-11-02T10:00:00Z"
+            },
+            {
+                "number": 2,
+                "title": "Fix crash",
+                "body": null,
+                "state": "closed",
+                "user": {"login": "hubot"},
+                "labels": [],
+                "created_at": "2022-11-03T10:00:00Z",
+                "pull_request": {"url": "https://api.github.com/repos/o/r/pulls/2"}
+            }
+        ],
+        "pull_requests": [
+            {
+                "number": 2,
+                "title": "Fix crash",
+                "body": "Fixes #1",
+                "state": "closed",
+                "user": {"login": "hubot"},
+                "labels": [],
+                "base": {"ref": "main"},
+                "head": {"ref": "fix-crash"},
+                "merged_at": "2022-11-04T10:00:00Z"
+            }
+        ],
+        "comments": [
+            {
+                "issue_url": "https://api.github.com/repos/o/r/issues/1",
+                "body": "Second",
+                "user": {"login": "hubot"},
+                "created_at": "2022-11-02T12:00:00Z"
+            },
+            {
+                "issue_url": "https://api.github.com/repos/o/r/issues/1",
+                "body": "First",
+                "user": null,
+                "created_at": "2022-11-02T11:00:00Z"
+            },
+            {
+                "issue_url": "https://api.github.com/repos/o/r/issues/2",
+                "body": "LGTM",
+                "user": {"login": "octocat"},
+                "created_at": "2022-11-03T11:00:00Z"
+            }
+        ]
+    }"##;
+
+    const GITLAB_DUMP: &str = r##"{
+        "labels": [{"name": "bug", "color": "#d9534f"}],
+        "issues": [
+            {
+                "iid": 3,
+                "title": "Crash on start",
+                "description": "It crashes",
+                "state": "opened",
+                "author": {"username": "alice"},
+                "labels": ["bug"],
+                "created_at": "2022-11-02T10:00:00.000Z",
+                "notes": [
+                    {"body": "changed the description", "author": {"username": "alice"}, "system": true},
+                    {"body": "Same here", "author": {"username": "bob"}, "system": false}
+                ]
+            }
+        ],
+        "merge_requests": [
+            {
+                "iid": 1,
+                "title": "Fix crash",
+                "description": "Closes #3",
+                "state": "merged",
+                "author": {"username": "bob"},
+                "labels": [],
+                "source_branch": "fix-crash",
+                "target_branch": "main"
+            }
+        ]
+    }"##;
+
+    #[test]
+    fn should_parse_github_dump() -> anyhow::Result<()> {
+        let dump = ImportDump::parse(DumpFormat::GitHub, None, GITHUB_DUMP.as_bytes())?;
+
+        assert_that!(dump.labels[0].color).is_equal_to("#d73a4a".to_string());
+        assert_that!(dump.labels[1].color).is_equal_to("#cbd5e1".to_string());
+        assert_that!(dump.issues).has_length(1);
+        assert_that!(dump.issues[0].closed).is_true();
+        assert_that!(dump.issues[0].labels).is_equal_to(vec!["bug".to_string()]);
+        let comments: Vec<&str> = dump.issues[0]
+            .comments
+            .iter()
+            .map(|comment| comment.body.as_str())
+            .collect();
+        assert_that!(comments).is_equal_to(vec!["First", "Second"]);
+        assert_that!(dump.issues[0].comments[0].author).is_equal_to("ghost".to_string());
+        assert_that!(dump.pull_requests[0].state).is_equal_to(ImportedPullRequestState::Merged);
+        assert_that!(dump.pull_requests[0].comments).has_length(1);
+        assert_that!(dump.len()).is_equal_to(4);
+        Ok(())
+    }
+
+    #[test]
+    fn should_number_gitlab_merge_requests_after_issues() -> anyhow::Result<()> {
+        let dump = ImportDump::parse(DumpFormat::GitLab, None, GITLAB_DUMP.as_bytes())?;
+
+        assert_that!(dump.issues[0].number).is_equal_to(3);
+        assert_that!(dump.issues[0].closed).is_false();
+        assert_that!(dump.issues[0].comments).has_length(1);
+        assert_that!(dump.pull_requests[0].number).is_equal_to(4);
+        assert_that!(dump.pull_requests[0].base).is_equal_to("main".to_string());
+        assert_that!(dump.authors().into_iter().collect::<Vec<_>>())
+            .is_equal_to(vec!["alice", "bob"]);
+        Ok(())
+    }
+}
@@ -0,0 +1,568 @@
+use crate::domain::id::ActivityPubId;
+use crate::domain::issue::comment::IssueComment;
+use crate::domain::issue::{Issue, IssueState};
+use crate::domain::repository::import::dump::{
+    ImportDump, ImportedComment, ImportedIssue, ImportedPullRequest, ImportedPullRequestState,
+};
+use crate::domain::repository::mirror::check_remote_url;
+use crate::domain::repository::Repository;
+use crate::domain::timeline::{ItemEventKind, RecordEventCommand};
+use crate::domain::user::create::CreateUser;
+use crate::domain::user::User;
+use crate::error::{AppError, AppResult};
+use anyhow::anyhow;
+use chrono::{NaiveDateTime, Utc};
+use gill_db::repository::import::{
+    ImportState as ImportStateEntity, RepositoryImport as RepositoryImportEntity,
+};
+use gill_db::repository::pull_request::PullRequestState as PullRequestStateEntity;
+use gill_db::repository::Repository as RepositoryEntity;
+use gill_git::GitRepository;
+use gill_settings::SETTINGS;
+use sqlx::PgPool;
+use std::collections::HashMap;
+use std::time::Duration;
+use tracing::{error, warn};
+use url::Url;
+use uuid::Uuid;
+
+pub mod dump;
+
+const IMPORT_POLL_INTERVAL: Duration = Duration::from_secs(10);
+/// A running import without progress for this long was interrupted by a restart
+const STALE_IMPORT_SECONDS: f64 = 30.0 * 60.0;
+const PROGRESS_UPDATE_STEP: usize = 10;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ImportState {
+    Pending,
+    Cloning,
+    Importing,
+    Done,
+    Failed,
+}
+
+/// Progress of a repository import
+#[derive(Debug, Clone)]
+pub struct RepositoryImport {
+    pub repository_id: i32,
+    pub remote_url: String,
+    pub state: ImportState,
+    pub progress: i32,
+    pub total: i32,
+    pub last_error: Option<String>,
+}
+
+impl From<ImportStateEntity> for ImportState {
+    fn from(state: ImportStateEntity) -> Self {
+        match state {
+            ImportStateEntity::Pending => ImportState::Pending,
+            ImportStateEntity::Cloning => ImportState::Cloning,
+            ImportStateEntity::Importing => ImportState::Importing,
+            ImportStateEntity::Done => ImportState::Done,
+            ImportStateEntity::Failed => ImportState::Failed,
+        }
+    }
+}
+
+impl From<RepositoryImportEntity> for RepositoryImport {
+    fn from(import: RepositoryImportEntity) -> Self {
+        Self {
+            repository_id: import.repository_id,
+            remote_url: import.remote_url,
+            state: import.state.into(),
+            progress: import.progress,
+            total: import.total,
+            last_error: import.last_error,
+        }
+    }
+}
+
+impl RepositoryImport {
+    pub fn is_running(&self) -> bool {
+        matches!(
+            self.state,
+            ImportState::Pending | ImportState::Cloning | ImportState::Importing
+        )
+    }
+
+    pub fn is_failed(&self) -> bool {
+        self.state == ImportState::Failed
+    }
+}
+
+/// Web url of the forge hosting `remote_url`, authors profiles are expected there
+pub fn default_source_url(remote_url: &str) -> Option<Url> {
+    let host = match Url::parse(remote_url) {
+        Ok(url) => url.host_str()?.to_string(),
+        // scp-like `git@host:path`
+        Err(_) => {
+            let (user_host, _) = remote_url.split_once(':')?;
+            user_host.rsplit_once('@')?.1.to_string()
+        }
+    };
+
+    Url::parse(&format!("https://{host}/")).ok()
+}
+
+impl Repository {
+    pub async fn get_import(&self, db: &PgPool) -> AppResult<Option<RepositoryImport>> {
+        let import = RepositoryImportEntity::by_repository_id(self.id, db).await?;
+        Ok(import.map(RepositoryImport::from))
+    }
+
+    /// Create the bare repository and queue the import of `remote_url` and `dump`.
+    /// Content of the source forge logins in `own_logins` is attributed to `importer`,
+    /// other authors get a placeholder user.
+    pub async fn start_import(
+        &self,
+        importer: &User,
+        remote_url: &str,
+        dump: Option<ImportDump>,
+        own_logins: &[String],
+        db: &PgPool,
+    ) -> AppResult<RepositoryImport> {
+        check_remote_url(remote_url)?;
+
+        // Nobody can claim other local users authored the imported content
+        let mapping: HashMap<&str, i32> = own_logins
+            .iter()
+            .map(|login| (login.as_str(), importer.id))
+            .collect();
+
+        let (owner, name) = (importer.username.clone(), self.name.clone());
+        tokio::task::spawn_blocking(move || gill_git::init::init_bare(&owner, &name))
+            .await
+            .map_err(anyhow::Error::from)??;
+
+        let dump = dump.map(serde_json::to_value).transpose()?;
+        let import = RepositoryImportEntity::create(
+            self.id,
+            remote_url,
+            dump,
+            serde_json::to_value(mapping)?,
+            db,
+        )
+        .await?;
+
+        Ok(import.into())
+    }
+
+    pub async fn retry_import(&self, user: &User, db: &PgPool) -> AppResult<()> {
+        if self.attributed_to != user.activity_pub_id {
+            return Err(AppError::Unauthorized);
+        }
+
+        RepositoryImportEntity::retry(self.id, db).await?;
+        Ok(())
+    }
+}
+
+/// Run queued imports one at a time until the instance shuts down
+pub async fn run_import_worker(db: PgPool) {
+    loop {
+        match import_next(&db).await {
+            Ok(true) => {}
+            Ok(false) => tokio::time::sleep(IMPORT_POLL_INTERVAL).await,
+            Err(err) => {
+                error!("Failed to run repository import: {err:?}");
+                tokio::time::sleep(IMPORT_POLL_INTERVAL).await
+            }
+        }
+    }
+}
+
+async fn import_next(db: &PgPool) -> AppResult<bool> {
+    let Some(import) = RepositoryImportEntity::claim_next(STALE_IMPORT_SECONDS, db).await? else {
+        return Ok(false);
+    };
+
+    let repository = Repository::by_id(import.repository_id, db).await?;
+    match run_import(&repository, import, db).await {
+        Ok(()) => RepositoryImportEntity::done(repository.id, db).await?,
+        Err(err) => {
+            let err = match err {
+                AppError::Internal(err) => format!("{err:#}"),
+                other => format!("{other:?}"),
+            };
+            error!("Failed to import {}: {err}", repository.name);
+            RepositoryImportEntity::failed(repository.id, &err, db).await?
+        }
+    }
+
+    Ok(true)
+}
+
+async fn run_import(
+    repository: &Repository,
+    import: RepositoryImportEntity,
+    db: &PgPool,
+) -> AppResult<()> {
+    let owner = repository.owner(db).await?;
+    let name = repository.name.clone();
+    let remote_url = import.remote_url.clone();
+    let (branches, default_branch) = tokio::task::spawn_blocking(move || {
+        let git = GitRepository::open(&owner, &name)?;
+        git.import_from(&remote_url)?;
+        let branches = git.list_branches()?;
+        let default_branch = git
+            .default_branch()
+            .ok()
+            .filter(|branch| branches.contains(branch));
+        anyhow::Ok((branches, default_branch))
+    })
+    .await??;
+
+    let entity: RepositoryEntity = repository.into();
+    entity
+        .sync_branches(&branches, default_branch.as_deref(), db)
+        .await?;
+
+    let Some(dump) = import.dump else {
+        return Ok(());
+    };
+
+    let dump: ImportDump = serde_json::from_value(dump)?;
+    let mapping: HashMap<String, i32> = serde_json::from_value(import.author_mapping)?;
+    let mut importer = Importer {
+        repository,
+        entity,
+        mapping,
+        source_url: dump.source_url.clone(),
+        authors: HashMap::new(),
+        labels: HashMap::new(),
+        branches,
+        default_branch,
+        progress: 0,
+        total: dump.len(),
+        db,
+    };
+
+    importer.import(dump).await?;
+    importer.entity.refresh_item_count(db).await?;
+    Ok(())
+}
+
+struct Importer<'a> {
+    repository: &'a Repository,
+    entity: RepositoryEntity,
+    /// Source forge login to local user id
+    mapping: HashMap<String, i32>,
+    source_url: Option<Url>,
+    authors: HashMap<String, User>,
+    /// Label name to label id
+    labels: HashMap<String, i32>,
+    branches: Vec<String>,
+    default_branch: Option<String>,
+    progress: usize,
+    total: usize,
+    db: &'a PgPool,
+}
+
+impl Importer<'_> {
+    async fn import(&mut self, dump: ImportDump) -> AppResult<()> {
+        self.report(ImportStateEntity::Importing).await?;
+
+        for label in dump.labels {
+            let label_entity = self
+                .entity
+                .upsert_label(&label.name, &label.color, self.db)
+                .await?;
+            self.labels.insert(label.name, label_entity.id);
+            self.step().await?;
+        }
+
+        for issue in dump.issues {
+            if !self.entity.has_item(issue.number, self.db).await? {
+                self.import_issue(issue).await?;
+            }
+
+            self.step().await?;
+        }
+
+        for pull_request in dump.pull_requests {
+            if !self.entity.has_item(pull_request.number, self.db).await? {
+                self.import_pull_request(pull_request).await?;
+            }
+
+            self.step().await?;
+        }
+
+        self.report(ImportStateEntity::Importing).await?;
+        Ok(())
+    }
+
+    async fn import_issue(&mut self, imported: ImportedIssue) -> AppResult<()> {
+        let author = self.author(&imported.author).await?;
+        let repository = self.repository;
+        let number = imported.number;
+        let activity_pub_id = format!("{}/issues/{number}", repository.activity_pub_id.to_string());
+
+        let issue = Issue {
+            repository_id: repository.id,
+            opened_by: author.id,
+            title: imported.title,
+            content: imported.body.escape_default().to_string(),
+            state: IssueState::Open,
+            activity_pub_id: ActivityPubId::try_from(activity_pub_id.clone())?,
+            context: repository.activity_pub_id.clone(),
+            attributed_to: author.activity_pub_id.clone(),
+            media_type: "text/markdown".to_string(),
+            published: imported
+                .created_at
+                .unwrap_or_else(|| Utc::now().naive_utc()),
+            followers_url: Url::parse(&format!("{activity_pub_id}/followers"))?,
+            team: Url::parse(&format!("{activity_pub_id}/team"))?,
+            replies: Url::parse(&format!("{activity_pub_id}/replies"))?,
+            history: Url::parse(&format!("{activity_pub_id}/history"))?,
+            dependants: Url::parse(&format!("{activity_pub_id}/dependants"))?,
+            dependencies: Url::parse(&format!("{activity_pub_id}/dependencies"))?,
+            resolved_by: None,
+            resolved: None,
+            number,
+            is_local: true,
+        };
+
+        let issue = issue.save(self.db).await?;
+        for comment in imported.comments {
+            self.import_issue_comment(&issue, comment).await?;
+        }
+
+        if imported.closed {
+            issue
+                .mark_resolved(&repository.attributed_to.clone().into(), None, self.db)
+                .await?;
+            let owner =
+                User::by_activity_pub_id(&repository.attributed_to.to_string(), self.db).await?;
+            issue
+                .record_event(owner.id, ItemEventKind::Closed, self.db)
+                .await;
+        }
+
+        // The dump does not say who labeled the issue nor when, labels go to its opening
+        self.add_labels(number, &imported.labels, author.id, Some(issue.published))
+            .await
+    }
+
+    async fn import_issue_comment(
+        &mut self,
+        issue: &Issue,
+        imported: ImportedComment,
+    ) -> AppResult<()> {
+        let author = self.author(&imported.author).await?;
+        let id = Uuid::new_v4();
+        let activity_pub_id = format!("{}/comments/{id}", issue.activity_pub_id.to_string());
+
+        let comment = IssueComment {
+            id,
+            activity_pub_id: ActivityPubId::try_from(activity_pub_id)?,
+            number: issue.number,
+            repository_id: issue.repository_id,
+            created_by: author.id,
+            content: imported.body.escape_default().to_string(),
+            media_type: "text/markdown".to_string(),
+            attributed_to: author.activity_pub_id.clone(),
+            context: issue.activity_pub_id.clone(),
+            in_reply_to: issue.activity_pub_id.clone().into(),
+            published: imported
+                .created_at
+                .unwrap_or_else(|| Utc::now().naive_utc()),
+            deleted_at: None,
+        };
+
+        comment.save(self.db).await?;
+        Ok(())
+    }
+
+    async fn import_pull_request(&mut self, imported: ImportedPullRequest) -> AppResult<()> {
+        // The base branch may have been deleted on the source forge since
+        let base = if self.branches.contains(&imported.base) {
+            Some(imported.base.clone())
+        } else {
+            self.default_branch.clone()
+        };
+
+        let Some(base) = base else {
+            warn!(
+                "Skipping pull request #{}, {} has no branch to target",
+                imported.number, self.repository.name
+            );
+            return Ok(());
+        };
+
+        let author = self.author(&imported.author).await?;
+        let state = match imported.state {
+            ImportedPullRequestState::Open => PullRequestStateEntity::Open,
+            ImportedPullRequestState::Closed => PullRequestStateEntity::Closed,
+            ImportedPullRequestState::Merged => PullRequestStateEntity::Merged,
+        };
+
+        let description = imported
+            .body
+            .map(|description| description.escape_default().to_string());
+        self.entity
+            .import_pull_request(
+                imported.number,
+                author.id,
+                &imported.title,
+                description.as_deref(),
+                &base,
+                &imported.head,
+                state,
+                self.db,
+            )
+            .await?;
+
+        let pull_request = self
+            .repository
+            .get_pull_request(imported.number, self.db)
+            .await?;
+        pull_request
+            .record_event(author.id, ItemEventKind::Opened, self.db)
+            .await;
+        for comment in imported.comments {
+            let author = self.author(&comment.author).await?;
+            pull_request
+                .comment(&comment.body, author.id, self.db)
+                .await?;
+        }
+
+        self.add_labels(imported.number, &imported.labels, author.id, None)
+            .await
+    }
+
+    async fn add_labels(
+        &mut self,
+        number: i32,
+        labels: &[String],
+        actor_id: i32,
+        created_at: Option<NaiveDateTime>,
+    ) -> AppResult<()> {
+        for name in labels {
+            let label_id = match self.labels.get(name) {
+                Some(id) => *id,
+                None => {
+                    let label = self
+                        .entity
+                        .upsert_label(name, dump::DEFAULT_LABEL_COLOR, self.db)
+                        .await?;
+                    self.labels.insert(name.clone(), label.id);
+                    label.id
+                }
+            };
+
+            self.entity
+                .add_item_label(number, label_id, self.db)
+                .await?;
+
+            RecordEventCommand {
+                repository_id: self.repository.id,
+                number,
+                actor_id,
+                kind: ItemEventKind::Labeled,
+                old_value: None,
+                new_value: Some(name.clone()),
+                created_at,
+            }
+            .execute(self.db)
+            .await;
+        }
+
+        Ok(())
+    }
+
+    /// Mapped local user, or a placeholder pointing to the author profile on the source forge
+    async fn author(&mut self, login: &str) -> AppResult<User> {
+        if let Some(user) = self.authors.get(login) {
+            return Ok(user.clone());
+        }
+
+        let user = match self.mapping.get(login) {
+            Some(id) => User::by_id(*id, self.db).await?,
+            None => self.placeholder(login).await?,
+        };
+
+        self.authors.insert(login.to_string(), user.clone());
+        Ok(user)
+    }
+
+    async fn placeholder(&self, login: &str) -> AppResult<User> {
+        let source_url = match &self.source_url {
+            Some(source_url) => source_url.clone(),
+            None => SETTINGS.domain_url()?.join("imported/")?,
+        };
+
+        let host = source_url
+            .host_str()
+            .ok_or_else(|| anyhow!("Invalid source url {source_url}"))?
+            .to_string();
+
+        let mut profile = source_url;
+        profile
+            .path_segments_mut()
+            .map_err(|_| anyhow!("Invalid source url"))?
+            .pop_if_empty()
+            .push(login);
+
+        if let Some(user) = User::by_activity_pub_id_optional(profile.as_str(), self.db).await? {
+            return Ok(user);
+        }
+
+        // Placeholders are never followed nor subscribed, nothing is delivered to them
+        let user = CreateUser {
+            username: format!("{login}@{host}"),
+            email: None,
+            private_key: None,
+            public_key: String::new(),
+            activity_pub_id: ActivityPubId::try_from(profile.to_string())?,
+            outbox_url: Url::parse(&format!("{profile}/outbox"))?,
+            inbox_url: Url::parse(&format!("{profile}/inbox"))?,
+            domain: host,
+            followers_url: Url::parse(&format!("{profile}/followers"))?,
+            is_local: false,
+        }
+        .save(self.db)
+        .await?;
+
+        Ok(user)
+    }
+
+    async fn step(&mut self) -> AppResult<()> {
+        self.progress += 1;
+        if self.progress % PROGRESS_UPDATE_STEP == 0 {
+            self.report(ImportStateEntity::Importing).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn report(&self, state: ImportStateEntity) -> AppResult<()> {
+        RepositoryImportEntity::update_progress(
+            self.repository.id,
+            state,
+            self.progress as i32,
+            self.total as i32,
+            self.db,
+        )
+        .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::domain::repository::import::default_source_url;
+    use speculoos::prelude::*;
+
+    #[test]
+    fn should_derive_source_url_from_git_url() {
+        let expected = Some(url::Url::parse("https://github.com/").unwrap());
+
+        assert_that!(default_source_url("https://github.com/oknozor/gill.git"))
+            .is_equal_to(expected.clone());
+        assert_that!(default_source_url("ssh://git@github.com/oknozor/gill.git"))
+            .is_equal_to(expected.clone());
+        assert_that!(default_source_url("git@github.com:oknozor/gill.git")).is_equal_to(expected);
+    }
+}
@@ -0,0 +1,70 @@
+use crate::domain::repository::import::dump::DEFAULT_LABEL_COLOR;
+use crate::domain::repository::Repository;
+use crate::domain::timeline::{ItemEventKind, RecordEventCommand};
+use crate::error::AppResult;
+use gill_db::repository::label::ItemLabel;
+use gill_db::repository::Repository as RepositoryEntity;
+use sqlx::PgPool;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Label {
+    pub name: String,
+    /// Hex color, '#' prefixed
+    pub color: String,
+}
+
+impl From<ItemLabel> for Label {
+    fn from(label: ItemLabel) -> Self {
+        Self {
+            name: label.name,
+            color: label.color,
+        }
+    }
+}
+
+impl Repository {
+    /// Labels of the repository issues and pull requests, by number
+    pub async fn labels_by_number(&self, db: &PgPool) -> AppResult<HashMap<i32, Vec<Label>>> {
+        let entity: RepositoryEntity = self.into();
+        let mut labels: HashMap<i32, Vec<Label>> = HashMap::new();
+        for label in entity.list_item_labels(db).await? {
+            labels.entry(label.number).or_default().push(label.into());
+        }
+
+        Ok(labels)
+    }
+
+    /// Add labels to issue or pull request `number`, unknown labels are created
+    pub async fn add_labels(
+        &self,
+        number: i32,
+        names: &[String],
+        actor_id: i32,
+        db: &PgPool,
+    ) -> AppResult<()> {
+        let entity: RepositoryEntity = self.into();
+        let existing = entity.list_labels(db).await?;
+        for name in names {
+            let label_id = match existing.iter().find(|label| &label.name == name) {
+                Some(label) => label.id,
+                None => entity.upsert_label(name, DEFAULT_LABEL_COLOR, db).await?.id,
+            };
+
+            entity.add_item_label(number, label_id, db).await?;
+            RecordEventCommand {
+                repository_id: self.id,
+                number,
+                actor_id,
+                kind: ItemEventKind::Labeled,
+                old_value: None,
+                new_value: Some(name.clone()),
+                created_at: None,
+            }
+            .execute(db)
+            .await;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,265 @@
+use crate::domain::repository::push_mirror::push_due;
+use crate::domain::repository::Repository;
+use crate::error::AppResult;
+use anyhow::anyhow;
+use chrono::NaiveDateTime;
+use gill_db::repository::mirror::RepositoryMirror as RepositoryMirrorEntity;
+use gill_db::repository::Repository as RepositoryEntity;
+use gill_git::GitRepository;
+use sqlx::PgPool;
+use std::time::Duration;
+use tracing::{debug, error};
+use url::Url;
+
+/// Time between two fetches of a mirror
+pub const DEFAULT_SYNC_INTERVAL_SECONDS: i32 = 60 * 60;
+const MIRROR_BATCH_SIZE: i64 = 5;
+const MIRROR_POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// A repository whose git data is pulled from a remote url
+#[derive(Debug, Clone)]
+pub struct RepositoryMirror {
+    pub repository_id: i32,
+    pub remote_url: String,
+    pub sync_interval: i32,
+    pub last_synced_at: Option<NaiveDateTime>,
+    pub last_error: Option<String>,
+}
+
+impl From<RepositoryMirrorEntity> for RepositoryMirror {
+    fn from(mirror: RepositoryMirrorEntity) -> Self {
+        Self {
+            repository_id: mirror.repository_id,
+            remote_url: mirror.remote_url,
+            sync_interval: mirror.sync_interval,
+            last_synced_at: mirror.last_synced_at,
+            last_error: mirror.last_error,
+        }
+    }
+}
+
+/// Only network transports are allowed, we don't want users to mirror files of the instance
+pub fn check_remote_url(remote_url: &str) -> AppResult<()> {
+    match Url::parse(remote_url) {
+        Ok(url) if matches!(url.scheme(), "http" | "https" | "git" | "ssh") => Ok(()),
+        Err(_) if is_scp_like(remote_url) => Ok(()),
+        _ => Err(anyhow!("Unsupported mirror url: {remote_url}").into()),
+    }
+}
+
+// Git reads `user@host:path` as an ssh url, unless a slash comes before the colon
+fn is_scp_like(remote_url: &str) -> bool {
+    let Some((user_host, path)) = remote_url.split_once(':') else {
+        return false;
+    };
+
+    if user_host.contains('/') {
+        return false;
+    }
+
+    let Some((user, host)) = user_host.split_once('@') else {
+        return false;
+    };
+
+    !user.is_empty() && !user.starts_with('-') && !host.is_empty() && !path.is_empty()
+}
+
+// Remote names end up in a path below the git home
+fn check_path_segment(segment: &str) -> AppResult<()> {
+    if segment.is_empty()
+        || segment.contains('/')
+        || segment.contains("..")
+        || segment.starts_with('-')
+    {
+        return Err(anyhow!("Invalid mirror path segment: {segment}").into());
+    }
+
+    Ok(())
+}
+
+impl Repository {
+    /// Directory holding the git data of the repository, mirrors of federated repositories
+    /// live under their domain qualified owner so they never share a path with a local user
+    pub fn git_namespace(&self, owner: &str) -> String {
+        if self.is_local {
+            owner.to_string()
+        } else {
+            format!("{owner}@{}", self.domain)
+        }
+    }
+
+    /// Open the git repository of `owner/name`, be it local or a federated mirror
+    pub async fn open_git(owner: &str, name: &str, db: &PgPool) -> AppResult<GitRepository> {
+        let repository = Repository::by_namespace(owner, name, db).await?;
+        let namespace = repository.git_namespace(owner);
+        Ok(GitRepository::open(&namespace, name)?)
+    }
+
+    pub async fn get_mirror(&self, db: &PgPool) -> AppResult<Option<RepositoryMirror>> {
+        let mirror = RepositoryMirrorEntity::by_repository_id(self.id, db).await?;
+        Ok(mirror.map(RepositoryMirror::from))
+    }
+
+    /// Username of the repository owner
+    pub async fn owner(&self, db: &PgPool) -> AppResult<String> {
+        let entity: RepositoryEntity = self.into();
+        entity.owner(db).await.map_err(Into::into)
+    }
+
+    /// Mirror a federated repository from its clone uri so its code can be browsed locally
+    pub async fn mirror_federated(&self, db: &PgPool) -> AppResult<RepositoryMirror> {
+        if self.is_local {
+            return Err(anyhow!("{} is a local repository", self.name).into());
+        }
+
+        let owner = self.owner(db).await?;
+        check_path_segment(&owner)?;
+        check_path_segment(&self.name)?;
+        self.start_mirror(&owner, self.clone_uri.as_str(), db).await
+    }
+
+    /// Create the mirror git repository, it is fetched on the next mirror worker run
+    pub async fn start_mirror(
+        &self,
+        owner: &str,
+        remote_url: &str,
+        db: &PgPool,
+    ) -> AppResult<RepositoryMirror> {
+        check_remote_url(remote_url)?;
+        if let Some(mirror) = self.get_mirror(db).await? {
+            return Ok(mirror);
+        }
+
+        let namespace = self.git_namespace(owner);
+        let (name, url) = (self.name.clone(), remote_url.to_string());
+        tokio::task::spawn_blocking(move || gill_git::mirror::init_mirror(&namespace, &name, &url))
+            .await
+            .map_err(anyhow::Error::from)??;
+
+        let mirror =
+            RepositoryMirrorEntity::create(self.id, remote_url, DEFAULT_SYNC_INTERVAL_SECONDS, db)
+                .await?;
+
+        Ok(mirror.into())
+    }
+
+    /// Mirrored refs are overwritten on each fetch, they must not be edited locally
+    pub async fn ensure_not_mirror(&self, db: &PgPool) -> AppResult<()> {
+        match self.get_mirror(db).await? {
+            Some(mirror) => Err(anyhow!("Repository is a mirror of {}", mirror.remote_url).into()),
+            None => Ok(()),
+        }
+    }
+
+    /// Fetch the mirror on the next worker run
+    pub async fn sync_mirror_now(&self, db: &PgPool) -> AppResult<()> {
+        RepositoryMirrorEntity::schedule_now(self.id, db).await?;
+        Ok(())
+    }
+
+    async fn fetch_mirror(&self, db: &PgPool) -> anyhow::Result<()> {
+        let owner = self.owner(db).await.map_err(|_| anyhow!("Unknown owner"))?;
+        let namespace = self.git_namespace(&owner);
+        let name = self.name.clone();
+        let (branches, default_branch) = tokio::task::spawn_blocking(move || {
+            let repository = GitRepository::open(&namespace, &name)?;
+            repository.sync_mirror()?;
+            let branches = repository.list_branches()?;
+            let default_branch = repository
+                .default_branch()
+                .ok()
+                .filter(|branch| branches.contains(branch));
+            anyhow::Ok((branches, default_branch))
+        })
+        .await??;
+
+        let entity: RepositoryEntity = self.into();
+        entity
+            .sync_branches(&branches, default_branch.as_deref(), db)
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// Fetch due pull mirrors and push pending push mirrors until the instance shuts down,
+/// a failing phase does not hold the other one back
+pub async fn run_mirror_worker(db: PgPool) {
+    loop {
+        let synced = sync_due(&db).await.unwrap_or_else(|err| {
+            error!("Failed to sync pull mirrors: {err:?}");
+            0
+        });
+
+        let pushed = push_due(&db).await.unwrap_or_else(|err| {
+            error!("Failed to push mirrors: {err:?}");
+            0
+        });
+
+        match synced + pushed {
+            0 => tokio::time::sleep(MIRROR_POLL_INTERVAL).await,
+            count => debug!("Synced {count} mirrors"),
+        }
+    }
+}
+
+async fn sync_due(db: &PgPool) -> AppResult<usize> {
+    let mirrors = RepositoryMirrorEntity::claim_due(MIRROR_BATCH_SIZE, db).await?;
+
+    for mirror in &mirrors {
+        match sync(mirror, db).await {
+            Ok(()) => RepositoryMirrorEntity::synced(mirror.repository_id, db).await?,
+            Err(err) => {
+                error!("Failed to sync mirror of {}: {err:#}", mirror.remote_url);
+                RepositoryMirrorEntity::sync_failed(mirror.repository_id, &format!("{err:#}"), db)
+                    .await?
+            }
+        }
+    }
+
+    Ok(mirrors.len())
+}
+
+async fn sync(mirror: &RepositoryMirrorEntity, db: &PgPool) -> anyhow::Result<()> {
+    let repository = Repository::by_id(mirror.repository_id, db)
+        .await
+        .map_err(|err| anyhow!("Failed to load repository: {err:?}"))?;
+    repository.fetch_mirror(db).await
+}
+
+#[cfg(test)]
+mod test {
+    use crate::domain::repository::mirror::{check_path_segment, check_remote_url};
+    use speculoos::prelude::*;
+
+    #[test]
+    fn should_accept_network_urls() {
+        assert_that!(check_remote_url("https://github.com/oknozor/gill.git")).is_ok();
+        assert_that!(check_remote_url(
+            "ssh://git@gill.example.org:2222/~/alice/repo.git"
+        ))
+        .is_ok();
+        assert_that!(check_remote_url("git://example.org/repo.git")).is_ok();
+        assert_that!(check_remote_url("git@github.com:oknozor/gill.git")).is_ok();
+    }
+
+    #[test]
+    fn should_reject_local_urls() {
+        assert_that!(check_remote_url("file:///home/git/alice/repo.git")).is_err();
+        assert_that!(check_remote_url("/home/git/alice/repo.git")).is_err();
+        assert_that!(check_remote_url("ext::sh -c touch% /tmp/pwned")).is_err();
+        assert_that!(check_remote_url("-oProxyCommand=sh@host:repo.git")).is_err();
+        assert_that!(check_remote_url("repo/sub@host:path")).is_err();
+    }
+
+    #[test]
+    fn should_reject_path_segments_escaping_the_namespace() {
+        assert_that!(check_path_segment("alice")).is_ok();
+        assert_that!(check_path_segment("gill.rs")).is_ok();
+        assert_that!(check_path_segment("..")).is_err();
+        assert_that!(check_path_segment("../alice")).is_err();
+        assert_that!(check_path_segment("alice/repo")).is_err();
+        assert_that!(check_path_segment("-repo")).is_err();
+        assert_that!(check_path_segment("")).is_err();
+    }
+}
@@ -1,5 +1,9 @@
 use crate::domain::id::ActivityPubId;
+use crate::domain::issue::reference::{IssueReference, ReferenceSource};
 use crate::domain::issue::Issue;
+use crate::domain::notification::{NotificationSubject, NotifyCommand};
+use crate::domain::timeline::{ItemEventKind, RecordEventCommand};
+use crate::domain::user::activity::{UserActivity, UserActivityKind};
 use crate::domain::user::User;
 use crate::error::{AppError, AppResult};
 
@@ -10,15 +14,25 @@ use sqlx::PgPool;
 
 use crate::domain::issue::digest::IssueDigest;
 use crate::domain::pull_request::PullRequest;
+use crate::domain::repository::push::commit_message;
 use crate::instance::InstanceHandle;
 
+use gill_git::commits::OwnedCommit;
 use gill_git::GitRepository;
 use url::{ParseError, Url};
 
 pub mod branch;
 pub mod create;
 pub mod digest;
+pub mod docs;
+pub mod import;
+pub mod label;
+pub mod mirror;
+pub mod push;
+pub mod push_mirror;
 pub mod stats;
+pub mod tag;
+pub mod template;
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Repository {
@@ -104,6 +118,11 @@ impl Repository {
         Repository::try_from(repository).map_err(Into::into)
     }
 
+    pub async fn by_id(id: i32, db: &PgPool) -> Result<Self, AppError> {
+        let entity = RepositoryEntity::by_id(id, db).await?;
+        Repository::try_from(entity).map_err(Into::into)
+    }
+
     pub async fn by_activity_pub_id(activity_pub_id: &str, db: &PgPool) -> Result<Self, AppError> {
         let entity = RepositoryEntity::by_activity_pub_id(activity_pub_id, db).await?;
         Repository::try_from(entity).map_err(Into::into)
@@ -139,12 +158,11 @@ impl Repository {
         Ok(())
     }
 
-    pub async fn add_fork(&self, forked_by: i32, fork: i32, db: &PgPool) -> Result<(), AppError> {
+    pub async fn add_fork(&self, fork: i32, forked_by: i32, db: &PgPool) -> Result<(), AppError> {
         let entity: RepositoryEntity = self.into();
-        entity
-            .add_fork(fork, forked_by, db)
-            .await
-            .map_err(Into::into)
+        entity.add_fork(fork, forked_by, db).await?;
+        UserActivity::record(forked_by, self.id, UserActivityKind::Fork, None, None, db).await;
+        Ok(())
     }
 
     pub async fn add_star(
@@ -154,6 +172,15 @@ impl Repository {
     ) -> Result<(), AppError> {
         let entity: RepositoryEntity = self.into();
         entity.add_star(starred_by.id, instance.database()).await?;
+        UserActivity::record(
+            starred_by.id,
+            self.id,
+            UserActivityKind::Star,
+            None,
+            None,
+            instance.database(),
+        )
+        .await;
 
         if !self.is_local {
             starred_by.star_repository(self, instance).await?;
@@ -162,6 +189,50 @@ impl Repository {
         Ok(())
     }
 
+    pub async fn remove_watcher(
+        &self,
+        watcher: &User,
+        instance: &InstanceHandle,
+    ) -> Result<(), AppError> {
+        let entity: RepositoryEntity = self.into();
+        entity
+            .remove_watcher(watcher.id, instance.database())
+            .await?;
+
+        if !self.is_local {
+            watcher.unwatch_repository(self, instance).await?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn is_watched_by(&self, user: &User, db: &PgPool) -> AppResult<bool> {
+        let entity: RepositoryEntity = self.into();
+        entity.is_watched_by(user.id, db).await.map_err(Into::into)
+    }
+
+    pub async fn remove_star(
+        &self,
+        starred_by: &User,
+        instance: &InstanceHandle,
+    ) -> Result<(), AppError> {
+        let entity: RepositoryEntity = self.into();
+        entity
+            .remove_star(starred_by.id, instance.database())
+            .await?;
+
+        if !self.is_local {
+            starred_by.unstar_repository(self, instance).await?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn is_starred_by(&self, user: &User, db: &PgPool) -> AppResult<bool> {
+        let entity: RepositoryEntity = self.into();
+        entity.is_starred_by(user.id, db).await.map_err(Into::into)
+    }
+
     pub async fn issue_by_number(&self, number: i32, db: &PgPool) -> Result<Issue, AppError> {
         let entity: RepositoryEntity = self.into();
         let issue = entity.get_issue(number, db).await?;
@@ -184,6 +255,11 @@ impl Repository {
         Ok(watchers)
     }
 
+    pub async fn count_watchers(&self, db: &PgPool) -> AppResult<i64> {
+        let entity: RepositoryEntity = self.into();
+        entity.count_watchers(db).await.map_err(Into::into)
+    }
+
     pub async fn get_issue_digest(
         &self,
         number: i32,
@@ -194,27 +270,23 @@ impl Repository {
         Ok(IssueDigest::from(issue))
     }
 
-    pub async fn close_issue(
-        &self,
-        issue_number: i32,
-        user_activity_pub_id: ActivityPubId<User>,
-        db: &PgPool,
-    ) -> AppResult<()> {
-        if self.attributed_to != user_activity_pub_id {
-            return Err(AppError::Unauthorized);
-        };
-
-        let entity: RepositoryEntity = self.into();
-        let issue = entity.get_issue_digest(issue_number, db).await?;
-        issue.close(db).await.map_err(Into::into)
-    }
-
     pub async fn get_pull_request(&self, number: i32, db: &PgPool) -> AppResult<PullRequest> {
         let entity: RepositoryEntity = self.into();
         let entity = entity.get_pull_request(number, db).await?;
         Ok(PullRequest::from(entity))
     }
 
+    /// Open pull requests comparing `branch`
+    pub async fn open_pull_requests_from(
+        &self,
+        branch: &str,
+        db: &PgPool,
+    ) -> AppResult<Vec<PullRequest>> {
+        let entity: RepositoryEntity = self.into();
+        let entities = entity.open_pull_requests_from(branch, db).await?;
+        Ok(entities.into_iter().map(PullRequest::from).collect())
+    }
+
     pub async fn list_pull_requests(&self, db: &PgPool) -> AppResult<Vec<PullRequest>> {
         let entity: RepositoryEntity = self.into();
         let entities = entity.list_pull_requests(db).await?;
@@ -246,8 +318,48 @@ impl Repository {
         let entity: RepositoryEntity = self.into();
         entity
             .create_pull_request(user_id, title, description, base, compare, db)
-            .await
-            .map_err(Into::into)
+            .await?;
+
+        RecordEventCommand {
+            repository_id: self.id,
+            number: self.item_count + 1,
+            actor_id: user_id,
+            kind: ItemEventKind::Opened,
+            old_value: None,
+            new_value: None,
+            created_at: None,
+        }
+        .execute(db)
+        .await;
+
+        UserActivity::record(
+            user_id,
+            self.id,
+            UserActivityKind::OpenPullRequest,
+            Some(self.item_count + 1),
+            Some(title.to_string()),
+            db,
+        )
+        .await;
+
+        NotifyCommand {
+            actor_id: user_id,
+            repository_id: self.id,
+            subject: NotificationSubject::PullRequest,
+            number: self.item_count + 1,
+            title,
+            action: "opened",
+            content: description,
+        }
+        .execute(db)
+        .await;
+
+        if let Some(description) = description {
+            let source = ReferenceSource::PullRequest(self.item_count + 1);
+            IssueReference::record_all(self.id, &source, user_id, description, db).await;
+        }
+
+        Ok(())
     }
 
     pub async fn list_issues(&self, db: &PgPool) -> AppResult<Vec<IssueDigest>> {
@@ -274,15 +386,17 @@ impl Repository {
         user: &User,
         owner: &str,
         pull_request_number: i32,
-        db: &PgPool,
+        instance: &InstanceHandle,
     ) -> AppResult<()> {
         if self.attributed_to != user.activity_pub_id {
             return Err(AppError::Unauthorized);
         };
 
+        let db = instance.database();
         let pull_request = self.get_pull_request(pull_request_number, db).await?;
 
         let git_repository = GitRepository::open(owner, &self.name)?;
+        let commits = git_repository.history_between(&pull_request.base, &pull_request.compare)?;
 
         git_repository.rebase(
             &pull_request.base,
@@ -291,7 +405,25 @@ impl Repository {
             user.email.as_ref().expect("local user has email"),
         )?;
 
-        pull_request.set_merged(db).await
+        pull_request.set_merged(db).await?;
+        pull_request
+            .record_event(user.id, ItemEventKind::Merged, db)
+            .await;
+        pull_request.notify(user.id, "merged", None, db).await;
+        UserActivity::record(
+            user.id,
+            self.id,
+            UserActivityKind::MergePullRequest,
+            Some(pull_request.number),
+            Some(pull_request.title.clone()),
+            db,
+        )
+        .await;
+
+        self.close_issues_fixed_by(&pull_request, &commits, user, instance)
+            .await;
+
+        Ok(())
     }
 
     pub async fn merge(
@@ -299,15 +431,17 @@ impl Repository {
         user: &User,
         owner: &str,
         pull_request_number: i32,
-        db: &PgPool,
+        instance: &InstanceHandle,
     ) -> AppResult<()> {
         if self.attributed_to != user.activity_pub_id {
             return Err(AppError::Unauthorized);
         };
 
+        let db = instance.database();
         let pull_request = self.get_pull_request(pull_request_number, db).await?;
 
         let git_repository = GitRepository::open(owner, &self.name)?;
+        let commits = git_repository.history_between(&pull_request.base, &pull_request.compare)?;
 
         git_repository.merge(
             &pull_request.base,
@@ -316,7 +450,52 @@ impl Repository {
             user.email.as_ref().expect("local user has email"),
         )?;
 
-        pull_request.set_merged(db).await
+        pull_request.set_merged(db).await?;
+        pull_request
+            .record_event(user.id, ItemEventKind::Merged, db)
+            .await;
+        pull_request.notify(user.id, "merged", None, db).await;
+        UserActivity::record(
+            user.id,
+            self.id,
+            UserActivityKind::MergePullRequest,
+            Some(pull_request.number),
+            Some(pull_request.title.clone()),
+            db,
+        )
+        .await;
+
+        self.close_issues_fixed_by(&pull_request, &commits, user, instance)
+            .await;
+
+        Ok(())
+    }
+
+    /// Close the issues the description or the commits of a merged pull request say are fixed
+    async fn close_issues_fixed_by(
+        &self,
+        pull_request: &PullRequest,
+        commits: &[OwnedCommit],
+        user: &User,
+        instance: &InstanceHandle,
+    ) {
+        // Same as pushes, only what lands on the default branch closes issues
+        let default_branch = self.get_default_branch(instance.database()).await;
+        if default_branch.map(|branch| branch.name).as_ref() != Some(&pull_request.base) {
+            return;
+        }
+
+        if let Some(description) = &pull_request.description {
+            let source = ReferenceSource::PullRequest(pull_request.number);
+            self.close_referenced_issues(description, &source, user, instance)
+                .await;
+        }
+
+        for commit in commits.iter().rev() {
+            let source = ReferenceSource::Commit(commit.id.clone());
+            self.close_referenced_issues(&commit_message(commit), &source, user, instance)
+                .await;
+        }
     }
 
     pub async fn close_pull_request(
@@ -329,10 +508,22 @@ impl Repository {
             return Err(AppError::Unauthorized);
         };
 
-        self.get_pull_request(pull_request_number, db)
-            .await?
-            .close(db)
-            .await
-            .map_err(Into::into)
+        let pull_request = self.get_pull_request(pull_request_number, db).await?;
+        pull_request.close(db).await?;
+        pull_request
+            .record_event(user.id, ItemEventKind::Closed, db)
+            .await;
+        pull_request.notify(user.id, "closed", None, db).await;
+        UserActivity::record(
+            user.id,
+            self.id,
+            UserActivityKind::ClosePullRequest,
+            Some(pull_request.number),
+            Some(pull_request.title),
+            db,
+        )
+        .await;
+
+        Ok(())
     }
 }
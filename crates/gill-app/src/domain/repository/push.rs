@@ -0,0 +1,191 @@
+use crate::domain::issue::reference::ReferenceSource;
+use crate::domain::repository::Repository;
+use crate::domain::timeline::{ItemEventKind, RecordEventCommand};
+use crate::domain::user::User;
+use crate::error::AppResult;
+use crate::instance::InstanceHandle;
+use gill_db::repository::push::{PushEvent, NULL_SHA, PUSH_EVENT_CHANNEL};
+use gill_git::commits::OwnedCommit;
+use gill_git::GitRepository;
+use sqlx::postgres::PgListener;
+use sqlx::PgPool;
+use std::time::Duration;
+use tracing::{debug, error};
+
+const PUSH_BATCH_SIZE: i64 = 20;
+/// Events are also polled, to pick up the ones whose lease expired or missed notifications
+const PUSH_POLL_INTERVAL: Duration = Duration::from_secs(5);
+/// Time a worker has to process a batch before its events are picked up again
+const PUSH_LEASE: Duration = Duration::from_secs(300);
+const PUSH_MAX_ATTEMPTS: i32 = 5;
+/// Commits of a single ref update searched for closing references
+const PUSH_COMMIT_LIMIT: usize = 250;
+
+/// Close the issues referenced by the commits pushed to default branches and record force
+/// pushes to pull requests until the instance shuts down. Push events are recorded by the
+/// post-receive hook, which notifies [`PUSH_EVENT_CHANNEL`] to wake the worker up.
+pub async fn run_push_worker(instance: InstanceHandle) {
+    let mut listener = match push_listener(instance.database()).await {
+        Ok(listener) => Some(listener),
+        Err(err) => {
+            error!("Failed to listen to push events, falling back to polling: {err:?}");
+            None
+        }
+    };
+
+    loop {
+        match process_pushes(&instance).await {
+            Ok(0) => wait_for_push(listener.as_mut()).await,
+            Ok(count) => debug!("Processed {count} push events"),
+            Err(err) => {
+                error!("Failed to process push events: {err:?}");
+                tokio::time::sleep(PUSH_POLL_INTERVAL).await
+            }
+        }
+    }
+}
+
+async fn push_listener(db: &PgPool) -> sqlx::Result<PgListener> {
+    let mut listener = PgListener::connect_with(db).await?;
+    listener.listen(PUSH_EVENT_CHANNEL).await?;
+    Ok(listener)
+}
+
+// Wait for a push notification, or at most until the next poll
+async fn wait_for_push(listener: Option<&mut PgListener>) {
+    match listener {
+        Some(listener) => {
+            if let Ok(Err(err)) = tokio::time::timeout(PUSH_POLL_INTERVAL, listener.recv()).await {
+                error!("Failed to receive push notification: {err:?}");
+                tokio::time::sleep(PUSH_POLL_INTERVAL).await
+            }
+        }
+        None => tokio::time::sleep(PUSH_POLL_INTERVAL).await,
+    }
+}
+
+async fn process_pushes(instance: &InstanceHandle) -> AppResult<usize> {
+    let db = instance.database();
+    let events = PushEvent::claim_unprocessed(
+        PUSH_BATCH_SIZE,
+        PUSH_LEASE.as_secs_f64(),
+        PUSH_MAX_ATTEMPTS,
+        db,
+    )
+    .await?;
+
+    // Failed events keep their lease and are retried once it expires
+    for event in &events {
+        match process_push(event, instance).await {
+            Ok(()) => event.mark_processed(db).await?,
+            Err(err) => error!("Failed to process push event {}: {err:?}", event.id),
+        }
+    }
+
+    Ok(events.len())
+}
+
+async fn process_push(event: &PushEvent, instance: &InstanceHandle) -> AppResult<()> {
+    let db = instance.database();
+
+    // Local pushes have no user to close issues or record events on behalf of
+    let Some(pushed_by) = event.pushed_by else {
+        return Ok(());
+    };
+
+    let Some(branch) = event.ref_name.strip_prefix("refs/heads/") else {
+        return Ok(());
+    };
+
+    if event.after == NULL_SHA {
+        return Ok(());
+    }
+
+    let repository = Repository::by_id(event.repository_id, db).await?;
+    if event.before != NULL_SHA {
+        if let Err(err) = record_force_push(event, branch, pushed_by, &repository, db).await {
+            error!(
+                "Failed to check push event {} for a force push: {err:?}",
+                event.id
+            );
+        }
+    }
+
+    // Like on other forges, only commits landing on the default branch close issues
+    let default_branch = repository.get_default_branch(db).await;
+    if default_branch.map(|branch| branch.name).as_deref() != Some(branch) {
+        return Ok(());
+    }
+
+    let user = User::by_id(pushed_by, db).await?;
+    let owner = repository.owner(db).await?;
+    let name = repository.name.clone();
+    let before = (event.before != NULL_SHA).then(|| event.before.clone());
+    let after = event.after.clone();
+    let commits = tokio::task::spawn_blocking(move || {
+        let git_repository = GitRepository::open(&owner, &name)?;
+        git_repository.history_range(before.as_deref(), &after, PUSH_COMMIT_LIMIT)
+    })
+    .await??;
+
+    // Oldest first, so references show up in the order they were made
+    for commit in commits.iter().rev() {
+        let source = ReferenceSource::Commit(commit.id.clone());
+        repository
+            .close_referenced_issues(&commit_message(commit), &source, &user, instance)
+            .await;
+    }
+
+    Ok(())
+}
+
+/// Record a push rewriting `branch` history in the open pull requests comparing it
+async fn record_force_push(
+    event: &PushEvent,
+    branch: &str,
+    actor_id: i32,
+    repository: &Repository,
+    db: &PgPool,
+) -> AppResult<()> {
+    let pull_requests = repository.open_pull_requests_from(branch, db).await?;
+    if pull_requests.is_empty() {
+        return Ok(());
+    }
+
+    let owner = repository.owner(db).await?;
+    let name = repository.name.clone();
+    let (before, after) = (event.before.clone(), event.after.clone());
+    let fast_forward = tokio::task::spawn_blocking(move || {
+        let git_repository = GitRepository::open(&owner, &name)?;
+        git_repository.is_fast_forward(&before, &after)
+    })
+    .await??;
+
+    if fast_forward {
+        return Ok(());
+    }
+
+    for pull_request in pull_requests {
+        RecordEventCommand {
+            repository_id: pull_request.repository_id,
+            number: pull_request.number,
+            actor_id,
+            kind: ItemEventKind::ForcePushed,
+            old_value: Some(event.before.clone()),
+            new_value: Some(event.after.clone()),
+            created_at: None,
+        }
+        .execute(db)
+        .await;
+    }
+
+    Ok(())
+}
+
+/// Full message of a commit, searched for closing references
+pub(crate) fn commit_message(commit: &OwnedCommit) -> String {
+    match &commit.body {
+        Some(body) => format!("{}\n\n{body}", commit.summary),
+        None => commit.summary.clone(),
+    }
+}
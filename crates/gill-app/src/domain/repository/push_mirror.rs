@@ -0,0 +1,205 @@
+use crate::domain::repository::mirror::check_remote_url;
+use crate::domain::repository::Repository;
+use crate::domain::secret;
+use crate::domain::user::User;
+use crate::error::{AppError, AppResult};
+use anyhow::anyhow;
+use chrono::NaiveDateTime;
+use gill_db::repository::push_mirror::{
+    PushMirror as PushMirrorEntity, PushMirrorCredential as PushMirrorCredentialEntity,
+    PushMirrorState as PushMirrorStateEntity,
+};
+use gill_git::mirror::PushCredential;
+use gill_git::GitRepository;
+use sqlx::PgPool;
+use tracing::error;
+use url::Url;
+
+const PUSH_MIRROR_BATCH_SIZE: i64 = 5;
+/// Time a claimed mirror push stays hidden from other workers
+const PUSH_LEASE_SECONDS: f64 = 10.0 * 60.0;
+
+/// Credential given when adding a push mirror, stored encrypted
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum PushMirrorCredential {
+    None,
+    Token(String),
+    DeployKey(String),
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum PushMirrorCredentialKind {
+    None,
+    Token,
+    DeployKey,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum PushMirrorState {
+    Idle,
+    Pending,
+    Success,
+    Failed,
+}
+
+/// A remote the repository is pushed to after each push
+#[derive(Debug, Clone)]
+pub struct PushMirror {
+    pub id: i32,
+    pub repository_id: i32,
+    pub remote_url: String,
+    pub credential_kind: PushMirrorCredentialKind,
+    pub state: PushMirrorState,
+    pub last_pushed_at: Option<NaiveDateTime>,
+    pub last_error: Option<String>,
+}
+
+impl From<PushMirrorCredentialEntity> for PushMirrorCredentialKind {
+    fn from(kind: PushMirrorCredentialEntity) -> Self {
+        match kind {
+            PushMirrorCredentialEntity::None => PushMirrorCredentialKind::None,
+            PushMirrorCredentialEntity::Token => PushMirrorCredentialKind::Token,
+            PushMirrorCredentialEntity::DeployKey => PushMirrorCredentialKind::DeployKey,
+        }
+    }
+}
+
+impl From<PushMirrorStateEntity> for PushMirrorState {
+    fn from(state: PushMirrorStateEntity) -> Self {
+        match state {
+            PushMirrorStateEntity::Idle => PushMirrorState::Idle,
+            PushMirrorStateEntity::Pending => PushMirrorState::Pending,
+            PushMirrorStateEntity::Success => PushMirrorState::Success,
+            PushMirrorStateEntity::Failed => PushMirrorState::Failed,
+        }
+    }
+}
+
+impl From<PushMirrorEntity> for PushMirror {
+    fn from(mirror: PushMirrorEntity) -> Self {
+        Self {
+            id: mirror.id,
+            repository_id: mirror.repository_id,
+            remote_url: mirror.remote_url,
+            credential_kind: mirror.credential_kind.into(),
+            state: mirror.state.into(),
+            last_pushed_at: mirror.last_pushed_at,
+            last_error: mirror.last_error,
+        }
+    }
+}
+
+impl Repository {
+    pub async fn list_push_mirrors(&self, user: &User, db: &PgPool) -> AppResult<Vec<PushMirror>> {
+        self.ensure_owned_by(user)?;
+        let mirrors = PushMirrorEntity::list_for_repository(self.id, db)
+            .await?
+            .into_iter()
+            .map(PushMirror::from)
+            .collect();
+
+        Ok(mirrors)
+    }
+
+    /// Add a push mirror target, it is pushed right away and then after each push
+    pub async fn add_push_mirror(
+        &self,
+        user: &User,
+        remote_url: &str,
+        credential: PushMirrorCredential,
+        db: &PgPool,
+    ) -> AppResult<PushMirror> {
+        self.ensure_owned_by(user)?;
+        check_remote_url(remote_url)?;
+        if Url::parse(remote_url).map_or(false, |url| url.password().is_some()) {
+            return Err(anyhow!("Credentials must not be part of the mirror url").into());
+        }
+
+        let (kind, encrypted_credential) = match credential {
+            PushMirrorCredential::None => (PushMirrorCredentialEntity::None, None),
+            PushMirrorCredential::Token(token) => (
+                PushMirrorCredentialEntity::Token,
+                Some(secret::encrypt(&token)?),
+            ),
+            PushMirrorCredential::DeployKey(key) => (
+                PushMirrorCredentialEntity::DeployKey,
+                Some(secret::encrypt(&key)?),
+            ),
+        };
+
+        let mirror = PushMirrorEntity::create(
+            self.id,
+            remote_url,
+            kind,
+            encrypted_credential.as_deref(),
+            db,
+        )
+        .await?;
+
+        PushMirrorEntity::schedule_for_repository(self.id, db).await?;
+        Ok(mirror.into())
+    }
+
+    pub async fn delete_push_mirror(&self, user: &User, id: i32, db: &PgPool) -> AppResult<()> {
+        self.ensure_owned_by(user)?;
+        PushMirrorEntity::delete(id, self.id, db).await?;
+        Ok(())
+    }
+
+    fn ensure_owned_by(&self, user: &User) -> AppResult<()> {
+        if self.is_local && self.attributed_to == user.activity_pub_id {
+            Ok(())
+        } else {
+            Err(AppError::Unauthorized)
+        }
+    }
+}
+
+/// Push the repositories having a pending mirror push
+pub(crate) async fn push_due(db: &PgPool) -> AppResult<usize> {
+    let mirrors =
+        PushMirrorEntity::claim_due(PUSH_MIRROR_BATCH_SIZE, PUSH_LEASE_SECONDS, db).await?;
+
+    for mirror in &mirrors {
+        let outcome = push(mirror, db).await;
+        if let Err(err) = &outcome {
+            error!("Failed to push mirror {}: {err:#}", mirror.remote_url);
+        }
+
+        let error = outcome.err().map(|err| format!("{err:#}"));
+        PushMirrorEntity::pushed(mirror.id, mirror.requested_at, error.as_deref(), db).await?;
+    }
+
+    Ok(mirrors.len())
+}
+
+async fn push(mirror: &PushMirrorEntity, db: &PgPool) -> anyhow::Result<()> {
+    let repository = Repository::by_id(mirror.repository_id, db)
+        .await
+        .map_err(|_| anyhow!("Unknown repository"))?;
+    let owner = repository
+        .owner(db)
+        .await
+        .map_err(|_| anyhow!("Unknown owner"))?;
+
+    let credential = match &mirror.encrypted_credential {
+        Some(encrypted) => {
+            Some(secret::decrypt(encrypted).map_err(|_| anyhow!("Invalid credential"))?)
+        }
+        None => None,
+    };
+
+    let kind = mirror.credential_kind;
+    let remote_url = mirror.remote_url.clone();
+    tokio::task::spawn_blocking(move || {
+        let credential = match (kind, credential.as_deref()) {
+            (PushMirrorCredentialEntity::Token, Some(token)) => PushCredential::Token(token),
+            (PushMirrorCredentialEntity::DeployKey, Some(key)) => PushCredential::DeployKey(key),
+            _ => PushCredential::None,
+        };
+
+        let repository = GitRepository::open(&owner, &repository.name)?;
+        repository.push_mirror(&remote_url, credential)
+    })
+    .await?
+}
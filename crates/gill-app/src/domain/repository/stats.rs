@@ -1,5 +1,11 @@
 use crate::domain::repository::digest::RepositoryDigest;
+use crate::domain::repository::import::RepositoryImport;
+use crate::domain::repository::mirror::RepositoryMirror;
 use gill_db::repository::digest::RepositoryLight;
+use gill_db::repository::import::RepositoryImport as RepositoryImportEntity;
+use gill_db::repository::mirror::RepositoryMirror as RepositoryMirrorEntity;
+use gill_db::repository::Repository as RepositoryEntity;
+use gill_db::user::User as UserEntity;
 use sqlx::PgPool;
 
 #[derive(Debug)]
@@ -8,6 +14,16 @@ pub struct RepositoryStats {
     pub star_count: u32,
     pub watch_count: u32,
     pub clone_url: String,
+    /// The connected user starred this repository
+    pub starred: bool,
+    /// The connected user watches this repository
+    pub watched: bool,
+    /// Sync status when the repository is pulled from a remote url
+    pub mirror: Option<RepositoryMirror>,
+    /// A federated repository which is not mirrored yet
+    pub mirrorable: bool,
+    /// Progress when the repository was imported from another forge
+    pub import: Option<RepositoryImport>,
 }
 
 impl From<RepositoryLight> for RepositoryStats {
@@ -17,6 +33,11 @@ impl From<RepositoryLight> for RepositoryStats {
             fork_count: stats.fork_count.unwrap_or(0) as u32,
             star_count: stats.star_count.unwrap_or(0) as u32,
             clone_url: stats.clone_url,
+            starred: false,
+            watched: false,
+            mirror: None,
+            mirrorable: false,
+            import: None,
         }
     }
 }
@@ -28,17 +49,40 @@ impl From<&RepositoryDigest> for RepositoryStats {
             fork_count: repo.fork_count.unwrap_or(0) as u32,
             star_count: repo.star_count.unwrap_or(0) as u32,
             clone_url: repo.clone_url.clone(),
+            starred: false,
+            watched: false,
+            mirror: None,
+            mirrorable: false,
+            import: None,
         }
     }
 }
 
 impl RepositoryStats {
+    /// Repository counters, mirror and import status, along with the star and watch state of `connected_username`
     pub async fn get(
         owner: &str,
         repository: &str,
+        connected_username: Option<&str>,
         db: &PgPool,
     ) -> anyhow::Result<RepositoryStats> {
         let repo = RepositoryLight::stats_by_namespace(owner, repository, db).await?;
-        Ok(RepositoryStats::from(repo))
+        let mut stats = RepositoryStats::from(repo);
+        let repository = RepositoryEntity::by_namespace(owner, repository, db).await?;
+        stats.mirror = RepositoryMirrorEntity::by_repository_id(repository.id, db)
+            .await?
+            .map(RepositoryMirror::from);
+        stats.mirrorable = !repository.is_local && stats.mirror.is_none();
+        stats.import = RepositoryImportEntity::by_repository_id(repository.id, db)
+            .await?
+            .map(RepositoryImport::from);
+
+        if let Some(username) = connected_username {
+            let user = UserEntity::by_user_name(username, db).await?;
+            stats.starred = repository.is_starred_by(user.id, db).await?;
+            stats.watched = repository.is_watched_by(user.id, db).await?;
+        }
+
+        Ok(stats)
     }
 }
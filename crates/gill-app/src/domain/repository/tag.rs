@@ -0,0 +1,38 @@
+use crate::domain::repository::Repository;
+use crate::error::AppResult;
+use gill_db::repository::tag::Tag as TagEntity;
+use gill_db::repository::Repository as RepositoryEntity;
+use sqlx::PgPool;
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Tag {
+    pub name: String,
+    pub repository_id: i32,
+    pub target: String,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+impl From<TagEntity> for Tag {
+    fn from(tag: TagEntity) -> Self {
+        Self {
+            name: tag.name,
+            repository_id: tag.repository_id,
+            target: tag.target,
+            created_at: tag.created_at,
+        }
+    }
+}
+
+impl Repository {
+    pub async fn list_tags(&self, limit: i64, offset: i64, db: &PgPool) -> AppResult<Vec<Tag>> {
+        let entity: RepositoryEntity = self.into();
+        let tags = entity.list_tags(limit, offset, db).await?;
+        Ok(tags.into_iter().map(Tag::from).collect())
+    }
+
+    pub async fn get_tag(&self, name: &str, db: &PgPool) -> AppResult<Tag> {
+        let entity: RepositoryEntity = self.into();
+        let tag = entity.get_tag(name, db).await?;
+        Ok(Tag::from(tag))
+    }
+}
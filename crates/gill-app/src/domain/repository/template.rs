@@ -0,0 +1,209 @@
+use crate::domain::repository::Repository;
+use crate::error::AppResult;
+use gill_git::GitRepository;
+use sqlx::PgPool;
+
+const TEMPLATE_DIR: &str = ".gill";
+const ISSUE_TEMPLATE_DIR: &str = ".gill/ISSUE_TEMPLATE";
+const PULL_REQUEST_TEMPLATE: &str = "pull_request_template.md";
+
+/// An issue template read from `.gill/ISSUE_TEMPLATE/*.md` on the default branch.
+/// The optional front matter gives its name, the new issue title prefix and its default labels:
+/// ```markdown
+/// ---
+/// name: Bug report
+/// about: Something is not working
+/// title: "[bug] "
+/// labels: [bug, triage]
+/// ---
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct IssueTemplate {
+    /// The template file name, used to find it back when the issue is created
+    pub filename: String,
+    pub name: String,
+    pub about: Option<String>,
+    pub title: String,
+    pub labels: Vec<String>,
+    pub body: String,
+}
+
+impl IssueTemplate {
+    pub fn parse(filename: &str, content: &str) -> Self {
+        let mut template = IssueTemplate {
+            filename: filename.to_string(),
+            name: filename.trim_end_matches(".md").replace(['_', '-'], " "),
+            about: None,
+            title: String::new(),
+            labels: vec![],
+            body: content.to_string(),
+        };
+
+        let Some((front_matter, body)) = split_front_matter(content) else {
+            return template;
+        };
+
+        template.body = body.to_string();
+        let mut lines = front_matter.lines().peekable();
+        while let Some(line) = lines.next() {
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+
+            let value = value.trim();
+            match key.trim() {
+                "name" if !value.is_empty() => template.name = unquote(value).to_string(),
+                "about" if !value.is_empty() => template.about = Some(unquote(value).to_string()),
+                "title" => template.title = unquote(value).to_string(),
+                "labels" if value.is_empty() => {
+                    // Block sequence, one `- label` per line
+                    while let Some(item) = lines.next_if(|line| line.trim().starts_with('-')) {
+                        template.labels.push(unquote(&item.trim()[1..]).to_string());
+                    }
+                }
+                "labels" => {
+                    // Flow sequence or comma separated labels
+                    let value = value.trim_start_matches('[').trim_end_matches(']');
+                    template.labels.extend(
+                        value
+                            .split(',')
+                            .map(unquote)
+                            .filter(|label| !label.is_empty())
+                            .map(str::to_string),
+                    );
+                }
+                _ => {}
+            }
+        }
+
+        template.labels.retain(|label| !label.is_empty());
+        template
+    }
+}
+
+/// Split `---` delimited front matter from the rest of the document
+fn split_front_matter(content: &str) -> Option<(&str, &str)> {
+    let content = content.strip_prefix("---")?;
+    let content = content
+        .strip_prefix("\r\n")
+        .or_else(|| content.strip_prefix('\n'))?;
+    if let Some(body) = content.strip_prefix("---") {
+        return Some(("", body.trim_start_matches(['\r', '\n'])));
+    }
+
+    let end = content.find("\n---")?;
+    let body = content[end + 4..].trim_start_matches(['\r', '\n']);
+    Some((&content[..end], body))
+}
+
+fn unquote(value: &str) -> &str {
+    let value = value.trim();
+    value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .or_else(|| {
+            value
+                .strip_prefix('\'')
+                .and_then(|value| value.strip_suffix('\''))
+        })
+        .unwrap_or(value)
+}
+
+impl Repository {
+    /// Issue templates of the default branch, ordered by file name
+    pub async fn issue_templates(&self, owner: &str, db: &PgPool) -> AppResult<Vec<IssueTemplate>> {
+        let Some(branch) = self.get_default_branch(db).await else {
+            return Ok(vec![]);
+        };
+
+        let git_repository = GitRepository::open(owner, &self.name)?;
+        let tree =
+            git_repository.get_tree_for_path(Some(&branch.name), Some(ISSUE_TEMPLATE_DIR))?;
+        let mut templates = vec![];
+        for blob in tree.blobs.iter() {
+            let filename = blob.filename();
+            if filename.ends_with(".md") {
+                let content = git_repository.blob_str(blob)?;
+                templates.push(IssueTemplate::parse(&filename, &content));
+            }
+        }
+
+        templates.sort_by(|a, b| a.filename.cmp(&b.filename));
+        Ok(templates)
+    }
+
+    pub async fn issue_template(
+        &self,
+        owner: &str,
+        filename: &str,
+        db: &PgPool,
+    ) -> AppResult<Option<IssueTemplate>> {
+        let templates = self.issue_templates(owner, db).await?;
+        Ok(templates
+            .into_iter()
+            .find(|template| template.filename == filename))
+    }
+
+    /// The pull request description template of the default branch
+    pub async fn pull_request_template(
+        &self,
+        owner: &str,
+        db: &PgPool,
+    ) -> AppResult<Option<String>> {
+        let Some(branch) = self.get_default_branch(db).await else {
+            return Ok(None);
+        };
+
+        let git_repository = GitRepository::open(owner, &self.name)?;
+        let tree = git_repository.get_tree_for_path(Some(&branch.name), Some(TEMPLATE_DIR))?;
+        let template = tree
+            .blobs
+            .iter()
+            .find(|blob| blob.filename() == PULL_REQUEST_TEMPLATE)
+            .map(|blob| git_repository.blob_str(blob))
+            .transpose()?;
+
+        Ok(template)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::domain::repository::template::IssueTemplate;
+    use speculoos::prelude::*;
+
+    #[test]
+    fn should_parse_issue_template_front_matter() {
+        let content = "---\nname: Bug report\nabout: 'Something is broken'\ntitle: \"[bug] \"\nlabels: [bug, \"needs triage\"]\n---\n\n## Steps to reproduce\n";
+
+        let template = IssueTemplate::parse("bug.md", content);
+
+        assert_that!(template.name).is_equal_to("Bug report".to_string());
+        assert_that!(template.about).is_equal_to(Some("Something is broken".to_string()));
+        assert_that!(template.title).is_equal_to("[bug] ".to_string());
+        assert_that!(template.labels)
+            .is_equal_to(vec!["bug".to_string(), "needs triage".to_string()]);
+        assert_that!(template.body).is_equal_to("## Steps to reproduce\n".to_string());
+    }
+
+    #[test]
+    fn should_parse_block_sequence_labels() {
+        let content = "---\nlabels:\n  - enhancement\n  - ui\ntitle: feat\n---\nDescribe it";
+
+        let template = IssueTemplate::parse("feature_request.md", content);
+
+        assert_that!(template.name).is_equal_to("feature request".to_string());
+        assert_that!(template.labels)
+            .is_equal_to(vec!["enhancement".to_string(), "ui".to_string()]);
+        assert_that!(template.title).is_equal_to("feat".to_string());
+        assert_that!(template.body).is_equal_to("Describe it".to_string());
+    }
+
+    #[test]
+    fn should_use_whole_file_without_front_matter() {
+        let template = IssueTemplate::parse("question.md", "What do you want to know?");
+
+        assert_that!(template.labels).is_empty();
+        assert_that!(template.body).is_equal_to("What do you want to know?".to_string());
+    }
+}
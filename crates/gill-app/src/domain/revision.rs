@@ -0,0 +1,222 @@
+use crate::domain::markdown::unescape;
+use crate::error::{AppError, AppResult};
+use chrono::NaiveDateTime;
+use gill_db::repository::revision::{CreateRevision, Revision as RevisionEntity};
+use gill_db::Insert;
+use imara_diff::intern::{InternedInput, Token};
+use imara_diff::{sources, Algorithm};
+use sqlx::PgPool;
+use std::collections::HashMap;
+use std::ops::Range;
+use tracing::error;
+use uuid::Uuid;
+
+/// An edit of an issue, a pull request or a comment
+#[derive(Debug, Clone)]
+pub struct Revision {
+    pub id: i32,
+    pub editor_name: String,
+    pub created_at: NaiveDateTime,
+    /// Previous and new title, only set when the edit renamed the item
+    pub old_title: Option<String>,
+    pub new_title: Option<String>,
+    old_content: String,
+    new_content: String,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum DiffLineKind {
+    Added,
+    Removed,
+    Unchanged,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct DiffLine {
+    pub kind: DiffLineKind,
+    pub text: String,
+}
+
+/// Revisions of an issue or a pull request and of its comments
+#[derive(Debug, Default)]
+pub struct ItemRevisions {
+    pub item: Vec<Revision>,
+    issue_comments: HashMap<Uuid, Vec<Revision>>,
+    pull_request_comments: HashMap<i32, Vec<Revision>>,
+}
+
+/// Record an edit of issue or pull request `number` of `repository_id`
+#[derive(Debug)]
+pub struct RecordRevisionCommand<'a> {
+    pub repository_id: i32,
+    pub number: i32,
+    pub issue_comment_id: Option<Uuid>,
+    pub pull_request_comment_id: Option<i32>,
+    pub old_title: Option<&'a str>,
+    pub new_title: Option<&'a str>,
+    pub old_content: &'a str,
+    pub new_content: &'a str,
+    pub edited_by: i32,
+}
+
+impl From<RevisionEntity> for Revision {
+    fn from(revision: RevisionEntity) -> Self {
+        Self {
+            id: revision.id,
+            editor_name: revision.editor_name,
+            created_at: revision.created_at,
+            old_title: revision.old_title,
+            new_title: revision.new_title,
+            old_content: revision.old_content,
+            new_content: revision.new_content,
+        }
+    }
+}
+
+impl Revision {
+    pub async fn by_id(repository_id: i32, id: i32, db: &PgPool) -> AppResult<Revision> {
+        let revision = RevisionEntity::by_id(repository_id, id, db)
+            .await
+            .map_err(|_| AppError::NotFound)?;
+        Ok(revision.into())
+    }
+
+    /// Line diff of the edited content, only computed when a revision is expanded
+    pub fn diff(&self) -> Vec<DiffLine> {
+        diff_lines(&unescape(&self.old_content), &unescape(&self.new_content))
+    }
+}
+
+impl RecordRevisionCommand<'_> {
+    /// Keep the previous title and content of an edit. Saves that change nothing are
+    /// skipped and errors only end up in the logs.
+    pub async fn execute(self, db: &PgPool) {
+        let renamed = self.old_title != self.new_title;
+        if !renamed && self.old_content == self.new_content {
+            return;
+        }
+
+        let (repository_id, number) = (self.repository_id, self.number);
+        let (old_title, new_title) = match renamed {
+            true => (
+                self.old_title.map(str::to_string),
+                self.new_title.map(str::to_string),
+            ),
+            false => (None, None),
+        };
+
+        let revision = CreateRevision {
+            repository_id,
+            number,
+            issue_comment_id: self.issue_comment_id,
+            pull_request_comment_id: self.pull_request_comment_id,
+            old_title,
+            new_title,
+            old_content: self.old_content.to_string(),
+            new_content: self.new_content.to_string(),
+            edited_by: self.edited_by,
+        };
+
+        if let Err(err) = revision.insert(db).await {
+            error!("Failed to record revision of #{number} of repository {repository_id}: {err}");
+        }
+    }
+}
+
+impl ItemRevisions {
+    pub async fn for_item(repository_id: i32, number: i32, db: &PgPool) -> AppResult<Self> {
+        let revisions = RevisionEntity::for_item(repository_id, number, db).await?;
+        let mut item_revisions = ItemRevisions::default();
+        for revision in revisions {
+            match (revision.issue_comment_id, revision.pull_request_comment_id) {
+                (Some(id), _) => item_revisions
+                    .issue_comments
+                    .entry(id)
+                    .or_default()
+                    .push(revision.into()),
+                (None, Some(id)) => item_revisions
+                    .pull_request_comments
+                    .entry(id)
+                    .or_default()
+                    .push(revision.into()),
+                (None, None) => item_revisions.item.push(revision.into()),
+            }
+        }
+
+        Ok(item_revisions)
+    }
+
+    pub fn of_issue_comment(&self, id: &Uuid) -> &[Revision] {
+        self.issue_comments
+            .get(id)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    pub fn of_pull_request_comment(&self, id: &i32) -> &[Revision] {
+        self.pull_request_comments
+            .get(id)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+}
+
+/// Line diff of `old` and `new`, Myers runs in linear space so large contents stay cheap
+fn diff_lines(old: &str, new: &str) -> Vec<DiffLine> {
+    let input = InternedInput::new(sources::lines(old), sources::lines(new));
+    let lines = |kind, tokens: &[Token]| {
+        tokens
+            .iter()
+            .map(|token| DiffLine {
+                kind,
+                text: input.interner[*token].to_string(),
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let mut diff = vec![];
+    let mut unchanged_from = 0;
+    imara_diff::diff(
+        Algorithm::Myers,
+        &input,
+        |before: Range<u32>, after: Range<u32>| {
+            let (before, after) = (as_usize(before), as_usize(after));
+            let unchanged = &input.before[unchanged_from..before.start];
+            diff.extend(lines(DiffLineKind::Unchanged, unchanged));
+            diff.extend(lines(DiffLineKind::Removed, &input.before[before.clone()]));
+            diff.extend(lines(DiffLineKind::Added, &input.after[after]));
+            unchanged_from = before.end;
+        },
+    );
+
+    let unchanged = &input.before[unchanged_from..];
+    diff.extend(lines(DiffLineKind::Unchanged, unchanged));
+    diff
+}
+
+fn as_usize(range: Range<u32>) -> Range<usize> {
+    range.start as usize..range.end as usize
+}
+
+#[cfg(test)]
+mod test {
+    use crate::domain::revision::{diff_lines, DiffLine, DiffLineKind};
+    use speculoos::prelude::*;
+
+    #[test]
+    fn should_diff_lines() {
+        let diff = diff_lines("a\nb\nc", "a\nc\nd");
+
+        let line = |kind, text: &str| DiffLine {
+            kind,
+            text: text.to_string(),
+        };
+
+        assert_that!(diff).is_equal_to(vec![
+            line(DiffLineKind::Unchanged, "a"),
+            line(DiffLineKind::Removed, "b"),
+            line(DiffLineKind::Unchanged, "c"),
+            line(DiffLineKind::Added, "d"),
+        ]);
+    }
+}
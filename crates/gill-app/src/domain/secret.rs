@@ -0,0 +1,103 @@
+use crate::error::AppResult;
+use anyhow::anyhow;
+use base64::engine::general_purpose;
+use base64::Engine;
+use gill_settings::SETTINGS;
+use openssl::symm::{decrypt_aead, encrypt_aead, Cipher};
+
+const NONCE_LEN: usize = 12;
+const TAG_LEN: usize = 16;
+
+/// Encrypt a credential with the instance `secret_key` (AES-256-GCM), the output is
+/// base64 encoded and safe to store in the database
+pub fn encrypt(plaintext: &str) -> AppResult<String> {
+    encrypt_with(&secret_key()?, plaintext).map_err(Into::into)
+}
+
+/// Decrypt a credential previously encrypted with [`encrypt`]
+pub fn decrypt(ciphertext: &str) -> AppResult<String> {
+    decrypt_with(&secret_key()?, ciphertext).map_err(Into::into)
+}
+
+fn secret_key() -> anyhow::Result<[u8; 32]> {
+    let secret_key = SETTINGS
+        .secret_key
+        .as_deref()
+        .filter(|secret_key| !secret_key.is_empty())
+        .ok_or_else(|| anyhow!("'secret_key' must be set to store credentials"))?;
+
+    Ok(openssl::sha::sha256(secret_key.as_bytes()))
+}
+
+fn encrypt_with(key: &[u8; 32], plaintext: &str) -> anyhow::Result<String> {
+    let mut nonce = [0; NONCE_LEN];
+    openssl::rand::rand_bytes(&mut nonce)?;
+    let mut tag = [0; TAG_LEN];
+    let ciphertext = encrypt_aead(
+        Cipher::aes_256_gcm(),
+        key,
+        Some(&nonce),
+        &[],
+        plaintext.as_bytes(),
+        &mut tag,
+    )?;
+
+    let mut encrypted = Vec::with_capacity(NONCE_LEN + TAG_LEN + ciphertext.len());
+    encrypted.extend_from_slice(&nonce);
+    encrypted.extend_from_slice(&tag);
+    encrypted.extend_from_slice(&ciphertext);
+    Ok(general_purpose::STANDARD.encode(encrypted))
+}
+
+fn decrypt_with(key: &[u8; 32], ciphertext: &str) -> anyhow::Result<String> {
+    let encrypted = general_purpose::STANDARD.decode(ciphertext)?;
+    if encrypted.len() < NONCE_LEN + TAG_LEN {
+        return Err(anyhow!("Invalid encrypted credential"));
+    }
+
+    let (nonce, encrypted) = encrypted.split_at(NONCE_LEN);
+    let (tag, ciphertext) = encrypted.split_at(TAG_LEN);
+    let plaintext = decrypt_aead(
+        Cipher::aes_256_gcm(),
+        key,
+        Some(nonce),
+        &[],
+        ciphertext,
+        tag,
+    )?;
+    Ok(String::from_utf8(plaintext)?)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::domain::secret::{decrypt_with, encrypt_with};
+    use speculoos::prelude::*;
+
+    const KEY: [u8; 32] = [7; 32];
+
+    #[test]
+    fn should_decrypt_encrypted_secret() -> anyhow::Result<()> {
+        let encrypted = encrypt_with(&KEY, "glpat-token")?;
+
+        assert_that!(encrypted).does_not_contain("glpat-token");
+        assert_that!(decrypt_with(&KEY, &encrypted)?).is_equal_to("glpat-token".to_string());
+        Ok(())
+    }
+
+    #[test]
+    fn should_use_a_fresh_nonce() -> anyhow::Result<()> {
+        let first = encrypt_with(&KEY, "glpat-token")?;
+        let second = encrypt_with(&KEY, "glpat-token")?;
+
+        assert_that!(first).is_not_equal_to(second);
+        Ok(())
+    }
+
+    #[test]
+    fn should_not_decrypt_with_another_key() -> anyhow::Result<()> {
+        let encrypted = encrypt_with(&KEY, "glpat-token")?;
+
+        assert_that!(decrypt_with(&[8; 32], &encrypted)).is_err();
+        Ok(())
+    }
+}
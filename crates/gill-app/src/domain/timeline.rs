@@ -0,0 +1,243 @@
+use crate::domain::issue::reference::IssueReference;
+use crate::domain::issue::Issue;
+use crate::domain::pull_request::PullRequest;
+use crate::error::AppResult;
+use chrono::NaiveDateTime;
+use gill_db::repository::event::{
+    CreateItemEvent, ItemEvent as ItemEventEntity, ItemEventKind as ItemEventKindEntity,
+};
+use gill_db::Insert;
+use sqlx::PgPool;
+use tracing::error;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ItemEventKind {
+    Opened,
+    Closed,
+    Reopened,
+    Renamed,
+    Labeled,
+    Merged,
+    ForcePushed,
+}
+
+/// Something that happened to an issue or a pull request, besides comments and references
+#[derive(Debug, Clone)]
+pub struct ItemEvent {
+    pub kind: ItemEventKind,
+    /// Previous title of a rename, previous head of a force push
+    pub old_value: Option<String>,
+    /// New title of a rename, label name, new head of a force push
+    pub new_value: Option<String>,
+    pub actor_name: String,
+    pub actor_activity_pub_id: String,
+    pub created_at: NaiveDateTime,
+}
+
+/// Record an event in the history of issue or pull request `number` of `repository_id`
+#[derive(Debug)]
+pub struct RecordEventCommand {
+    pub repository_id: i32,
+    pub number: i32,
+    pub actor_id: i32,
+    pub kind: ItemEventKind,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+    /// Defaults to now
+    pub created_at: Option<NaiveDateTime>,
+}
+
+/// An entry of an issue or pull request timeline
+#[derive(Debug, Clone)]
+pub enum TimelineItem<Comment> {
+    Comment(Comment),
+    Event(ItemEvent),
+    Reference(IssueReference),
+}
+
+/// An entry of an issue or pull request history, its timeline without the comments
+#[derive(Debug, Clone)]
+pub enum HistoryItem {
+    Event(ItemEvent),
+    Reference(IssueReference),
+}
+
+impl From<ItemEventKind> for ItemEventKindEntity {
+    fn from(kind: ItemEventKind) -> Self {
+        match kind {
+            ItemEventKind::Opened => ItemEventKindEntity::Opened,
+            ItemEventKind::Closed => ItemEventKindEntity::Closed,
+            ItemEventKind::Reopened => ItemEventKindEntity::Reopened,
+            ItemEventKind::Renamed => ItemEventKindEntity::Renamed,
+            ItemEventKind::Labeled => ItemEventKindEntity::Labeled,
+            ItemEventKind::Merged => ItemEventKindEntity::Merged,
+            ItemEventKind::ForcePushed => ItemEventKindEntity::ForcePushed,
+        }
+    }
+}
+
+impl From<ItemEventKindEntity> for ItemEventKind {
+    fn from(kind: ItemEventKindEntity) -> Self {
+        match kind {
+            ItemEventKindEntity::Opened => ItemEventKind::Opened,
+            ItemEventKindEntity::Closed => ItemEventKind::Closed,
+            ItemEventKindEntity::Reopened => ItemEventKind::Reopened,
+            ItemEventKindEntity::Renamed => ItemEventKind::Renamed,
+            ItemEventKindEntity::Labeled => ItemEventKind::Labeled,
+            ItemEventKindEntity::Merged => ItemEventKind::Merged,
+            ItemEventKindEntity::ForcePushed => ItemEventKind::ForcePushed,
+        }
+    }
+}
+
+impl From<ItemEventEntity> for ItemEvent {
+    fn from(event: ItemEventEntity) -> Self {
+        Self {
+            kind: event.kind.into(),
+            old_value: event.old_value,
+            new_value: event.new_value,
+            actor_name: event.actor_name,
+            actor_activity_pub_id: event.actor_activity_pub_id,
+            created_at: event.created_at,
+        }
+    }
+}
+
+impl ItemEvent {
+    /// Previous and new head of a force push, e.g. `3f2a9c1..a8e0b7d`
+    pub fn pushed_range(&self) -> String {
+        let short_sha = |sha: &Option<String>| -> String {
+            sha.as_deref()
+                .map(|sha| sha.chars().take(7).collect())
+                .unwrap_or_default()
+        };
+
+        format!(
+            "{}..{}",
+            short_sha(&self.old_value),
+            short_sha(&self.new_value)
+        )
+    }
+}
+
+impl RecordEventCommand {
+    /// Append the event to the item timeline, a database error is logged and swallowed
+    pub async fn execute(self, db: &PgPool) {
+        let (repository_id, number) = (self.repository_id, self.number);
+        let event = CreateItemEvent {
+            repository_id,
+            number,
+            actor_id: self.actor_id,
+            kind: self.kind.into(),
+            old_value: self.old_value,
+            new_value: self.new_value,
+            created_at: self.created_at,
+        };
+
+        if let Err(err) = event.insert(db).await {
+            error!("Failed to record event of #{number} of repository {repository_id}: {err}");
+        }
+    }
+}
+
+impl HistoryItem {
+    /// Events and references of issue or pull request `number` of `repository_id`, oldest first
+    pub async fn for_item(
+        repository_id: i32,
+        number: i32,
+        db: &PgPool,
+    ) -> AppResult<Vec<HistoryItem>> {
+        let events = ItemEventEntity::for_item(repository_id, number, db).await?;
+        let references = IssueReference::for_issue(repository_id, number, db).await?;
+
+        let mut items: Vec<HistoryItem> = events
+            .into_iter()
+            .map(|event| HistoryItem::Event(ItemEvent::from(event)))
+            .collect();
+
+        items.extend(references.into_iter().map(HistoryItem::Reference));
+
+        // Stable, so an event and a reference sharing a date keep the order above
+        items.sort_by_key(HistoryItem::created_at);
+        Ok(items)
+    }
+
+    pub fn created_at(&self) -> NaiveDateTime {
+        match self {
+            HistoryItem::Event(event) => event.created_at,
+            HistoryItem::Reference(reference) => reference.created_at,
+        }
+    }
+}
+
+impl<Comment> From<HistoryItem> for TimelineItem<Comment> {
+    fn from(item: HistoryItem) -> Self {
+        match item {
+            HistoryItem::Event(event) => TimelineItem::Event(event),
+            HistoryItem::Reference(reference) => TimelineItem::Reference(reference),
+        }
+    }
+}
+
+impl<Comment> TimelineItem<Comment> {
+    /// Comments, events and references of issue or pull request `number` of `repository_id`,
+    /// oldest first. `published` gives the date of a comment.
+    pub async fn for_item(
+        repository_id: i32,
+        number: i32,
+        comments: Vec<Comment>,
+        published: impl Fn(&Comment) -> NaiveDateTime,
+        db: &PgPool,
+    ) -> AppResult<Vec<TimelineItem<Comment>>> {
+        let history = HistoryItem::for_item(repository_id, number, db).await?;
+
+        let mut items: Vec<(NaiveDateTime, TimelineItem<Comment>)> = comments
+            .into_iter()
+            .map(|comment| (published(&comment), TimelineItem::Comment(comment)))
+            .collect();
+
+        items.extend(
+            history
+                .into_iter()
+                .map(|item| (item.created_at(), TimelineItem::from(item))),
+        );
+
+        // Stable, so items sharing a date keep the order above
+        items.sort_by_key(|(created_at, _)| *created_at);
+        Ok(items.into_iter().map(|(_, item)| item).collect())
+    }
+}
+
+impl Issue {
+    /// Record an event without values in the issue history
+    pub async fn record_event(&self, actor_id: i32, kind: ItemEventKind, db: &PgPool) {
+        RecordEventCommand {
+            repository_id: self.repository_id,
+            number: self.number,
+            actor_id,
+            kind,
+            old_value: None,
+            new_value: None,
+            created_at: None,
+        }
+        .execute(db)
+        .await
+    }
+}
+
+impl PullRequest {
+    /// Record an event without values in the pull request history
+    pub async fn record_event(&self, actor_id: i32, kind: ItemEventKind, db: &PgPool) {
+        RecordEventCommand {
+            repository_id: self.repository_id,
+            number: self.number,
+            actor_id,
+            kind,
+            old_value: None,
+            new_value: None,
+            created_at: None,
+        }
+        .execute(db)
+        .await
+    }
+}
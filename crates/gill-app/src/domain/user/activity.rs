@@ -0,0 +1,136 @@
+use crate::domain::user::User;
+use crate::error::AppResult;
+use chrono::NaiveDateTime;
+use gill_db::user::activity::{
+    CreateUserActivity, UserActivity as UserActivityEntity,
+    UserActivityKind as UserActivityKindEntity,
+};
+use gill_db::Insert;
+use sqlx::PgPool;
+use tracing::error;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum UserActivityKind {
+    Push,
+    OpenIssue,
+    CloseIssue,
+    ReopenIssue,
+    CommentIssue,
+    OpenPullRequest,
+    MergePullRequest,
+    ClosePullRequest,
+    CommentPullRequest,
+    Star,
+    Fork,
+}
+
+/// An entry of the dashboard feed
+#[derive(Debug, Clone)]
+pub struct UserActivity {
+    pub kind: UserActivityKind,
+    pub number: Option<i32>,
+    pub summary: Option<String>,
+    pub published: NaiveDateTime,
+    pub actor_name: String,
+    pub actor_domain: String,
+    pub actor_is_local: bool,
+    pub repository_owner: String,
+    pub repository_name: String,
+    pub repository_domain: String,
+    pub repository_is_local: bool,
+}
+
+impl From<UserActivityKind> for UserActivityKindEntity {
+    fn from(kind: UserActivityKind) -> Self {
+        match kind {
+            UserActivityKind::Push => UserActivityKindEntity::Push,
+            UserActivityKind::OpenIssue => UserActivityKindEntity::OpenIssue,
+            UserActivityKind::CloseIssue => UserActivityKindEntity::CloseIssue,
+            UserActivityKind::ReopenIssue => UserActivityKindEntity::ReopenIssue,
+            UserActivityKind::CommentIssue => UserActivityKindEntity::CommentIssue,
+            UserActivityKind::OpenPullRequest => UserActivityKindEntity::OpenPullRequest,
+            UserActivityKind::MergePullRequest => UserActivityKindEntity::MergePullRequest,
+            UserActivityKind::ClosePullRequest => UserActivityKindEntity::ClosePullRequest,
+            UserActivityKind::CommentPullRequest => UserActivityKindEntity::CommentPullRequest,
+            UserActivityKind::Star => UserActivityKindEntity::Star,
+            UserActivityKind::Fork => UserActivityKindEntity::Fork,
+        }
+    }
+}
+
+impl From<UserActivityKindEntity> for UserActivityKind {
+    fn from(kind: UserActivityKindEntity) -> Self {
+        match kind {
+            UserActivityKindEntity::Push => UserActivityKind::Push,
+            UserActivityKindEntity::OpenIssue => UserActivityKind::OpenIssue,
+            UserActivityKindEntity::CloseIssue => UserActivityKind::CloseIssue,
+            UserActivityKindEntity::ReopenIssue => UserActivityKind::ReopenIssue,
+            UserActivityKindEntity::CommentIssue => UserActivityKind::CommentIssue,
+            UserActivityKindEntity::OpenPullRequest => UserActivityKind::OpenPullRequest,
+            UserActivityKindEntity::MergePullRequest => UserActivityKind::MergePullRequest,
+            UserActivityKindEntity::ClosePullRequest => UserActivityKind::ClosePullRequest,
+            UserActivityKindEntity::CommentPullRequest => UserActivityKind::CommentPullRequest,
+            UserActivityKindEntity::Star => UserActivityKind::Star,
+            UserActivityKindEntity::Fork => UserActivityKind::Fork,
+        }
+    }
+}
+
+impl From<UserActivityEntity> for UserActivity {
+    fn from(activity: UserActivityEntity) -> Self {
+        Self {
+            kind: activity.kind.into(),
+            number: activity.number,
+            summary: activity.summary,
+            published: activity.published,
+            actor_name: activity.actor_name,
+            actor_domain: activity.actor_domain,
+            actor_is_local: activity.actor_is_local,
+            repository_owner: activity.repository_owner,
+            repository_name: activity.repository_name,
+            repository_domain: activity.repository_domain,
+            repository_is_local: activity.repository_is_local,
+        }
+    }
+}
+
+impl UserActivity {
+    /// Append an entry to the activity log. The log only feeds dashboards,
+    /// a failure is reported but never fails the action being logged.
+    pub async fn record(
+        actor_id: i32,
+        repository_id: i32,
+        kind: UserActivityKind,
+        number: Option<i32>,
+        summary: Option<String>,
+        db: &PgPool,
+    ) {
+        let activity = CreateUserActivity {
+            actor_id,
+            repository_id,
+            kind: kind.into(),
+            number,
+            summary,
+        };
+
+        if let Err(err) = activity.insert(db).await {
+            error!("Failed to record {kind:?} activity of user {actor_id}: {err}");
+        }
+    }
+
+    /// Activities of the users `user` follows and of the repositories they watch, newest first
+    pub async fn dashboard(
+        user: &User,
+        limit: i64,
+        offset: i64,
+        db: &PgPool,
+    ) -> AppResult<Vec<UserActivity>> {
+        let activities = UserActivityEntity::dashboard(user.id, limit, offset, db)
+            .await?
+            .into_iter()
+            .map(UserActivity::from)
+            .collect();
+
+        Ok(activities)
+    }
+}
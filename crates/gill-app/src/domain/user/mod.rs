@@ -1,7 +1,8 @@
 use crate::apub::common::GillApubObject;
-use crate::apub::repository::star::Star;
-use crate::apub::repository::watch::Watch;
-use crate::apub::user::follow::Follow;
+use crate::apub::repository::star::{Star, UndoStar};
+use crate::apub::repository::watch::{UndoWatch, Watch};
+use crate::apub::user::follow::{Follow, UndoFollow};
+use crate::domain::activity::Activity as OutboxActivity;
 use crate::domain::id::ActivityPubId;
 use crate::domain::repository::digest::RepositoryDigest;
 use crate::domain::repository::Repository;
@@ -16,6 +17,7 @@ use std::str::FromStr;
 use url::{ParseError, Url};
 use uuid::Uuid;
 
+pub mod activity;
 pub mod create;
 pub mod ssh_key;
 
@@ -108,6 +110,20 @@ impl User {
         User::try_from(entity).map_err(Into::into)
     }
 
+    /// The user mentioned as `@username` or `@username@domain`, if known to this instance
+    pub async fn by_mention(
+        username: &str,
+        domain: Option<&str>,
+        db: &PgPool,
+    ) -> AppResult<Option<User>> {
+        let domain = domain.unwrap_or(&SETTINGS.domain);
+        match UserEntity::by_user_name_and_domain(username, domain, db).await {
+            Ok(entity) => Ok(Some(User::try_from(entity)?)),
+            Err(sqlx::Error::RowNotFound) => Ok(None),
+            Err(err) => Err(AppError::from(err)),
+        }
+    }
+
     pub async fn add_follower(&self, follower_id: i32, db: &PgPool) -> AppResult<()> {
         let entity: UserEntity = self.into();
         entity.add_follower(follower_id, db).await?;
@@ -121,7 +137,23 @@ impl User {
         Ok(ObjectId::new(url))
     }
 
+    /// Admins are local users listed in the instance settings
+    pub fn is_admin(&self) -> bool {
+        self.is_local && SETTINGS.is_admin(&self.username)
+    }
+
+    /// The following collection url is not stored, it lives next to the user activity pub id
+    pub fn following_url(&self) -> AppResult<Url> {
+        let activity_pub_id = self.activity_pub_id.to_string();
+        Ok(Url::parse(&format!("{activity_pub_id}/following"))?)
+    }
+
     pub async fn follow(&self, other: &User, instance: &InstanceHandle) -> AppResult<()> {
+        other.add_follower(self.id, instance.database()).await?;
+        if other.is_local {
+            return Ok(());
+        }
+
         let follower = self.activity_pub_id.clone().into();
         let following = other.activity_pub_id.clone().into();
         let hostname = instance.local_instance().hostname();
@@ -134,12 +166,8 @@ impl User {
             other.shared_inbox_or_inbox()
         );
 
-        self.send(
-            follow,
-            vec![other.shared_inbox_or_inbox()],
-            instance.local_instance(),
-        )
-        .await?;
+        self.send(follow, vec![other.shared_inbox_or_inbox()], instance)
+            .await?;
         Ok(())
     }
 
@@ -160,12 +188,8 @@ impl User {
             other.shared_inbox_or_inbox()
         );
 
-        self.send(
-            watch,
-            vec![other.shared_inbox_or_inbox()],
-            instance.local_instance(),
-        )
-        .await?;
+        self.send(watch, vec![other.shared_inbox_or_inbox()], instance)
+            .await?;
         Ok(())
     }
 
@@ -185,15 +209,117 @@ impl User {
             other.shared_inbox_or_inbox()
         );
 
-        self.send(
-            star,
-            vec![other.shared_inbox_or_inbox()],
-            instance.local_instance(),
-        )
-        .await?;
+        self.send(star, vec![other.shared_inbox_or_inbox()], instance)
+            .await?;
         Ok(())
     }
 
+    pub async fn unfollow(&self, other: &User, instance: &InstanceHandle) -> AppResult<()> {
+        other.remove_follower(self.id, instance.database()).await?;
+        if other.is_local {
+            return Ok(());
+        }
+
+        let hostname = instance.local_instance().hostname();
+        let actor: Url = self.activity_pub_id.clone().into();
+        let followed: Url = other.activity_pub_id.clone().into();
+        let db = instance.database();
+        let follow =
+            match OutboxActivity::last_sent(&actor, "Follow", "followed", &followed, db).await? {
+                Some(follow) => follow,
+                // Follows sent before activities were recorded can't be found
+                None => {
+                    let follow_id =
+                        format!("https://{hostname}/activity/{uuid}", uuid = Uuid::new_v4());
+                    Follow::new(
+                        ObjectId::new(actor),
+                        ObjectId::new(followed),
+                        Url::parse(&follow_id)?,
+                    )
+                }
+            };
+
+        let activity_id = format!("https://{hostname}/activity/{uuid}", uuid = Uuid::new_v4());
+        let undo = UndoFollow::new(follow, Url::parse(&activity_id)?);
+        self.send(undo, vec![other.shared_inbox_or_inbox()], instance)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn unwatch_repository(
+        &self,
+        other: &Repository,
+        instance: &InstanceHandle,
+    ) -> AppResult<()> {
+        let hostname = instance.local_instance().hostname();
+        let actor: Url = self.activity_pub_id.clone().into();
+        let repository: Url = other.activity_pub_id.clone().into();
+        let db = instance.database();
+        let watch = match OutboxActivity::last_sent(&actor, "Follow", "repository", &repository, db)
+            .await?
+        {
+            Some(watch) => watch,
+            None => {
+                let watch_id = format!("https://{hostname}/activity/{uuid}", uuid = Uuid::new_v4());
+                Watch::new(
+                    ObjectId::new(actor),
+                    ObjectId::new(repository),
+                    Url::parse(&watch_id)?,
+                )
+            }
+        };
+
+        let activity_id = format!("https://{hostname}/activity/{uuid}", uuid = Uuid::new_v4());
+        let undo = UndoWatch::new(watch, Url::parse(&activity_id)?);
+        self.send(undo, vec![other.shared_inbox_or_inbox()], instance)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn unstar_repository(
+        &self,
+        other: &Repository,
+        instance: &InstanceHandle,
+    ) -> AppResult<()> {
+        let hostname = instance.local_instance().hostname();
+        let actor: Url = self.activity_pub_id.clone().into();
+        let repository: Url = other.activity_pub_id.clone().into();
+        let db = instance.database();
+        let star = match OutboxActivity::last_sent(&actor, "Like", "repository", &repository, db)
+            .await?
+        {
+            Some(star) => star,
+            None => {
+                let star_id = format!("https://{hostname}/activity/{uuid}", uuid = Uuid::new_v4());
+                Star::new(
+                    ObjectId::new(actor),
+                    ObjectId::new(repository),
+                    Url::parse(&star_id)?,
+                )
+            }
+        };
+
+        let activity_id = format!("https://{hostname}/activity/{uuid}", uuid = Uuid::new_v4());
+        let undo = UndoStar::new(star, Url::parse(&activity_id)?);
+        self.send(undo, vec![other.shared_inbox_or_inbox()], instance)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn remove_follower(&self, follower_id: i32, db: &PgPool) -> AppResult<()> {
+        let entity: UserEntity = self.into();
+        entity.remove_follower(follower_id, db).await?;
+        Ok(())
+    }
+
+    pub async fn is_followed_by(&self, follower: &User, db: &PgPool) -> AppResult<bool> {
+        let entity: UserEntity = self.into();
+        entity
+            .is_followed_by(follower.id, db)
+            .await
+            .map_err(Into::into)
+    }
+
     pub async fn add_ssh_key(
         &self,
         key_name: &str,
@@ -225,6 +351,29 @@ impl User {
         Ok(followers)
     }
 
+    pub async fn count_followers(&self, db: &PgPool) -> AppResult<i64> {
+        let entity: UserEntity = self.into();
+        entity.count_followers(db).await.map_err(Into::into)
+    }
+
+    /// Users followed and repositories watched by this user
+    pub async fn get_following(&self, limit: i64, offset: i64, db: &PgPool) -> AppResult<Vec<Url>> {
+        let entity: UserEntity = self.into();
+        let following = entity.get_following_ids(limit, offset, db).await?;
+        let following = following
+            .iter()
+            .map(|id| Url::parse(id))
+            .filter_map(Result::ok)
+            .collect();
+
+        Ok(following)
+    }
+
+    pub async fn count_following(&self, db: &PgPool) -> AppResult<i64> {
+        let entity: UserEntity = self.into();
+        entity.count_following(db).await.map_err(Into::into)
+    }
+
     pub async fn list_repositories(
         &self,
         limit: i64,
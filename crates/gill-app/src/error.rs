@@ -9,6 +9,8 @@ pub enum AppError {
     Internal(anyhow::Error),
     Unauthorized,
     NotFound,
+    /// The request conflicts with the current state of the resource, e.g. closing a blocked issue
+    Conflict(String),
 }
 
 impl<T> From<T> for AppError
@@ -30,6 +32,7 @@ impl IntoResponse for AppError {
             }
             AppError::Unauthorized => (StatusCode::UNAUTHORIZED, "UNAUTHORIZED").into_response(),
             AppError::NotFound => (StatusCode::NOT_FOUND, "NOT_FOUND").into_response(),
+            AppError::Conflict(reason) => (StatusCode::CONFLICT, reason).into_response(),
         }
     }
 }
@@ -1,3 +1,10 @@
+use crate::apub::delivery::run_delivery_worker;
+use crate::domain::federation::FederationPolicy;
+use crate::domain::notification::digest::run_digest_worker;
+use crate::domain::notification::mailer::Mailer;
+use crate::domain::repository::import::run_import_worker;
+use crate::domain::repository::mirror::run_mirror_worker;
+use crate::domain::repository::push::run_push_worker;
 use crate::error::AppResult;
 use crate::oauth::oauth_client;
 use crate::{api, apub, view};
@@ -14,7 +21,7 @@ use http::StatusCode;
 use reqwest::Client;
 use sqlx::PgPool;
 use std::io;
-use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, TcpListener};
 use std::sync::Arc;
 
 use tower_http::services::ServeDir;
@@ -33,18 +40,22 @@ pub struct Instance {
     db: PgPool,
 }
 
-/// Use this to store your federation blocklist, or a database connection needed to retrieve it.
+/// Check every remote url we fetch or receive activities from against the federation policy
 #[derive(Clone)]
-struct MyUrlVerifier();
+struct FederationUrlVerifier {
+    db: PgPool,
+}
 
 #[async_trait]
-impl UrlVerifier for MyUrlVerifier {
-    // TODO: check against known instance
+impl UrlVerifier for FederationUrlVerifier {
     async fn verify(&self, url: &Url) -> Result<(), &'static str> {
-        if url.domain() == Some("malicious.com") {
-            Err("malicious domain")
-        } else {
-            Ok(())
+        match FederationPolicy::is_allowed(url, &self.db).await {
+            Ok(true) => Ok(()),
+            Ok(false) => Err("domain is not allowed to federate with this instance"),
+            Err(err) => {
+                tracing::error!("Failed to check federation policy for {url}: {err:?}");
+                Err("unable to check federation policy")
+            }
         }
     }
 }
@@ -53,7 +64,7 @@ impl Instance {
     pub fn new(hostname: String, db: PgPool) -> AppResult<InstanceHandle> {
         let settings = InstanceSettings::builder()
             .debug(true)
-            .url_verifier(Box::new(MyUrlVerifier()))
+            .url_verifier(Box::new(FederationUrlVerifier { db: db.clone() }))
             .build()?;
 
         let local_instance = LocalInstance::new(hostname, Client::default().into(), settings);
@@ -72,10 +83,24 @@ impl Instance {
     }
 
     pub async fn listen(instance: &InstanceHandle) -> anyhow::Result<()> {
+        let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(0, 0, 0, 0)), SETTINGS.port);
+        let listener = TcpListener::bind(addr)?;
+        Instance::serve(instance, listener).await
+    }
+
+    /// Serve the instance on an already bound listener and run the background workers
+    /// until the server stops.
+    pub async fn serve(instance: &InstanceHandle, listener: TcpListener) -> anyhow::Result<()> {
         let instance = instance.clone();
         let store = MemoryStore::new();
         let oauth_client = oauth_client();
         let db = instance.db.clone();
+        let mirror_db = db.clone();
+        let import_db = db.clone();
+        let digest_db = db.clone();
+        let push_instance = instance.clone();
+        let mailer = Mailer::from_settings()
+            .map_err(|err| anyhow::anyhow!("Invalid SMTP settings: {err:?}"))?;
         let app_state = AppState {
             store,
             oauth_client,
@@ -93,16 +118,22 @@ impl Instance {
                 get(crate::webfinger::webfinger).with_state(app_state.clone()),
             )
             .nest("/api/v1/", api::router(app_state.clone()))
-            .merge(apub::router(instance))
+            .merge(apub::router(instance.clone()))
             .merge(view::router(app_state.clone()))
             .layer(TraceLayer::new_for_http())
             .layer(Extension(db))
             .into_make_service();
 
-        let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(0, 0, 0, 0)), SETTINGS.port);
-        let app = axum::Server::bind(&addr).serve(app);
+        let app = axum::Server::from_tcp(listener)?.serve(app);
 
-        let _ = tokio::join!(app);
+        let _ = tokio::join!(
+            app,
+            run_delivery_worker(instance),
+            run_mirror_worker(mirror_db),
+            run_import_worker(import_db),
+            run_digest_worker(digest_db, mailer),
+            run_push_worker(push_instance)
+        );
 
         Ok(())
     }
@@ -0,0 +1,131 @@
+use crate::domain::delivery::{Delivery, DeliveryState, FailingDomain};
+use crate::error::{AppError, AppResult};
+use crate::get_connected_user;
+use crate::oauth::Oauth2User;
+use crate::view::HtmlTemplate;
+use askama::Template;
+use axum::response::Redirect;
+use axum::{Extension, Form};
+use gill_authorize_derive::authorized;
+use serde::Deserialize;
+use sqlx::PgPool;
+
+const DELIVERIES_PER_PAGE: i64 = 50;
+
+#[derive(Template, Debug)]
+#[template(path = "admin/deliveries.html")]
+pub struct DeliveriesTemplate {
+    user: Option<String>,
+    pending_count: i64,
+    failed_count: i64,
+    pending: Vec<DeliveryDto>,
+    failed: Vec<DeliveryDto>,
+    domains: Vec<FailingDomainDto>,
+}
+
+#[derive(Debug)]
+pub struct DeliveryDto {
+    id: i32,
+    activity_id: String,
+    kind: String,
+    sender: String,
+    inbox: String,
+    attempts: i32,
+    last_error: String,
+    next_attempt_at: String,
+    created_at: String,
+}
+
+#[derive(Debug)]
+pub struct FailingDomainDto {
+    domain: String,
+    consecutive_failures: i32,
+    open_until: String,
+}
+
+impl From<Delivery> for DeliveryDto {
+    fn from(delivery: Delivery) -> Self {
+        let kind = delivery
+            .payload
+            .get("type")
+            .and_then(serde_json::Value::as_str)
+            .unwrap_or_default()
+            .to_string();
+
+        Self {
+            id: delivery.id,
+            activity_id: delivery.activity_id,
+            kind,
+            sender: delivery.sender.to_string(),
+            inbox: delivery.inbox.to_string(),
+            attempts: delivery.attempts,
+            last_error: delivery.last_error.unwrap_or_default(),
+            next_attempt_at: delivery
+                .next_attempt_at
+                .format("%Y-%m-%d %H:%M")
+                .to_string(),
+            created_at: delivery.created_at.format("%Y-%m-%d %H:%M").to_string(),
+        }
+    }
+}
+
+impl From<FailingDomain> for FailingDomainDto {
+    fn from(domain: FailingDomain) -> Self {
+        Self {
+            domain: domain.domain,
+            consecutive_failures: domain.consecutive_failures,
+            open_until: domain
+                .open_until
+                .map(|open_until| open_until.format("%Y-%m-%d %H:%M").to_string())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct RetryForm {
+    pub id: i32,
+}
+
+#[authorized]
+pub async fn view(
+    user: Option<Oauth2User>,
+    Extension(db): Extension<PgPool>,
+) -> AppResult<HtmlTemplate<DeliveriesTemplate>> {
+    let pending_count = Delivery::count(&user, DeliveryState::Pending, &db).await?;
+    let failed_count = Delivery::count(&user, DeliveryState::Failed, &db).await?;
+    let pending = Delivery::list(&user, DeliveryState::Pending, DELIVERIES_PER_PAGE, 0, &db)
+        .await?
+        .into_iter()
+        .map(DeliveryDto::from)
+        .collect();
+    let failed = Delivery::list(&user, DeliveryState::Failed, DELIVERIES_PER_PAGE, 0, &db)
+        .await?
+        .into_iter()
+        .map(DeliveryDto::from)
+        .collect();
+    let domains = FailingDomain::list(&user, &db)
+        .await?
+        .into_iter()
+        .map(FailingDomainDto::from)
+        .collect();
+
+    Ok(HtmlTemplate(DeliveriesTemplate {
+        user: Some(user.username),
+        pending_count,
+        failed_count,
+        pending,
+        failed,
+        domains,
+    }))
+}
+
+#[authorized]
+pub async fn retry(
+    user: Option<Oauth2User>,
+    Extension(db): Extension<PgPool>,
+    Form(input): Form<RetryForm>,
+) -> AppResult<Redirect> {
+    Delivery::retry(&user, input.id, &db).await?;
+    Ok(Redirect::to("/admin/deliveries"))
+}
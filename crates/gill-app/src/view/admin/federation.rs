@@ -0,0 +1,131 @@
+use crate::domain::federation::{DomainPolicy, FederationMode, FederationPolicy, KnownInstance};
+use crate::error::{AppError, AppResult};
+use crate::get_connected_user;
+use crate::oauth::Oauth2User;
+use crate::view::HtmlTemplate;
+use askama::Template;
+use axum::response::Redirect;
+use axum::{Extension, Form};
+use gill_authorize_derive::authorized;
+use serde::Deserialize;
+use sqlx::PgPool;
+
+#[derive(Template, Debug)]
+#[template(path = "admin/federation.html")]
+pub struct FederationTemplate {
+    user: Option<String>,
+    allowlist_mode: bool,
+    instances: Vec<KnownInstanceDto>,
+}
+
+#[derive(Debug)]
+pub struct KnownInstanceDto {
+    domain: String,
+    user_count: i64,
+    repository_count: i64,
+    policy: &'static str,
+    reason: String,
+}
+
+impl From<KnownInstance> for KnownInstanceDto {
+    fn from(instance: KnownInstance) -> Self {
+        let policy = match instance.policy {
+            None => "",
+            Some(DomainPolicy::Allowed) => "allowed",
+            Some(DomainPolicy::Blocked) => "blocked",
+            Some(DomainPolicy::Silenced) => "silenced",
+        };
+
+        Self {
+            domain: instance.domain,
+            user_count: instance.user_count,
+            repository_count: instance.repository_count,
+            policy,
+            reason: instance.reason.unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct FederationModeForm {
+    pub mode: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct DomainPolicyForm {
+    pub domain: String,
+    pub policy: String,
+    pub reason: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct DomainForm {
+    pub domain: String,
+}
+
+#[authorized]
+pub async fn view(
+    user: Option<Oauth2User>,
+    Extension(db): Extension<PgPool>,
+) -> AppResult<HtmlTemplate<FederationTemplate>> {
+    if !user.is_admin() {
+        return Err(AppError::Unauthorized);
+    }
+
+    let mode = FederationPolicy::mode(&db).await?;
+    let instances = FederationPolicy::known_instances(&db)
+        .await?
+        .into_iter()
+        .map(KnownInstanceDto::from)
+        .collect();
+
+    Ok(HtmlTemplate(FederationTemplate {
+        user: Some(user.username),
+        allowlist_mode: mode == FederationMode::Allowlist,
+        instances,
+    }))
+}
+
+#[authorized]
+pub async fn set_mode(
+    user: Option<Oauth2User>,
+    Extension(db): Extension<PgPool>,
+    Form(input): Form<FederationModeForm>,
+) -> AppResult<Redirect> {
+    let mode = match input.mode.as_str() {
+        "allowlist" => FederationMode::Allowlist,
+        "blocklist" => FederationMode::Blocklist,
+        mode => return Err(anyhow::anyhow!("Unknown federation mode '{mode}'").into()),
+    };
+
+    FederationPolicy::set_mode(&user, mode, &db).await?;
+    Ok(Redirect::to("/admin/federation"))
+}
+
+#[authorized]
+pub async fn set_domain_policy(
+    user: Option<Oauth2User>,
+    Extension(db): Extension<PgPool>,
+    Form(input): Form<DomainPolicyForm>,
+) -> AppResult<Redirect> {
+    let policy = match input.policy.as_str() {
+        "allowed" => DomainPolicy::Allowed,
+        "blocked" => DomainPolicy::Blocked,
+        "silenced" => DomainPolicy::Silenced,
+        policy => return Err(anyhow::anyhow!("Unknown domain policy '{policy}'").into()),
+    };
+
+    let reason = input.reason.as_deref().filter(|reason| !reason.is_empty());
+    FederationPolicy::set_domain_policy(&user, &input.domain, policy, reason, &db).await?;
+    Ok(Redirect::to("/admin/federation"))
+}
+
+#[authorized]
+pub async fn remove_domain_policy(
+    user: Option<Oauth2User>,
+    Extension(db): Extension<PgPool>,
+    Form(input): Form<DomainForm>,
+) -> AppResult<Redirect> {
+    FederationPolicy::remove_domain_policy(&user, &input.domain, &db).await?;
+    Ok(Redirect::to("/admin/federation"))
+}
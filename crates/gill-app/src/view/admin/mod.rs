@@ -0,0 +1,22 @@
+use crate::state::AppState;
+use axum::routing::get;
+use axum::Router;
+
+pub mod deliveries;
+pub mod federation;
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/admin/federation", get(federation::view))
+        .route("/admin/federation/mode", get(federation::set_mode))
+        .route(
+            "/admin/federation/domain",
+            get(federation::set_domain_policy),
+        )
+        .route(
+            "/admin/federation/domain/remove",
+            get(federation::remove_domain_policy),
+        )
+        .route("/admin/deliveries", get(deliveries::view))
+        .route("/admin/deliveries/retry", get(deliveries::retry))
+}
@@ -1,5 +1,17 @@
+use crate::domain;
+
 // This filter does not have extra arguments
 pub fn sha_digest<T: std::fmt::Display>(s: T) -> askama::Result<String> {
     let s = s.to_string();
     Ok(s[0..7].to_string())
 }
+
+/// Render stored markdown content, the output is sanitized and safe to inline
+pub fn render_markdown(content: &str, owner: &str, repository: &str) -> askama::Result<String> {
+    Ok(domain::markdown::render(content, owner, repository))
+}
+
+/// Stored markdown content as typed by its author, e.g. to fill an edit form
+pub fn unescape(content: &str) -> askama::Result<String> {
+    Ok(domain::markdown::unescape(content))
+}
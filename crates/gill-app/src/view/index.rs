@@ -1,16 +1,114 @@
 use crate::oauth::Oauth2User;
 use crate::view::HtmlTemplate;
 use askama::Template;
+use axum::extract::Query;
 use axum::response::IntoResponse;
 use axum::Extension;
 
 use crate::domain::repository::digest::RepositoryDigest;
+use crate::domain::user::activity::{UserActivity, UserActivityKind};
 use crate::error::AppResult;
-use crate::get_connected_user_username;
+use crate::get_connected_user;
 use crate::view::dto::{FederatedRepositoryDto, RepositoryDto};
+use serde::Deserialize;
 use sqlx::PgPool;
 
-pub struct ActivityDto {}
+const ACTIVITY_PAGE_SIZE: i64 = 20;
+
+#[derive(Deserialize)]
+pub struct IndexQuery {
+    #[serde(default)]
+    page: i64,
+}
+
+pub struct ActivityDto {
+    pub actor: String,
+    pub actor_url: String,
+    pub action: String,
+    pub repository: String,
+    pub repository_url: String,
+    /// Branch name, issue or pull request title
+    pub target: Option<String>,
+    pub target_url: Option<String>,
+    pub published: String,
+}
+
+impl From<UserActivity> for ActivityDto {
+    fn from(activity: UserActivity) -> Self {
+        let actor = if activity.actor_is_local {
+            activity.actor_name.clone()
+        } else {
+            format!("{}@{}", activity.actor_name, activity.actor_domain)
+        };
+
+        let repository_path = format!(
+            "/{}/{}",
+            activity.repository_owner, activity.repository_name
+        );
+        let repository = if activity.repository_is_local {
+            format!("{}/{}", activity.repository_owner, activity.repository_name)
+        } else {
+            format!(
+                "{}/{}@{}",
+                activity.repository_owner, activity.repository_name, activity.repository_domain
+            )
+        };
+
+        let action = match activity.kind {
+            UserActivityKind::Push => "pushed to",
+            UserActivityKind::OpenIssue => "opened an issue on",
+            UserActivityKind::CloseIssue => "closed an issue on",
+            UserActivityKind::ReopenIssue => "reopened an issue on",
+            UserActivityKind::CommentIssue => "commented an issue on",
+            UserActivityKind::OpenPullRequest => "opened a pull request on",
+            UserActivityKind::MergePullRequest => "merged a pull request on",
+            UserActivityKind::ClosePullRequest => "closed a pull request on",
+            UserActivityKind::CommentPullRequest => "commented a pull request on",
+            UserActivityKind::Star => "starred",
+            UserActivityKind::Fork => "forked",
+        }
+        .to_string();
+
+        let target_url = match (activity.kind, activity.number) {
+            (UserActivityKind::Push, _) => activity
+                .summary
+                .as_ref()
+                .map(|branch| format!("{repository_path}/tree/{branch}")),
+            (
+                UserActivityKind::OpenIssue
+                | UserActivityKind::CloseIssue
+                | UserActivityKind::ReopenIssue
+                | UserActivityKind::CommentIssue,
+                Some(number),
+            ) => Some(format!("{repository_path}/issues/{number}")),
+            (
+                UserActivityKind::OpenPullRequest
+                | UserActivityKind::MergePullRequest
+                | UserActivityKind::ClosePullRequest
+                | UserActivityKind::CommentPullRequest,
+                Some(number),
+            ) => Some(format!("{repository_path}/pulls/{number}")),
+            _ => None,
+        };
+
+        let target = match (activity.number, activity.summary) {
+            (Some(number), Some(summary)) => Some(format!("#{number} {summary}")),
+            (Some(number), None) => Some(format!("#{number}")),
+            (None, summary) => summary,
+        };
+
+        Self {
+            actor_url: format!("/{}", activity.actor_name),
+            actor,
+            action,
+            repository,
+            repository_url: repository_path,
+            target,
+            target_url,
+            published: activity.published.format("%Y-%m-%d %H:%M").to_string(),
+        }
+    }
+}
 
 #[derive(Template)]
 #[template(path = "index.html")]
@@ -18,13 +116,17 @@ struct LandingPageTemplate {
     user: Option<String>,
     local_repositories: Vec<RepositoryDto>,
     federated_repositories: Vec<FederatedRepositoryDto>,
+    activities: Vec<ActivityDto>,
+    page: i64,
+    has_next_page: bool,
 }
 
 pub async fn index(
     Extension(db): Extension<PgPool>,
     user: Option<Oauth2User>,
+    Query(query): Query<IndexQuery>,
 ) -> AppResult<impl IntoResponse> {
-    let username = get_connected_user_username(&db, user).await;
+    let user = get_connected_user(&db, user).await;
     let local_repositories = RepositoryDigest::all_local(10, 0, &db).await?;
     let local_repositories = local_repositories
         .into_iter()
@@ -37,11 +139,82 @@ pub async fn index(
         .map(FederatedRepositoryDto::from)
         .collect();
 
+    let page = query.page.max(0);
+    let (activities, has_next_page) = match &user {
+        Some(user) => {
+            // Fetch one more entry to know whether there is a next page
+            let mut activities = UserActivity::dashboard(
+                user,
+                ACTIVITY_PAGE_SIZE + 1,
+                page * ACTIVITY_PAGE_SIZE,
+                &db,
+            )
+            .await?;
+            let has_next_page = activities.len() as i64 > ACTIVITY_PAGE_SIZE;
+            activities.truncate(ACTIVITY_PAGE_SIZE as usize);
+            let activities = activities.into_iter().map(ActivityDto::from).collect();
+            (activities, has_next_page)
+        }
+        None => (vec![], false),
+    };
+
     let template = LandingPageTemplate {
-        user: username,
+        user: user.map(|user| user.username),
         local_repositories,
         federated_repositories,
+        activities,
+        page,
+        has_next_page,
     };
 
     Ok(HtmlTemplate(template))
 }
+
+#[cfg(test)]
+mod test {
+    use crate::domain::user::activity::{UserActivity, UserActivityKind};
+    use crate::view::index::ActivityDto;
+    use speculoos::prelude::*;
+
+    fn activity(
+        kind: UserActivityKind,
+        number: Option<i32>,
+        summary: Option<&str>,
+    ) -> UserActivity {
+        UserActivity {
+            kind,
+            number,
+            summary: summary.map(ToString::to_string),
+            published: chrono::NaiveDateTime::default(),
+            actor_name: "bob".to_string(),
+            actor_domain: "remote.example.org".to_string(),
+            actor_is_local: false,
+            repository_owner: "alice".to_string(),
+            repository_name: "gill".to_string(),
+            repository_domain: "gill.example.org".to_string(),
+            repository_is_local: true,
+        }
+    }
+
+    #[test]
+    fn should_link_issue_activities_to_the_issue() {
+        let dto = ActivityDto::from(activity(
+            UserActivityKind::CommentIssue,
+            Some(3),
+            Some("Crash on push"),
+        ));
+
+        assert_that!(dto.actor).is_equal_to("bob@remote.example.org".to_string());
+        assert_that!(dto.repository).is_equal_to("alice/gill".to_string());
+        assert_that!(dto.target).is_equal_to(Some("#3 Crash on push".to_string()));
+        assert_that!(dto.target_url).is_equal_to(Some("/alice/gill/issues/3".to_string()));
+    }
+
+    #[test]
+    fn should_link_pushes_to_the_branch() {
+        let dto = ActivityDto::from(activity(UserActivityKind::Push, None, Some("main")));
+
+        assert_that!(dto.target).is_equal_to(Some("main".to_string()));
+        assert_that!(dto.target_url).is_equal_to(Some("/alice/gill/tree/main".to_string()));
+    }
+}
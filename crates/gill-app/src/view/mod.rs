@@ -9,11 +9,13 @@ use axum::response::{Html, IntoResponse};
 use axum::routing::get;
 use axum::Router;
 
+pub mod admin;
 pub mod component;
 pub mod dto;
 mod filters;
 pub mod follow;
 pub mod index;
+pub mod notification;
 pub mod repository;
 pub mod user;
 
@@ -31,6 +33,8 @@ pub fn router(app_state: AppState) -> Router {
     Router::new()
         .merge(repository::routes())
         .merge(user::routes())
+        .merge(admin::routes())
+        .merge(notification::routes())
         .route("/", get(index::index))
         .route("/auth/gill/", get(oauth::openid_auth))
         .route("/auth/gill", get(oauth::openid_auth))
@@ -0,0 +1,196 @@
+use crate::domain::notification::{
+    Notification, NotificationLevel, NotificationReason, NotificationSettings, NotificationSubject,
+};
+use crate::error::{AppError, AppResult};
+use crate::get_connected_user;
+use crate::oauth::Oauth2User;
+use crate::state::AppState;
+use crate::view::HtmlTemplate;
+use askama::Template;
+use axum::extract::{Path, Query};
+use axum::response::Redirect;
+use axum::routing::get;
+use axum::{Extension, Form, Json, Router};
+use gill_authorize_derive::authorized;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+
+const NOTIFICATION_PAGE_SIZE: i64 = 30;
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/notifications", get(list))
+        .route("/notifications/unread-count", get(unread_count))
+        .route("/notifications/read-all", get(read_all))
+        .route("/notifications/settings", get(save_settings))
+        .route("/notifications/:id", get(open))
+}
+
+#[derive(Deserialize)]
+pub struct NotificationQuery {
+    #[serde(default)]
+    page: i64,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct NotificationSettingsForm {
+    pub level: String,
+    /// Checkbox, only sent when checked
+    pub email_digest: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct UnreadCount {
+    count: i64,
+}
+
+#[derive(Debug)]
+pub struct NotificationDto {
+    id: i32,
+    actor: String,
+    action: String,
+    repository: String,
+    title: String,
+    number: i32,
+    icon: &'static str,
+    reason: &'static str,
+    read: bool,
+    created_at: String,
+}
+
+impl From<Notification> for NotificationDto {
+    fn from(notification: Notification) -> Self {
+        let actor = if notification.actor_is_local {
+            notification.actor_name
+        } else {
+            format!("{}@{}", notification.actor_name, notification.actor_domain)
+        };
+
+        let icon = match notification.subject {
+            NotificationSubject::Issue => "ti-circle-dot",
+            NotificationSubject::PullRequest => "ti-git-pull-request",
+        };
+
+        let reason = match notification.reason {
+            NotificationReason::Watching => "watching",
+            NotificationReason::Participating => "participating",
+            NotificationReason::Mention => "mention",
+        };
+
+        Self {
+            id: notification.id,
+            actor,
+            action: notification.action,
+            repository: format!(
+                "{}/{}",
+                notification.repository_owner, notification.repository_name
+            ),
+            title: notification.title,
+            number: notification.number,
+            icon,
+            reason,
+            read: notification.read,
+            created_at: notification.created_at.format("%Y-%m-%d %H:%M").to_string(),
+        }
+    }
+}
+
+#[derive(Template, Debug)]
+#[template(path = "notifications.html")]
+pub struct NotificationsTemplate {
+    user: Option<String>,
+    notifications: Vec<NotificationDto>,
+    level: &'static str,
+    email_digest: bool,
+    page: i64,
+    has_next_page: bool,
+}
+
+#[authorized]
+pub async fn list(
+    user: Option<Oauth2User>,
+    Extension(db): Extension<PgPool>,
+    Query(query): Query<NotificationQuery>,
+) -> AppResult<HtmlTemplate<NotificationsTemplate>> {
+    let page = query.page.max(0);
+    let mut notifications = Notification::list(
+        &user,
+        NOTIFICATION_PAGE_SIZE + 1,
+        page * NOTIFICATION_PAGE_SIZE,
+        &db,
+    )
+    .await?;
+    let has_next_page = notifications.len() as i64 > NOTIFICATION_PAGE_SIZE;
+    notifications.truncate(NOTIFICATION_PAGE_SIZE as usize);
+
+    let settings = NotificationSettings::for_user(&user, &db).await?;
+    let level = match settings.level {
+        NotificationLevel::Watching => "watching",
+        NotificationLevel::Participating => "participating",
+        NotificationLevel::MentionsOnly => "mentions",
+    };
+
+    Ok(HtmlTemplate(NotificationsTemplate {
+        user: Some(user.username),
+        notifications: notifications
+            .into_iter()
+            .map(NotificationDto::from)
+            .collect(),
+        level,
+        email_digest: settings.email_digest,
+        page,
+        has_next_page,
+    }))
+}
+
+/// Mark the notification as read and go to its issue or pull request
+#[authorized]
+pub async fn open(
+    user: Option<Oauth2User>,
+    Extension(db): Extension<PgPool>,
+    Path(id): Path<i32>,
+) -> AppResult<Redirect> {
+    let notification = Notification::open(id, &user, &db).await?;
+    Ok(Redirect::to(&notification.url()))
+}
+
+#[authorized]
+pub async fn read_all(
+    user: Option<Oauth2User>,
+    Extension(db): Extension<PgPool>,
+) -> AppResult<Redirect> {
+    Notification::mark_all_read(&user, &db).await?;
+    Ok(Redirect::to("/notifications"))
+}
+
+/// Polled by the navbar to display the unread badge
+#[authorized]
+pub async fn unread_count(
+    user: Option<Oauth2User>,
+    Extension(db): Extension<PgPool>,
+) -> AppResult<Json<UnreadCount>> {
+    let count = Notification::unread_count(&user, &db).await?;
+    Ok(Json(UnreadCount { count }))
+}
+
+#[authorized]
+pub async fn save_settings(
+    user: Option<Oauth2User>,
+    Extension(db): Extension<PgPool>,
+    Form(input): Form<NotificationSettingsForm>,
+) -> AppResult<Redirect> {
+    let level = match input.level.as_str() {
+        "watching" => NotificationLevel::Watching,
+        "participating" => NotificationLevel::Participating,
+        "mentions" => NotificationLevel::MentionsOnly,
+        level => return Err(anyhow::anyhow!("Unknown notification level '{level}'").into()),
+    };
+
+    let settings = NotificationSettings {
+        level,
+        email_digest: input.email_digest.is_some(),
+    };
+
+    settings.save(&user, &db).await?;
+    Ok(Redirect::to("/notifications"))
+}
@@ -4,25 +4,40 @@ use crate::get_connected_user;
 use crate::oauth::Oauth2User;
 use crate::state::AppState;
 use axum::extract::{Path, State};
-use axum::response::{IntoResponse, Response};
-use axum::Extension;
+use axum::{Extension, Json};
 use gill_authorize_derive::authorized;
-use http::StatusCode;
+use serde::Serialize;
 use sqlx::PgPool;
 
+/// State of a star or watch toggle once the request went through
+#[derive(Serialize, Debug)]
+pub struct ToggleState {
+    active: bool,
+}
+
+impl ToggleState {
+    pub fn new(active: bool) -> Self {
+        Self { active }
+    }
+}
+
 #[authorized]
 pub async fn star(
     State(state): State<AppState>,
     user: Option<Oauth2User>,
     Extension(db): Extension<PgPool>,
     Path((owner, repository)): Path<(String, String)>,
-) -> AppResult<Response> {
-    Repository::by_namespace(&owner, &repository, &db)
-        .await?
-        .add_star(&user, &state.instance)
-        .await?;
+) -> AppResult<Json<ToggleState>> {
+    let repository = Repository::by_namespace(&owner, &repository, &db).await?;
+    let active = if repository.is_starred_by(&user, &db).await? {
+        repository.remove_star(&user, &state.instance).await?;
+        false
+    } else {
+        repository.add_star(&user, &state.instance).await?;
+        true
+    };
 
-    Ok(StatusCode::NO_CONTENT.into_response())
+    Ok(Json(ToggleState::new(active)))
 }
 
 #[authorized]
@@ -31,11 +46,15 @@ pub async fn watch(
     user: Option<Oauth2User>,
     Extension(db): Extension<PgPool>,
     Path((owner, repository)): Path<(String, String)>,
-) -> AppResult<Response> {
-    Repository::by_namespace(&owner, &repository, &db)
-        .await?
-        .add_watcher(&user, &state.instance)
-        .await?;
+) -> AppResult<Json<ToggleState>> {
+    let repository = Repository::by_namespace(&owner, &repository, &db).await?;
+    let active = if repository.is_watched_by(&user, &db).await? {
+        repository.remove_watcher(&user, &state.instance).await?;
+        false
+    } else {
+        repository.add_watcher(&user, &state.instance).await?;
+        true
+    };
 
-    Ok(StatusCode::NO_CONTENT.into_response())
+    Ok(Json(ToggleState::new(active)))
 }
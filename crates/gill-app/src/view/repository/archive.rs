@@ -0,0 +1,60 @@
+use crate::domain::repository::Repository;
+use crate::error::{AppError, AppResult};
+use crate::view::repository::attachment::content_disposition;
+use axum::body::{Bytes, StreamBody};
+use axum::extract::Path;
+use axum::http::header;
+use axum::response::{IntoResponse, Response};
+use axum::Extension;
+use gill_git::archive::ArchiveFormat;
+use sqlx::PgPool;
+use std::io::Read;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+
+const CHUNK_SIZE: usize = 64 * 1024;
+
+pub async fn archive(
+    Path((owner, repository, filename)): Path<(String, String, String)>,
+    Extension(db): Extension<PgPool>,
+) -> AppResult<Response> {
+    let filename = filename.trim_start_matches('/');
+    let (reference, format) = ArchiveFormat::split(filename).ok_or(AppError::NotFound)?;
+    let repo = Repository::open_git(&owner, &repository, &db).await?;
+    let mut archive = repo
+        .archive(reference, format)
+        .map_err(|_| AppError::NotFound)?;
+    let attachment = content_disposition(&format!(
+        "{repository}-{}.{}",
+        reference.replace('/', "-"),
+        format.extension()
+    ));
+
+    // Reading the archive blocks on git, forward it chunk by chunk from a blocking thread
+    let (sender, receiver) = mpsc::channel(4);
+    tokio::task::spawn_blocking(move || {
+        let mut buf = vec![0; CHUNK_SIZE];
+        loop {
+            let chunk = match archive.read(&mut buf) {
+                Ok(0) => break,
+                Ok(read) => Ok(Bytes::copy_from_slice(&buf[..read])),
+                Err(err) => Err(err),
+            };
+
+            let failed = chunk.is_err();
+            // The receiver is dropped when the client goes away
+            if sender.blocking_send(chunk).is_err() || failed {
+                break;
+            }
+        }
+    });
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, format.mime().to_string()),
+            (header::CONTENT_DISPOSITION, attachment),
+        ],
+        StreamBody::new(ReceiverStream::new(receiver)),
+    )
+        .into_response())
+}
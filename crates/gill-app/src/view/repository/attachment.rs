@@ -0,0 +1,56 @@
+/// `Content-Disposition` header downloading a file as `filename`.
+/// The quoted name is an ascii fallback for old clients, the exact name is percent encoded
+/// in `filename*` (RFC 6266), so neither can break out of the header value.
+pub fn content_disposition(filename: &str) -> String {
+    let fallback: String = filename
+        .chars()
+        .map(|c| match c {
+            '"' | '\\' => '_',
+            ' '..='~' => c,
+            _ => '_',
+        })
+        .collect();
+
+    let mut encoded = String::with_capacity(filename.len());
+    for byte in filename.bytes() {
+        match byte {
+            b'a'..=b'z'
+            | b'A'..=b'Z'
+            | b'0'..=b'9'
+            | b'!'
+            | b'#'
+            | b'$'
+            | b'&'
+            | b'+'
+            | b'-'
+            | b'.'
+            | b'^'
+            | b'_'
+            | b'`'
+            | b'|'
+            | b'~' => encoded.push(byte as char),
+            byte => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+
+    format!("attachment; filename=\"{fallback}\"; filename*=UTF-8''{encoded}")
+}
+
+#[cfg(test)]
+mod test {
+    use crate::view::repository::attachment::content_disposition;
+    use speculoos::prelude::*;
+
+    #[test]
+    fn should_encode_attachment_filename() {
+        assert_that!(content_disposition("gill-v1.0.tar.gz")).is_equal_to(
+            "attachment; filename=\"gill-v1.0.tar.gz\"; filename*=UTF-8''gill-v1.0.tar.gz"
+                .to_string(),
+        );
+
+        assert_that!(content_disposition("a\"b\r\nSet-Cookie: x;é.zip")).is_equal_to(
+            "attachment; filename=\"a_b__Set-Cookie: x;_.zip\"; filename*=UTF-8''a%22b%0D%0ASet-Cookie%3A%20x%3B%C3%A9.zip"
+                .to_string(),
+        );
+    }
+}
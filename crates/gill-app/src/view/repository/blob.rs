@@ -3,18 +3,21 @@ use crate::oauth::Oauth2User;
 use crate::view::repository::{get_repository_branches, tree_and_blob_from_query, BranchDto, Tab};
 use crate::view::HtmlTemplate;
 use askama::Template;
-use axum::extract::Path;
+use axum::extract::{Path, Query};
 use axum::Extension;
 use gill_syntax::highlight::highlight_blob;
+use serde::Deserialize;
 
 use crate::get_connected_user_username;
 
 use gill_git::traversal::BlobMime;
-use gill_git::GitRepository;
+use gill_markdown::Location;
 use sqlx::PgPool;
 use std::fmt::Formatter;
 
+use crate::domain::repository::docs::is_markdown;
 use crate::domain::repository::stats::RepositoryStats;
+use crate::domain::repository::Repository;
 use base64::engine::general_purpose;
 use base64::Engine;
 use std::fmt;
@@ -29,6 +32,8 @@ pub struct GitBLobTemplate {
     owner: String,
     stats: RepositoryStats,
     blob: BlobDto,
+    /// Markdown files can be displayed rendered or as source
+    is_markdown: bool,
     branches: Vec<BranchDto>,
     current_branch: Option<String>,
     user: Option<String>,
@@ -37,37 +42,59 @@ pub struct GitBLobTemplate {
 
 #[derive(Debug)]
 enum BlobDto {
+    Markdown(String),
     Highlighted { content: String, language: String },
     PlainText(String),
     Image(String),
     Binary { content: String, filename: String },
 }
 
+impl BlobDto {
+    fn is_rendered(&self) -> bool {
+        matches!(self, Markdown(_))
+    }
+}
+
 impl fmt::Display for BlobDto {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         writeln!(f, "{self:?}")
     }
 }
 
+#[derive(Deserialize, Debug)]
+pub struct BlobQuery {
+    /// Display the source of a markdown file instead of rendering it
+    #[serde(default)]
+    pub plain: bool,
+}
+
 pub async fn blob(
     user: Option<Oauth2User>,
     Path((owner, repository, current_branch)): Path<(String, String, String)>,
     Path(path): Path<Vec<String>>,
+    Query(query): Query<BlobQuery>,
     Extension(db): Extension<PgPool>,
 ) -> AppResult<HtmlTemplate<GitBLobTemplate>> {
     let connected_username = get_connected_user_username(&db, user).await;
     let path = path.last().unwrap();
-    let (tree, blob_name) = tree_and_blob_from_query(path);
+    let (directory, blob_name) = tree_and_blob_from_query(path);
 
-    let repo = GitRepository::open(&owner, &repository)?;
-    let tree = repo.get_tree_for_path(Some(&current_branch), tree)?;
+    let repo = Repository::open_git(&owner, &repository, &db).await?;
+    let tree = repo.get_tree_for_path(Some(&current_branch), directory)?;
     let blob = tree
         .blobs
         .iter()
         .find(|blob| blob.filename() == blob_name)
         .unwrap();
 
+    let is_markdown = is_markdown(blob_name);
     let blob = match repo.blob_mime(blob) {
+        BlobMime::Text if is_markdown && !query.plain => {
+            let blob = repo.blob_str(blob)?;
+            let directory = directory.unwrap_or_default();
+            let location = Location::file(&owner, &repository, &current_branch, directory);
+            Markdown(gill_markdown::render_at(&blob, location))
+        }
         BlobMime::Text => {
             let blob = repo.blob_str(blob)?;
             let language = get_blob_language(blob_name);
@@ -92,13 +119,15 @@ pub async fn blob(
     };
 
     let branches = get_repository_branches(&owner, &repository, &current_branch, &db).await?;
-    let stats = RepositoryStats::get(&owner, &repository, &db).await?;
+    let stats =
+        RepositoryStats::get(&owner, &repository, connected_username.as_deref(), &db).await?;
 
     let template = GitBLobTemplate {
         repository,
         owner,
         stats,
         blob,
+        is_markdown,
         branches,
         current_branch: Some(current_branch),
         user: connected_username,
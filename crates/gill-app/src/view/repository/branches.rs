@@ -0,0 +1,179 @@
+use crate::domain::repository::stats::RepositoryStats;
+use crate::domain::repository::Repository;
+use crate::error::{AppError, AppResult};
+use crate::oauth::Oauth2User;
+use crate::view::filters;
+use crate::view::repository::Tab;
+use crate::view::HtmlTemplate;
+use crate::{get_connected_user, get_connected_user_username};
+use askama::Template;
+use axum::extract::Path;
+use axum::response::Redirect;
+use axum::{Extension, Form};
+use chrono::NaiveDateTime;
+use gill_authorize_derive::authorized;
+use gill_git::GitRepository;
+use serde::Deserialize;
+use sqlx::PgPool;
+
+#[derive(Debug)]
+pub struct BranchInfoDto {
+    name: String,
+    is_default: bool,
+    ahead: usize,
+    behind: usize,
+    last_commit_sha: String,
+    last_commit_summary: String,
+    last_commit_date: String,
+}
+
+impl BranchInfoDto {
+    fn is_merged(&self) -> bool {
+        !self.is_default && self.ahead == 0
+    }
+}
+
+#[derive(Template, Debug)]
+#[template(path = "repository/branches.html")]
+pub struct BranchesTemplate {
+    user: Option<String>,
+    owner: String,
+    repository: String,
+    default_branch: String,
+    branches: Vec<BranchInfoDto>,
+    stats: RepositoryStats,
+    current_branch: Option<String>,
+    tab: Tab,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct CreateBranchForm {
+    pub name: String,
+    pub from: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct BranchForm {
+    pub name: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct RenameBranchForm {
+    pub name: String,
+    pub new_name: String,
+}
+
+pub async fn list(
+    user: Option<Oauth2User>,
+    Extension(db): Extension<PgPool>,
+    Path((owner, repository)): Path<(String, String)>,
+) -> AppResult<HtmlTemplate<BranchesTemplate>> {
+    let connected_username = get_connected_user_username(&db, user).await;
+    let stats =
+        RepositoryStats::get(&owner, &repository, connected_username.as_deref(), &db).await?;
+    let repo = Repository::by_namespace(&owner, &repository, &db).await?;
+    let default_branch = repo
+        .get_default_branch(&db)
+        .await
+        .ok_or(AppError::NotFound)?
+        .name;
+    let git_repository = GitRepository::open(&repo.git_namespace(&owner), &repository)?;
+    let mut branches = vec![];
+
+    for branch in repo.list_branches(i64::MAX, 0, &db).await? {
+        let Ok(last_commit) = git_repository.branch_head(&branch.name) else {
+            tracing::warn!("branch {} not found in {owner}/{repository}", branch.name);
+            continue;
+        };
+
+        let ahead_behind = if branch.is_default {
+            Default::default()
+        } else {
+            git_repository
+                .ahead_behind(&default_branch, &branch.name)
+                .unwrap_or_else(|err| {
+                    tracing::warn!(
+                        "cannot compare {} with {default_branch}: {err}",
+                        branch.name
+                    );
+                    Default::default()
+                })
+        };
+
+        let last_commit_date = NaiveDateTime::from_timestamp_opt(last_commit.created_at as i64, 0)
+            .map(|date| date.format("%Y-%m-%d").to_string())
+            .unwrap_or_default();
+
+        branches.push(BranchInfoDto {
+            name: branch.name,
+            is_default: branch.is_default,
+            ahead: ahead_behind.ahead,
+            behind: ahead_behind.behind,
+            last_commit_sha: last_commit.id,
+            last_commit_summary: last_commit.summary,
+            last_commit_date,
+        });
+    }
+
+    Ok(HtmlTemplate(BranchesTemplate {
+        user: connected_username,
+        owner,
+        repository,
+        current_branch: Some(default_branch.clone()),
+        default_branch,
+        branches,
+        stats,
+        tab: Tab::Code,
+    }))
+}
+
+#[authorized]
+pub async fn create(
+    user: Option<Oauth2User>,
+    Extension(db): Extension<PgPool>,
+    Path((owner, repository)): Path<(String, String)>,
+    Form(input): Form<CreateBranchForm>,
+) -> AppResult<Redirect> {
+    let repo = Repository::by_namespace(&owner, &repository, &db).await?;
+    repo.create_branch(&user, &owner, &input.name, &input.from, &db)
+        .await?;
+    Ok(Redirect::to(&format!("/{owner}/{repository}/branches")))
+}
+
+#[authorized]
+pub async fn delete(
+    user: Option<Oauth2User>,
+    Extension(db): Extension<PgPool>,
+    Path((owner, repository)): Path<(String, String)>,
+    Form(input): Form<BranchForm>,
+) -> AppResult<Redirect> {
+    let repo = Repository::by_namespace(&owner, &repository, &db).await?;
+    repo.delete_branch(&user, &owner, &input.name, &db).await?;
+    Ok(Redirect::to(&format!("/{owner}/{repository}/branches")))
+}
+
+#[authorized]
+pub async fn rename(
+    user: Option<Oauth2User>,
+    Extension(db): Extension<PgPool>,
+    Path((owner, repository)): Path<(String, String)>,
+    Form(input): Form<RenameBranchForm>,
+) -> AppResult<Redirect> {
+    let repo = Repository::by_namespace(&owner, &repository, &db).await?;
+    repo.rename_branch(&user, &owner, &input.name, &input.new_name, &db)
+        .await?;
+    Ok(Redirect::to(&format!("/{owner}/{repository}/branches")))
+}
+
+#[authorized]
+pub async fn set_default(
+    user: Option<Oauth2User>,
+    Extension(db): Extension<PgPool>,
+    Path((owner, repository)): Path<(String, String)>,
+    Form(input): Form<BranchForm>,
+) -> AppResult<Redirect> {
+    let repo = Repository::by_namespace(&owner, &repository, &db).await?;
+    repo.change_default_branch(&user, &owner, &input.name, &db)
+        .await?;
+    Ok(Redirect::to(&format!("/{owner}/{repository}/branches")))
+}
@@ -5,6 +5,7 @@ use crate::view::HtmlTemplate;
 
 use askama::Template;
 use axum::extract::Path;
+use axum::response::{IntoResponse, Response};
 use axum::Extension;
 
 use crate::domain::commit::Author;
@@ -13,6 +14,7 @@ use crate::domain::repository::stats::RepositoryStats;
 use crate::domain::repository::Repository;
 use crate::get_connected_user_username;
 use crate::view::filters;
+use crate::view::repository::patch::{Patch, PatchFormat};
 
 use gill_syntax::diff::diff2html;
 use sqlx::PgPool;
@@ -38,7 +40,8 @@ pub async fn git_log(
     let connected_username = get_connected_user_username(&db, user).await;
     let commits = Repository::history(&owner, &repository, &current_branch, &db).await?;
     let branches = get_repository_branches(&owner, &repository, &current_branch, &db).await?;
-    let stats = RepositoryStats::get(&owner, &repository, &db).await?;
+    let stats =
+        RepositoryStats::get(&owner, &repository, connected_username.as_deref(), &db).await?;
 
     Ok(HtmlTemplate(CommitLogTemplate {
         repository,
@@ -55,10 +58,8 @@ pub async fn git_log(
 #[derive(Template, Debug)]
 #[template(path = "repository/commit-diff.html")]
 pub struct CommitDiffTemplate {
-    // TODO
-    _repository: String,
-    // TODO
-    _owner: String,
+    repository: String,
+    owner: String,
     // TODO
     _stats: RepositoryStats,
     commit: Commit,
@@ -74,20 +75,32 @@ pub async fn commit_diff(
     user: Option<Oauth2User>,
     Path((owner, repository, sha)): Path<(String, String, String)>,
     Extension(db): Extension<PgPool>,
-) -> AppResult<HtmlTemplate<CommitDiffTemplate>> {
+) -> AppResult<Response> {
+    if let (sha, Some(format)) = PatchFormat::split(&sha) {
+        let repo = Repository::open_git(&owner, &repository, &db).await?;
+        let patch = match format {
+            PatchFormat::Patch => repo.commit_patch(sha)?,
+            PatchFormat::Diff => repo.commit_raw_diff(sha)?,
+        };
+
+        return Ok(Patch(patch).into_response());
+    }
+
     let connected_username = get_connected_user_username(&db, user).await;
     let (commit, diff) = Repository::commit_with_diff(&owner, &repository, &sha, &db).await?;
     let diff = diff2html(&diff)?;
-    let stats = RepositoryStats::get(&owner, &repository, &db).await?;
+    let stats =
+        RepositoryStats::get(&owner, &repository, connected_username.as_deref(), &db).await?;
 
     Ok(HtmlTemplate(CommitDiffTemplate {
-        _repository: repository,
-        _owner: owner,
+        repository,
+        owner,
         _stats: stats,
         commit,
         diff,
         _current_branch: None,
         user: connected_username,
         _tab: Tab::History,
-    }))
+    })
+    .into_response())
 }
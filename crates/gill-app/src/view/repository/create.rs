@@ -1,5 +1,6 @@
 use crate::domain::id::ActivityPubId;
 use crate::domain::repository::create::CreateRepository;
+use crate::domain::repository::mirror::check_remote_url;
 use crate::domain::user::User;
 use crate::error::AppError;
 use crate::error::AppResult;
@@ -26,6 +27,8 @@ pub struct CreateRepositoryView {
 pub struct CreateRepositoryForm {
     pub name: String,
     pub summary: Option<String>,
+    /// Pull the repository from this url instead of waiting for a push
+    pub mirror_url: Option<String>,
 }
 
 #[authorized]
@@ -34,9 +37,27 @@ pub async fn submit(
     Extension(db): Extension<PgPool>,
     Form(input): Form<CreateRepositoryForm>,
 ) -> AppResult<Redirect> {
+    let mirror_url = input
+        .mirror_url
+        .clone()
+        .filter(|mirror_url| !mirror_url.trim().is_empty());
+
+    if let Some(mirror_url) = &mirror_url {
+        check_remote_url(mirror_url.trim())?;
+    }
+
     let create_repository_command = input.map_to_domain(&user)?;
     let repository = create_repository_command.save(&db).await?;
-    gill_git::init::init_bare(&user.username, &repository.name)?;
+    match mirror_url {
+        Some(mirror_url) => {
+            repository
+                .start_mirror(&user.username, mirror_url.trim(), &db)
+                .await?;
+        }
+        None => {
+            gill_git::init::init_bare(&user.username, &repository.name)?;
+        }
+    }
 
     Ok(Redirect::to(&format!(
         "/{}/{}",
@@ -55,7 +76,7 @@ pub async fn view(
 }
 
 impl CreateRepositoryForm {
-    fn map_to_domain(self, user: &User) -> AppResult<CreateRepository> {
+    pub(crate) fn map_to_domain(self, user: &User) -> AppResult<CreateRepository> {
         let apub_id = self.generate_activity_pub_id(user);
         let clone_uri = self.generate_clone_uri(user);
         let key_pair = generate_actor_keypair()?;
@@ -1,14 +1,17 @@
-use crate::error::AppResult;
+use crate::domain::repository::Repository;
+use crate::error::{AppError, AppResult};
 use crate::get_connected_user_username;
 use crate::oauth::Oauth2User;
+use crate::view::repository::patch::{Patch, PatchFormat};
 use crate::view::HtmlTemplate;
 use askama::Template;
 use axum::extract::{Path, Query};
+use axum::response::{IntoResponse, Redirect, Response};
 use axum::Extension;
-use gill_git::GitRepository;
 use gill_syntax::diff::diff2html;
 use serde::Deserialize;
 use sqlx::PgPool;
+use url::form_urlencoded;
 
 #[derive(Deserialize)]
 pub struct DiffQuery {
@@ -19,6 +22,10 @@ pub struct DiffQuery {
 #[derive(Template)]
 #[template(path = "repository/diff.html")]
 pub struct GitDiffTemplate {
+    owner: String,
+    repository: String,
+    from: String,
+    to: String,
     diff: String,
     user: Option<String>,
 }
@@ -30,21 +37,63 @@ pub async fn view(
     Extension(db): Extension<PgPool>,
 ) -> AppResult<HtmlTemplate<GitDiffTemplate>> {
     let connected_username = get_connected_user_username(&db, user).await;
-    let repo = GitRepository::open(&owner, &repository)?;
-    let diff = repo.diff(&diff.from, &diff.to)?;
+    let repo = Repository::open_git(&owner, &repository, &db).await?;
+    let DiffQuery { from, to } = diff;
+    let diff = repo.diff(&from, &to)?;
     let diff = diff2html(&diff)?;
 
     Ok(HtmlTemplate(GitDiffTemplate {
+        owner,
+        repository,
+        from,
+        to,
         diff,
         user: connected_username,
     }))
 }
 
+/// Compare two branches using a `base...compare` or `base..compare` range, append `.patch`
+/// or `.diff` to the range to download the raw patch series or unified diff. The three-dot
+/// diff starts from the merge base of both branches, the two-dot one from `base` itself.
+pub async fn compare_range(
+    Path((owner, repository, range)): Path<(String, String, String)>,
+    Extension(db): Extension<PgPool>,
+) -> AppResult<Response> {
+    let range = range.trim_start_matches('/');
+    let (range, format) = PatchFormat::split(range);
+    let (from, to, from_merge_base) = match range.split_once("...") {
+        Some((from, to)) => (from, to, true),
+        None => {
+            let (from, to) = range.split_once("..").ok_or(AppError::NotFound)?;
+            (from, to, false)
+        }
+    };
+
+    let Some(format) = format else {
+        let query = form_urlencoded::Serializer::new(String::new())
+            .append_pair("from", from)
+            .append_pair("to", to)
+            .finish();
+
+        return Ok(Redirect::to(&format!("/{owner}/{repository}/diff?{query}")).into_response());
+    };
+
+    let repo = Repository::open_git(&owner, &repository, &db).await?;
+    let patch = match format {
+        PatchFormat::Patch => repo.format_patch(from, to)?,
+        PatchFormat::Diff if from_merge_base => repo.raw_diff_from_merge_base(from, to)?,
+        PatchFormat::Diff => repo.raw_diff(from, to)?,
+    };
+
+    Ok(Patch(patch).into_response())
+}
+
 pub async fn get_diff(
     Path((owner, repository)): Path<(String, String)>,
     Query(diff): Query<DiffQuery>,
+    Extension(db): Extension<PgPool>,
 ) -> AppResult<String> {
-    let repo = GitRepository::open(&owner, &repository)?;
+    let repo = Repository::open_git(&owner, &repository, &db).await?;
     let diff = repo.diff(&diff.from, &diff.to)?;
     let diff = diff2html(&diff)?;
 
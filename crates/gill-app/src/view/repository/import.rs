@@ -0,0 +1,129 @@
+use crate::domain::repository::import::default_source_url;
+use crate::domain::repository::import::dump::ImportDump;
+use crate::domain::repository::mirror::check_remote_url;
+use crate::domain::repository::Repository;
+use crate::error::{AppError, AppResult};
+use crate::get_connected_user;
+use crate::oauth::Oauth2User;
+use crate::view::repository::create::CreateRepositoryForm;
+use crate::view::HtmlTemplate;
+use anyhow::anyhow;
+use askama::Template;
+use axum::extract::{Multipart, Path};
+use axum::response::Redirect;
+use axum::Extension;
+use gill_authorize_derive::authorized;
+use sqlx::PgPool;
+use std::collections::HashMap;
+use url::Url;
+
+#[derive(Template)]
+#[template(path = "repository/import.html")]
+pub struct ImportRepositoryView {
+    user: Option<String>,
+}
+
+#[authorized]
+pub async fn view(
+    user: Option<Oauth2User>,
+    Extension(db): Extension<PgPool>,
+) -> AppResult<HtmlTemplate<ImportRepositoryView>> {
+    Ok(HtmlTemplate(ImportRepositoryView {
+        user: Some(user.username),
+    }))
+}
+
+/// Create the repository and queue its import, the dump is parsed right away
+/// so a malformed one is reported before anything is created
+#[authorized]
+pub async fn submit(
+    user: Option<Oauth2User>,
+    Extension(db): Extension<PgPool>,
+    mut multipart: Multipart,
+) -> AppResult<Redirect> {
+    let mut fields = HashMap::new();
+    let mut dump = None;
+
+    while let Some(field) = multipart.next_field().await? {
+        let name = field.name().map(ToString::to_string);
+        match name.as_deref() {
+            Some("dump") => {
+                let data = field.bytes().await?;
+                // Browsers send an empty part when no file was selected
+                if !data.is_empty() {
+                    dump = Some(data);
+                }
+            }
+            Some(name) => {
+                let name = name.to_string();
+                fields.insert(name, field.text().await?);
+            }
+            None => {}
+        }
+    }
+
+    let field = |name: &str| {
+        fields
+            .get(name)
+            .map(|value| value.trim())
+            .filter(|value| !value.is_empty())
+    };
+
+    let name = field("name").ok_or_else(|| anyhow!("Missing repository name"))?;
+    let remote_url = field("remote_url").ok_or_else(|| anyhow!("Missing git url"))?;
+    check_remote_url(remote_url)?;
+
+    let source_url = match field("source_url") {
+        Some(source_url) => Some(Url::parse(source_url)?),
+        None => default_source_url(remote_url),
+    };
+
+    let dump = match field("format") {
+        None | Some("none") => None,
+        Some(format) => {
+            let data = dump.ok_or_else(|| anyhow!("Missing issue tracker dump"))?;
+            Some(ImportDump::parse(format.parse()?, source_url, &data)?)
+        }
+    };
+
+    let own_logins = parse_logins(field("own_logins").unwrap_or_default());
+
+    let repository = CreateRepositoryForm {
+        name: name.to_string(),
+        summary: field("summary").map(ToString::to_string),
+        mirror_url: None,
+    }
+    .map_to_domain(&user)?
+    .save(&db)
+    .await?;
+
+    repository
+        .start_import(&user, remote_url, dump, &own_logins, &db)
+        .await?;
+
+    Ok(Redirect::to(&format!(
+        "/{}/{}",
+        user.username, repository.name
+    )))
+}
+
+#[authorized]
+pub async fn retry(
+    user: Option<Oauth2User>,
+    Extension(db): Extension<PgPool>,
+    Path((owner, repository)): Path<(String, String)>,
+) -> AppResult<Redirect> {
+    let repo = Repository::by_namespace(&owner, &repository, &db).await?;
+    repo.retry_import(&user, &db).await?;
+    Ok(Redirect::to(&format!("/{owner}/{repository}")))
+}
+
+// One source forge login per line
+fn parse_logins(logins: &str) -> Vec<String> {
+    logins
+        .lines()
+        .map(str::trim)
+        .filter(|login| !login.is_empty())
+        .map(ToString::to_string)
+        .collect()
+}
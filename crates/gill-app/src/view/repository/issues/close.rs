@@ -2,21 +2,53 @@ use crate::domain::repository::Repository;
 use crate::error::{AppError, AppResult};
 use crate::get_connected_user;
 use crate::oauth::Oauth2User;
-use axum::extract::Path;
+use crate::state::AppState;
+use axum::extract::{Path, Query, State};
 use axum::response::Redirect;
 use axum::Extension;
 use gill_authorize_derive::authorized;
+use serde::Deserialize;
 use sqlx::PgPool;
 
+#[derive(Deserialize, Debug)]
+pub struct CloseQuery {
+    /// Close the issue even if it is blocked by open issues
+    #[serde(default)]
+    pub force: bool,
+}
+
 #[authorized]
 pub async fn close(
     user: Option<Oauth2User>,
+    State(state): State<AppState>,
+    Extension(db): Extension<PgPool>,
+    Path((owner, repository, issue_number)): Path<(String, String, i32)>,
+    Query(query): Query<CloseQuery>,
+) -> AppResult<Redirect> {
+    Repository::by_namespace(&owner, &repository, &db)
+        .await?
+        .issue_by_number(issue_number, &db)
+        .await?
+        .close(&user, query.force, &state.instance)
+        .await?;
+
+    Ok(Redirect::to(&format!(
+        "/{owner}/{repository}/issues/{issue_number}"
+    )))
+}
+
+#[authorized]
+pub async fn reopen(
+    user: Option<Oauth2User>,
+    State(state): State<AppState>,
     Extension(db): Extension<PgPool>,
     Path((owner, repository, issue_number)): Path<(String, String, i32)>,
 ) -> AppResult<Redirect> {
     Repository::by_namespace(&owner, &repository, &db)
         .await?
-        .close_issue(issue_number, user.activity_pub_id, &db)
+        .issue_by_number(issue_number, &db)
+        .await?
+        .reopen(&user, &state.instance)
         .await?;
 
     Ok(Redirect::to(&format!(
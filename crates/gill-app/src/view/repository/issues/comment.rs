@@ -1,4 +1,5 @@
 use crate::domain::issue::comment::create::CreateIssueCommentCommand;
+use crate::domain::issue::comment::IssueComment;
 use crate::error::{AppError, AppResult};
 use crate::get_connected_user;
 use crate::oauth::Oauth2User;
@@ -10,6 +11,7 @@ use axum::{Extension, Form};
 use gill_authorize_derive::authorized;
 use serde::Deserialize;
 use sqlx::PgPool;
+use uuid::Uuid;
 
 #[derive(Deserialize, Debug)]
 pub struct IssueCommentForm {
@@ -38,3 +40,40 @@ pub async fn comment(
         "/{owner}/{repository}/issues/{issue_number}"
     )))
 }
+
+#[authorized]
+pub async fn edit_comment(
+    user: Option<Oauth2User>,
+    Path((owner, repository, issue_number, comment_id)): Path<(String, String, i32, String)>,
+    State(state): State<AppState>,
+    Extension(db): Extension<PgPool>,
+    Form(input): Form<IssueCommentForm>,
+) -> AppResult<Redirect> {
+    let comment_id = Uuid::parse_str(&comment_id)?;
+    IssueComment::by_id(comment_id, &db)
+        .await?
+        .edit(&user, &input.content, &state.instance)
+        .await?;
+
+    Ok(Redirect::to(&format!(
+        "/{owner}/{repository}/issues/{issue_number}"
+    )))
+}
+
+#[authorized]
+pub async fn delete_comment(
+    user: Option<Oauth2User>,
+    Path((owner, repository, issue_number, comment_id)): Path<(String, String, i32, String)>,
+    State(state): State<AppState>,
+    Extension(db): Extension<PgPool>,
+) -> AppResult<Redirect> {
+    let comment_id = Uuid::parse_str(&comment_id)?;
+    IssueComment::by_id(comment_id, &db)
+        .await?
+        .delete_by(&user, &state.instance)
+        .await?;
+
+    Ok(Redirect::to(&format!(
+        "/{owner}/{repository}/issues/{issue_number}"
+    )))
+}
@@ -15,6 +15,9 @@ use sqlx::PgPool;
 pub struct CreateIssueForm {
     pub title: String,
     pub content: String,
+    /// File name of the issue template the issue was started from
+    #[serde(default)]
+    pub template: Option<String>,
 }
 
 #[authorized]
@@ -0,0 +1,56 @@
+use crate::domain::repository::Repository;
+use crate::error::{AppError, AppResult};
+use crate::get_connected_user;
+use crate::oauth::Oauth2User;
+use crate::state::AppState;
+use axum::extract::{Path, State};
+use axum::response::Redirect;
+use axum::{Extension, Form};
+use gill_authorize_derive::authorized;
+use serde::Deserialize;
+use sqlx::PgPool;
+
+#[derive(Deserialize, Debug)]
+pub struct EditIssueForm {
+    pub title: String,
+    pub content: String,
+}
+
+#[authorized]
+pub async fn edit(
+    user: Option<Oauth2User>,
+    State(state): State<AppState>,
+    Extension(db): Extension<PgPool>,
+    Path((owner, repository, issue_number)): Path<(String, String, i32)>,
+    Form(input): Form<EditIssueForm>,
+) -> AppResult<Redirect> {
+    Repository::by_namespace(&owner, &repository, &db)
+        .await?
+        .issue_by_number(issue_number, &db)
+        .await?
+        .edit(&user, &input.title, &input.content, &state.instance)
+        .await?;
+
+    Ok(Redirect::to(&format!(
+        "/{owner}/{repository}/issues/{issue_number}"
+    )))
+}
+
+#[authorized]
+pub async fn delete(
+    user: Option<Oauth2User>,
+    State(state): State<AppState>,
+    Extension(db): Extension<PgPool>,
+    Path((owner, repository, issue_number)): Path<(String, String, i32)>,
+) -> AppResult<Redirect> {
+    Repository::by_namespace(&owner, &repository, &db)
+        .await?
+        .issue_by_number(issue_number, &db)
+        .await?
+        .delete_by(&user, &state.instance)
+        .await?;
+
+    Ok(Redirect::to(&format!(
+        "/{owner}/{repository}/issues/{issue_number}"
+    )))
+}
@@ -0,0 +1,59 @@
+use crate::domain::issue::link::IssueRelation;
+use crate::domain::repository::Repository;
+use crate::error::{AppError, AppResult};
+use crate::get_connected_user;
+use crate::oauth::Oauth2User;
+use crate::state::AppState;
+use axum::extract::{Path, State};
+use axum::response::Redirect;
+use axum::{Extension, Form};
+use gill_authorize_derive::authorized;
+use serde::Deserialize;
+use sqlx::PgPool;
+
+#[derive(Deserialize, Debug)]
+pub struct LinkIssueForm {
+    /// One of the `IssueRelation` names
+    pub relation: String,
+    /// `#12`, `owner/repository#12` or the url of a remote ticket
+    pub issue: String,
+}
+
+#[authorized]
+pub async fn link(
+    user: Option<Oauth2User>,
+    State(state): State<AppState>,
+    Extension(db): Extension<PgPool>,
+    Path((owner, repository, issue_number)): Path<(String, String, i32)>,
+    Form(input): Form<LinkIssueForm>,
+) -> AppResult<Redirect> {
+    let relation = IssueRelation::from_name(&input.relation).ok_or(AppError::NotFound)?;
+    let repo = Repository::by_namespace(&owner, &repository, &db).await?;
+    let issue = repo.issue_by_number(issue_number, &db).await?;
+    let other = repo
+        .find_linked_issue(&input.issue, &state.instance)
+        .await?;
+    issue.link(&user, relation, &other, &db).await?;
+
+    Ok(Redirect::to(&format!(
+        "/{owner}/{repository}/issues/{issue_number}"
+    )))
+}
+
+#[authorized]
+pub async fn unlink(
+    user: Option<Oauth2User>,
+    Extension(db): Extension<PgPool>,
+    Path((owner, repository, issue_number, link_id)): Path<(String, String, i32, i32)>,
+) -> AppResult<Redirect> {
+    Repository::by_namespace(&owner, &repository, &db)
+        .await?
+        .issue_by_number(issue_number, &db)
+        .await?
+        .unlink(&user, link_id, &db)
+        .await?;
+
+    Ok(Redirect::to(&format!(
+        "/{owner}/{repository}/issues/{issue_number}"
+    )))
+}
@@ -11,6 +11,8 @@ use axum::Extension;
 
 use crate::domain::issue::digest::IssueDigest;
 use crate::domain::issue::IssueState;
+use crate::domain::repository::label::Label;
+use crate::domain::repository::template::IssueTemplate;
 use crate::domain::repository::Repository;
 use crate::view::component::MarkdownPreviewForm;
 use crate::view::repository::Tab;
@@ -22,10 +24,11 @@ pub struct IssuesTemplate {
     user: Option<String>,
     owner: String,
     repository: String,
-    issues: Option<Vec<IssueDigest>>,
+    issues: Option<Vec<(IssueDigest, Vec<Label>)>>,
     stats: RepositoryStats,
     current_branch: Option<String>,
     markdown_preview_form: MarkdownPreviewForm,
+    issue_templates: Vec<IssueTemplate>,
     tab: Tab,
 }
 
@@ -35,11 +38,23 @@ pub async fn list_view(
     Path((owner, repository)): Path<(String, String)>,
 ) -> AppResult<HtmlTemplate<IssuesTemplate>> {
     let connected_username = get_connected_user_username(&db, user).await;
-    let stats = RepositoryStats::get(&owner, &repository, &db).await?;
+    let stats =
+        RepositoryStats::get(&owner, &repository, connected_username.as_deref(), &db).await?;
     let repo = Repository::by_namespace(&owner, &repository, &db).await?;
-    let issues = repo.list_issues(&db).await?;
+    let mut labels = repo.labels_by_number(&db).await?;
+    let issues: Vec<_> = repo
+        .list_issues(&db)
+        .await?
+        .into_iter()
+        .map(|issue| {
+            let issue_labels = labels.remove(&issue.number).unwrap_or_default();
+            (issue, issue_labels)
+        })
+        .collect();
     let pull_requests = (!issues.is_empty()).then_some(issues);
     let current_branch = repo.get_default_branch(&db).await.map(|branch| branch.name);
+    // Federated repositories have no git repository on this instance
+    let issue_templates = repo.issue_templates(&owner, &db).await.unwrap_or_default();
 
     let action_href = format!("/{owner}/{repository}/issues/create");
 
@@ -51,6 +66,7 @@ pub async fn list_view(
         stats,
         current_branch,
         tab: Tab::Issues,
+        issue_templates,
         markdown_preview_form: MarkdownPreviewForm {
             with_title: true,
             action_href,
@@ -1,15 +1,20 @@
 use crate::state::AppState;
-use crate::view::repository::issues::close::close;
-use crate::view::repository::issues::comment::comment;
+use crate::view::repository::issues::close::{close, reopen};
+use crate::view::repository::issues::comment::{comment, delete_comment, edit_comment};
 use crate::view::repository::issues::create::create;
+use crate::view::repository::issues::edit::{delete, edit};
+use crate::view::repository::issues::link::{link, unlink};
 use crate::view::repository::issues::list_view::list_view;
 use crate::view::repository::issues::view::view;
+use crate::view::repository::reaction::{react_to_issue, react_to_issue_comment};
 use axum::routing::get;
 use axum::Router;
 
 pub mod close;
 pub mod comment;
 pub mod create;
+pub mod edit;
+pub mod link;
 pub mod list_view;
 pub mod view;
 
@@ -19,5 +24,29 @@ pub fn router() -> Router<AppState> {
         .route("/:owner/:repository/issues/:number", get(view))
         .route("/:owner/:repository/issues/:number/comment", get(comment))
         .route("/:owner/:repository/issues/:number/close", get(close))
+        .route("/:owner/:repository/issues/:number/reopen", get(reopen))
+        .route("/:owner/:repository/issues/:number/edit", get(edit))
+        .route("/:owner/:repository/issues/:number/delete", get(delete))
+        .route("/:owner/:repository/issues/:number/link", get(link))
+        .route(
+            "/:owner/:repository/issues/:number/links/:link/delete",
+            get(unlink),
+        )
+        .route(
+            "/:owner/:repository/issues/:number/react",
+            get(react_to_issue),
+        )
+        .route(
+            "/:owner/:repository/issues/:number/comments/:comment/edit",
+            get(edit_comment),
+        )
+        .route(
+            "/:owner/:repository/issues/:number/comments/:comment/delete",
+            get(delete_comment),
+        )
+        .route(
+            "/:owner/:repository/issues/:number/comments/:comment/react",
+            get(react_to_issue_comment),
+        )
         .route("/:owner/:repository/issues/create", get(create))
 }
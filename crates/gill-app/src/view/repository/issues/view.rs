@@ -5,17 +5,22 @@ use crate::view::component::MarkdownPreviewForm;
 
 use crate::get_connected_user_username;
 
-use crate::view::HtmlTemplate;
+use crate::view::{filters, HtmlTemplate};
 
 use askama::Template;
 use axum::extract::Path;
+use axum::response::{IntoResponse, Redirect, Response};
 
 use axum::Extension;
 
 use crate::domain::issue::comment::digest::IssueCommentDigest;
 use crate::domain::issue::digest::IssueDigest;
+use crate::domain::issue::link::{IssueLink, IssueRelation};
 use crate::domain::issue::IssueState;
+use crate::domain::reaction::{ItemReactions, ReactionEmoji};
 use crate::domain::repository::Repository;
+use crate::domain::revision::ItemRevisions;
+use crate::domain::timeline::{ItemEventKind, TimelineItem};
 use crate::view::repository::Tab;
 use sqlx::PgPool;
 
@@ -28,7 +33,14 @@ pub struct IssueTemplate {
     issue: IssueDigest,
     stats: RepositoryStats,
     current_branch: Option<String>,
-    comments: Vec<IssueCommentDigest>,
+    timeline: Vec<TimelineItem<IssueCommentDigest>>,
+    revisions: ItemRevisions,
+    reactions: ItemReactions,
+    links: Vec<IssueLink>,
+    /// Some linked issues blocking this one are still open
+    is_blocked: bool,
+    /// The connected user is the issue author or the repository owner
+    can_manage: bool,
     markdown_preview_form: MarkdownPreviewForm,
     tab: Tab,
 }
@@ -37,14 +49,45 @@ pub async fn view(
     user: Option<Oauth2User>,
     Extension(db): Extension<PgPool>,
     Path((owner, repository, issue_number)): Path<(String, String, i32)>,
-) -> AppResult<HtmlTemplate<IssueTemplate>> {
+) -> AppResult<Response> {
     let connected_username = get_connected_user_username(&db, user).await;
-    let stats = RepositoryStats::get(&owner, &repository, &db).await?;
+    let stats =
+        RepositoryStats::get(&owner, &repository, connected_username.as_deref(), &db).await?;
     let repo = Repository::by_namespace(&owner, &repository, &db).await?;
-    let issue = repo.get_issue_digest(issue_number, &db).await?;
+    let issue = match repo.get_issue_digest(issue_number, &db).await {
+        Ok(issue) => issue,
+        // `#number` references link to issues, pull requests share the same numbering
+        Err(err) => {
+            return match repo.get_pull_request(issue_number, &db).await {
+                Ok(_) => {
+                    let pull_request = format!("/{owner}/{repository}/pulls/{issue_number}");
+                    Ok(Redirect::to(&pull_request).into_response())
+                }
+                Err(_) => Err(err),
+            };
+        }
+    };
+
     let comments = issue.get_comments(&db).await?;
+    let timeline = TimelineItem::for_item(
+        issue.repository_id,
+        issue.number,
+        comments,
+        |comment| comment.published,
+        &db,
+    )
+    .await?;
+    let revisions = ItemRevisions::for_item(issue.repository_id, issue.number, &db).await?;
+    let reactions = ItemReactions::for_item(issue.repository_id, issue.number, &db).await?;
+    let links = IssueLink::for_issue(issue.repository_id, issue.number, &db).await?;
+    let is_blocked = links.iter().any(IssueLink::is_open_dependency);
     let current_branch = repo.get_default_branch(&db).await.map(|branch| branch.name);
 
+    let can_manage = connected_username
+        .as_deref()
+        .map(|username| username == owner || username == issue.opened_by)
+        .unwrap_or(false);
+
     let action_href = format!("/{owner}/{repository}/issues/{issue_number}/comment");
     Ok(HtmlTemplate(IssueTemplate {
         user: connected_username,
@@ -53,7 +96,12 @@ pub async fn view(
         issue,
         stats,
         current_branch,
-        comments,
+        timeline,
+        revisions,
+        reactions,
+        links,
+        is_blocked,
+        can_manage,
         markdown_preview_form: MarkdownPreviewForm {
             with_title: false,
             action_href,
@@ -62,5 +110,6 @@ pub async fn view(
             repository,
         },
         tab: Tab::Issues,
-    }))
+    })
+    .into_response())
 }
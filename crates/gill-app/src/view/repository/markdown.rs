@@ -0,0 +1,10 @@
+use axum::extract::Path;
+use axum::response::Html;
+
+/// Render a markdown draft the way it will be displayed once submitted
+pub async fn preview(
+    Path((owner, repository)): Path<(String, String)>,
+    content: String,
+) -> Html<String> {
+    Html(gill_markdown::render(&content, &owner, &repository))
+}
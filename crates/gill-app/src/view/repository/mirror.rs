@@ -0,0 +1,37 @@
+use crate::domain::repository::Repository;
+use crate::error::{AppError, AppResult};
+use crate::get_connected_user;
+use crate::oauth::Oauth2User;
+use axum::extract::Path;
+use axum::response::Redirect;
+use axum::Extension;
+use gill_authorize_derive::authorized;
+use sqlx::PgPool;
+
+/// Start mirroring a federated repository
+#[authorized]
+pub async fn mirror(
+    user: Option<Oauth2User>,
+    Extension(db): Extension<PgPool>,
+    Path((owner, repository)): Path<(String, String)>,
+) -> AppResult<Redirect> {
+    let repo = Repository::by_namespace(&owner, &repository, &db).await?;
+    repo.mirror_federated(&db).await?;
+    Ok(Redirect::to(&format!("/{owner}/{repository}")))
+}
+
+/// Fetch a mirror without waiting for its sync interval
+#[authorized]
+pub async fn sync(
+    user: Option<Oauth2User>,
+    Extension(db): Extension<PgPool>,
+    Path((owner, repository)): Path<(String, String)>,
+) -> AppResult<Redirect> {
+    let repo = Repository::by_namespace(&owner, &repository, &db).await?;
+    if repo.is_local && repo.attributed_to != user.activity_pub_id {
+        return Err(AppError::Unauthorized);
+    }
+
+    repo.sync_mirror_now(&db).await?;
+    Ok(Redirect::to(&format!("/{owner}/{repository}")))
+}
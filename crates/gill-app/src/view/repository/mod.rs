@@ -1,7 +1,8 @@
-use crate::domain::user::User;
+use crate::domain::repository::Repository;
 use crate::error::AppResult;
 use crate::state::AppState;
 
+use axum::extract::DefaultBodyLimit;
 use axum::routing::{get, post};
 use axum::Router;
 use sqlx::PgPool;
@@ -9,27 +10,48 @@ use std::fmt;
 use std::fmt::Formatter;
 
 pub mod activity;
+pub mod archive;
+pub mod attachment;
 pub mod blob;
+pub mod branches;
 pub mod commits;
 pub mod create;
 pub mod diff;
+pub mod import;
 pub mod issues;
+pub mod markdown;
+pub mod mirror;
+pub mod patch;
 pub mod pull_request;
+pub mod reaction;
+pub mod release;
+pub mod revision;
+pub mod settings;
 pub mod tree;
 pub mod user_content;
 
+/// Maximum size of an import request, the issue tracker dump included
+const IMPORT_MAX_UPLOAD_SIZE: usize = 64 * 1024 * 1024;
+
 #[derive(Debug)]
 pub enum Tab {
     Code,
     Issues,
     PullRequests,
     History,
+    Releases,
+    Settings,
 }
 
 pub fn routes() -> Router<AppState> {
     let router = Router::new()
         .route("/new", get(create::view))
         .route("/create-repository", get(create::submit))
+        .route("/import", get(import::view))
+        .route(
+            "/import-repository",
+            post(import::submit).layer(DefaultBodyLimit::max(IMPORT_MAX_UPLOAD_SIZE)),
+        )
         .route("/:owner/:repository", get(tree::root))
         .route("/:owner/:repository/tree/:branch", get(tree::tree_root))
         .route("/:owner/:repository/tree/:branch/*tree", get(tree::tree))
@@ -42,11 +64,51 @@ pub fn routes() -> Router<AppState> {
         .route("/:owner/:repository/commit/:sha", get(commits::commit_diff))
         .route("/:owner/:repository/diff", get(diff::view))
         .route("/:owner/:repository/get_diff", get(diff::get_diff))
+        .route(
+            "/:owner/:repository/compare/*range",
+            get(diff::compare_range),
+        )
+        .route(
+            "/:owner/:repository/archive/*filename",
+            get(archive::archive),
+        )
+        .route("/:owner/:repository/branches", get(branches::list))
+        .route("/:owner/:repository/branches/create", get(branches::create))
+        .route("/:owner/:repository/branches/delete", get(branches::delete))
+        .route("/:owner/:repository/branches/rename", get(branches::rename))
+        .route(
+            "/:owner/:repository/branches/default",
+            get(branches::set_default),
+        )
+        .route("/:owner/:repository/import/retry", get(import::retry))
+        .route("/:owner/:repository/mirror", get(mirror::mirror))
+        .route("/:owner/:repository/mirror/sync", get(mirror::sync))
+        .route("/:owner/:repository/settings", get(settings::view))
+        .route(
+            "/:owner/:repository/settings/push-mirrors/add",
+            post(settings::add_push_mirror),
+        )
+        .route(
+            "/:owner/:repository/settings/push-mirrors/delete",
+            get(settings::delete_push_mirror),
+        )
+        .route(
+            "/:owner/:repository/revisions/:id/diff",
+            get(revision::diff),
+        )
+        .route("/:owner/:repository/markdown", post(markdown::preview))
         .route("/:owner/:repository/star", post(activity::star))
         .route("/:owner/:repository/watch", post(activity::watch))
+        .route(
+            "/:owner/:repository/raw/*path",
+            get(user_content::image_at),
+        )
         .route("/:owner/:repository/*path", get(user_content::image));
 
-    router.merge(pull_request::router()).merge(issues::router())
+    router
+        .merge(pull_request::router())
+        .merge(issues::router())
+        .merge(release::router())
 }
 
 #[derive(Debug)]
@@ -62,8 +124,7 @@ async fn get_repository_branches(
     current_branch: &str,
     db: &PgPool,
 ) -> AppResult<Vec<BranchDto>> {
-    let user = User::by_name(owner, db).await.unwrap();
-    let repository = user.get_local_repository_by_name(repository, db).await?;
+    let repository = Repository::by_namespace(owner, repository, db).await?;
     let branches = repository.list_branches(20, 0, db).await?;
     let branches = branches
         .into_iter()
@@ -0,0 +1,36 @@
+use axum::http::header;
+use axum::response::{IntoResponse, Response};
+
+/// Raw download format, selected with a `.patch` or `.diff` suffix on the url.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum PatchFormat {
+    /// Mbox formatted patch series, suitable for `git am`
+    Patch,
+    /// Plain unified diff, suitable for `git apply`
+    Diff,
+}
+
+impl PatchFormat {
+    /// Split a path segment like `1a2b3c.patch` into `("1a2b3c", Some(PatchFormat::Patch))`
+    pub fn split(segment: &str) -> (&str, Option<PatchFormat>) {
+        if let Some(segment) = segment.strip_suffix(".patch") {
+            (segment, Some(PatchFormat::Patch))
+        } else if let Some(segment) = segment.strip_suffix(".diff") {
+            (segment, Some(PatchFormat::Diff))
+        } else {
+            (segment, None)
+        }
+    }
+}
+
+pub struct Patch(pub Vec<u8>);
+
+impl IntoResponse for Patch {
+    fn into_response(self) -> Response {
+        (
+            [(header::CONTENT_TYPE, "text/plain; charset=utf-8")],
+            self.0,
+        )
+            .into_response()
+    }
+}
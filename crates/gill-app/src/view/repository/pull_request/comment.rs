@@ -32,3 +32,38 @@ pub async fn comment(
         "/{owner}/{repository}/pulls/{pull_request_number}"
     )))
 }
+
+#[authorized]
+pub async fn edit_comment(
+    user: Option<Oauth2User>,
+    Extension(db): Extension<PgPool>,
+    Path((owner, repository, pull_request_number, comment_id)): Path<(String, String, i32, i32)>,
+    Form(input): Form<CommentPullRequestForm>,
+) -> AppResult<Redirect> {
+    let repo = Repository::by_namespace(&owner, &repository, &db).await?;
+    repo.get_pull_request(pull_request_number, &db)
+        .await?
+        .edit_comment(&user, &repo, comment_id, &input.content, &db)
+        .await?;
+
+    Ok(Redirect::to(&format!(
+        "/{owner}/{repository}/pulls/{pull_request_number}"
+    )))
+}
+
+#[authorized]
+pub async fn delete_comment(
+    user: Option<Oauth2User>,
+    Extension(db): Extension<PgPool>,
+    Path((owner, repository, pull_request_number, comment_id)): Path<(String, String, i32, i32)>,
+) -> AppResult<Redirect> {
+    let repo = Repository::by_namespace(&owner, &repository, &db).await?;
+    repo.get_pull_request(pull_request_number, &db)
+        .await?
+        .delete_comment_by(&user, &repo, comment_id, &db)
+        .await?;
+
+    Ok(Redirect::to(&format!(
+        "/{owner}/{repository}/pulls/{pull_request_number}"
+    )))
+}
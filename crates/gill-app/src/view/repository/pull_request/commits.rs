@@ -36,7 +36,8 @@ pub async fn commits(
     Path((owner, repository, pull_request_number)): Path<(String, String, i32)>,
 ) -> Result<HtmlTemplate<PullRequestCommitsTemplate>, AppError> {
     let connected_username = get_connected_user_username(&db, user).await;
-    let stats = RepositoryStats::get(&owner, &repository, &db).await?;
+    let stats =
+        RepositoryStats::get(&owner, &repository, connected_username.as_deref(), &db).await?;
     let repo = Repository::by_namespace(&owner, &repository, &db).await?;
     let pull_request = repo.get_pull_request(pull_request_number, &db).await?;
     let current_branch = repo.get_default_branch(&db).await.map(|branch| branch.name);
@@ -75,7 +76,8 @@ pub async fn commit_diff(
     Path((owner, repository, pull_request_number, sha)): Path<(String, String, i32, String)>,
 ) -> Result<HtmlTemplate<PullRequestCommitDiffTemplate>, AppError> {
     let connected_username = get_connected_user_username(&db, user).await;
-    let stats = RepositoryStats::get(&owner, &repository, &db).await?;
+    let stats =
+        RepositoryStats::get(&owner, &repository, connected_username.as_deref(), &db).await?;
     let repo = Repository::by_namespace(&owner, &repository, &db).await?;
     let pull_request = repo.get_pull_request(pull_request_number, &db).await?;
     let current_branch = repo.get_default_branch(&db).await.map(|branch| branch.name);
@@ -21,6 +21,8 @@ pub struct CompareTemplate {
     stats: RepositoryStats,
     branches: Vec<BranchDto>,
     current_branch: Option<String>,
+    /// Prefilled from the repository pull request template
+    description: String,
     tab: Tab,
 }
 
@@ -30,7 +32,8 @@ pub async fn compare(
     Path((owner, repository)): Path<(String, String)>,
 ) -> AppResult<HtmlTemplate<CompareTemplate>> {
     let connected_username = get_connected_user_username(&db, user).await;
-    let stats = RepositoryStats::get(&owner, &repository, &db).await?;
+    let stats =
+        RepositoryStats::get(&owner, &repository, connected_username.as_deref(), &db).await?;
     let repo = Repository::by_namespace(&owner, &repository, &db).await?;
     let current_branch = repo
         .get_default_branch(&db)
@@ -39,6 +42,10 @@ pub async fn compare(
 
     let current_branch = current_branch.name;
     let branches = get_repository_branches(&owner, &repository, &current_branch, &db).await?;
+    let description = repo
+        .pull_request_template(&owner, &db)
+        .await?
+        .unwrap_or_default();
 
     Ok(HtmlTemplate(CompareTemplate {
         user: connected_username,
@@ -47,6 +54,7 @@ pub async fn compare(
         stats,
         branches,
         current_branch: Some(current_branch),
+        description,
         tab: Tab::PullRequests,
     }))
 }
@@ -32,7 +32,8 @@ pub async fn diff(
     Path((owner, repository, pull_request_number)): Path<(String, String, i32)>,
 ) -> Result<HtmlTemplate<PullRequestDiffTemplate>, AppError> {
     let connected_username = get_connected_user_username(&db, user).await;
-    let stats = RepositoryStats::get(&owner, &repository, &db).await?;
+    let stats =
+        RepositoryStats::get(&owner, &repository, connected_username.as_deref(), &db).await?;
     let repo = Repository::by_namespace(&owner, &repository, &db).await?;
     let pull_request = repo.get_pull_request(pull_request_number, &db).await?;
     let current_branch = repo.get_default_branch(&db).await.map(|branch| branch.name);
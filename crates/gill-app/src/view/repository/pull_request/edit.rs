@@ -0,0 +1,51 @@
+use crate::domain::repository::Repository;
+use crate::error::{AppError, AppResult};
+use crate::get_connected_user;
+use crate::oauth::Oauth2User;
+use axum::extract::Path;
+use axum::response::Redirect;
+use axum::{Extension, Form};
+use gill_authorize_derive::authorized;
+use serde::Deserialize;
+use sqlx::PgPool;
+
+#[derive(Deserialize, Debug)]
+pub struct EditPullRequestForm {
+    pub title: String,
+    pub description: String,
+}
+
+#[authorized]
+pub async fn edit(
+    user: Option<Oauth2User>,
+    Extension(db): Extension<PgPool>,
+    Path((owner, repository, pull_request_number)): Path<(String, String, i32)>,
+    Form(input): Form<EditPullRequestForm>,
+) -> AppResult<Redirect> {
+    let repo = Repository::by_namespace(&owner, &repository, &db).await?;
+    repo.get_pull_request(pull_request_number, &db)
+        .await?
+        .edit(&user, &repo, &input.title, &input.description, &db)
+        .await?;
+
+    Ok(Redirect::to(&format!(
+        "/{owner}/{repository}/pulls/{pull_request_number}"
+    )))
+}
+
+#[authorized]
+pub async fn delete(
+    user: Option<Oauth2User>,
+    Extension(db): Extension<PgPool>,
+    Path((owner, repository, pull_request_number)): Path<(String, String, i32)>,
+) -> AppResult<Redirect> {
+    let repo = Repository::by_namespace(&owner, &repository, &db).await?;
+    repo.get_pull_request(pull_request_number, &db)
+        .await?
+        .delete_by(&user, &repo, &db)
+        .await?;
+
+    Ok(Redirect::to(&format!(
+        "/{owner}/{repository}/pulls/{pull_request_number}"
+    )))
+}
@@ -31,7 +31,8 @@ pub async fn list_view(
     Path((owner, repository)): Path<(String, String)>,
 ) -> AppResult<HtmlTemplate<PullRequestsTemplate>> {
     let connected_username = get_connected_user_username(&db, user).await;
-    let stats = RepositoryStats::get(&owner, &repository, &db).await?;
+    let stats =
+        RepositoryStats::get(&owner, &repository, connected_username.as_deref(), &db).await?;
     let repo = Repository::by_namespace(&owner, &repository, &db).await?;
     let pull_requests = repo.list_pull_requests(&db).await?;
     let pull_requests = (!pull_requests.is_empty()).then_some(pull_requests);
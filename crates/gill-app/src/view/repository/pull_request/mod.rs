@@ -1,11 +1,13 @@
 use crate::state::AppState;
-use crate::view::repository::pull_request::comment::comment;
+use crate::view::repository::pull_request::comment::{comment, delete_comment, edit_comment};
 use crate::view::repository::pull_request::commits::{commit_diff, commits};
 use crate::view::repository::pull_request::compare::compare;
 use crate::view::repository::pull_request::create::create;
 use crate::view::repository::pull_request::diff::diff;
+use crate::view::repository::pull_request::edit::{delete, edit};
 use crate::view::repository::pull_request::list_view::list_view;
 use crate::view::repository::pull_request::view::{close, merge, view};
+use crate::view::repository::reaction::{react_to_pull_request, react_to_pull_request_comment};
 use axum::routing::get;
 use axum::Router;
 
@@ -14,6 +16,7 @@ pub mod commits;
 pub mod compare;
 pub mod create;
 pub mod diff;
+pub mod edit;
 pub mod list_view;
 pub mod view;
 
@@ -28,6 +31,24 @@ pub fn router() -> Router<AppState> {
             get(commit_diff),
         )
         .route("/:owner/:repository/pulls/:number/comment", get(comment))
+        .route("/:owner/:repository/pulls/:number/edit", get(edit))
+        .route("/:owner/:repository/pulls/:number/delete", get(delete))
+        .route(
+            "/:owner/:repository/pulls/:number/react",
+            get(react_to_pull_request),
+        )
+        .route(
+            "/:owner/:repository/pulls/:number/comments/:comment/edit",
+            get(edit_comment),
+        )
+        .route(
+            "/:owner/:repository/pulls/:number/comments/:comment/delete",
+            get(delete_comment),
+        )
+        .route(
+            "/:owner/:repository/pulls/:number/comments/:comment/react",
+            get(react_to_pull_request_comment),
+        )
         .route("/:owner/:repository/pulls/:number/merge", get(merge))
         .route(
             "/:owner/:repository/pulls/:number/rebase",
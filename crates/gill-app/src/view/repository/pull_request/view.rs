@@ -1,18 +1,23 @@
 use crate::domain::repository::stats::RepositoryStats;
 use crate::error::{AppError, AppResult};
 use crate::oauth::Oauth2User;
+use crate::state::AppState;
 use crate::view::component::MarkdownPreviewForm;
-use crate::view::HtmlTemplate;
+use crate::view::{filters, HtmlTemplate};
 use crate::{get_connected_user, get_connected_user_username};
 
 use crate::domain::pull_request::comment::PullRequestComment;
 use crate::domain::pull_request::{PullRequest, PullRequestState};
+use crate::domain::reaction::{ItemReactions, ReactionEmoji};
 use crate::domain::repository::Repository;
+use crate::domain::revision::ItemRevisions;
+use crate::domain::timeline::{ItemEventKind, TimelineItem};
 use askama::Template;
-use axum::extract::Path;
-use axum::response::Redirect;
+use axum::extract::{Path, State};
+use axum::response::{IntoResponse, Redirect, Response};
 use axum::Extension;
 
+use crate::view::repository::patch::{Patch, PatchFormat};
 use crate::view::repository::Tab;
 use gill_authorize_derive::authorized;
 use sqlx::PgPool;
@@ -26,7 +31,11 @@ pub struct PullRequestTemplate {
     pull_request: PullRequest,
     stats: RepositoryStats,
     current_branch: Option<String>,
-    comments: Vec<PullRequestComment>,
+    timeline: Vec<TimelineItem<PullRequestComment>>,
+    revisions: ItemRevisions,
+    reactions: ItemReactions,
+    /// The connected user is the pull request author or the repository owner
+    can_manage: bool,
     markdown_preview_form: MarkdownPreviewForm,
     tab: Tab,
 }
@@ -34,15 +43,47 @@ pub struct PullRequestTemplate {
 pub async fn view(
     user: Option<Oauth2User>,
     Extension(db): Extension<PgPool>,
-    Path((owner, repository, pull_request_number)): Path<(String, String, i32)>,
-) -> Result<HtmlTemplate<PullRequestTemplate>, AppError> {
-    let connected_username = get_connected_user_username(&db, user).await;
-    let stats = RepositoryStats::get(&owner, &repository, &db).await?;
+    Path((owner, repository, pull_request_number)): Path<(String, String, String)>,
+) -> Result<Response, AppError> {
+    let (pull_request_number, format) = PatchFormat::split(&pull_request_number);
+    let pull_request_number: i32 = pull_request_number
+        .parse()
+        .map_err(|_| AppError::NotFound)?;
     let repo = Repository::by_namespace(&owner, &repository, &db).await?;
     let pull_request = repo.get_pull_request(pull_request_number, &db).await?;
+
+    if let Some(format) = format {
+        let patch = match format {
+            PatchFormat::Patch => pull_request.get_patch(&owner, &repository)?,
+            PatchFormat::Diff => pull_request.get_raw_diff(&owner, &repository)?,
+        };
+
+        return Ok(Patch(patch).into_response());
+    }
+
+    let connected_username = get_connected_user_username(&db, user).await;
+    let stats =
+        RepositoryStats::get(&owner, &repository, connected_username.as_deref(), &db).await?;
     let comments = pull_request.get_comments(&db).await?;
+    let timeline = TimelineItem::for_item(
+        pull_request.repository_id,
+        pull_request.number,
+        comments,
+        |comment| comment.created_at,
+        &db,
+    )
+    .await?;
+    let revisions =
+        ItemRevisions::for_item(pull_request.repository_id, pull_request.number, &db).await?;
+    let reactions =
+        ItemReactions::for_item(pull_request.repository_id, pull_request.number, &db).await?;
     let current_branch = repo.get_default_branch(&db).await.map(|branch| branch.name);
 
+    let can_manage = connected_username
+        .as_deref()
+        .map(|username| username == owner || username == pull_request.opened_by)
+        .unwrap_or(false);
+
     let action_href = format!(
         "/{owner}/{repository}/pulls/{}/comment",
         pull_request.number
@@ -55,7 +96,10 @@ pub async fn view(
         pull_request,
         stats,
         current_branch,
-        comments,
+        timeline,
+        revisions,
+        reactions,
+        can_manage,
         markdown_preview_form: MarkdownPreviewForm {
             with_title: false,
             action_href,
@@ -64,18 +108,20 @@ pub async fn view(
             repository,
         },
         tab: Tab::PullRequests,
-    }))
+    })
+    .into_response())
 }
 
 #[authorized]
 pub async fn rebase(
     user: Option<Oauth2User>,
+    State(state): State<AppState>,
     Extension(db): Extension<PgPool>,
     Path((owner, repository, pull_request_number)): Path<(String, String, i32)>,
 ) -> AppResult<Redirect> {
     Repository::by_namespace(&owner, &repository, &db)
         .await?
-        .rebase(&user, &owner, pull_request_number, &db)
+        .rebase(&user, &owner, pull_request_number, &state.instance)
         .await?;
 
     Ok(Redirect::to(&format!(
@@ -86,12 +132,13 @@ pub async fn rebase(
 #[authorized]
 pub async fn merge(
     user: Option<Oauth2User>,
+    State(state): State<AppState>,
     Extension(db): Extension<PgPool>,
     Path((owner, repository, pull_request_number)): Path<(String, String, i32)>,
 ) -> Result<Redirect, AppError> {
     Repository::by_namespace(&owner, &repository, &db)
         .await?
-        .merge(&user, &owner, pull_request_number, &db)
+        .merge(&user, &owner, pull_request_number, &state.instance)
         .await?;
 
     Ok(Redirect::to(&format!(
@@ -0,0 +1,116 @@
+use crate::domain::reaction::{Reaction, ReactionEmoji, ReactionTarget};
+use crate::domain::repository::Repository;
+use crate::error::{AppError, AppResult};
+use crate::get_connected_user;
+use crate::oauth::Oauth2User;
+use crate::state::AppState;
+use axum::extract::{Path, State};
+use axum::response::Redirect;
+use axum::{Extension, Form};
+use gill_authorize_derive::authorized;
+use serde::Deserialize;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+#[derive(Deserialize, Debug)]
+pub struct ReactionForm {
+    /// One of the `ReactionEmoji` names
+    pub emoji: String,
+}
+
+impl ReactionForm {
+    fn reaction(
+        &self,
+        repository: &Repository,
+        number: i32,
+        target: ReactionTarget,
+    ) -> AppResult<Reaction> {
+        let emoji = ReactionEmoji::from_name(&self.emoji).ok_or(AppError::NotFound)?;
+        Ok(Reaction {
+            repository_id: repository.id,
+            number,
+            target,
+            emoji,
+        })
+    }
+}
+
+#[authorized]
+pub async fn react_to_issue(
+    user: Option<Oauth2User>,
+    State(state): State<AppState>,
+    Extension(db): Extension<PgPool>,
+    Path((owner, repository, number)): Path<(String, String, i32)>,
+    Form(input): Form<ReactionForm>,
+) -> AppResult<Redirect> {
+    let target = ReactionTarget::Issue;
+    let repo = Repository::by_namespace(&owner, &repository, &db).await?;
+    input
+        .reaction(&repo, number, target)?
+        .toggle(&user, &repo, &state.instance)
+        .await?;
+
+    Ok(Redirect::to(&format!(
+        "/{owner}/{repository}/issues/{number}"
+    )))
+}
+
+#[authorized]
+pub async fn react_to_issue_comment(
+    user: Option<Oauth2User>,
+    State(state): State<AppState>,
+    Extension(db): Extension<PgPool>,
+    Path((owner, repository, number, comment_id)): Path<(String, String, i32, String)>,
+    Form(input): Form<ReactionForm>,
+) -> AppResult<Redirect> {
+    let target = ReactionTarget::IssueComment(Uuid::parse_str(&comment_id)?);
+    let repo = Repository::by_namespace(&owner, &repository, &db).await?;
+    input
+        .reaction(&repo, number, target)?
+        .toggle(&user, &repo, &state.instance)
+        .await?;
+
+    Ok(Redirect::to(&format!(
+        "/{owner}/{repository}/issues/{number}"
+    )))
+}
+
+#[authorized]
+pub async fn react_to_pull_request(
+    user: Option<Oauth2User>,
+    State(state): State<AppState>,
+    Extension(db): Extension<PgPool>,
+    Path((owner, repository, number)): Path<(String, String, i32)>,
+    Form(input): Form<ReactionForm>,
+) -> AppResult<Redirect> {
+    let target = ReactionTarget::PullRequest;
+    let repo = Repository::by_namespace(&owner, &repository, &db).await?;
+    input
+        .reaction(&repo, number, target)?
+        .toggle(&user, &repo, &state.instance)
+        .await?;
+
+    Ok(Redirect::to(&format!(
+        "/{owner}/{repository}/pulls/{number}"
+    )))
+}
+
+#[authorized]
+pub async fn react_to_pull_request_comment(
+    user: Option<Oauth2User>,
+    State(state): State<AppState>,
+    Extension(db): Extension<PgPool>,
+    Path((owner, repository, number, comment_id)): Path<(String, String, i32, i32)>,
+    Form(input): Form<ReactionForm>,
+) -> AppResult<Redirect> {
+    let target = ReactionTarget::PullRequestComment(comment_id);
+    let repo = Repository::by_namespace(&owner, &repository, &db).await?;
+    input
+        .reaction(&repo, number, target)?
+        .toggle(&user, &repo, &state.instance)
+        .await?;
+
+    Ok(Redirect::to(&format!(
+        "/{owner}/{repository}/pulls/{number}"
+    )))
+}
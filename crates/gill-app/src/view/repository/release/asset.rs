@@ -0,0 +1,32 @@
+use crate::domain::repository::Repository;
+use crate::error::AppResult;
+use crate::view::repository::attachment::content_disposition;
+use axum::extract::Path;
+use axum::http::header;
+use axum::response::{IntoResponse, Response};
+use axum::Extension;
+use sqlx::PgPool;
+
+pub async fn download(
+    Extension(db): Extension<PgPool>,
+    Path((owner, repository, tag, filename)): Path<(String, String, String, String)>,
+) -> AppResult<Response> {
+    let repo = Repository::by_namespace(&owner, &repository, &db).await?;
+    let release = repo.get_release(&tag, &db).await?;
+    let asset = release.get_asset(&filename, &db).await?;
+    let data = release.read_asset(&owner, &repository, &asset)?;
+
+    // Never serve the uploader's content type, a html asset would run on our origin
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/octet-stream".to_string()),
+            (header::X_CONTENT_TYPE_OPTIONS, "nosniff".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                content_disposition(&asset.filename),
+            ),
+        ],
+        data,
+    )
+        .into_response())
+}
@@ -0,0 +1,131 @@
+use crate::domain::release::create::{CreateReleaseCommand, ReleaseAssetUpload};
+use crate::domain::repository::stats::RepositoryStats;
+use crate::domain::repository::Repository;
+use crate::error::{AppError, AppResult};
+use crate::oauth::Oauth2User;
+use crate::state::AppState;
+use crate::view::repository::Tab;
+use crate::view::HtmlTemplate;
+use crate::{get_connected_user, get_connected_user_username};
+use askama::Template;
+use axum::extract::{Multipart, Path, State};
+use axum::response::Redirect;
+use axum::Extension;
+use gill_authorize_derive::authorized;
+use sqlx::PgPool;
+use tokio::fs::File;
+use tokio::io::AsyncWriteExt;
+
+#[derive(Template, Debug)]
+#[template(path = "repository/releases/create.html")]
+pub struct CreateReleaseTemplate {
+    user: Option<String>,
+    owner: String,
+    repository: String,
+    tags: Vec<String>,
+    stats: RepositoryStats,
+    current_branch: Option<String>,
+    tab: Tab,
+}
+
+pub async fn new_release(
+    user: Option<Oauth2User>,
+    Extension(db): Extension<PgPool>,
+    Path((owner, repository)): Path<(String, String)>,
+) -> AppResult<HtmlTemplate<CreateReleaseTemplate>> {
+    let connected_username = get_connected_user_username(&db, user).await;
+    if connected_username.as_deref() != Some(owner.as_str()) {
+        return Err(AppError::Unauthorized);
+    }
+
+    let stats =
+        RepositoryStats::get(&owner, &repository, connected_username.as_deref(), &db).await?;
+    let repo = Repository::by_namespace(&owner, &repository, &db).await?;
+    let tags = repo
+        .list_tags(i64::MAX, 0, &db)
+        .await?
+        .into_iter()
+        .map(|tag| tag.name)
+        .collect();
+    let current_branch = repo.get_default_branch(&db).await.map(|branch| branch.name);
+
+    Ok(HtmlTemplate(CreateReleaseTemplate {
+        user: connected_username,
+        owner,
+        repository,
+        tags,
+        stats,
+        current_branch,
+        tab: Tab::Releases,
+    }))
+}
+
+#[authorized]
+pub async fn create(
+    user: Option<Oauth2User>,
+    Path((owner, repository)): Path<(String, String)>,
+    State(state): State<AppState>,
+    Extension(db): Extension<PgPool>,
+    mut multipart: Multipart,
+) -> AppResult<Redirect> {
+    // Check ownership before any upload is written to disk
+    if user.username != owner {
+        return Err(AppError::Unauthorized);
+    }
+
+    let mut tag = None;
+    let mut title = None;
+    let mut content = String::new();
+    let mut assets = vec![];
+
+    while let Some(mut field) = multipart.next_field().await? {
+        let name = field.name().map(ToString::to_string);
+        match name.as_deref() {
+            Some("tag") => tag = Some(field.text().await?),
+            Some("title") => title = Some(field.text().await?),
+            Some("content") => content = field.text().await?,
+            Some("assets") => {
+                let filename = field.file_name().map(ToString::to_string);
+                let content_type = field
+                    .content_type()
+                    .unwrap_or("application/octet-stream")
+                    .to_string();
+
+                // Browsers send an empty part when no file was selected
+                let Some(filename) = filename.filter(|filename| !filename.is_empty()) else {
+                    continue;
+                };
+
+                let mut upload =
+                    ReleaseAssetUpload::staged(&owner, &repository, filename, content_type)?;
+                let mut file = File::create(&upload.path).await?;
+                while let Some(chunk) = field.chunk().await? {
+                    file.write_all(&chunk).await?;
+                    upload.size += chunk.len() as i64;
+                }
+
+                file.flush().await?;
+                assets.push(upload);
+            }
+            _ => {}
+        }
+    }
+
+    let tag = tag.ok_or_else(|| anyhow::anyhow!("Missing release tag"))?;
+    let title = title
+        .filter(|title| !title.is_empty())
+        .unwrap_or_else(|| tag.clone());
+
+    CreateReleaseCommand {
+        owner: &owner,
+        repository: &repository,
+        tag: &tag,
+        title: &title,
+        content: &content,
+        assets,
+    }
+    .execute(&user, &state.instance)
+    .await?;
+
+    Ok(Redirect::to(&format!("/{owner}/{repository}/releases")))
+}
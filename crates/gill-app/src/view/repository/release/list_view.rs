@@ -0,0 +1,126 @@
+use crate::domain::release::{Release, ReleaseAsset};
+use crate::domain::repository::stats::RepositoryStats;
+use crate::domain::repository::Repository;
+use crate::domain::user::User;
+use crate::error::AppResult;
+use crate::get_connected_user_username;
+use crate::oauth::Oauth2User;
+use crate::view::repository::Tab;
+use crate::view::HtmlTemplate;
+use askama::Template;
+use axum::extract::Path;
+use axum::Extension;
+use sqlx::PgPool;
+
+#[derive(Debug)]
+pub struct ReleaseDto {
+    tag: String,
+    title: String,
+    author: String,
+    published: String,
+    content: String,
+    is_latest: bool,
+    assets: Vec<ReleaseAssetDto>,
+}
+
+#[derive(Debug)]
+pub struct ReleaseAssetDto {
+    filename: String,
+    size: String,
+}
+
+impl From<ReleaseAsset> for ReleaseAssetDto {
+    fn from(asset: ReleaseAsset) -> Self {
+        Self {
+            filename: asset.filename,
+            size: human_readable_size(asset.size),
+        }
+    }
+}
+
+#[derive(Template, Debug)]
+#[template(path = "repository/releases/list.html")]
+pub struct ReleasesTemplate {
+    user: Option<String>,
+    owner: String,
+    repository: String,
+    releases: Vec<ReleaseDto>,
+    stats: RepositoryStats,
+    current_branch: Option<String>,
+    tab: Tab,
+}
+
+pub async fn list_view(
+    user: Option<Oauth2User>,
+    Extension(db): Extension<PgPool>,
+    Path((owner, repository)): Path<(String, String)>,
+) -> AppResult<HtmlTemplate<ReleasesTemplate>> {
+    let connected_username = get_connected_user_username(&db, user).await;
+    let stats =
+        RepositoryStats::get(&owner, &repository, connected_username.as_deref(), &db).await?;
+    let repo = Repository::by_namespace(&owner, &repository, &db).await?;
+    let current_branch = repo.get_default_branch(&db).await.map(|branch| branch.name);
+    let mut releases = vec![];
+
+    for (idx, release) in repo
+        .list_releases(20, 0, &db)
+        .await?
+        .into_iter()
+        .enumerate()
+    {
+        let release = to_dto(release, idx == 0, &owner, &repository, &db).await?;
+        releases.push(release);
+    }
+
+    Ok(HtmlTemplate(ReleasesTemplate {
+        user: connected_username,
+        owner,
+        repository,
+        releases,
+        stats,
+        current_branch,
+        tab: Tab::Releases,
+    }))
+}
+
+async fn to_dto(
+    release: Release,
+    is_latest: bool,
+    owner: &str,
+    repository: &str,
+    db: &PgPool,
+) -> AppResult<ReleaseDto> {
+    let author = User::by_activity_pub_id(&release.attributed_to.to_string(), db).await?;
+    let assets = release
+        .list_assets(db)
+        .await?
+        .into_iter()
+        .map(ReleaseAssetDto::from)
+        .collect();
+
+    Ok(ReleaseDto {
+        content: gill_markdown::render(&release.content, owner, repository),
+        tag: release.tag,
+        title: release.title,
+        author: author.username,
+        published: release.published.format("%Y-%m-%d").to_string(),
+        is_latest,
+        assets,
+    })
+}
+
+fn human_readable_size(size: i64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut size = size as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{size} {}", UNITS[unit])
+    } else {
+        format!("{size:.1} {}", UNITS[unit])
+    }
+}
@@ -0,0 +1,31 @@
+use crate::state::AppState;
+use crate::view::repository::release::asset::download;
+use crate::view::repository::release::create::{create, new_release};
+use crate::view::repository::release::list_view::list_view;
+use crate::view::repository::release::tags::tags;
+use axum::extract::DefaultBodyLimit;
+use axum::routing::{get, post};
+use axum::Router;
+
+pub mod asset;
+pub mod create;
+pub mod list_view;
+pub mod tags;
+
+/// Maximum size of a release creation request, assets included
+const RELEASE_MAX_UPLOAD_SIZE: usize = 256 * 1024 * 1024;
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/:owner/:repository/tags", get(tags))
+        .route("/:owner/:repository/releases", get(list_view))
+        .route("/:owner/:repository/releases/new", get(new_release))
+        .route(
+            "/:owner/:repository/releases/create",
+            post(create).layer(DefaultBodyLimit::max(RELEASE_MAX_UPLOAD_SIZE)),
+        )
+        .route(
+            "/:owner/:repository/releases/download/:tag/:filename",
+            get(download),
+        )
+}
@@ -0,0 +1,75 @@
+use crate::domain::release::Release;
+use crate::domain::repository::stats::RepositoryStats;
+use crate::domain::repository::tag::Tag;
+use crate::domain::repository::Repository;
+use crate::error::AppResult;
+use crate::get_connected_user_username;
+use crate::oauth::Oauth2User;
+use crate::view::filters;
+use crate::view::repository::Tab;
+use crate::view::HtmlTemplate;
+use askama::Template;
+use axum::extract::Path;
+use axum::Extension;
+use sqlx::PgPool;
+
+#[derive(Debug)]
+pub struct TagDto {
+    name: String,
+    target: String,
+    created_at: String,
+    has_release: bool,
+}
+
+#[derive(Template, Debug)]
+#[template(path = "repository/releases/tags.html")]
+pub struct TagsTemplate {
+    user: Option<String>,
+    owner: String,
+    repository: String,
+    tags: Vec<TagDto>,
+    stats: RepositoryStats,
+    current_branch: Option<String>,
+    tab: Tab,
+}
+
+impl TagDto {
+    fn from(tag: Tag, releases: &[Release]) -> Self {
+        let has_release = releases.iter().any(|release| release.tag == tag.name);
+        Self {
+            name: tag.name,
+            target: tag.target,
+            created_at: tag.created_at.format("%Y-%m-%d").to_string(),
+            has_release,
+        }
+    }
+}
+
+pub async fn tags(
+    user: Option<Oauth2User>,
+    Extension(db): Extension<PgPool>,
+    Path((owner, repository)): Path<(String, String)>,
+) -> AppResult<HtmlTemplate<TagsTemplate>> {
+    let connected_username = get_connected_user_username(&db, user).await;
+    let stats =
+        RepositoryStats::get(&owner, &repository, connected_username.as_deref(), &db).await?;
+    let repo = Repository::by_namespace(&owner, &repository, &db).await?;
+    let releases = repo.list_releases(i64::MAX, 0, &db).await?;
+    let tags = repo
+        .list_tags(i64::MAX, 0, &db)
+        .await?
+        .into_iter()
+        .map(|tag| TagDto::from(tag, &releases))
+        .collect();
+    let current_branch = repo.get_default_branch(&db).await.map(|branch| branch.name);
+
+    Ok(HtmlTemplate(TagsTemplate {
+        user: connected_username,
+        owner,
+        repository,
+        tags,
+        stats,
+        current_branch,
+        tab: Tab::Releases,
+    }))
+}
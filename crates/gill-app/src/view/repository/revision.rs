@@ -0,0 +1,27 @@
+use crate::domain::repository::Repository;
+use crate::domain::revision::{DiffLine, DiffLineKind, Revision};
+use crate::error::AppResult;
+use crate::view::HtmlTemplate;
+use askama::Template;
+use axum::extract::Path;
+use axum::Extension;
+use sqlx::PgPool;
+
+#[derive(Template)]
+#[template(path = "repository/components/revision-diff.html")]
+pub struct RevisionDiffTemplate {
+    diff: Vec<DiffLine>,
+}
+
+/// Diff of a single edit, fetched when its revision list is expanded
+pub async fn diff(
+    Path((owner, repository, id)): Path<(String, String, i32)>,
+    Extension(db): Extension<PgPool>,
+) -> AppResult<HtmlTemplate<RevisionDiffTemplate>> {
+    let repo = Repository::by_namespace(&owner, &repository, &db).await?;
+    let revision = Revision::by_id(repo.id, id, &db).await?;
+
+    Ok(HtmlTemplate(RevisionDiffTemplate {
+        diff: revision.diff(),
+    }))
+}
@@ -0,0 +1,146 @@
+use crate::domain::repository::push_mirror::{
+    PushMirror, PushMirrorCredential, PushMirrorCredentialKind, PushMirrorState,
+};
+use crate::domain::repository::stats::RepositoryStats;
+use crate::domain::repository::Repository;
+use crate::error::{AppError, AppResult};
+use crate::get_connected_user;
+use crate::oauth::Oauth2User;
+use crate::view::repository::Tab;
+use crate::view::HtmlTemplate;
+use anyhow::anyhow;
+use askama::Template;
+use axum::extract::Path;
+use axum::response::Redirect;
+use axum::{Extension, Form};
+use gill_authorize_derive::authorized;
+use serde::Deserialize;
+use sqlx::PgPool;
+
+#[derive(Debug)]
+pub struct PushMirrorDto {
+    id: i32,
+    remote_url: String,
+    credential: &'static str,
+    state: &'static str,
+    last_pushed_at: Option<String>,
+    last_error: Option<String>,
+}
+
+impl From<PushMirror> for PushMirrorDto {
+    fn from(mirror: PushMirror) -> Self {
+        let credential = match mirror.credential_kind {
+            PushMirrorCredentialKind::None => "None",
+            PushMirrorCredentialKind::Token => "Token",
+            PushMirrorCredentialKind::DeployKey => "Deploy key",
+        };
+
+        let state = match mirror.state {
+            PushMirrorState::Idle => "Idle",
+            PushMirrorState::Pending => "Pending",
+            PushMirrorState::Success => "Success",
+            PushMirrorState::Failed => "Failed",
+        };
+
+        Self {
+            id: mirror.id,
+            remote_url: mirror.remote_url,
+            credential,
+            state,
+            last_pushed_at: mirror
+                .last_pushed_at
+                .map(|date| date.format("%Y-%m-%d %H:%M").to_string()),
+            last_error: mirror.last_error,
+        }
+    }
+}
+
+#[derive(Template, Debug)]
+#[template(path = "repository/settings.html")]
+pub struct SettingsTemplate {
+    user: Option<String>,
+    owner: String,
+    repository: String,
+    push_mirrors: Vec<PushMirrorDto>,
+    stats: RepositoryStats,
+    current_branch: Option<String>,
+    tab: Tab,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct AddPushMirrorForm {
+    pub remote_url: String,
+    pub credential_kind: String,
+    pub credential: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct DeletePushMirrorForm {
+    pub id: i32,
+}
+
+#[authorized]
+pub async fn view(
+    user: Option<Oauth2User>,
+    Extension(db): Extension<PgPool>,
+    Path((owner, repository)): Path<(String, String)>,
+) -> AppResult<HtmlTemplate<SettingsTemplate>> {
+    let stats = RepositoryStats::get(&owner, &repository, Some(&user.username), &db).await?;
+    let repo = Repository::by_namespace(&owner, &repository, &db).await?;
+    let push_mirrors = repo
+        .list_push_mirrors(&user, &db)
+        .await?
+        .into_iter()
+        .map(PushMirrorDto::from)
+        .collect();
+
+    let current_branch = repo.get_default_branch(&db).await.map(|branch| branch.name);
+
+    Ok(HtmlTemplate(SettingsTemplate {
+        user: Some(user.username),
+        owner,
+        repository,
+        push_mirrors,
+        stats,
+        current_branch,
+        tab: Tab::Settings,
+    }))
+}
+
+/// Credentials are sent in the request body so they don't end up in access logs
+#[authorized]
+pub async fn add_push_mirror(
+    user: Option<Oauth2User>,
+    Extension(db): Extension<PgPool>,
+    Path((owner, repository)): Path<(String, String)>,
+    Form(input): Form<AddPushMirrorForm>,
+) -> AppResult<Redirect> {
+    let secret = input
+        .credential
+        .map(|credential| credential.trim().to_string())
+        .filter(|credential| !credential.is_empty());
+
+    let credential = match (input.credential_kind.as_str(), secret) {
+        ("none", _) => PushMirrorCredential::None,
+        ("token", Some(token)) => PushMirrorCredential::Token(token),
+        ("deploy_key", Some(key)) => PushMirrorCredential::DeployKey(key),
+        (kind, _) => return Err(anyhow!("Missing credential for push mirror ({kind})").into()),
+    };
+
+    let repo = Repository::by_namespace(&owner, &repository, &db).await?;
+    repo.add_push_mirror(&user, input.remote_url.trim(), credential, &db)
+        .await?;
+    Ok(Redirect::to(&format!("/{owner}/{repository}/settings")))
+}
+
+#[authorized]
+pub async fn delete_push_mirror(
+    user: Option<Oauth2User>,
+    Extension(db): Extension<PgPool>,
+    Path((owner, repository)): Path<(String, String)>,
+    Form(input): Form<DeletePushMirrorForm>,
+) -> AppResult<Redirect> {
+    let repo = Repository::by_namespace(&owner, &repository, &db).await?;
+    repo.delete_push_mirror(&user, input.id, &db).await?;
+    Ok(Redirect::to(&format!("/{owner}/{repository}/settings")))
+}
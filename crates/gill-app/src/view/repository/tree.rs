@@ -9,12 +9,16 @@ use axum::Extension;
 
 use gill_git::traversal::{BlobInfo, TreeEntry, TreeInfo};
 
+use crate::domain::repository::docs::{Readme, RepositoryDocs};
 use crate::domain::repository::stats::RepositoryStats;
 use crate::get_connected_user_username;
 
 use crate::domain::repository::Repository;
 use sqlx::PgPool;
 
+// Needed in template
+use crate::domain::repository::docs::ReadmeContent;
+
 #[derive(Debug)]
 struct TreeDto {
     // TODO
@@ -91,7 +95,10 @@ pub struct GitTreeTemplate {
     owner: String,
     stats: RepositoryStats,
     tree: TreeDto,
-    readme: Option<String>,
+    readme: Option<Readme>,
+    /// Community files, only looked up at the repository root
+    docs: RepositoryDocs,
+    latest_release: Option<String>,
     branches: Vec<BranchDto>,
     current_branch: Option<String>,
     user: Option<String>,
@@ -155,7 +162,8 @@ pub async fn root(
 ) -> AppResult<DynHtmlTemplate<Box<dyn DynTemplate>>> {
     let connected_username = get_connected_user_username(&db, user).await;
     let repo = Repository::by_namespace(&owner, &repository, &db).await?;
-    let stats = RepositoryStats::get(&owner, &repository, &db).await?;
+    let stats =
+        RepositoryStats::get(&owner, &repository, connected_username.as_deref(), &db).await?;
 
     if repo.is_local {
         match repo.get_default_branch(&db).await {
@@ -174,6 +182,10 @@ pub async fn root(
                 Ok(DynHtmlTemplate(Box::new(template.inner())))
             }
         }
+    } else if let (Some(_), Some(branch)) = (&stats.mirror, repo.get_default_branch(&db).await) {
+        let template =
+            imp::get_tree_root(&owner, &repository, branch.name, connected_username, &db).await?;
+        Ok(DynHtmlTemplate(Box::new(template.inner())))
     } else {
         Ok(DynHtmlTemplate(Box::new(FederatedRepositoryTemplate {
             repository: repository.to_string(),
@@ -190,11 +202,12 @@ mod imp {
     use crate::view::repository::tree::TreeDto;
     use crate::view::HtmlTemplate;
 
+    use crate::domain::repository::docs::{Readme, RepositoryDocs};
     use crate::domain::repository::stats::RepositoryStats;
+    use crate::domain::repository::Repository;
     use crate::view::repository::{get_repository_branches, Tab};
 
-    use gill_git::traversal::TreeEntry;
-    use gill_git::GitRepository;
+    use gill_markdown::Location;
     use sqlx::PgPool;
 
     pub(crate) async fn get_tree_root(
@@ -204,12 +217,16 @@ mod imp {
         connected_username: Option<String>,
         db: &PgPool,
     ) -> AppResult<HtmlTemplate<GitTreeTemplate>> {
-        let repo = GitRepository::open(owner, repository)?;
+        let repo = Repository::open_git(owner, repository, db).await?;
         let tree = repo.get_tree_for_path(Some(&current_branch), None)?;
-        let readme = get_readme(&tree, &repo, owner, repository);
+        let location = Location::file(owner, repository, &current_branch, "");
+        let readme = Readme::find(&tree, &repo, location)?;
+        let docs = RepositoryDocs::find(&tree, &repo)?;
         let tree = TreeDto::from(tree);
         let branches = get_repository_branches(owner, repository, &current_branch, db).await?;
-        let stats = RepositoryStats::get(owner, repository, db).await?;
+        let stats =
+            RepositoryStats::get(owner, repository, connected_username.as_deref(), db).await?;
+        let latest_release = get_latest_release(owner, repository, db).await?;
 
         let template = GitTreeTemplate {
             repository: repository.to_string(),
@@ -217,6 +234,8 @@ mod imp {
             stats,
             tree,
             readme,
+            docs,
+            latest_release,
             branches,
             current_branch: Some(current_branch),
             user: connected_username,
@@ -234,12 +253,16 @@ mod imp {
         connected_username: Option<String>,
         db: &PgPool,
     ) -> AppResult<HtmlTemplate<GitTreeTemplate>> {
-        let repo = GitRepository::open(&owner, &repository)?;
+        let repo = Repository::open_git(&owner, &repository, db).await?;
         let tree = repo.get_tree_for_path(Some(&current_branch), tree_path)?;
-        let readme = get_readme(&tree, &repo, &owner, &repository);
+        let directory = tree_path.unwrap_or_default();
+        let location = Location::file(&owner, &repository, &current_branch, directory);
+        let readme = Readme::find(&tree, &repo, location)?;
         let tree = TreeDto::from(tree);
         let branches = get_repository_branches(&owner, &repository, &current_branch, db).await?;
-        let stats = RepositoryStats::get(&owner, &repository, db).await?;
+        let stats =
+            RepositoryStats::get(&owner, &repository, connected_username.as_deref(), db).await?;
+        let latest_release = get_latest_release(&owner, &repository, db).await?;
 
         let template = GitTreeTemplate {
             repository,
@@ -247,6 +270,8 @@ mod imp {
             stats,
             tree,
             readme,
+            docs: RepositoryDocs::default(),
+            latest_release,
             branches,
             current_branch: Some(current_branch),
             user: connected_username,
@@ -256,16 +281,13 @@ mod imp {
         Ok(HtmlTemplate(template))
     }
 
-    pub fn get_readme(
-        tree: &TreeEntry,
-        repo: &GitRepository,
+    async fn get_latest_release(
         owner: &str,
-        repository_name: &str,
-    ) -> Option<String> {
-        tree.blobs
-            .iter()
-            .find(|blob| &blob.filename() == "README.md")
-            .and_then(|blob| repo.blob_str(blob).ok())
-            .map(|readme| gill_markdown::render(&readme, owner, repository_name))
+        repository: &str,
+        db: &PgPool,
+    ) -> AppResult<Option<String>> {
+        let repo = Repository::by_namespace(owner, repository, db).await?;
+        let release = repo.get_latest_release(db).await?;
+        Ok(release.map(|release| release.tag))
     }
 }
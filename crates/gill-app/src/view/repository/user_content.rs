@@ -7,25 +7,63 @@ use gill_git::traversal::BlobMime;
 use gill_git::GitRepository;
 use sqlx::PgPool;
 
+/// Images of the default branch, linked from documents which are not repository files
 pub async fn image(
     Path((owner, repository)): Path<(String, String)>,
     Path(path): Path<Vec<String>>,
     Extension(db): Extension<PgPool>,
 ) -> AppResult<Vec<u8>> {
     let path = path.last().unwrap();
-    let (tree, blob_name) = tree_and_blob_from_query(path);
-    let repo = GitRepository::open(&owner, &repository)?;
     let repo_entity = Repository::by_namespace(&owner, &repository, &db).await?;
     let branch = repo_entity
         .get_default_branch(&db)
         .await
         .ok_or(AppError::NotFound)?;
-    let tree = repo.get_tree_for_path(Some(&branch.name), tree)?;
+
+    image_bytes(&repo_entity, &owner, &repository, &branch.name, path)
+}
+
+/// Images of a branch, linked from a file of that branch. Branch names may contain slashes,
+/// the branch is the longest one prefixing the requested path.
+pub async fn image_at(
+    Path((owner, repository, path)): Path<(String, String, String)>,
+    Extension(db): Extension<PgPool>,
+) -> AppResult<Vec<u8>> {
+    let repo_entity = Repository::by_namespace(&owner, &repository, &db).await?;
+    let repo = GitRepository::open(&repo_entity.git_namespace(&owner), &repository)?;
+    let branches = repo.list_branches()?;
+    let (branch, path) = split_branch(&branches, &path).ok_or(AppError::NotFound)?;
+    image_bytes(&repo_entity, &owner, &repository, branch, path)
+}
+
+/// Split `path` into the longest branch of `branches` it starts with and the remaining path
+fn split_branch<'a>(branches: &'a [String], path: &'a str) -> Option<(&'a str, &'a str)> {
+    let path = path.trim_start_matches('/');
+    branches
+        .iter()
+        .filter_map(|branch| {
+            path.strip_prefix(branch.as_str())
+                .and_then(|rest| rest.strip_prefix('/'))
+                .map(|rest| (branch.as_str(), rest))
+        })
+        .max_by_key(|(branch, _)| branch.len())
+}
+
+fn image_bytes(
+    repo_entity: &Repository,
+    owner: &str,
+    repository: &str,
+    branch: &str,
+    path: &str,
+) -> AppResult<Vec<u8>> {
+    let (tree, blob_name) = tree_and_blob_from_query(path);
+    let repo = GitRepository::open(&repo_entity.git_namespace(owner), repository)?;
+    let tree = repo.get_tree_for_path(Some(branch), tree)?;
     let blob = tree
         .blobs
         .iter()
         .find(|blob| blob.filename() == blob_name)
-        .unwrap();
+        .ok_or(AppError::NotFound)?;
     let blob = match repo.blob_mime(blob) {
         BlobMime::Image => repo.blob_bytes(blob).ok(),
         _ => None,
@@ -33,3 +71,24 @@ pub async fn image(
 
     blob.ok_or(AppError::NotFound)
 }
+
+#[cfg(test)]
+mod test {
+    use super::split_branch;
+    use speculoos::prelude::*;
+
+    #[test]
+    fn should_resolve_longest_matching_branch() {
+        let branches = vec![
+            "feature".to_string(),
+            "feature/docs".to_string(),
+            "main".to_string(),
+        ];
+
+        assert_that!(split_branch(&branches, "feature/docs/img/logo.png"))
+            .is_equal_to(Some(("feature/docs", "img/logo.png")));
+        assert_that!(split_branch(&branches, "feature/logo.png"))
+            .is_equal_to(Some(("feature", "logo.png")));
+        assert_that!(split_branch(&branches, "unknown/logo.png")).is_none();
+    }
+}
@@ -0,0 +1,33 @@
+use crate::domain::user::User;
+use crate::error::{AppError, AppResult};
+use crate::get_connected_user;
+use crate::oauth::Oauth2User;
+use crate::state::AppState;
+use crate::view::repository::activity::ToggleState;
+use axum::extract::{Path, State};
+use axum::{Extension, Json};
+use gill_authorize_derive::authorized;
+use sqlx::PgPool;
+
+#[authorized]
+pub async fn follow(
+    State(state): State<AppState>,
+    user: Option<Oauth2User>,
+    Extension(db): Extension<PgPool>,
+    Path(owner): Path<String>,
+) -> AppResult<Json<ToggleState>> {
+    let followed = User::by_name(&owner, &db).await?;
+    if followed.id == user.id {
+        return Err(AppError::Unauthorized);
+    }
+
+    let active = if followed.is_followed_by(&user, &db).await? {
+        user.unfollow(&followed, &state.instance).await?;
+        false
+    } else {
+        user.follow(&followed, &state.instance).await?;
+        true
+    };
+
+    Ok(Json(ToggleState::new(active)))
+}
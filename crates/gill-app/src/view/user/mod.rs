@@ -1,7 +1,8 @@
 use crate::state::AppState;
-use axum::routing::get;
+use axum::routing::{get, post};
 use axum::Router;
 
+pub mod follow;
 pub mod profile;
 pub mod settings;
 pub mod ssh_key;
@@ -10,6 +11,7 @@ pub fn routes() -> Router<AppState> {
     Router::new()
         .route("/:owner", get(profile::user_view))
         .route("/:owner/", get(profile::user_view))
+        .route("/follow/:owner", post(follow::follow))
         .route("/settings/profile", get(settings::settings))
         .route("/settings/profile/add-ssh-key", get(ssh_key::add))
 }
@@ -8,7 +8,7 @@ use axum::Extension;
 use crate::view::dto::RepositoryDto;
 
 use crate::domain::user::User;
-use crate::get_connected_user_username;
+use crate::get_connected_user;
 use serde::Deserialize;
 use sqlx::PgPool;
 
@@ -39,6 +39,8 @@ pub struct UserPageTemplate {
     user: Option<String>,
     repositories: Vec<RepositoryDto>,
     stars: Vec<RepositoryDto>,
+    /// Whether the connected user can follow this profile and already does
+    follow: Option<bool>,
     // TODO
     _tab: Tab,
 }
@@ -66,13 +68,22 @@ pub async fn user_view(
         .map(RepositoryDto::from)
         .collect();
 
-    let username = get_connected_user_username(&db, connected_user).await;
+    let connected_user = get_connected_user(&db, connected_user).await;
+    let follow = match &connected_user {
+        Some(connected_user) if connected_user.id != user.id => {
+            Some(user.is_followed_by(connected_user, &db).await?)
+        }
+        _ => None,
+    };
+
+    let username = connected_user.map(|user| user.username);
 
     let template = UserPageTemplate {
         profile_username,
         user: username,
         repositories,
         stars,
+        follow,
         _tab: page.tab,
     };
 
@@ -0,0 +1,26 @@
+use serde_json::Value;
+use std::fs;
+use std::path::PathBuf;
+
+/// Load a payload captured from another forge under `tests/fixtures`.
+/// Hosts and ids are replaced by `{{name}}` placeholders, filled in from `vars`.
+pub fn load(name: &str, vars: &[(&str, &str)]) -> Value {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name);
+
+    let mut payload = fs::read_to_string(&path)
+        .unwrap_or_else(|err| panic!("Failed to read fixture {path:?}: {err}"));
+
+    for (key, value) in vars {
+        payload = payload.replace(&format!("{{{{{key}}}}}"), value);
+    }
+
+    assert!(
+        !payload.contains("{{"),
+        "Missing placeholder value for fixture {name}"
+    );
+
+    serde_json::from_str(&payload)
+        .unwrap_or_else(|err| panic!("Fixture {name} is not valid json: {err}"))
+}
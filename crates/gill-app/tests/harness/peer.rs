@@ -0,0 +1,274 @@
+use crate::harness::fixture;
+use activitypub_federation::core::activity_queue::send_activity;
+use activitypub_federation::core::signatures::{generate_actor_keypair, PublicKey};
+use activitypub_federation::data::Data;
+use activitypub_federation::traits::ActivityHandler;
+use activitypub_federation::{InstanceSettings, LocalInstance};
+use axum::async_trait;
+use axum::body::Bytes;
+use axum::extract::State;
+use axum::http::{header, HeaderMap, Method, StatusCode, Uri};
+use axum::response::{IntoResponse, Response};
+use axum::Router;
+use reqwest::Client;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::net::TcpListener;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use url::Url;
+
+/// Gill delivery worker polls its queue every few seconds
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// A remote ForgeFed instance: it serves actors and objects, signs the activities
+/// it sends with its actor keys and records everything posted to its inboxes.
+pub struct FakePeer {
+    pub domain: String,
+    state: PeerState,
+    local_instance: LocalInstance,
+}
+
+#[derive(Clone, Default)]
+struct PeerState {
+    objects: Arc<Mutex<HashMap<String, Value>>>,
+    deliveries: Arc<Mutex<Vec<InboxDelivery>>>,
+    inbox_down: Arc<AtomicBool>,
+}
+
+#[derive(Debug, Clone)]
+pub struct InboxDelivery {
+    pub inbox: String,
+    pub signature: Option<String>,
+    pub activity: Value,
+}
+
+impl InboxDelivery {
+    /// Whether the http signature was made with `actor` main key
+    pub fn is_signed_by(&self, actor: &str) -> bool {
+        self.signature
+            .as_deref()
+            .map(|signature| signature.contains(&format!("keyId=\"{actor}#main-key\"")))
+            .unwrap_or(false)
+    }
+}
+
+pub struct PeerActor {
+    pub id: Url,
+    pub inbox: Url,
+    public_key: PublicKey,
+    private_key: String,
+}
+
+impl FakePeer {
+    /// Start the peer on the scenario runtime
+    pub async fn start() -> FakePeer {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind peer listener");
+        let port = listener.local_addr().expect("listener address").port();
+        let domain = format!("localhost:{port}");
+        let state = PeerState::default();
+
+        let app = Router::new().fallback(handle).with_state(state.clone());
+        let server = axum::Server::from_tcp(listener)
+            .expect("peer server")
+            .serve(app.into_make_service());
+        tokio::spawn(server);
+
+        let settings = InstanceSettings::builder()
+            .debug(true)
+            .build()
+            .expect("peer settings");
+        let local_instance = LocalInstance::new(domain.clone(), Client::default().into(), settings);
+
+        FakePeer {
+            domain,
+            state,
+            local_instance,
+        }
+    }
+
+    pub fn base_url(&self) -> String {
+        format!("http://{}", self.domain)
+    }
+
+    pub fn url(&self, path: &str) -> Url {
+        Url::parse(&format!("{}{path}", self.base_url())).expect("valid url")
+    }
+
+    /// Serve the actor described by `fixture` at `path` with a freshly generated key pair.
+    /// `{{peer}}` and `{{actor}}` are available to the fixture along with `vars`.
+    pub fn actor(&self, fixture: &str, path: &str, vars: &[(&str, &str)]) -> PeerActor {
+        let id = self.url(path);
+        let base_url = self.base_url();
+        let id_str = id.to_string();
+        let mut all_vars = vec![("peer", base_url.as_str()), ("actor", id_str.as_str())];
+        all_vars.extend_from_slice(vars);
+
+        let keys = generate_actor_keypair().expect("actor keypair");
+        let mut actor = fixture::load(fixture, &all_vars);
+        actor["publicKey"]["publicKeyPem"] = Value::String(keys.public_key.clone());
+
+        let inbox = actor["inbox"]
+            .as_str()
+            .and_then(|inbox| Url::parse(inbox).ok())
+            .expect("actor fixture has an inbox");
+
+        self.serve(actor);
+
+        PeerActor {
+            public_key: PublicKey::new_main_key(id.clone(), keys.public_key),
+            private_key: keys.private_key,
+            id,
+            inbox,
+        }
+    }
+
+    /// Serve an object at the path of its id
+    pub fn serve(&self, object: Value) {
+        let id = object["id"]
+            .as_str()
+            .and_then(|id| Url::parse(id).ok())
+            .expect("object has an id");
+
+        self.state
+            .objects
+            .lock()
+            .unwrap()
+            .insert(id.path().to_string(), object);
+    }
+
+    /// Sign `activity` with `actor` key and post it to `inbox`
+    pub async fn send(&self, actor: &PeerActor, activity: Value, inbox: &Url) {
+        let activity = RawActivity {
+            id: activity["id"]
+                .as_str()
+                .and_then(|id| Url::parse(id).ok())
+                .expect("activity has an id"),
+            actor: actor.id.clone(),
+            payload: activity,
+        };
+
+        send_activity(
+            activity,
+            actor.public_key.clone(),
+            actor.private_key.clone(),
+            vec![inbox.clone()],
+            &self.local_instance,
+        )
+        .await
+        .expect("Failed to send activity");
+    }
+
+    /// Answer every inbox post with a server error from now on
+    pub fn take_inboxes_down(&self) {
+        self.state.inbox_down.store(true, Ordering::SeqCst);
+    }
+
+    /// Wait until gill posts an activity matching `predicate` to `inbox`
+    pub async fn wait_for_delivery<F>(&self, inbox: &Url, predicate: F) -> InboxDelivery
+    where
+        F: Fn(&Value) -> bool,
+    {
+        let deadline = Instant::now() + DELIVERY_TIMEOUT;
+        loop {
+            let delivery = self
+                .state
+                .deliveries
+                .lock()
+                .unwrap()
+                .iter()
+                .find(|delivery| delivery.inbox == inbox.path() && predicate(&delivery.activity))
+                .cloned();
+
+            if let Some(delivery) = delivery {
+                return delivery;
+            }
+
+            if Instant::now() > deadline {
+                panic!(
+                    "No matching delivery to {inbox} after {DELIVERY_TIMEOUT:?}, received: {:#?}",
+                    self.state.deliveries.lock().unwrap()
+                );
+            }
+
+            tokio::time::sleep(Duration::from_millis(200)).await;
+        }
+    }
+}
+
+async fn handle(
+    State(state): State<PeerState>,
+    method: Method,
+    uri: Uri,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    let path = uri.path().to_string();
+
+    if method == Method::POST && path.ends_with("/inbox") {
+        if state.inbox_down.load(Ordering::SeqCst) {
+            return StatusCode::SERVICE_UNAVAILABLE.into_response();
+        }
+
+        let Ok(activity) = serde_json::from_slice(&body) else {
+            return StatusCode::BAD_REQUEST.into_response();
+        };
+
+        let signature = headers
+            .get("signature")
+            .and_then(|signature| signature.to_str().ok())
+            .map(ToString::to_string);
+
+        state.deliveries.lock().unwrap().push(InboxDelivery {
+            inbox: path,
+            signature,
+            activity,
+        });
+
+        return StatusCode::ACCEPTED.into_response();
+    }
+
+    match state.objects.lock().unwrap().get(&path) {
+        Some(object) => (
+            [(header::CONTENT_TYPE, "application/activity+json")],
+            object.to_string(),
+        )
+            .into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+/// A fixture payload sent as is, the peer never receives activities through the library
+#[derive(Clone, Debug, Serialize)]
+#[serde(transparent)]
+struct RawActivity {
+    payload: Value,
+    #[serde(skip)]
+    id: Url,
+    #[serde(skip)]
+    actor: Url,
+}
+
+#[async_trait]
+impl ActivityHandler for RawActivity {
+    type DataType = ();
+    type Error = anyhow::Error;
+
+    fn id(&self) -> &Url {
+        &self.id
+    }
+
+    fn actor(&self) -> &Url {
+        &self.actor
+    }
+
+    async fn receive(
+        self,
+        _data: &Data<Self::DataType>,
+        _request_counter: &mut i32,
+    ) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
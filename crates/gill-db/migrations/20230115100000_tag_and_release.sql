@@ -0,0 +1,33 @@
+CREATE TABLE tag
+(
+    repository_id INT REFERENCES repository (id) NOT NULL,
+    name          VARCHAR(255)                   NOT NULL,
+    target        VARCHAR(40)                    NOT NULL,
+    created_at    TIMESTAMP                      NOT NULL DEFAULT now(),
+    PRIMARY KEY (name, repository_id)
+);
+
+CREATE TABLE release
+(
+    id              SERIAL PRIMARY KEY,
+    repository_id   INT REFERENCES repository (id)                  NOT NULL,
+    tag             VARCHAR(255)                                    NOT NULL,
+    activity_pub_id VARCHAR(255)                                    NOT NULL UNIQUE,
+    title           VARCHAR(255)                                    NOT NULL,
+    content         TEXT                                            NOT NULL,
+    media_type      VARCHAR(255)                                    NOT NULL,
+    attributed_to   VARCHAR(255) REFERENCES users (activity_pub_id) NOT NULL,
+    published       TIMESTAMP                                       NOT NULL DEFAULT now(),
+    is_local        BOOLEAN                                         NOT NULL,
+    CONSTRAINT unique_release_tag UNIQUE (tag, repository_id)
+);
+
+CREATE TABLE release_asset
+(
+    id           SERIAL PRIMARY KEY,
+    release_id   INT REFERENCES release (id) ON DELETE CASCADE NOT NULL,
+    filename     VARCHAR(255)                                  NOT NULL,
+    content_type VARCHAR(255)                                  NOT NULL,
+    size         BIGINT                                        NOT NULL,
+    CONSTRAINT unique_release_asset UNIQUE (release_id, filename)
+);
@@ -0,0 +1,12 @@
+CREATE TABLE push_event
+(
+    id            SERIAL PRIMARY KEY,
+    repository_id INT REFERENCES repository (id) NOT NULL,
+    pushed_by     INT REFERENCES users (id),
+    ref_name      VARCHAR(255)                   NOT NULL,
+    before        VARCHAR(40)                    NOT NULL,
+    after         VARCHAR(40)                    NOT NULL,
+    created_at    TIMESTAMP                      NOT NULL DEFAULT now()
+);
+
+CREATE INDEX push_event_repository_idx ON push_event (repository_id, created_at DESC);
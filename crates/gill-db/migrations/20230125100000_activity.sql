@@ -0,0 +1,11 @@
+CREATE TABLE activity
+(
+    id              SERIAL PRIMARY KEY,
+    activity_pub_id VARCHAR(255) UNIQUE NOT NULL,
+    actor           VARCHAR(255)        NOT NULL,
+    kind            VARCHAR(64)         NOT NULL,
+    payload         JSONB               NOT NULL,
+    published       TIMESTAMP           NOT NULL DEFAULT now()
+);
+
+CREATE INDEX activity_actor_idx ON activity (actor, id DESC);
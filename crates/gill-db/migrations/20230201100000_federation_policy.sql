@@ -0,0 +1,19 @@
+CREATE TYPE federation_mode AS ENUM ('Blocklist', 'Allowlist');
+CREATE TYPE domain_policy AS ENUM ('Allowed', 'Blocked', 'Silenced');
+
+-- Single row table holding instance wide federation settings
+CREATE TABLE federation_settings
+(
+    id   BOOLEAN PRIMARY KEY      DEFAULT TRUE CHECK (id),
+    mode federation_mode NOT NULL DEFAULT 'Blocklist'
+);
+
+INSERT INTO federation_settings DEFAULT VALUES;
+
+CREATE TABLE domain_rule
+(
+    domain     VARCHAR(255) PRIMARY KEY,
+    policy     domain_policy NOT NULL,
+    reason     TEXT,
+    created_at TIMESTAMP     NOT NULL DEFAULT now()
+);
@@ -0,0 +1,30 @@
+-- Delivered activities are removed from the queue, failed ones are kept until retried
+CREATE TYPE delivery_state AS ENUM ('Pending', 'Failed');
+
+-- Outgoing activities waiting to be posted to a remote inbox
+CREATE TABLE delivery
+(
+    id              SERIAL PRIMARY KEY,
+    activity_id     VARCHAR(255)   NOT NULL,
+    -- Local actor whose key signs the request
+    sender          VARCHAR(255)   NOT NULL,
+    inbox           VARCHAR(255)   NOT NULL,
+    domain          VARCHAR(255)   NOT NULL,
+    payload         JSONB          NOT NULL,
+    state           delivery_state NOT NULL DEFAULT 'Pending',
+    attempts        INT            NOT NULL DEFAULT 0,
+    last_error      TEXT,
+    next_attempt_at TIMESTAMP      NOT NULL DEFAULT now(),
+    created_at      TIMESTAMP      NOT NULL DEFAULT now(),
+    updated_at      TIMESTAMP      NOT NULL DEFAULT now()
+);
+
+CREATE INDEX delivery_due_idx ON delivery (next_attempt_at) WHERE state = 'Pending';
+
+-- Circuit breaker state for remote domains, deliveries to an open domain are postponed
+CREATE TABLE delivery_domain
+(
+    domain               VARCHAR(255) PRIMARY KEY,
+    consecutive_failures INT NOT NULL DEFAULT 0,
+    open_until           TIMESTAMP
+);
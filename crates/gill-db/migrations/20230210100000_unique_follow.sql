@@ -0,0 +1,8 @@
+-- A user can follow another user only once, this lets unfollow remove a single row
+DELETE
+FROM user_follow a USING user_follow b
+WHERE a.id > b.id
+  AND a.user_id = b.user_id
+  AND a.follower_id = b.follower_id;
+
+CREATE UNIQUE INDEX user_follow_unique_idx ON user_follow (user_id, follower_id);
@@ -0,0 +1,14 @@
+-- Repositories whose git data is fetched from a remote url instead of being pushed to
+CREATE TABLE repository_mirror
+(
+    repository_id  INT PRIMARY KEY REFERENCES repository (id) ON DELETE CASCADE,
+    remote_url     VARCHAR(2048) NOT NULL,
+    -- Seconds between two fetches
+    sync_interval  INT           NOT NULL DEFAULT 3600,
+    last_synced_at TIMESTAMP,
+    last_error     TEXT,
+    next_sync_at   TIMESTAMP     NOT NULL DEFAULT now(),
+    created_at     TIMESTAMP     NOT NULL DEFAULT now()
+);
+
+CREATE INDEX repository_mirror_due_idx ON repository_mirror (next_sync_at);
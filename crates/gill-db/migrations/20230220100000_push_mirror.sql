@@ -0,0 +1,24 @@
+CREATE TYPE push_mirror_credential AS ENUM ('None', 'Token', 'DeployKey');
+CREATE TYPE push_mirror_state AS ENUM ('Idle', 'Pending', 'Success', 'Failed');
+
+-- Remotes a repository is pushed to after each push it receives
+CREATE TABLE push_mirror
+(
+    id                   SERIAL PRIMARY KEY,
+    repository_id        INT REFERENCES repository (id) ON DELETE CASCADE NOT NULL,
+    remote_url           VARCHAR(2048)                                    NOT NULL,
+    credential_kind      push_mirror_credential                           NOT NULL DEFAULT 'None',
+    -- Token or deploy key, encrypted with the instance secret key
+    encrypted_credential TEXT,
+    state                push_mirror_state                                NOT NULL DEFAULT 'Idle',
+    last_pushed_at       TIMESTAMP,
+    last_error           TEXT,
+    -- Last time a push to the repository requested a mirror push
+    requested_at         TIMESTAMP,
+    -- Null when there is nothing left to push
+    next_push_at         TIMESTAMP,
+    created_at           TIMESTAMP                                        NOT NULL DEFAULT now(),
+    CONSTRAINT Unique_Push_Mirror_Remote UNIQUE (repository_id, remote_url)
+);
+
+CREATE INDEX push_mirror_due_idx ON push_mirror (next_push_at) WHERE next_push_at IS NOT NULL;
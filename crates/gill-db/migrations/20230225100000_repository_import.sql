@@ -0,0 +1,39 @@
+CREATE TABLE label
+(
+    id            SERIAL PRIMARY KEY,
+    repository_id INT REFERENCES repository (id) ON DELETE CASCADE NOT NULL,
+    name          VARCHAR(255)                                     NOT NULL,
+    color         VARCHAR(7)                                       NOT NULL DEFAULT '#cbd5e1',
+    CONSTRAINT Unique_Label_Name UNIQUE (repository_id, name)
+);
+
+-- Issues and pull requests share their numbering, `number` refers to either of them
+CREATE TABLE issue_label
+(
+    repository_id INT REFERENCES repository (id) ON DELETE CASCADE NOT NULL,
+    number        INT                                              NOT NULL,
+    label_id      INT REFERENCES label (id) ON DELETE CASCADE      NOT NULL,
+    PRIMARY KEY (repository_id, number, label_id)
+);
+
+CREATE TYPE import_state AS ENUM ('Pending', 'Cloning', 'Importing', 'Done', 'Failed');
+
+-- Background import of a repository from a git url and an optional issue tracker dump
+CREATE TABLE repository_import
+(
+    repository_id  INT PRIMARY KEY REFERENCES repository (id) ON DELETE CASCADE,
+    remote_url     VARCHAR(2048) NOT NULL,
+    -- Issues, pull requests, comments and labels to import, cleared once done
+    dump           JSONB,
+    -- Source forge login to local user id
+    author_mapping JSONB         NOT NULL DEFAULT '{}',
+    state          import_state  NOT NULL DEFAULT 'Pending',
+    progress       INT           NOT NULL DEFAULT 0,
+    total          INT           NOT NULL DEFAULT 0,
+    last_error     TEXT,
+    created_at     TIMESTAMP     NOT NULL DEFAULT now(),
+    -- Bumped on each progress update, stale running imports are picked up again
+    updated_at     TIMESTAMP     NOT NULL DEFAULT now()
+);
+
+CREATE INDEX repository_import_running_idx ON repository_import (updated_at) WHERE state IN ('Pending', 'Cloning', 'Importing');
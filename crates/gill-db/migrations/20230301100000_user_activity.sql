@@ -0,0 +1,35 @@
+-- The placeholder table from the base schema was never used
+DROP TABLE user_activity;
+DROP TYPE activity_type;
+
+CREATE TYPE user_activity_kind AS ENUM (
+    'Push',
+    'OpenIssue',
+    'CloseIssue',
+    'ReopenIssue',
+    'CommentIssue',
+    'OpenPullRequest',
+    'MergePullRequest',
+    'ClosePullRequest',
+    'CommentPullRequest',
+    'Star',
+    'Fork'
+    );
+
+-- Activity log of local and remote users, the dashboard of a user lists
+-- the entries of the users they follow and the repositories they watch
+CREATE TABLE user_activity
+(
+    id            SERIAL PRIMARY KEY,
+    actor_id      INT REFERENCES users (id) ON DELETE CASCADE      NOT NULL,
+    repository_id INT REFERENCES repository (id) ON DELETE CASCADE NOT NULL,
+    kind          user_activity_kind                               NOT NULL,
+    -- Issue or pull request number
+    number        INT,
+    -- Issue or pull request title, pushed branch or fork name
+    summary       TEXT,
+    published     TIMESTAMP                                        NOT NULL DEFAULT now()
+);
+
+CREATE INDEX user_activity_actor_idx ON user_activity (actor_id, published DESC);
+CREATE INDEX user_activity_repository_idx ON user_activity (repository_id, published DESC);
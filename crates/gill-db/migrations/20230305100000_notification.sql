@@ -0,0 +1,31 @@
+CREATE TYPE notification_level AS ENUM ('Watching', 'Participating', 'MentionsOnly');
+
+CREATE TYPE notification_reason AS ENUM ('Watching', 'Participating', 'Mention');
+
+CREATE TYPE notification_subject AS ENUM ('Issue', 'PullRequest');
+
+CREATE TABLE notification_settings
+(
+    user_id      INT PRIMARY KEY REFERENCES users (id) ON DELETE CASCADE,
+    level        notification_level NOT NULL DEFAULT 'Participating',
+    email_digest BOOLEAN            NOT NULL DEFAULT false
+);
+
+CREATE TABLE notification
+(
+    id            SERIAL PRIMARY KEY,
+    recipient_id  INT REFERENCES users (id) ON DELETE CASCADE      NOT NULL,
+    actor_id      INT REFERENCES users (id) ON DELETE CASCADE      NOT NULL,
+    repository_id INT REFERENCES repository (id) ON DELETE CASCADE NOT NULL,
+    reason        notification_reason                              NOT NULL,
+    subject       notification_subject                             NOT NULL,
+    number        INT                                              NOT NULL,
+    title         TEXT                                             NOT NULL,
+    action        TEXT                                             NOT NULL,
+    created_at    TIMESTAMP                                        NOT NULL DEFAULT now(),
+    read_at       TIMESTAMP,
+    emailed_at    TIMESTAMP
+);
+
+CREATE INDEX notification_recipient_idx ON notification (recipient_id, created_at DESC);
+CREATE INDEX notification_unread_idx ON notification (recipient_id) WHERE read_at IS NULL;
@@ -0,0 +1,16 @@
+CREATE TYPE reference_source AS ENUM ('Issue', 'PullRequest');
+
+-- An issue mentioned as `#number` or `owner/repository#number` from another issue or pull request
+CREATE TABLE issue_reference
+(
+    id                   SERIAL PRIMARY KEY,
+    repository_id        INT                                              NOT NULL,
+    number               INT                                              NOT NULL,
+    source_repository_id INT REFERENCES repository (id) ON DELETE CASCADE NOT NULL,
+    source_number        INT                                              NOT NULL,
+    source               reference_source                                 NOT NULL,
+    actor_id             INT REFERENCES users (id) ON DELETE CASCADE      NOT NULL,
+    created_at           TIMESTAMP                                        NOT NULL DEFAULT now(),
+    CONSTRAINT issue_key FOREIGN KEY (number, repository_id) REFERENCES issue (number, repository_id) ON DELETE CASCADE,
+    UNIQUE (repository_id, number, source_repository_id, source_number, source)
+);
@@ -0,0 +1,20 @@
+-- Push events are picked up by gill to close the issues referenced in the pushed commits,
+-- the ones recorded before are left alone
+ALTER TABLE push_event
+    ADD COLUMN processed_at TIMESTAMP;
+
+UPDATE push_event
+SET processed_at = created_at;
+
+CREATE INDEX push_event_unprocessed_idx ON push_event (id) WHERE processed_at IS NULL;
+
+ALTER TYPE reference_source ADD VALUE 'Commit';
+
+ALTER TABLE issue_reference
+    ALTER COLUMN source_number DROP NOT NULL,
+    ADD COLUMN source_sha VARCHAR(40),
+    ADD COLUMN closes     BOOLEAN NOT NULL DEFAULT false;
+
+CREATE UNIQUE INDEX issue_reference_commit_idx
+    ON issue_reference (repository_id, number, source_repository_id, source_sha)
+    WHERE source_sha IS NOT NULL;
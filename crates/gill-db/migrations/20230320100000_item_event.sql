@@ -0,0 +1,33 @@
+CREATE TYPE item_event_kind AS ENUM ('Opened', 'Closed', 'Reopened', 'Renamed', 'Labeled', 'Merged', 'ForcePushed');
+
+-- History of an issue or a pull request, `number` refers to either of them.
+-- References are not recorded here, `issue_reference` already keeps track of them.
+CREATE TABLE item_event
+(
+    id            SERIAL PRIMARY KEY,
+    repository_id INT REFERENCES repository (id) ON DELETE CASCADE NOT NULL,
+    number        INT                                              NOT NULL,
+    actor_id      INT REFERENCES users (id) ON DELETE CASCADE      NOT NULL,
+    kind          item_event_kind                                  NOT NULL,
+    -- Previous and new title of a rename, label name, shas of a force push
+    old_value     VARCHAR(255),
+    new_value     VARCHAR(255),
+    created_at    TIMESTAMP                                        NOT NULL DEFAULT now()
+);
+
+CREATE INDEX item_event_item_idx ON item_event (repository_id, number);
+
+-- Pull request comments need a date to be placed in the timeline, older ones get the migration date
+ALTER TABLE pull_request_comment
+    ADD COLUMN created_at TIMESTAMP NOT NULL DEFAULT now();
+
+-- Pull requests have no creation date, only issues get their history backfilled
+INSERT INTO item_event (repository_id, number, actor_id, kind, created_at)
+SELECT repository_id, number, opened_by, 'Opened', published
+FROM issue;
+
+INSERT INTO item_event (repository_id, number, actor_id, kind, created_at)
+SELECT i.repository_id, i.number, u.id, 'Closed', i.resolved
+FROM issue i
+JOIN users u ON u.activity_pub_id = i.resolved_by
+WHERE i.state = 'Closed' AND i.resolved IS NOT NULL;
@@ -0,0 +1,33 @@
+-- An edit of an issue, a pull request or one of their comments, `number` refers to the issue
+-- or the pull request. Item revisions have no comment id.
+CREATE TABLE revision
+(
+    id                      SERIAL PRIMARY KEY,
+    repository_id           INT REFERENCES repository (id) ON DELETE CASCADE   NOT NULL,
+    number                  INT                                                NOT NULL,
+    issue_comment_id        UUID REFERENCES issue_comment (id) ON DELETE CASCADE,
+    -- `pull_request_comment` ids are not unique constraints and can't be referenced
+    pull_request_comment_id INT,
+    -- Titles are only set when the edit changed it
+    old_title               VARCHAR(255),
+    new_title               VARCHAR(255),
+    old_content             TEXT                                               NOT NULL,
+    new_content             TEXT                                               NOT NULL,
+    edited_by               INT REFERENCES users (id) ON DELETE CASCADE        NOT NULL,
+    created_at              TIMESTAMP                                          NOT NULL DEFAULT now()
+);
+
+CREATE INDEX revision_item_idx ON revision (repository_id, number);
+
+-- Deleted items and comments are kept without their content so threads and numbering stay intact
+ALTER TABLE issue
+    ADD COLUMN deleted_at TIMESTAMP;
+
+ALTER TABLE pull_request
+    ADD COLUMN deleted_at TIMESTAMP;
+
+ALTER TABLE issue_comment
+    ADD COLUMN deleted_at TIMESTAMP;
+
+ALTER TABLE pull_request_comment
+    ADD COLUMN deleted_at TIMESTAMP;
@@ -0,0 +1,23 @@
+-- An emoji reaction to an issue, a pull request or one of their comments, `number` refers to the
+-- issue or the pull request. Item reactions have no comment id.
+CREATE TABLE reaction
+(
+    id                      SERIAL PRIMARY KEY,
+    repository_id           INT REFERENCES repository (id) ON DELETE CASCADE   NOT NULL,
+    number                  INT                                                NOT NULL,
+    issue_comment_id        UUID REFERENCES issue_comment (id) ON DELETE CASCADE,
+    pull_request_comment_id INT,
+    user_id                 INT REFERENCES users (id) ON DELETE CASCADE        NOT NULL,
+    emoji                   VARCHAR(16)                                        NOT NULL,
+    -- The `Like` or `EmojiReact` activity, needed to undo it
+    activity_pub_id         VARCHAR(255) UNIQUE                                NOT NULL,
+    created_at              TIMESTAMP                                          NOT NULL DEFAULT now()
+);
+
+CREATE INDEX reaction_item_idx ON reaction (repository_id, number);
+
+-- A user reacts at most once with each emoji, comment ids are nullable hence the COALESCE
+CREATE UNIQUE INDEX reaction_unique_idx ON reaction (repository_id, number,
+                                                     COALESCE(issue_comment_id, '00000000-0000-0000-0000-000000000000'),
+                                                     COALESCE(pull_request_comment_id, 0),
+                                                     user_id, emoji);
@@ -0,0 +1,21 @@
+CREATE TYPE issue_link_kind AS ENUM ('Blocks', 'RelatesTo', 'Duplicates');
+
+-- Issue `number` of `repository_id` blocks, relates to or duplicates the target issue.
+-- The target can belong to another repository, local or federated.
+CREATE TABLE issue_link
+(
+    id                   SERIAL PRIMARY KEY,
+    repository_id        INT                                         NOT NULL,
+    number               INT                                         NOT NULL,
+    target_repository_id INT                                         NOT NULL,
+    target_number        INT                                         NOT NULL,
+    kind                 issue_link_kind                             NOT NULL,
+    actor_id             INT REFERENCES users (id) ON DELETE CASCADE NOT NULL,
+    created_at           TIMESTAMP                                   NOT NULL DEFAULT now(),
+    CONSTRAINT issue_key FOREIGN KEY (number, repository_id) REFERENCES issue (number, repository_id) ON DELETE CASCADE,
+    CONSTRAINT target_issue_key FOREIGN KEY (target_number, target_repository_id) REFERENCES issue (number, repository_id) ON DELETE CASCADE,
+    CHECK ((repository_id, number) <> (target_repository_id, target_number)),
+    UNIQUE (repository_id, number, target_repository_id, target_number, kind)
+);
+
+CREATE INDEX issue_link_target_idx ON issue_link (target_repository_id, target_number);
@@ -0,0 +1,3 @@
+-- Closed and merged pull requests keep their base name once the branch is deleted
+ALTER TABLE pull_request
+    DROP CONSTRAINT base_key;
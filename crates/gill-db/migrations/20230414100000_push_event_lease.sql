@@ -0,0 +1,5 @@
+-- Push events are leased by the worker processing them and only marked as processed once
+-- they succeed, an event whose lease expired is picked up again until it runs out of attempts
+ALTER TABLE push_event
+    ADD COLUMN locked_until TIMESTAMP,
+    ADD COLUMN attempts     INT NOT NULL DEFAULT 0;
@@ -0,0 +1,7 @@
+-- Actor outboxes only list publicly addressed activities
+ALTER TABLE activity
+    ADD COLUMN public BOOLEAN NOT NULL DEFAULT false;
+
+UPDATE activity
+SET public = payload -> 'to' ?| ARRAY ['https://www.w3.org/ns/activitystreams#Public', 'as:Public', 'Public']
+    OR payload -> 'cc' ?| ARRAY ['https://www.w3.org/ns/activitystreams#Public', 'as:Public', 'Public'];
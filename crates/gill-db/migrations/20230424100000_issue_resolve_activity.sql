@@ -0,0 +1,3 @@
+-- The `Resolve` activity which closed an issue, reopening it sends an `Undo` of this activity
+ALTER TABLE issue
+    ADD COLUMN resolve_activity_id VARCHAR(255);
@@ -0,0 +1,4 @@
+-- Digests which could not be emailed are retried with an exponential backoff
+ALTER TABLE notification_settings
+    ADD COLUMN digest_failures INT NOT NULL DEFAULT 0,
+    ADD COLUMN digest_retry_at TIMESTAMP;
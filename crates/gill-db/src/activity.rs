@@ -0,0 +1,122 @@
+use crate::Insert;
+use async_trait::async_trait;
+use sqlx::PgPool;
+
+/// An activity published by a local actor, kept to serve actor outboxes
+#[derive(sqlx::FromRow, Debug)]
+pub struct Activity {
+    pub id: i32,
+    pub activity_pub_id: String,
+    pub actor: String,
+    pub kind: String,
+    pub payload: serde_json::Value,
+    pub published: chrono::NaiveDateTime,
+}
+
+#[derive(Debug)]
+pub struct CreateActivity {
+    pub activity_pub_id: String,
+    pub actor: String,
+    pub kind: String,
+    pub payload: serde_json::Value,
+    /// Whether the activity is addressed to the public collection
+    pub public: bool,
+}
+
+#[async_trait]
+impl Insert for CreateActivity {
+    type Output = Activity;
+
+    async fn insert(self, db: &PgPool) -> sqlx::Result<Self::Output> {
+        let activity = sqlx::query_as!(
+            Activity,
+            // language=PostgreSQL
+            r#"
+            INSERT INTO activity (activity_pub_id, actor, kind, payload, public)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (activity_pub_id) DO UPDATE SET payload = $4, public = $5
+            RETURNING id, activity_pub_id, actor, kind, payload, published
+            "#,
+            self.activity_pub_id,
+            self.actor,
+            self.kind,
+            self.payload,
+            self.public
+        )
+        .fetch_one(db)
+        .await?;
+
+        Ok(activity)
+    }
+}
+
+impl Activity {
+    /// Last `kind` activity sent by `actor` whose `object_field` is `object`
+    pub async fn last_sent(
+        actor: &str,
+        kind: &str,
+        object_field: &str,
+        object: &str,
+        db: &PgPool,
+    ) -> sqlx::Result<Option<Activity>> {
+        let activity = sqlx::query_as!(
+            Activity,
+            // language=PostgreSQL
+            r#"
+            SELECT id, activity_pub_id, actor, kind, payload, published
+            FROM activity
+            WHERE actor = $1 AND kind = $2 AND payload ->> $3 = $4
+            ORDER BY id DESC
+            LIMIT 1
+            "#,
+            actor,
+            kind,
+            object_field,
+            object
+        )
+        .fetch_optional(db)
+        .await?;
+
+        Ok(activity)
+    }
+
+    /// Public activities published by `actor`, most recent first
+    pub async fn list_public_by_actor(
+        actor: &str,
+        limit: i64,
+        offset: i64,
+        db: &PgPool,
+    ) -> sqlx::Result<Vec<Activity>> {
+        let activities = sqlx::query_as!(
+            Activity,
+            // language=PostgreSQL
+            r#"
+            SELECT id, activity_pub_id, actor, kind, payload, published
+            FROM activity
+            WHERE actor = $1 AND public
+            ORDER BY id DESC
+            LIMIT $2
+            OFFSET $3
+            "#,
+            actor,
+            limit,
+            offset
+        )
+        .fetch_all(db)
+        .await?;
+
+        Ok(activities)
+    }
+
+    pub async fn count_public_by_actor(actor: &str, db: &PgPool) -> sqlx::Result<i64> {
+        let count = sqlx::query_scalar!(
+            // language=PostgreSQL
+            r#"SELECT count(*) as "count!" FROM activity WHERE actor = $1 AND public"#,
+            actor
+        )
+        .fetch_one(db)
+        .await?;
+
+        Ok(count)
+    }
+}
@@ -0,0 +1,322 @@
+use sqlx::PgPool;
+
+#[derive(sqlx::Type, Debug, Copy, Clone, Eq, PartialEq)]
+#[sqlx(type_name = "delivery_state")]
+pub enum DeliveryState {
+    Pending,
+    /// Dead letter: the delivery gave up after too many attempts
+    Failed,
+}
+
+/// An outgoing activity queued for a single remote inbox
+#[derive(sqlx::FromRow, Debug)]
+pub struct Delivery {
+    pub id: i32,
+    pub activity_id: String,
+    pub sender: String,
+    pub inbox: String,
+    pub domain: String,
+    pub payload: serde_json::Value,
+    pub state: DeliveryState,
+    pub attempts: i32,
+    pub last_error: Option<String>,
+    pub next_attempt_at: chrono::NaiveDateTime,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+/// Circuit breaker state of a remote domain
+#[derive(sqlx::FromRow, Debug)]
+pub struct DeliveryDomain {
+    pub domain: String,
+    pub consecutive_failures: i32,
+    pub open_until: Option<chrono::NaiveDateTime>,
+}
+
+impl Delivery {
+    /// Queue `payload` once per inbox, `domains` holds the domain of each inbox
+    pub async fn enqueue(
+        activity_id: &str,
+        sender: &str,
+        payload: &serde_json::Value,
+        inboxes: &[String],
+        domains: &[String],
+        db: &PgPool,
+    ) -> sqlx::Result<()> {
+        sqlx::query!(
+            // language=PostgreSQL
+            r#"
+            INSERT INTO delivery (activity_id, sender, payload, inbox, domain)
+            SELECT $1, $2, $3, t.inbox, t.domain
+            FROM UNNEST($4::text[], $5::text[]) AS t(inbox, domain)
+            "#,
+            activity_id,
+            sender,
+            payload,
+            inboxes,
+            domains
+        )
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Take up to `limit` due deliveries. Claimed rows are leased for `lease_secs` so
+    /// a crashed worker does not lose them and concurrent workers do not send them twice.
+    pub async fn claim_due(
+        limit: i64,
+        lease_secs: f64,
+        db: &PgPool,
+    ) -> sqlx::Result<Vec<Delivery>> {
+        let deliveries = sqlx::query_as!(
+            Delivery,
+            // language=PostgreSQL
+            r#"
+            UPDATE delivery
+            SET next_attempt_at = now() + make_interval(secs => $2),
+                updated_at = now()
+            WHERE id IN (
+                SELECT id FROM delivery
+                WHERE state = 'Pending' AND next_attempt_at <= now()
+                ORDER BY next_attempt_at
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING id, activity_id, sender, inbox, domain, payload,
+                      state as "state: DeliveryState",
+                      attempts, last_error, next_attempt_at, created_at
+            "#,
+            limit,
+            lease_secs
+        )
+        .fetch_all(db)
+        .await?;
+
+        Ok(deliveries)
+    }
+
+    pub async fn delete(id: i32, db: &PgPool) -> sqlx::Result<()> {
+        sqlx::query!(
+            // language=PostgreSQL
+            r#"DELETE FROM delivery WHERE id = $1"#,
+            id
+        )
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Record a failed attempt and schedule the next one
+    pub async fn reschedule(
+        id: i32,
+        error: &str,
+        next_attempt_at: chrono::NaiveDateTime,
+        db: &PgPool,
+    ) -> sqlx::Result<()> {
+        sqlx::query!(
+            // language=PostgreSQL
+            r#"
+            UPDATE delivery
+            SET attempts = attempts + 1,
+                last_error = $2,
+                next_attempt_at = $3,
+                updated_at = now()
+            WHERE id = $1
+            "#,
+            id,
+            error,
+            next_attempt_at
+        )
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Move the delivery to the next attempt time without counting an attempt
+    pub async fn postpone(
+        id: i32,
+        next_attempt_at: chrono::NaiveDateTime,
+        db: &PgPool,
+    ) -> sqlx::Result<()> {
+        sqlx::query!(
+            // language=PostgreSQL
+            r#"UPDATE delivery SET next_attempt_at = $2, updated_at = now() WHERE id = $1"#,
+            id,
+            next_attempt_at
+        )
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Record the last failed attempt and dead letter the delivery
+    pub async fn fail(id: i32, error: &str, db: &PgPool) -> sqlx::Result<()> {
+        sqlx::query!(
+            // language=PostgreSQL
+            r#"
+            UPDATE delivery
+            SET state = 'Failed',
+                attempts = attempts + 1,
+                last_error = $2,
+                updated_at = now()
+            WHERE id = $1
+            "#,
+            id,
+            error
+        )
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Put a dead lettered delivery back in the queue
+    pub async fn retry(id: i32, db: &PgPool) -> sqlx::Result<()> {
+        sqlx::query!(
+            // language=PostgreSQL
+            r#"
+            UPDATE delivery
+            SET state = 'Pending',
+                attempts = 0,
+                next_attempt_at = now(),
+                updated_at = now()
+            WHERE id = $1 AND state = 'Failed'
+            "#,
+            id
+        )
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn list(
+        state: DeliveryState,
+        limit: i64,
+        offset: i64,
+        db: &PgPool,
+    ) -> sqlx::Result<Vec<Delivery>> {
+        let deliveries = sqlx::query_as!(
+            Delivery,
+            // language=PostgreSQL
+            r#"
+            SELECT id, activity_id, sender, inbox, domain, payload,
+                   state as "state: DeliveryState",
+                   attempts, last_error, next_attempt_at, created_at
+            FROM delivery
+            WHERE state = $1
+            ORDER BY id DESC
+            LIMIT $2
+            OFFSET $3
+            "#,
+            state as DeliveryState,
+            limit,
+            offset
+        )
+        .fetch_all(db)
+        .await?;
+
+        Ok(deliveries)
+    }
+
+    pub async fn count(state: DeliveryState, db: &PgPool) -> sqlx::Result<i64> {
+        let count = sqlx::query_scalar!(
+            // language=PostgreSQL
+            r#"SELECT count(*) as "count!" FROM delivery WHERE state = $1"#,
+            state as DeliveryState
+        )
+        .fetch_one(db)
+        .await?;
+
+        Ok(count)
+    }
+}
+
+impl DeliveryDomain {
+    pub async fn by_domain(domain: &str, db: &PgPool) -> sqlx::Result<Option<DeliveryDomain>> {
+        let domain = sqlx::query_as!(
+            DeliveryDomain,
+            // language=PostgreSQL
+            r#"
+            SELECT domain, consecutive_failures, open_until
+            FROM delivery_domain
+            WHERE domain = $1
+            "#,
+            domain
+        )
+        .fetch_optional(db)
+        .await?;
+
+        Ok(domain)
+    }
+
+    /// Domains with failed deliveries, most failing first
+    pub async fn list_failing(db: &PgPool) -> sqlx::Result<Vec<DeliveryDomain>> {
+        let domains = sqlx::query_as!(
+            DeliveryDomain,
+            // language=PostgreSQL
+            r#"
+            SELECT domain, consecutive_failures, open_until
+            FROM delivery_domain
+            ORDER BY consecutive_failures DESC, domain
+            "#
+        )
+        .fetch_all(db)
+        .await?;
+
+        Ok(domains)
+    }
+
+    /// Increment the domain consecutive failure count and return it
+    pub async fn record_failure(domain: &str, db: &PgPool) -> sqlx::Result<i32> {
+        let failures = sqlx::query_scalar!(
+            // language=PostgreSQL
+            r#"
+            INSERT INTO delivery_domain (domain, consecutive_failures)
+            VALUES ($1, 1)
+            ON CONFLICT (domain) DO UPDATE
+                SET consecutive_failures = delivery_domain.consecutive_failures + 1
+            RETURNING consecutive_failures
+            "#,
+            domain
+        )
+        .fetch_one(db)
+        .await?;
+
+        Ok(failures)
+    }
+
+    /// Stop delivering to `domain` until `open_until`
+    pub async fn open(
+        domain: &str,
+        open_until: chrono::NaiveDateTime,
+        db: &PgPool,
+    ) -> sqlx::Result<()> {
+        sqlx::query!(
+            // language=PostgreSQL
+            r#"UPDATE delivery_domain SET open_until = $2 WHERE domain = $1"#,
+            domain,
+            open_until
+        )
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// A delivery went through, the domain is healthy again
+    pub async fn reset(domain: &str, db: &PgPool) -> sqlx::Result<()> {
+        sqlx::query!(
+            // language=PostgreSQL
+            r#"DELETE FROM delivery_domain WHERE domain = $1"#,
+            domain
+        )
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,163 @@
+use sqlx::PgPool;
+
+/// In `Blocklist` mode every domain federates unless blocked,
+/// in `Allowlist` mode only allowed and silenced domains do.
+#[derive(sqlx::Type, Debug, Copy, Clone, Eq, PartialEq)]
+#[sqlx(type_name = "federation_mode")]
+pub enum FederationMode {
+    Blocklist,
+    Allowlist,
+}
+
+#[derive(sqlx::Type, Debug, Copy, Clone, Eq, PartialEq)]
+#[sqlx(type_name = "domain_policy")]
+pub enum DomainPolicy {
+    Allowed,
+    Blocked,
+    /// Federates, but content is hidden from public listings
+    Silenced,
+}
+
+#[derive(sqlx::FromRow, Debug)]
+pub struct DomainRule {
+    pub domain: String,
+    pub policy: DomainPolicy,
+    pub reason: Option<String>,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+/// A remote domain we have seen users or repositories from
+#[derive(sqlx::FromRow, Debug)]
+pub struct KnownInstance {
+    pub domain: String,
+    pub user_count: i64,
+    pub repository_count: i64,
+    pub policy: Option<DomainPolicy>,
+}
+
+pub async fn federation_mode(db: &PgPool) -> sqlx::Result<FederationMode> {
+    let mode = sqlx::query_scalar!(
+        // language=PostgreSQL
+        r#"SELECT mode as "mode: FederationMode" FROM federation_settings"#
+    )
+    .fetch_one(db)
+    .await?;
+
+    Ok(mode)
+}
+
+pub async fn set_federation_mode(mode: FederationMode, db: &PgPool) -> sqlx::Result<()> {
+    sqlx::query!(
+        // language=PostgreSQL
+        r#"UPDATE federation_settings SET mode = $1"#,
+        mode as FederationMode
+    )
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+impl DomainRule {
+    /// The most specific rule for `domain`, rules also apply to the subdomains of their domain
+    pub async fn matching(domain: &str, db: &PgPool) -> sqlx::Result<Option<DomainRule>> {
+        let rule = sqlx::query_as!(
+            DomainRule,
+            // language=PostgreSQL
+            r#"
+            SELECT domain, policy as "policy: DomainPolicy", reason, created_at
+            FROM domain_rule
+            WHERE domain = $1 OR right($1, length(domain) + 1) = '.' || domain
+            ORDER BY length(domain) DESC
+            LIMIT 1
+            "#,
+            domain
+        )
+        .fetch_optional(db)
+        .await?;
+
+        Ok(rule)
+    }
+
+    pub async fn list(db: &PgPool) -> sqlx::Result<Vec<DomainRule>> {
+        let rules = sqlx::query_as!(
+            DomainRule,
+            // language=PostgreSQL
+            r#"
+            SELECT domain, policy as "policy: DomainPolicy", reason, created_at
+            FROM domain_rule
+            ORDER BY domain
+            "#
+        )
+        .fetch_all(db)
+        .await?;
+
+        Ok(rules)
+    }
+
+    pub async fn upsert(
+        domain: &str,
+        policy: DomainPolicy,
+        reason: Option<&str>,
+        db: &PgPool,
+    ) -> sqlx::Result<()> {
+        sqlx::query!(
+            // language=PostgreSQL
+            r#"
+            INSERT INTO domain_rule (domain, policy, reason)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (domain) DO UPDATE SET policy = $2, reason = $3
+            "#,
+            domain,
+            policy as DomainPolicy,
+            reason
+        )
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn delete(domain: &str, db: &PgPool) -> sqlx::Result<()> {
+        sqlx::query!(
+            // language=PostgreSQL
+            r#"DELETE FROM domain_rule WHERE domain = $1"#,
+            domain
+        )
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+}
+
+impl KnownInstance {
+    /// Remote domains taken from federated users and repositories, with their policy if any
+    pub async fn list(db: &PgPool) -> sqlx::Result<Vec<KnownInstance>> {
+        let instances = sqlx::query_as!(
+            KnownInstance,
+            // language=PostgreSQL
+            r#"
+            SELECT d.domain as "domain!",
+                   (SELECT count(*) FROM users u WHERE u.domain = d.domain AND NOT u.is_local)
+                       as "user_count!",
+                   (SELECT count(*) FROM repository r WHERE r.domain = d.domain AND NOT r.is_local)
+                       as "repository_count!",
+                   dr.policy as "policy: DomainPolicy"
+            FROM (
+                SELECT domain FROM users WHERE NOT is_local
+                UNION
+                SELECT domain FROM repository WHERE NOT is_local
+                UNION
+                SELECT domain FROM domain_rule
+            ) d
+            LEFT JOIN domain_rule dr ON dr.domain = d.domain
+            ORDER BY d.domain
+            "#
+        )
+        .fetch_all(db)
+        .await?;
+
+        Ok(instances)
+    }
+}
@@ -2,6 +2,9 @@ use async_trait::async_trait;
 
 use sqlx::PgPool;
 
+pub mod activity;
+pub mod delivery;
+pub mod federation;
 pub mod pagination;
 pub mod repository;
 pub mod subscribe;
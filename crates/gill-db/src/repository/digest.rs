@@ -73,6 +73,9 @@ impl RepositoryDigest {
                      LEFT JOIN repository_fork rf ON rf.repository_id = r.id
                      LEFT JOIN repository_watch rw ON rw.repository_id = r.id
             WHERE NOT r.private AND NOT r.is_local
+              AND NOT EXISTS(SELECT 1
+                             FROM domain_rule dr
+                             WHERE dr.domain = r.domain AND dr.policy IN ('Silenced', 'Blocked'))
             GROUP BY r.id, u.username, r.name, r.id, r.summary
             LIMIT $1 OFFSET $2;"#,
             limit,
@@ -105,9 +108,9 @@ impl RepositoryLight {
             // language=PostgreSQL
             r#"
             SELECT r.summary,
-                   COUNT(rs.repository_id) as star_count,
-                   COUNT(rf.repository_id) as fork_count,
-                   COUNT(rw.repository_id) as watch_count,
+                   COUNT(DISTINCT rs.starred_by) as star_count,
+                   COUNT(DISTINCT rf.fork_id) as fork_count,
+                   COUNT(DISTINCT rw.watched_by) as watch_count,
                    r.clone_uri as clone_url
             FROM repository r
                      RIGHT JOIN users u ON r.attributed_to = u.activity_pub_id
@@ -0,0 +1,98 @@
+use crate::Insert;
+use async_trait::async_trait;
+use sqlx::PgPool;
+
+#[derive(sqlx::Type, Debug, Copy, Clone, Eq, PartialEq)]
+#[sqlx(type_name = "item_event_kind")]
+pub enum ItemEventKind {
+    Opened,
+    Closed,
+    Reopened,
+    Renamed,
+    Labeled,
+    Merged,
+    ForcePushed,
+}
+
+/// Something `actor_id` did to issue or pull request `number` of `repository_id`
+#[derive(Debug)]
+pub struct CreateItemEvent {
+    pub repository_id: i32,
+    pub number: i32,
+    pub actor_id: i32,
+    pub kind: ItemEventKind,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+    /// Defaults to now, imported and federated items keep their original date
+    pub created_at: Option<chrono::NaiveDateTime>,
+}
+
+#[derive(sqlx::FromRow, Debug)]
+pub struct ItemEvent {
+    pub id: i32,
+    pub kind: ItemEventKind,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+    pub actor_name: String,
+    pub actor_activity_pub_id: String,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+#[async_trait]
+impl Insert for CreateItemEvent {
+    type Output = ();
+
+    async fn insert(self, db: &PgPool) -> sqlx::Result<Self::Output> {
+        sqlx::query!(
+            // language=PostgreSQL
+            r#"
+            INSERT INTO item_event (repository_id, number, actor_id, kind, old_value, new_value, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, COALESCE($7, now()::timestamp))
+            "#,
+            self.repository_id,
+            self.number,
+            self.actor_id,
+            self.kind as ItemEventKind,
+            self.old_value,
+            self.new_value,
+            self.created_at,
+        )
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+}
+
+impl ItemEvent {
+    /// Events of issue or pull request `number` of `repository_id`, oldest first
+    pub async fn for_item(
+        repository_id: i32,
+        number: i32,
+        db: &PgPool,
+    ) -> sqlx::Result<Vec<ItemEvent>> {
+        let events = sqlx::query_as!(
+            ItemEvent,
+            // language=PostgreSQL
+            r#"
+            SELECT e.id,
+                   e.kind as "kind: ItemEventKind",
+                   e.old_value,
+                   e.new_value,
+                   u.username as actor_name,
+                   u.activity_pub_id as actor_activity_pub_id,
+                   e.created_at
+            FROM item_event e
+            JOIN users u ON u.id = e.actor_id
+            WHERE e.repository_id = $1 AND e.number = $2
+            ORDER BY e.created_at, e.id
+            "#,
+            repository_id,
+            number,
+        )
+        .fetch_all(db)
+        .await?;
+
+        Ok(events)
+    }
+}
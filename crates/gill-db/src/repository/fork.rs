@@ -7,7 +7,7 @@ impl Repository {
         sqlx::query!(
             // language=PostgreSQL
             r#"
-            INSERT INTO gill.public.repository_fork (repository_id, fork_id, forked_by)
+            INSERT INTO repository_fork (repository_id, fork_id, forked_by)
             VALUES ($1, $2, $3)
             "#,
             self.id,
@@ -0,0 +1,264 @@
+use crate::repository::pull_request::PullRequestState;
+use crate::repository::Repository;
+use sqlx::PgPool;
+
+#[derive(sqlx::Type, Debug, Copy, Clone, Eq, PartialEq)]
+#[sqlx(type_name = "import_state")]
+pub enum ImportState {
+    Pending,
+    Cloning,
+    Importing,
+    Done,
+    Failed,
+}
+
+/// Background import of a repository git data and issue tracker
+#[derive(sqlx::FromRow, Debug)]
+pub struct RepositoryImport {
+    pub repository_id: i32,
+    pub remote_url: String,
+    pub dump: Option<serde_json::Value>,
+    pub author_mapping: serde_json::Value,
+    pub state: ImportState,
+    pub progress: i32,
+    pub total: i32,
+    pub last_error: Option<String>,
+    pub updated_at: chrono::NaiveDateTime,
+}
+
+impl RepositoryImport {
+    pub async fn create(
+        repository_id: i32,
+        remote_url: &str,
+        dump: Option<serde_json::Value>,
+        author_mapping: serde_json::Value,
+        db: &PgPool,
+    ) -> sqlx::Result<RepositoryImport> {
+        let import = sqlx::query_as!(
+            RepositoryImport,
+            // language=PostgreSQL
+            r#"
+            INSERT INTO repository_import (repository_id, remote_url, dump, author_mapping)
+            VALUES ($1, $2, $3, $4)
+            RETURNING repository_id, remote_url, dump, author_mapping,
+                      state as "state: ImportState",
+                      progress, total, last_error, updated_at
+            "#,
+            repository_id,
+            remote_url,
+            dump,
+            author_mapping
+        )
+        .fetch_one(db)
+        .await?;
+
+        Ok(import)
+    }
+
+    /// Progress of the import, the dump is left out
+    pub async fn by_repository_id(
+        repository_id: i32,
+        db: &PgPool,
+    ) -> sqlx::Result<Option<RepositoryImport>> {
+        let import = sqlx::query_as!(
+            RepositoryImport,
+            // language=PostgreSQL
+            r#"
+            SELECT repository_id, remote_url,
+                   NULL::jsonb as dump,
+                   author_mapping,
+                   state as "state: ImportState",
+                   progress, total, last_error, updated_at
+            FROM repository_import
+            WHERE repository_id = $1
+            "#,
+            repository_id
+        )
+        .fetch_optional(db)
+        .await?;
+
+        Ok(import)
+    }
+
+    /// Take the oldest pending import, or a running one which has not made progress
+    /// for `stale_after_secs` because the instance stopped in the middle of it.
+    pub async fn claim_next(
+        stale_after_secs: f64,
+        db: &PgPool,
+    ) -> sqlx::Result<Option<RepositoryImport>> {
+        let import = sqlx::query_as!(
+            RepositoryImport,
+            // language=PostgreSQL
+            r#"
+            UPDATE repository_import
+            SET state = 'Cloning', updated_at = now()
+            WHERE repository_id = (
+                SELECT repository_id FROM repository_import
+                WHERE state = 'Pending'
+                   OR (state IN ('Cloning', 'Importing')
+                       AND updated_at < now() - make_interval(secs => $1))
+                ORDER BY created_at
+                LIMIT 1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING repository_id, remote_url, dump, author_mapping,
+                      state as "state: ImportState",
+                      progress, total, last_error, updated_at
+            "#,
+            stale_after_secs
+        )
+        .fetch_optional(db)
+        .await?;
+
+        Ok(import)
+    }
+
+    pub async fn update_progress(
+        repository_id: i32,
+        state: ImportState,
+        progress: i32,
+        total: i32,
+        db: &PgPool,
+    ) -> sqlx::Result<()> {
+        sqlx::query!(
+            // language=PostgreSQL
+            r#"
+            UPDATE repository_import
+            SET state = $2, progress = $3, total = $4, updated_at = now()
+            WHERE repository_id = $1
+            "#,
+            repository_id,
+            state as ImportState,
+            progress,
+            total
+        )
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn done(repository_id: i32, db: &PgPool) -> sqlx::Result<()> {
+        sqlx::query!(
+            // language=PostgreSQL
+            r#"
+            UPDATE repository_import
+            SET state = 'Done', dump = NULL, last_error = NULL, updated_at = now()
+            WHERE repository_id = $1
+            "#,
+            repository_id
+        )
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn failed(repository_id: i32, error: &str, db: &PgPool) -> sqlx::Result<()> {
+        sqlx::query!(
+            // language=PostgreSQL
+            r#"
+            UPDATE repository_import
+            SET state = 'Failed', last_error = $2, updated_at = now()
+            WHERE repository_id = $1
+            "#,
+            repository_id,
+            error
+        )
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Run a failed import again, already imported items are skipped
+    pub async fn retry(repository_id: i32, db: &PgPool) -> sqlx::Result<()> {
+        sqlx::query!(
+            // language=PostgreSQL
+            r#"
+            UPDATE repository_import
+            SET state = 'Pending', progress = 0, updated_at = now()
+            WHERE repository_id = $1 AND state = 'Failed'
+            "#,
+            repository_id
+        )
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+}
+
+impl Repository {
+    /// Whether an issue or a pull request already uses this number
+    pub async fn has_item(&self, number: i32, db: &PgPool) -> sqlx::Result<bool> {
+        let exists = sqlx::query_scalar!(
+            // language=PostgreSQL
+            r#"
+            SELECT EXISTS(SELECT 1 FROM issue WHERE repository_id = $1 AND number = $2)
+                OR EXISTS(SELECT 1 FROM pull_request WHERE repository_id = $1 AND number = $2)
+                as "exists!"
+            "#,
+            self.id,
+            number
+        )
+        .fetch_one(db)
+        .await?;
+
+        Ok(exists)
+    }
+
+    /// Insert a pull request keeping the number it had on the source forge
+    #[allow(clippy::too_many_arguments)]
+    pub async fn import_pull_request(
+        &self,
+        number: i32,
+        opened_by: i32,
+        title: &str,
+        description: Option<&str>,
+        base: &str,
+        compare: &str,
+        state: PullRequestState,
+        db: &PgPool,
+    ) -> sqlx::Result<()> {
+        sqlx::query!(
+            // language=PostgreSQL
+            r#"
+            INSERT INTO pull_request (number, repository_id, opened_by, title, description, base, compare, state)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#,
+            number,
+            self.id,
+            opened_by,
+            title,
+            description,
+            base,
+            compare,
+            state as PullRequestState
+        )
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Point the item counter after the highest issue or pull request number,
+    /// imported items keep their original numbers
+    pub async fn refresh_item_count(&self, db: &PgPool) -> sqlx::Result<()> {
+        sqlx::query!(
+            // language=PostgreSQL
+            r#"
+            UPDATE repository SET item_count = GREATEST(
+                (SELECT COALESCE(MAX(number), 0) FROM issue WHERE repository_id = $1),
+                (SELECT COALESCE(MAX(number), 0) FROM pull_request WHERE repository_id = $1)
+            )
+            WHERE id = $1
+            "#,
+            self.id
+        )
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -10,6 +10,8 @@ pub struct IssueCommentDigest {
     pub repository_id: i32,
     pub created_by: String,
     pub content: String,
+    pub published: chrono::NaiveDateTime,
+    pub deleted_at: Option<chrono::NaiveDateTime>,
 }
 
 #[derive(Debug, sqlx::FromRow)]
@@ -25,6 +27,7 @@ pub struct IssueComment {
     pub context: String,
     pub in_reply_to: String,
     pub published: chrono::NaiveDateTime,
+    pub deleted_at: Option<chrono::NaiveDateTime>,
 }
 
 #[async_trait]
@@ -48,7 +51,7 @@ impl Insert for IssueComment {
                                       in_reply_to,
                                       published)
            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
-           RETURNING id, activity_pub_id, number, repository_id, created_by, content, media_type, attributed_to, context, in_reply_to, published;"#,
+           RETURNING id, activity_pub_id, number, repository_id, created_by, content, media_type, attributed_to, context, in_reply_to, published, deleted_at;"#,
             self.id,
             self.activity_pub_id,
             self.number,
@@ -83,6 +86,58 @@ impl IssueComment {
 
         Ok(user)
     }
+
+    pub async fn by_id(id: Uuid, db: &PgPool) -> sqlx::Result<Self> {
+        let comment = sqlx::query_as!(
+            IssueComment,
+            // language=PostgreSQL
+            r#"
+            select * from issue_comment
+            where id = $1
+            "#,
+            id,
+        )
+        .fetch_one(db)
+        .await?;
+
+        Ok(comment)
+    }
+
+    pub async fn update_content(&self, content: &str, db: &PgPool) -> sqlx::Result<()> {
+        sqlx::query!(
+            // language=PostgreSQL
+            r#"UPDATE issue_comment SET content = $1 WHERE id = $2"#,
+            content,
+            self.id
+        )
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Replace the comment with a tombstone, its content and revisions are dropped
+    pub async fn mark_deleted(&self, db: &PgPool) -> sqlx::Result<()> {
+        let mut transaction = db.begin().await?;
+        sqlx::query!(
+            // language=PostgreSQL
+            r#"UPDATE issue_comment SET content = '', deleted_at = now() WHERE id = $1"#,
+            self.id
+        )
+        .execute(&mut transaction)
+        .await?;
+
+        sqlx::query!(
+            // language=PostgreSQL
+            r#"DELETE FROM revision WHERE issue_comment_id = $1"#,
+            self.id
+        )
+        .execute(&mut transaction)
+        .await?;
+
+        transaction.commit().await?;
+        Ok(())
+    }
 }
 
 impl IssueDigest {
@@ -91,10 +146,12 @@ impl IssueDigest {
             IssueCommentDigest,
             // language=PostgreSQL
             r#"
-           SELECT c.id, c.repository_id, u.username as created_by, c.content FROM issue_comment c
+           SELECT c.id, c.repository_id, u.username as created_by, c.content, c.published, c.deleted_at
+                FROM issue_comment c
                 JOIN users u on u.id = c.created_by
                 WHERE c.repository_id = $1
-                AND c.number = $2;
+                AND c.number = $2
+                ORDER BY c.published;
            "#,
             self.repository_id,
             self.number,
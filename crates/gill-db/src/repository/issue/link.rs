@@ -0,0 +1,143 @@
+use crate::repository::issue::IssueState;
+use crate::Insert;
+use async_trait::async_trait;
+use sqlx::PgPool;
+
+#[derive(sqlx::Type, Debug, Copy, Clone, Eq, PartialEq)]
+#[sqlx(type_name = "issue_link_kind")]
+pub enum IssueLinkKind {
+    Blocks,
+    RelatesTo,
+    Duplicates,
+}
+
+/// Issue `number` of `repository_id` blocks, relates to or duplicates the target issue
+#[derive(Debug)]
+pub struct CreateIssueLink {
+    pub repository_id: i32,
+    pub number: i32,
+    pub target_repository_id: i32,
+    pub target_number: i32,
+    pub kind: IssueLinkKind,
+    pub actor_id: i32,
+}
+
+/// A link seen from one of its issues, the other fields describe the other issue
+#[derive(sqlx::FromRow, Debug)]
+pub struct IssueLink {
+    pub id: i32,
+    pub kind: IssueLinkKind,
+    /// The link was declared from the other issue, e.g. it blocks this one
+    pub incoming: bool,
+    pub repository_id: i32,
+    pub number: i32,
+    pub title: String,
+    pub state: IssueState,
+    pub owner: String,
+    pub repository: String,
+    pub activity_pub_id: String,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+#[async_trait]
+impl Insert for CreateIssueLink {
+    type Output = ();
+
+    /// Linking the same issues twice is a no-op
+    async fn insert(self, db: &PgPool) -> sqlx::Result<Self::Output> {
+        sqlx::query!(
+            // language=PostgreSQL
+            r#"
+            INSERT INTO issue_link (repository_id, number, target_repository_id, target_number, kind, actor_id)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT DO NOTHING
+            "#,
+            self.repository_id,
+            self.number,
+            self.target_repository_id,
+            self.target_number,
+            self.kind as IssueLinkKind,
+            self.actor_id,
+        )
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+}
+
+impl IssueLink {
+    /// Links from and to issue `number` of `repository_id`, oldest first
+    pub async fn for_issue(
+        repository_id: i32,
+        number: i32,
+        db: &PgPool,
+    ) -> sqlx::Result<Vec<IssueLink>> {
+        let links = sqlx::query_as!(
+            IssueLink,
+            // language=PostgreSQL
+            r#"
+            SELECT l.id as "id!",
+                   l.kind as "kind!: IssueLinkKind",
+                   false as "incoming!",
+                   i.repository_id as "repository_id!",
+                   i.number as "number!",
+                   i.title as "title!",
+                   i.state as "state!: IssueState",
+                   owner.username as "owner!",
+                   r.name as "repository!",
+                   i.activity_pub_id as "activity_pub_id!",
+                   l.created_at as "created_at!"
+            FROM issue_link l
+            JOIN issue i ON i.repository_id = l.target_repository_id AND i.number = l.target_number
+            JOIN repository r ON r.id = i.repository_id
+            JOIN users owner ON owner.activity_pub_id = r.attributed_to
+            WHERE l.repository_id = $1 AND l.number = $2
+            UNION ALL
+            SELECT l.id,
+                   l.kind,
+                   true,
+                   i.repository_id,
+                   i.number,
+                   i.title,
+                   i.state,
+                   owner.username,
+                   r.name,
+                   i.activity_pub_id,
+                   l.created_at
+            FROM issue_link l
+            JOIN issue i ON i.repository_id = l.repository_id AND i.number = l.number
+            JOIN repository r ON r.id = i.repository_id
+            JOIN users owner ON owner.activity_pub_id = r.attributed_to
+            WHERE l.target_repository_id = $1 AND l.target_number = $2
+            ORDER BY 11
+            "#,
+            repository_id,
+            number,
+        )
+        .fetch_all(db)
+        .await?;
+
+        Ok(links)
+    }
+
+    /// Delete link `id` if issue `number` of `repository_id` is one of its ends
+    pub async fn delete(id: i32, repository_id: i32, number: i32, db: &PgPool) -> sqlx::Result<()> {
+        sqlx::query!(
+            // language=PostgreSQL
+            r#"
+            DELETE FROM issue_link
+            WHERE id = $1
+              AND ((repository_id = $2 AND number = $3)
+                OR (target_repository_id = $2 AND target_number = $3))
+            "#,
+            id,
+            repository_id,
+            number,
+        )
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+}
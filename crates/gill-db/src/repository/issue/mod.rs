@@ -6,9 +6,11 @@ use async_trait::async_trait;
 use sqlx::PgPool;
 
 pub mod comment;
+pub mod link;
+pub mod reference;
 
 #[derive(sqlx::Type, Debug)]
-#[sqlx(type_name = "pull_request_state")]
+#[sqlx(type_name = "issue_state")]
 pub enum IssueState {
     Open,
     Closed,
@@ -22,6 +24,7 @@ pub struct IssueDigest {
     pub title: String,
     pub content: String,
     pub state: IssueState,
+    pub deleted_at: Option<chrono::NaiveDateTime>,
 }
 
 #[derive(sqlx::FromRow, Debug)]
@@ -174,24 +177,112 @@ impl Issue {
 
         Ok(issue)
     }
-}
 
-impl IssueDigest {
-    pub async fn close(&self, db: &PgPool) -> sqlx::Result<()> {
+    pub async fn update_content(
+        &self,
+        title: &str,
+        content: &str,
+        db: &PgPool,
+    ) -> sqlx::Result<()> {
         sqlx::query!(
             // language=PostgreSQL
             r#"
-           UPDATE issue SET state = 'Closed'
-            WHERE issue.number = $1 AND repository_id = $2;
-           "#,
-            self.number,
-            self.repository_id
+            UPDATE issue SET title = $1, content = $2
+            WHERE activity_pub_id = $3
+            "#,
+            title,
+            content,
+            self.activity_pub_id
         )
         .execute(db)
         .await?;
 
         Ok(())
     }
+
+    /// Replace the issue with a tombstone, its content and revisions are dropped
+    pub async fn mark_deleted(&self, db: &PgPool) -> sqlx::Result<()> {
+        let mut transaction = db.begin().await?;
+        sqlx::query!(
+            // language=PostgreSQL
+            r#"
+            UPDATE issue SET content = '', deleted_at = now()
+            WHERE activity_pub_id = $1
+            "#,
+            self.activity_pub_id
+        )
+        .execute(&mut transaction)
+        .await?;
+
+        sqlx::query!(
+            // language=PostgreSQL
+            r#"
+            DELETE FROM revision
+            WHERE repository_id = $1 AND number = $2 AND issue_comment_id IS NULL
+            "#,
+            self.repository_id,
+            self.number
+        )
+        .execute(&mut transaction)
+        .await?;
+
+        transaction.commit().await?;
+        Ok(())
+    }
+
+    /// Close the issue, `resolve_activity_id` is the `Resolve` activity closing it if any
+    pub async fn resolve(
+        &self,
+        resolved_by: &str,
+        resolve_activity_id: Option<&str>,
+        db: &PgPool,
+    ) -> sqlx::Result<()> {
+        sqlx::query!(
+            // language=PostgreSQL
+            r#"
+            UPDATE issue SET state = 'Closed', resolved_by = $1, resolved = now(), resolve_activity_id = $3
+            WHERE activity_pub_id = $2
+            "#,
+            resolved_by,
+            self.activity_pub_id,
+            resolve_activity_id
+        )
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn reopen(&self, db: &PgPool) -> sqlx::Result<()> {
+        sqlx::query!(
+            // language=PostgreSQL
+            r#"
+            UPDATE issue SET state = 'Open', resolved_by = NULL, resolved = NULL, resolve_activity_id = NULL
+            WHERE activity_pub_id = $1
+            "#,
+            self.activity_pub_id
+        )
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Id of the `Resolve` activity which closed the issue, with its actor
+    pub async fn resolve_activity(&self, db: &PgPool) -> sqlx::Result<Option<(String, String)>> {
+        let resolve = sqlx::query!(
+            // language=PostgreSQL
+            r#"
+            SELECT resolve_activity_id, resolved_by FROM issue
+            WHERE activity_pub_id = $1
+            "#,
+            self.activity_pub_id
+        )
+        .fetch_one(db)
+        .await?;
+
+        Ok(resolve.resolve_activity_id.zip(resolve.resolved_by))
+    }
 }
 
 impl Repository {
@@ -207,11 +298,12 @@ impl Repository {
                         u.username as opened_by,
                         i.title,
                         i.content,
-                        i.state as "state: IssueState"
+                        i.state as "state: IssueState",
+                        i.deleted_at
                 FROM issue i
                 JOIN repository r ON r.id = $1
                 JOIN users u on u.id = i.opened_by
-                WHERE i.repository_id = $1
+                WHERE i.repository_id = $1 AND i.deleted_at IS NULL
                 LIMIT $2
                 OFFSET $3
             "#,
@@ -236,7 +328,8 @@ impl Repository {
                     u.username as opened_by,
                     i.title,
                     i.content,
-                    i.state as "state: IssueState"
+                    i.state as "state: IssueState",
+                    i.deleted_at
                 FROM issue i
                 JOIN users u on u.id = i.opened_by
                 WHERE number = $1 AND repository_id = $2
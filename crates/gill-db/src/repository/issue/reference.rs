@@ -0,0 +1,138 @@
+use crate::Insert;
+use async_trait::async_trait;
+use sqlx::PgPool;
+
+#[derive(sqlx::Type, Debug, Copy, Clone, Eq, PartialEq)]
+#[sqlx(type_name = "reference_source")]
+pub enum ReferenceSource {
+    Issue,
+    PullRequest,
+    Commit,
+}
+
+/// Issue `number` of `repository_id` being referenced from another issue, a pull request
+/// or a commit. Issues and pull requests have a `source_number`, commits a `source_sha`.
+#[derive(Debug)]
+pub struct CreateIssueReference {
+    pub repository_id: i32,
+    pub number: i32,
+    pub source_repository_id: i32,
+    pub source_number: Option<i32>,
+    pub source_sha: Option<String>,
+    pub source: ReferenceSource,
+    pub actor_id: i32,
+    /// The reference closes the issue, e.g. "fixes #12"
+    pub closes: bool,
+}
+
+/// Where an issue has been referenced from
+#[derive(sqlx::FromRow, Debug)]
+pub struct IssueReference {
+    pub source: ReferenceSource,
+    pub source_number: Option<i32>,
+    pub source_sha: Option<String>,
+    pub source_title: Option<String>,
+    pub source_owner: String,
+    pub source_repository: String,
+    pub actor_name: String,
+    pub actor_activity_pub_id: String,
+    pub closes: bool,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+#[async_trait]
+impl Insert for CreateIssueReference {
+    type Output = ();
+
+    /// References to unknown issues are ignored. A reference already recorded is only
+    /// updated when it now closes the issue.
+    async fn insert(self, db: &PgPool) -> sqlx::Result<Self::Output> {
+        if self.source_sha.is_some() {
+            sqlx::query!(
+                // language=PostgreSQL
+                r#"
+                INSERT INTO issue_reference (repository_id, number, source_repository_id, source_sha, source, actor_id, closes)
+                SELECT i.repository_id, i.number, $3, $4, $5, $6, $7
+                FROM issue i
+                WHERE i.repository_id = $1 AND i.number = $2
+                ON CONFLICT (repository_id, number, source_repository_id, source_sha) WHERE source_sha IS NOT NULL
+                DO UPDATE SET closes = issue_reference.closes OR excluded.closes
+                "#,
+                self.repository_id,
+                self.number,
+                self.source_repository_id,
+                self.source_sha,
+                self.source as ReferenceSource,
+                self.actor_id,
+                self.closes,
+            )
+            .execute(db)
+            .await?;
+        } else {
+            sqlx::query!(
+                // language=PostgreSQL
+                r#"
+                INSERT INTO issue_reference (repository_id, number, source_repository_id, source_number, source, actor_id, closes)
+                SELECT i.repository_id, i.number, $3, $4, $5, $6, $7
+                FROM issue i
+                WHERE i.repository_id = $1 AND i.number = $2
+                ON CONFLICT (repository_id, number, source_repository_id, source_number, source)
+                DO UPDATE SET closes = issue_reference.closes OR excluded.closes
+                "#,
+                self.repository_id,
+                self.number,
+                self.source_repository_id,
+                self.source_number,
+                self.source as ReferenceSource,
+                self.actor_id,
+                self.closes,
+            )
+            .execute(db)
+            .await?;
+        }
+
+        Ok(())
+    }
+}
+
+impl IssueReference {
+    /// Issues, pull requests and commits referencing issue `number` of `repository_id`, oldest first
+    pub async fn for_issue(
+        repository_id: i32,
+        number: i32,
+        db: &PgPool,
+    ) -> sqlx::Result<Vec<IssueReference>> {
+        let references = sqlx::query_as!(
+            IssueReference,
+            // language=PostgreSQL
+            r#"
+            SELECT r.source as "source: ReferenceSource",
+                   r.source_number,
+                   r.source_sha,
+                   COALESCE(i.title, p.title) as source_title,
+                   owner.username as source_owner,
+                   repository.name as source_repository,
+                   actor.username as actor_name,
+                   actor.activity_pub_id as actor_activity_pub_id,
+                   r.closes,
+                   r.created_at
+            FROM issue_reference r
+            JOIN repository ON repository.id = r.source_repository_id
+            JOIN users owner ON owner.activity_pub_id = repository.attributed_to
+            JOIN users actor ON actor.id = r.actor_id
+            LEFT JOIN issue i ON r.source = 'Issue'
+                AND i.repository_id = r.source_repository_id AND i.number = r.source_number
+            LEFT JOIN pull_request p ON r.source = 'PullRequest'
+                AND p.repository_id = r.source_repository_id AND p.number = r.source_number
+            WHERE r.repository_id = $1 AND r.number = $2
+            ORDER BY r.created_at
+            "#,
+            repository_id,
+            number,
+        )
+        .fetch_all(db)
+        .await?;
+
+        Ok(references)
+    }
+}
@@ -0,0 +1,100 @@
+use crate::repository::Repository;
+use sqlx::PgPool;
+
+#[derive(sqlx::FromRow, Debug)]
+pub struct Label {
+    pub id: i32,
+    pub repository_id: i32,
+    pub name: String,
+    pub color: String,
+}
+
+/// A label attached to an issue or a pull request
+#[derive(sqlx::FromRow, Debug)]
+pub struct ItemLabel {
+    pub number: i32,
+    pub name: String,
+    pub color: String,
+}
+
+impl Repository {
+    /// Create the label, or update its color if it already exists
+    pub async fn upsert_label(&self, name: &str, color: &str, db: &PgPool) -> sqlx::Result<Label> {
+        let label = sqlx::query_as!(
+            Label,
+            // language=PostgreSQL
+            r#"
+            INSERT INTO label (repository_id, name, color)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (repository_id, name) DO UPDATE SET color = $3
+            RETURNING id, repository_id, name, color
+            "#,
+            self.id,
+            name,
+            color
+        )
+        .fetch_one(db)
+        .await?;
+
+        Ok(label)
+    }
+
+    pub async fn list_labels(&self, db: &PgPool) -> sqlx::Result<Vec<Label>> {
+        let labels = sqlx::query_as!(
+            Label,
+            // language=PostgreSQL
+            r#"
+            SELECT id, repository_id, name, color FROM label
+            WHERE repository_id = $1
+            ORDER BY name
+            "#,
+            self.id
+        )
+        .fetch_all(db)
+        .await?;
+
+        Ok(labels)
+    }
+
+    pub async fn add_item_label(
+        &self,
+        number: i32,
+        label_id: i32,
+        db: &PgPool,
+    ) -> sqlx::Result<()> {
+        sqlx::query!(
+            // language=PostgreSQL
+            r#"
+            INSERT INTO issue_label (repository_id, number, label_id)
+            VALUES ($1, $2, $3)
+            ON CONFLICT DO NOTHING
+            "#,
+            self.id,
+            number,
+            label_id
+        )
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Labels of every issue and pull request of the repository
+    pub async fn list_item_labels(&self, db: &PgPool) -> sqlx::Result<Vec<ItemLabel>> {
+        let labels = sqlx::query_as!(
+            ItemLabel,
+            // language=PostgreSQL
+            r#"
+            SELECT il.number, l.name, l.color FROM issue_label il
+            JOIN label l ON l.id = il.label_id
+            WHERE il.repository_id = $1
+            ORDER BY il.number, l.name
+            "#,
+            self.id
+        )
+        .fetch_all(db)
+        .await?;
+
+        Ok(labels)
+    }
+}
@@ -0,0 +1,188 @@
+use crate::repository::Repository;
+use sqlx::PgPool;
+
+/// Remote a repository git data is fetched from
+#[derive(sqlx::FromRow, Debug)]
+pub struct RepositoryMirror {
+    pub repository_id: i32,
+    pub remote_url: String,
+    pub sync_interval: i32,
+    pub last_synced_at: Option<chrono::NaiveDateTime>,
+    pub last_error: Option<String>,
+    pub next_sync_at: chrono::NaiveDateTime,
+}
+
+impl RepositoryMirror {
+    /// Mirror `remote_url` into the repository, the first sync is due immediately
+    pub async fn create(
+        repository_id: i32,
+        remote_url: &str,
+        sync_interval: i32,
+        db: &PgPool,
+    ) -> sqlx::Result<RepositoryMirror> {
+        let mirror = sqlx::query_as!(
+            RepositoryMirror,
+            // language=PostgreSQL
+            r#"
+            INSERT INTO repository_mirror (repository_id, remote_url, sync_interval)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (repository_id) DO UPDATE
+                SET remote_url = $2, sync_interval = $3, next_sync_at = now()
+            RETURNING repository_id, remote_url, sync_interval, last_synced_at, last_error, next_sync_at
+            "#,
+            repository_id,
+            remote_url,
+            sync_interval
+        )
+        .fetch_one(db)
+        .await?;
+
+        Ok(mirror)
+    }
+
+    pub async fn by_repository_id(
+        repository_id: i32,
+        db: &PgPool,
+    ) -> sqlx::Result<Option<RepositoryMirror>> {
+        let mirror = sqlx::query_as!(
+            RepositoryMirror,
+            // language=PostgreSQL
+            r#"
+            SELECT repository_id, remote_url, sync_interval, last_synced_at, last_error, next_sync_at
+            FROM repository_mirror
+            WHERE repository_id = $1
+            "#,
+            repository_id
+        )
+        .fetch_optional(db)
+        .await?;
+
+        Ok(mirror)
+    }
+
+    /// Take up to `limit` mirrors due for a sync and schedule their next one,
+    /// so concurrent workers and crashed syncs do not fetch in a loop.
+    pub async fn claim_due(limit: i64, db: &PgPool) -> sqlx::Result<Vec<RepositoryMirror>> {
+        let mirrors = sqlx::query_as!(
+            RepositoryMirror,
+            // language=PostgreSQL
+            r#"
+            UPDATE repository_mirror
+            SET next_sync_at = now() + make_interval(secs => sync_interval)
+            WHERE repository_id IN (
+                SELECT repository_id FROM repository_mirror
+                WHERE next_sync_at <= now()
+                ORDER BY next_sync_at
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING repository_id, remote_url, sync_interval, last_synced_at, last_error, next_sync_at
+            "#,
+            limit
+        )
+        .fetch_all(db)
+        .await?;
+
+        Ok(mirrors)
+    }
+
+    pub async fn synced(repository_id: i32, db: &PgPool) -> sqlx::Result<()> {
+        sqlx::query!(
+            // language=PostgreSQL
+            r#"
+            UPDATE repository_mirror
+            SET last_synced_at = now(), last_error = NULL
+            WHERE repository_id = $1
+            "#,
+            repository_id
+        )
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn sync_failed(repository_id: i32, error: &str, db: &PgPool) -> sqlx::Result<()> {
+        sqlx::query!(
+            // language=PostgreSQL
+            r#"UPDATE repository_mirror SET last_error = $2 WHERE repository_id = $1"#,
+            repository_id,
+            error
+        )
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Fetch the mirror on the next worker run
+    pub async fn schedule_now(repository_id: i32, db: &PgPool) -> sqlx::Result<()> {
+        sqlx::query!(
+            // language=PostgreSQL
+            r#"UPDATE repository_mirror SET next_sync_at = now() WHERE repository_id = $1"#,
+            repository_id
+        )
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+}
+
+impl Repository {
+    /// Replace the repository branches with the fetched ones. Rows of deleted branches
+    /// are kept as long as pull requests still reference them.
+    pub async fn sync_branches(
+        &self,
+        branches: &[String],
+        default_branch: Option<&str>,
+        db: &PgPool,
+    ) -> sqlx::Result<()> {
+        let mut tx = db.begin().await?;
+
+        sqlx::query!(
+            // language=PostgreSQL
+            r#"
+            DELETE FROM branch b
+            WHERE b.repository_id = $1 AND NOT b.name = ANY($2)
+            AND NOT EXISTS (
+                SELECT 1 FROM pull_request pr
+                WHERE pr.repository_id = b.repository_id AND (pr.base = b.name OR pr.compare = b.name)
+            )
+            "#,
+            self.id,
+            branches
+        )
+        .execute(&mut tx)
+        .await?;
+
+        sqlx::query!(
+            // language=PostgreSQL
+            r#"
+            INSERT INTO branch (name, repository_id, is_default)
+            SELECT name, $1, COALESCE(name = $3, false) FROM UNNEST($2::text[]) AS t(name)
+            ON CONFLICT (name, repository_id) DO UPDATE SET is_default = excluded.is_default
+            "#,
+            self.id,
+            branches,
+            default_branch
+        )
+        .execute(&mut tx)
+        .await?;
+
+        sqlx::query!(
+            // language=PostgreSQL
+            r#"
+            UPDATE branch SET is_default = false
+            WHERE repository_id = $1 AND NOT name = ANY($2)
+            "#,
+            self.id,
+            branches
+        )
+        .execute(&mut tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+}
@@ -1,14 +1,24 @@
 use branch::Branch;
 
-use sqlx::PgPool;
+use sqlx::{PgPool, Postgres, Transaction};
 
 pub mod branch;
 pub mod create;
 pub mod digest;
+pub mod event;
 pub mod fork;
+pub mod import;
 pub mod issue;
+pub mod label;
+pub mod mirror;
 pub mod pull_request;
+pub mod push;
+pub mod push_mirror;
+pub mod reaction;
+pub mod release;
+pub mod revision;
 pub mod star;
+pub mod tag;
 pub mod watch;
 
 #[derive(sqlx::FromRow, Debug)]
@@ -83,6 +93,7 @@ impl Repository {
                 SELECT b.name, b.repository_id, b.is_default FROM repository r
                 JOIN branch b ON b.repository_id = r.id
                 WHERE r.id = $3
+                ORDER BY b.is_default DESC, b.name
                 LIMIT $1
                 OFFSET $2
             "#,
@@ -136,6 +147,87 @@ impl Repository {
         Ok(())
     }
 
+    /// Delete a branch row unless an open pull request uses it as base or compare branch,
+    /// returns whether it was deleted. The caller commits once the git branch is gone.
+    pub async fn delete_branch(
+        &self,
+        branch_name: &str,
+        tx: &mut Transaction<'_, Postgres>,
+    ) -> sqlx::Result<bool> {
+        let deleted = sqlx::query!(
+            // language=PostgreSQL
+            r#"
+            DELETE FROM branch b
+            WHERE b.repository_id = $1 AND b.name = $2
+            AND NOT EXISTS (
+                SELECT 1 FROM pull_request pr
+                WHERE pr.repository_id = b.repository_id
+                AND (pr.base = b.name OR pr.compare = b.name)
+                AND pr.state = 'Open'
+            )
+            "#,
+            self.id,
+            branch_name
+        )
+        .execute(tx)
+        .await?
+        .rows_affected();
+
+        Ok(deleted > 0)
+    }
+
+    /// Rename a branch and retarget open pull requests using it as base or compare branch.
+    /// The caller commits once the git branch is renamed.
+    pub async fn rename_branch(
+        &self,
+        branch_name: &str,
+        new_name: &str,
+        tx: &mut Transaction<'_, Postgres>,
+    ) -> sqlx::Result<()> {
+        sqlx::query!(
+            // language=PostgreSQL
+            r#"
+            INSERT INTO branch (name, repository_id, is_default)
+            SELECT $3, repository_id, is_default FROM branch
+            WHERE repository_id = $1 AND name = $2
+            "#,
+            self.id,
+            branch_name,
+            new_name
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            // language=PostgreSQL
+            r#"
+            UPDATE pull_request SET base = $3
+            WHERE repository_id = $1 AND base = $2 AND state = 'Open'
+            "#,
+            self.id,
+            branch_name,
+            new_name
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            // language=PostgreSQL
+            r#"
+            UPDATE pull_request SET compare = $3
+            WHERE repository_id = $1 AND compare = $2 AND state = 'Open'
+            "#,
+            self.id,
+            branch_name,
+            new_name
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        self.delete_branch_in(branch_name, tx).await?;
+        Ok(())
+    }
+
     pub async fn by_activity_pub_id(
         activity_pub_id: &str,
         pool: &PgPool,
@@ -160,7 +252,7 @@ impl Repository {
             // language=PostgreSQL
             r#"
             select username from repository r
-            JOIN users u ON r.attributed_to = r.activity_pub_id
+            JOIN users u ON r.attributed_to = u.activity_pub_id
             where r.id = $1
 
             "#,
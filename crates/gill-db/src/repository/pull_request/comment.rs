@@ -4,4 +4,6 @@ pub struct PullRequestComment {
     pub repository_id: i32,
     pub created_by: String,
     pub content: String,
+    pub created_at: chrono::NaiveDateTime,
+    pub deleted_at: Option<chrono::NaiveDateTime>,
 }
@@ -23,6 +23,7 @@ pub struct PullRequest {
     pub base: String,
     pub compare: String,
     pub state: PullRequestState,
+    pub deleted_at: Option<chrono::NaiveDateTime>,
 }
 
 impl PullRequest {
@@ -47,22 +48,154 @@ impl PullRequest {
     pub async fn get_comments(&self, db: &PgPool) -> sqlx::Result<Vec<PullRequestComment>> {
         let comments = sqlx::query_as!(
             PullRequestComment,
-           // language=PostgreSQL
-           r#"
-           SELECT c.id, c.repository_id, u.username as created_by, c.content FROM pull_request_comment c
+            // language=PostgreSQL
+            r#"
+           SELECT c.id, c.repository_id, u.username as created_by, c.content, c.created_at,
+                  c.deleted_at
+                FROM pull_request_comment c
                 JOIN users u on u.id = c.created_by
                 WHERE c.repository_id = $1
-                AND c.number = $2;
+                AND c.number = $2
+                ORDER BY c.created_at, c.id;
            "#,
-           self.repository_id,
-           self.number,
-       )
-            .fetch_all(db)
-            .await?;
+            self.repository_id,
+            self.number,
+        )
+        .fetch_all(db)
+        .await?;
 
         Ok(comments)
     }
 
+    pub async fn get_comment(&self, id: i32, db: &PgPool) -> sqlx::Result<PullRequestComment> {
+        let comment = sqlx::query_as!(
+            PullRequestComment,
+            // language=PostgreSQL
+            r#"
+           SELECT c.id, c.repository_id, u.username as created_by, c.content, c.created_at,
+                  c.deleted_at
+                FROM pull_request_comment c
+                JOIN users u on u.id = c.created_by
+                WHERE c.repository_id = $1
+                AND c.number = $2
+                AND c.id = $3;
+           "#,
+            self.repository_id,
+            self.number,
+            id,
+        )
+        .fetch_one(db)
+        .await?;
+
+        Ok(comment)
+    }
+
+    pub async fn update_comment(&self, id: i32, content: &str, db: &PgPool) -> sqlx::Result<()> {
+        sqlx::query!(
+            // language=PostgreSQL
+            r#"
+           UPDATE pull_request_comment SET content = $1
+            WHERE id = $2 AND number = $3 AND repository_id = $4;
+           "#,
+            content,
+            id,
+            self.number,
+            self.repository_id
+        )
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Replace comment `id` with a tombstone, its content and revisions are dropped
+    pub async fn mark_comment_deleted(&self, id: i32, db: &PgPool) -> sqlx::Result<()> {
+        let mut transaction = db.begin().await?;
+        sqlx::query!(
+            // language=PostgreSQL
+            r#"
+           UPDATE pull_request_comment SET content = '', deleted_at = now()
+            WHERE id = $1 AND number = $2 AND repository_id = $3;
+           "#,
+            id,
+            self.number,
+            self.repository_id
+        )
+        .execute(&mut transaction)
+        .await?;
+
+        sqlx::query!(
+            // language=PostgreSQL
+            r#"
+           DELETE FROM revision
+            WHERE pull_request_comment_id = $1 AND number = $2 AND repository_id = $3;
+           "#,
+            id,
+            self.number,
+            self.repository_id
+        )
+        .execute(&mut transaction)
+        .await?;
+
+        transaction.commit().await?;
+        Ok(())
+    }
+
+    pub async fn update_content(
+        &self,
+        title: &str,
+        description: Option<&str>,
+        db: &PgPool,
+    ) -> sqlx::Result<()> {
+        sqlx::query!(
+            // language=PostgreSQL
+            r#"
+           UPDATE pull_request SET title = $1, description = $2
+            WHERE pull_request.number = $3 AND repository_id = $4;
+           "#,
+            title,
+            description,
+            self.number,
+            self.repository_id
+        )
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Replace the pull request with a tombstone, its description and revisions are dropped
+    pub async fn mark_deleted(&self, db: &PgPool) -> sqlx::Result<()> {
+        let mut transaction = db.begin().await?;
+        sqlx::query!(
+            // language=PostgreSQL
+            r#"
+           UPDATE pull_request SET description = NULL, deleted_at = now()
+            WHERE pull_request.number = $1 AND repository_id = $2;
+           "#,
+            self.number,
+            self.repository_id
+        )
+        .execute(&mut transaction)
+        .await?;
+
+        sqlx::query!(
+            // language=PostgreSQL
+            r#"
+           DELETE FROM revision
+            WHERE number = $1 AND repository_id = $2
+            AND issue_comment_id IS NULL AND pull_request_comment_id IS NULL;
+           "#,
+            self.number,
+            self.repository_id
+        )
+        .execute(&mut transaction)
+        .await?;
+
+        transaction.commit().await?;
+        Ok(())
+    }
+
     pub async fn close(&self, db: &PgPool) -> sqlx::Result<()> {
         sqlx::query!(
             // language=PostgreSQL
@@ -156,11 +289,12 @@ impl Repository {
                         p.description,
                         p.base,
                         p.compare,
-                        p.state as "state: PullRequestState"
+                        p.state as "state: PullRequestState",
+                        p.deleted_at
                 FROM pull_request p
                 JOIN repository r ON r.id = $1
                 JOIN users u on u.id = p.opened_by
-                WHERE r.id = p.repository_id
+                WHERE r.id = p.repository_id AND p.deleted_at IS NULL
                 LIMIT $2
                 OFFSET $3
             "#,
@@ -174,6 +308,40 @@ impl Repository {
         Ok(pull_requests)
     }
 
+    /// Open pull requests of this repository comparing `branch`
+    pub async fn open_pull_requests_from(
+        &self,
+        branch: &str,
+        db: &PgPool,
+    ) -> sqlx::Result<Vec<PullRequest>> {
+        let pull_requests = sqlx::query_as!(
+            PullRequest,
+            // language=PostgreSQL
+            r#"
+                SELECT
+                    p.repository_id,
+                    p.number,
+                    u.username as opened_by,
+                    p.title,
+                    p.description,
+                    p.base,
+                    p.compare,
+                    p.state as "state: PullRequestState",
+                    p.deleted_at
+                FROM pull_request p
+                JOIN users u on u.id = p.opened_by
+                WHERE p.repository_id = $1 AND p.compare = $2 AND p.state = 'Open'
+                  AND p.deleted_at IS NULL
+            "#,
+            self.id,
+            branch,
+        )
+        .fetch_all(db)
+        .await?;
+
+        Ok(pull_requests)
+    }
+
     pub async fn get_pull_request(&self, number: i32, db: &PgPool) -> sqlx::Result<PullRequest> {
         let pull_request = sqlx::query_as!(
             PullRequest,
@@ -187,7 +355,8 @@ impl Repository {
                     p.description,
                     p.base,
                     p.compare,
-                    p.state as "state: PullRequestState"
+                    p.state as "state: PullRequestState",
+                    p.deleted_at
                 FROM pull_request p
                 JOIN users u on u.id = p.opened_by
                 WHERE number = $1 AND repository_id = $2
@@ -0,0 +1,378 @@
+use crate::repository::Repository;
+use crate::user::activity::{CreateUserActivity, UserActivityKind};
+use sqlx::{PgPool, Postgres, Transaction};
+
+/// Postgres channel notified with the id of every new push event
+pub const PUSH_EVENT_CHANNEL: &str = "push_event";
+
+/// Sha git uses for the missing side of a ref creation or deletion
+pub const NULL_SHA: &str = "0000000000000000000000000000000000000000";
+
+/// A single line of a `post-receive` hook input: `<old-sha> <new-sha> <ref-name>`
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct RefUpdate {
+    pub before: String,
+    pub after: String,
+    pub ref_name: String,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum RefUpdateKind {
+    Create,
+    Update,
+    Delete,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum RefTarget<'a> {
+    Branch(&'a str),
+    Tag(&'a str),
+    Other(&'a str),
+}
+
+#[derive(sqlx::FromRow, Debug)]
+pub struct PushEvent {
+    pub id: i32,
+    pub repository_id: i32,
+    pub pushed_by: Option<i32>,
+    pub ref_name: String,
+    pub before: String,
+    pub after: String,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+impl RefUpdate {
+    pub fn kind(&self) -> RefUpdateKind {
+        if self.before == NULL_SHA {
+            RefUpdateKind::Create
+        } else if self.after == NULL_SHA {
+            RefUpdateKind::Delete
+        } else {
+            RefUpdateKind::Update
+        }
+    }
+
+    pub fn target(&self) -> RefTarget<'_> {
+        if let Some(branch) = self.ref_name.strip_prefix("refs/heads/") {
+            RefTarget::Branch(branch)
+        } else if let Some(tag) = self.ref_name.strip_prefix("refs/tags/") {
+            RefTarget::Tag(tag)
+        } else {
+            RefTarget::Other(&self.ref_name)
+        }
+    }
+}
+
+impl PushEvent {
+    pub async fn by_id(id: i32, db: &PgPool) -> sqlx::Result<PushEvent> {
+        let event = sqlx::query_as!(
+            PushEvent,
+            // language=PostgreSQL
+            r#"
+                SELECT id, repository_id, pushed_by, ref_name, before, after, created_at
+                FROM push_event
+                WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_one(db)
+        .await?;
+
+        Ok(event)
+    }
+
+    /// Lease up to `limit` unprocessed push events for `lease_secs` seconds and return them,
+    /// oldest first. Events leased `max_attempts` times are not picked up anymore.
+    pub async fn claim_unprocessed(
+        limit: i64,
+        lease_secs: f64,
+        max_attempts: i32,
+        db: &PgPool,
+    ) -> sqlx::Result<Vec<PushEvent>> {
+        let mut events = sqlx::query_as!(
+            PushEvent,
+            // language=PostgreSQL
+            r#"
+                UPDATE push_event
+                SET locked_until = now() + make_interval(secs => $2),
+                    attempts = attempts + 1
+                WHERE id IN (
+                    SELECT id FROM push_event
+                    WHERE processed_at IS NULL
+                    AND attempts < $3
+                    AND (locked_until IS NULL OR locked_until < now())
+                    ORDER BY id
+                    LIMIT $1
+                    FOR UPDATE SKIP LOCKED
+                )
+                RETURNING id, repository_id, pushed_by, ref_name, before, after, created_at
+            "#,
+            limit,
+            lease_secs,
+            max_attempts
+        )
+        .fetch_all(db)
+        .await?;
+
+        // UPDATE .. RETURNING does not keep the sub-query order
+        events.sort_by_key(|event| event.id);
+        Ok(events)
+    }
+
+    pub async fn mark_processed(&self, db: &PgPool) -> sqlx::Result<()> {
+        sqlx::query!(
+            // language=PostgreSQL
+            r#"
+                UPDATE push_event
+                SET processed_at = now(), locked_until = NULL
+                WHERE id = $1
+            "#,
+            self.id
+        )
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+}
+
+impl Repository {
+    /// Reconcile the branch and tag tables with a batch of ref updates and record
+    /// a push event for each of them. Everything happens in a single transaction,
+    /// listeners on [`PUSH_EVENT_CHANNEL`] are notified once it is committed.
+    pub async fn apply_push(
+        &self,
+        pushed_by: Option<i32>,
+        updates: &[RefUpdate],
+        db: &PgPool,
+    ) -> sqlx::Result<Vec<PushEvent>> {
+        let mut tx = db.begin().await?;
+        let mut created_branches = vec![];
+        let mut events = vec![];
+
+        for update in updates {
+            match (update.target(), update.kind()) {
+                (RefTarget::Branch(branch), RefUpdateKind::Delete) => {
+                    self.delete_branch_in(branch, &mut tx).await?;
+                }
+                (RefTarget::Branch(branch), _) => {
+                    self.create_branch_in(branch, &mut tx).await?;
+                    created_branches.push(branch);
+                    if let Some(actor_id) = pushed_by {
+                        CreateUserActivity {
+                            actor_id,
+                            repository_id: self.id,
+                            kind: UserActivityKind::Push,
+                            number: None,
+                            summary: Some(branch.to_string()),
+                        }
+                        .insert_in(&mut *tx)
+                        .await?;
+                    }
+                }
+                (RefTarget::Tag(tag), RefUpdateKind::Delete) => {
+                    self.delete_tag_in(tag, &mut tx).await?;
+                }
+                (RefTarget::Tag(tag), _) => {
+                    self.upsert_tag_in(tag, &update.after, &mut tx).await?;
+                }
+                (RefTarget::Other(_), _) => {}
+            }
+
+            let event = self.insert_push_event(pushed_by, update, &mut tx).await?;
+            events.push(event);
+        }
+
+        self.ensure_default_branch(&created_branches, &mut tx)
+            .await?;
+
+        for event in &events {
+            sqlx::query!(
+                // language=PostgreSQL
+                r#"SELECT pg_notify($1, $2)"#,
+                PUSH_EVENT_CHANNEL,
+                event.id.to_string()
+            )
+            .execute(&mut tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(events)
+    }
+
+    async fn create_branch_in(
+        &self,
+        branch: &str,
+        tx: &mut Transaction<'_, Postgres>,
+    ) -> sqlx::Result<()> {
+        sqlx::query!(
+            // language=PostgreSQL
+            r#"
+                INSERT INTO branch (name, repository_id, is_default)
+                VALUES ($1, $2, false)
+                ON CONFLICT (name, repository_id) DO NOTHING
+            "#,
+            branch,
+            self.id
+        )
+        .execute(tx)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Branches used as the base of an open pull request are kept until the pull request
+    /// is resolved, returns whether the branch row was deleted
+    pub(crate) async fn delete_branch_in(
+        &self,
+        branch: &str,
+        tx: &mut Transaction<'_, Postgres>,
+    ) -> sqlx::Result<bool> {
+        let deleted = sqlx::query!(
+            // language=PostgreSQL
+            r#"
+                DELETE FROM branch b
+                WHERE b.repository_id = $1 AND b.name = $2
+                AND NOT EXISTS (
+                    SELECT 1 FROM pull_request pr
+                    WHERE pr.repository_id = b.repository_id AND pr.base = b.name
+                    AND pr.state = 'Open'
+                )
+            "#,
+            self.id,
+            branch
+        )
+        .execute(tx)
+        .await?
+        .rows_affected();
+
+        Ok(deleted > 0)
+    }
+
+    async fn upsert_tag_in(
+        &self,
+        tag: &str,
+        target: &str,
+        tx: &mut Transaction<'_, Postgres>,
+    ) -> sqlx::Result<()> {
+        sqlx::query!(
+            // language=PostgreSQL
+            r#"
+                INSERT INTO tag (name, repository_id, target)
+                VALUES ($1, $2, $3)
+                ON CONFLICT (name, repository_id) DO UPDATE SET target = $3
+            "#,
+            tag,
+            self.id,
+            target
+        )
+        .execute(tx)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn delete_tag_in(
+        &self,
+        tag: &str,
+        tx: &mut Transaction<'_, Postgres>,
+    ) -> sqlx::Result<()> {
+        sqlx::query!(
+            // language=PostgreSQL
+            r#"
+                DELETE FROM tag
+                WHERE repository_id = $1 AND name = $2
+            "#,
+            self.id,
+            tag
+        )
+        .execute(tx)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn insert_push_event(
+        &self,
+        pushed_by: Option<i32>,
+        update: &RefUpdate,
+        tx: &mut Transaction<'_, Postgres>,
+    ) -> sqlx::Result<PushEvent> {
+        let event = sqlx::query_as!(
+            PushEvent,
+            // language=PostgreSQL
+            r#"
+                INSERT INTO push_event (repository_id, pushed_by, ref_name, before, after)
+                VALUES ($1, $2, $3, $4, $5)
+                RETURNING id, repository_id, pushed_by, ref_name, before, after, created_at
+            "#,
+            self.id,
+            pushed_by,
+            update.ref_name,
+            update.before,
+            update.after
+        )
+        .fetch_one(tx)
+        .await?;
+
+        Ok(event)
+    }
+
+    // When the default branch is missing (first push, or it was deleted), the first
+    // branch created by this push is elected, otherwise any remaining branch.
+    async fn ensure_default_branch(
+        &self,
+        created_branches: &[&str],
+        tx: &mut Transaction<'_, Postgres>,
+    ) -> sqlx::Result<()> {
+        let default = sqlx::query_scalar!(
+            // language=PostgreSQL
+            r#"
+                SELECT name FROM branch
+                WHERE repository_id = $1 AND is_default
+            "#,
+            self.id
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        if default.is_some() {
+            return Ok(());
+        }
+
+        let candidate = match created_branches.first() {
+            Some(branch) => Some(branch.to_string()),
+            None => {
+                sqlx::query_scalar!(
+                    // language=PostgreSQL
+                    r#"
+                        SELECT name FROM branch
+                        WHERE repository_id = $1
+                        ORDER BY name
+                        LIMIT 1
+                    "#,
+                    self.id
+                )
+                .fetch_optional(&mut *tx)
+                .await?
+            }
+        };
+
+        if let Some(candidate) = candidate {
+            sqlx::query!(
+                // language=PostgreSQL
+                r#"
+                    UPDATE branch SET is_default = true
+                    WHERE repository_id = $1 AND name = $2
+                "#,
+                self.id,
+                candidate
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        Ok(())
+    }
+}
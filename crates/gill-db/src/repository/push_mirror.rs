@@ -0,0 +1,189 @@
+use sqlx::PgPool;
+
+#[derive(sqlx::Type, Debug, Copy, Clone, Eq, PartialEq)]
+#[sqlx(type_name = "push_mirror_credential")]
+pub enum PushMirrorCredential {
+    None,
+    Token,
+    DeployKey,
+}
+
+#[derive(sqlx::Type, Debug, Copy, Clone, Eq, PartialEq)]
+#[sqlx(type_name = "push_mirror_state")]
+pub enum PushMirrorState {
+    /// Nothing was pushed yet
+    Idle,
+    Pending,
+    Success,
+    Failed,
+}
+
+/// A remote the repository is pushed to after each push
+#[derive(sqlx::FromRow, Debug)]
+pub struct PushMirror {
+    pub id: i32,
+    pub repository_id: i32,
+    pub remote_url: String,
+    pub credential_kind: PushMirrorCredential,
+    pub encrypted_credential: Option<String>,
+    pub state: PushMirrorState,
+    pub last_pushed_at: Option<chrono::NaiveDateTime>,
+    pub last_error: Option<String>,
+    pub requested_at: Option<chrono::NaiveDateTime>,
+}
+
+impl PushMirror {
+    pub async fn create(
+        repository_id: i32,
+        remote_url: &str,
+        credential_kind: PushMirrorCredential,
+        encrypted_credential: Option<&str>,
+        db: &PgPool,
+    ) -> sqlx::Result<PushMirror> {
+        let mirror = sqlx::query_as!(
+            PushMirror,
+            // language=PostgreSQL
+            r#"
+            INSERT INTO push_mirror (repository_id, remote_url, credential_kind, encrypted_credential)
+            VALUES ($1, $2, $3, $4)
+            RETURNING id, repository_id, remote_url,
+                      credential_kind as "credential_kind: PushMirrorCredential",
+                      encrypted_credential,
+                      state as "state: PushMirrorState",
+                      last_pushed_at, last_error, requested_at
+            "#,
+            repository_id,
+            remote_url,
+            credential_kind as PushMirrorCredential,
+            encrypted_credential
+        )
+        .fetch_one(db)
+        .await?;
+
+        Ok(mirror)
+    }
+
+    pub async fn list_for_repository(
+        repository_id: i32,
+        db: &PgPool,
+    ) -> sqlx::Result<Vec<PushMirror>> {
+        let mirrors = sqlx::query_as!(
+            PushMirror,
+            // language=PostgreSQL
+            r#"
+            SELECT id, repository_id, remote_url,
+                   credential_kind as "credential_kind: PushMirrorCredential",
+                   encrypted_credential,
+                   state as "state: PushMirrorState",
+                   last_pushed_at, last_error, requested_at
+            FROM push_mirror
+            WHERE repository_id = $1
+            ORDER BY id
+            "#,
+            repository_id
+        )
+        .fetch_all(db)
+        .await?;
+
+        Ok(mirrors)
+    }
+
+    pub async fn delete(id: i32, repository_id: i32, db: &PgPool) -> sqlx::Result<()> {
+        sqlx::query!(
+            // language=PostgreSQL
+            r#"DELETE FROM push_mirror WHERE id = $1 AND repository_id = $2"#,
+            id,
+            repository_id
+        )
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Request a push to every mirror of the repository
+    pub async fn schedule_for_repository(repository_id: i32, db: &PgPool) -> sqlx::Result<()> {
+        sqlx::query!(
+            // language=PostgreSQL
+            r#"
+            UPDATE push_mirror
+            SET state = 'Pending', requested_at = now(), next_push_at = now()
+            WHERE repository_id = $1
+            "#,
+            repository_id
+        )
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Take up to `limit` due mirror pushes. Claimed rows are leased for `lease_secs`
+    /// so a crashed worker does not lose them.
+    pub async fn claim_due(
+        limit: i64,
+        lease_secs: f64,
+        db: &PgPool,
+    ) -> sqlx::Result<Vec<PushMirror>> {
+        let mirrors = sqlx::query_as!(
+            PushMirror,
+            // language=PostgreSQL
+            r#"
+            UPDATE push_mirror
+            SET next_push_at = now() + make_interval(secs => $2)
+            WHERE id IN (
+                SELECT id FROM push_mirror
+                WHERE next_push_at <= now()
+                ORDER BY next_push_at
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING id, repository_id, remote_url,
+                      credential_kind as "credential_kind: PushMirrorCredential",
+                      encrypted_credential,
+                      state as "state: PushMirrorState",
+                      last_pushed_at, last_error, requested_at
+            "#,
+            limit,
+            lease_secs
+        )
+        .fetch_all(db)
+        .await?;
+
+        Ok(mirrors)
+    }
+
+    /// Record a push outcome, the mirror stays due when another push was requested
+    /// after `claimed_requested_at`
+    pub async fn pushed(
+        id: i32,
+        claimed_requested_at: Option<chrono::NaiveDateTime>,
+        error: Option<&str>,
+        db: &PgPool,
+    ) -> sqlx::Result<()> {
+        let state = match error {
+            None => PushMirrorState::Success,
+            Some(_) => PushMirrorState::Failed,
+        };
+
+        sqlx::query!(
+            // language=PostgreSQL
+            r#"
+            UPDATE push_mirror
+            SET state = CASE WHEN requested_at IS DISTINCT FROM $2 THEN 'Pending'::push_mirror_state ELSE $3 END,
+                last_pushed_at = CASE WHEN $4::text IS NULL THEN now() ELSE last_pushed_at END,
+                last_error = $4,
+                next_push_at = CASE WHEN requested_at IS DISTINCT FROM $2 THEN now() END
+            WHERE id = $1
+            "#,
+            id,
+            claimed_requested_at,
+            state as PushMirrorState,
+            error
+        )
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+}
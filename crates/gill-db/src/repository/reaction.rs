@@ -0,0 +1,163 @@
+use crate::Insert;
+use async_trait::async_trait;
+use sqlx::types::Uuid;
+use sqlx::PgPool;
+
+/// `user_id` reacted with `emoji` to issue or pull request `number`, or to one of their comments
+#[derive(Debug)]
+pub struct CreateReaction {
+    pub repository_id: i32,
+    pub number: i32,
+    pub issue_comment_id: Option<Uuid>,
+    pub pull_request_comment_id: Option<i32>,
+    pub user_id: i32,
+    pub emoji: String,
+    pub activity_pub_id: String,
+}
+
+#[derive(sqlx::FromRow, Debug)]
+pub struct Reaction {
+    pub id: i32,
+    pub repository_id: i32,
+    pub number: i32,
+    pub issue_comment_id: Option<Uuid>,
+    pub pull_request_comment_id: Option<i32>,
+    pub user_id: i32,
+    pub emoji: String,
+    pub activity_pub_id: String,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+/// Reactions with the same emoji to the same item or comment
+#[derive(sqlx::FromRow, Debug)]
+pub struct ReactionDigest {
+    pub issue_comment_id: Option<Uuid>,
+    pub pull_request_comment_id: Option<i32>,
+    pub emoji: String,
+    pub count: i64,
+    pub usernames: Vec<String>,
+}
+
+#[async_trait]
+impl Insert for CreateReaction {
+    type Output = ();
+
+    async fn insert(self, db: &PgPool) -> sqlx::Result<Self::Output> {
+        sqlx::query!(
+            // language=PostgreSQL
+            r#"
+            INSERT INTO reaction (repository_id, number, issue_comment_id, pull_request_comment_id,
+                                  user_id, emoji, activity_pub_id)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            ON CONFLICT DO NOTHING
+            "#,
+            self.repository_id,
+            self.number,
+            self.issue_comment_id,
+            self.pull_request_comment_id,
+            self.user_id,
+            self.emoji,
+            self.activity_pub_id,
+        )
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+}
+
+impl CreateReaction {
+    /// The existing reaction this one would duplicate
+    pub async fn existing(&self, db: &PgPool) -> sqlx::Result<Option<Reaction>> {
+        let reaction = sqlx::query_as!(
+            Reaction,
+            // language=PostgreSQL
+            r#"
+            SELECT id, repository_id, number, issue_comment_id, pull_request_comment_id, user_id,
+                   emoji, activity_pub_id, created_at
+            FROM reaction
+            WHERE repository_id = $1 AND number = $2
+              AND issue_comment_id IS NOT DISTINCT FROM $3
+              AND pull_request_comment_id IS NOT DISTINCT FROM $4
+              AND user_id = $5 AND emoji = $6
+            "#,
+            self.repository_id,
+            self.number,
+            self.issue_comment_id,
+            self.pull_request_comment_id,
+            self.user_id,
+            self.emoji,
+        )
+        .fetch_optional(db)
+        .await?;
+
+        Ok(reaction)
+    }
+}
+
+impl Reaction {
+    pub async fn by_activity_pub_id(
+        activity_pub_id: &str,
+        db: &PgPool,
+    ) -> sqlx::Result<Option<Reaction>> {
+        let reaction = sqlx::query_as!(
+            Reaction,
+            // language=PostgreSQL
+            r#"
+            SELECT id, repository_id, number, issue_comment_id, pull_request_comment_id, user_id,
+                   emoji, activity_pub_id, created_at
+            FROM reaction
+            WHERE activity_pub_id = $1
+            "#,
+            activity_pub_id,
+        )
+        .fetch_optional(db)
+        .await?;
+
+        Ok(reaction)
+    }
+
+    pub async fn delete(&self, db: &PgPool) -> sqlx::Result<()> {
+        sqlx::query!(
+            // language=PostgreSQL
+            r#"DELETE FROM reaction WHERE id = $1"#,
+            self.id
+        )
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+}
+
+impl ReactionDigest {
+    /// Reactions to issue or pull request `number` and to its comments, grouped by emoji
+    pub async fn for_item(
+        repository_id: i32,
+        number: i32,
+        db: &PgPool,
+    ) -> sqlx::Result<Vec<ReactionDigest>> {
+        let reactions = sqlx::query_as!(
+            ReactionDigest,
+            // language=PostgreSQL
+            r#"
+            SELECT r.issue_comment_id,
+                   r.pull_request_comment_id,
+                   r.emoji,
+                   count(*) as "count!",
+                   array_agg(u.username ORDER BY r.created_at) as "usernames!"
+            FROM reaction r
+            JOIN users u ON u.id = r.user_id
+            WHERE r.repository_id = $1 AND r.number = $2
+            GROUP BY r.issue_comment_id, r.pull_request_comment_id, r.emoji
+            ORDER BY min(r.created_at)
+            "#,
+            repository_id,
+            number,
+        )
+        .fetch_all(db)
+        .await?;
+
+        Ok(reactions)
+    }
+}
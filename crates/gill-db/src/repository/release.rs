@@ -0,0 +1,233 @@
+use crate::repository::Repository;
+use crate::Insert;
+use async_trait::async_trait;
+use sqlx::{PgExecutor, PgPool};
+
+#[derive(sqlx::FromRow, Debug)]
+pub struct Release {
+    pub id: i32,
+    pub repository_id: i32,
+    pub tag: String,
+    pub activity_pub_id: String,
+    pub title: String,
+    pub content: String,
+    pub media_type: String,
+    pub attributed_to: String,
+    pub published: chrono::NaiveDateTime,
+    pub is_local: bool,
+}
+
+#[derive(Debug)]
+pub struct CreateRelease {
+    pub repository_id: i32,
+    pub tag: String,
+    pub activity_pub_id: String,
+    pub title: String,
+    pub content: String,
+    pub media_type: String,
+    pub attributed_to: String,
+    pub published: chrono::NaiveDateTime,
+    pub is_local: bool,
+}
+
+#[derive(sqlx::FromRow, Debug)]
+pub struct ReleaseAsset {
+    pub id: i32,
+    pub release_id: i32,
+    pub filename: String,
+    pub content_type: String,
+    pub size: i64,
+}
+
+#[async_trait]
+impl Insert for CreateRelease {
+    type Output = Release;
+
+    async fn insert(self, db: &PgPool) -> sqlx::Result<Self::Output> {
+        self.insert_in(db).await
+    }
+}
+
+impl CreateRelease {
+    /// Insert with any executor, so a release and its assets can share a transaction
+    pub async fn insert_in<'e, E>(self, executor: E) -> sqlx::Result<Release>
+    where
+        E: PgExecutor<'e>,
+    {
+        let release = sqlx::query_as!(
+            Release,
+            // language=PostgreSQL
+            r#"
+            INSERT INTO release (repository_id,
+                                 tag,
+                                 activity_pub_id,
+                                 title,
+                                 content,
+                                 media_type,
+                                 attributed_to,
+                                 published,
+                                 is_local)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            RETURNING id, repository_id, tag, activity_pub_id, title, content, media_type, attributed_to, published, is_local;
+            "#,
+            self.repository_id,
+            self.tag,
+            self.activity_pub_id,
+            self.title,
+            self.content,
+            self.media_type,
+            self.attributed_to,
+            self.published,
+            self.is_local,
+        )
+        .fetch_one(executor)
+        .await?;
+
+        Ok(release)
+    }
+}
+
+impl Release {
+    pub async fn by_activity_pub_id(activity_pub_id: &str, db: &PgPool) -> sqlx::Result<Self> {
+        let release = sqlx::query_as!(
+            Release,
+            // language=PostgreSQL
+            r#"
+            SELECT * FROM release
+            WHERE activity_pub_id = $1
+            "#,
+            activity_pub_id,
+        )
+        .fetch_one(db)
+        .await?;
+
+        Ok(release)
+    }
+
+    pub async fn add_asset<'e, E>(
+        &self,
+        filename: &str,
+        content_type: &str,
+        size: i64,
+        executor: E,
+    ) -> sqlx::Result<ReleaseAsset>
+    where
+        E: PgExecutor<'e>,
+    {
+        let asset = sqlx::query_as!(
+            ReleaseAsset,
+            // language=PostgreSQL
+            r#"
+            INSERT INTO release_asset (release_id, filename, content_type, size)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (release_id, filename) DO UPDATE SET content_type = $3, size = $4
+            RETURNING id, release_id, filename, content_type, size
+            "#,
+            self.id,
+            filename,
+            content_type,
+            size
+        )
+        .fetch_one(executor)
+        .await?;
+
+        Ok(asset)
+    }
+
+    pub async fn list_assets(&self, db: &PgPool) -> sqlx::Result<Vec<ReleaseAsset>> {
+        let assets = sqlx::query_as!(
+            ReleaseAsset,
+            // language=PostgreSQL
+            r#"
+            SELECT id, release_id, filename, content_type, size FROM release_asset
+            WHERE release_id = $1
+            ORDER BY filename
+            "#,
+            self.id,
+        )
+        .fetch_all(db)
+        .await?;
+
+        Ok(assets)
+    }
+
+    pub async fn get_asset(&self, filename: &str, db: &PgPool) -> sqlx::Result<ReleaseAsset> {
+        let asset = sqlx::query_as!(
+            ReleaseAsset,
+            // language=PostgreSQL
+            r#"
+            SELECT id, release_id, filename, content_type, size FROM release_asset
+            WHERE release_id = $1 AND filename = $2
+            "#,
+            self.id,
+            filename
+        )
+        .fetch_one(db)
+        .await?;
+
+        Ok(asset)
+    }
+}
+
+impl Repository {
+    pub async fn list_releases(
+        &self,
+        limit: i64,
+        offset: i64,
+        db: &PgPool,
+    ) -> sqlx::Result<Vec<Release>> {
+        let releases = sqlx::query_as!(
+            Release,
+            // language=PostgreSQL
+            r#"
+            SELECT * FROM release
+            WHERE repository_id = $1
+            ORDER BY published DESC
+            LIMIT $2
+            OFFSET $3
+            "#,
+            self.id,
+            limit,
+            offset
+        )
+        .fetch_all(db)
+        .await?;
+
+        Ok(releases)
+    }
+
+    pub async fn get_release(&self, tag: &str, db: &PgPool) -> sqlx::Result<Release> {
+        let release = sqlx::query_as!(
+            Release,
+            // language=PostgreSQL
+            r#"
+            SELECT * FROM release
+            WHERE repository_id = $1 AND tag = $2
+            "#,
+            self.id,
+            tag
+        )
+        .fetch_one(db)
+        .await?;
+
+        Ok(release)
+    }
+
+    pub async fn get_latest_release(&self, db: &PgPool) -> sqlx::Result<Option<Release>> {
+        let release = sqlx::query_as!(
+            Release,
+            // language=PostgreSQL
+            r#"
+            SELECT * FROM release
+            WHERE repository_id = $1
+            ORDER BY published DESC
+            LIMIT 1
+            "#,
+            self.id,
+        )
+        .fetch_optional(db)
+        .await?;
+
+        Ok(release)
+    }
+}
@@ -0,0 +1,122 @@
+use crate::Insert;
+use async_trait::async_trait;
+use sqlx::types::Uuid;
+use sqlx::PgPool;
+
+/// An edit of issue or pull request `number`, or of one of their comments
+#[derive(Debug)]
+pub struct CreateRevision {
+    pub repository_id: i32,
+    pub number: i32,
+    pub issue_comment_id: Option<Uuid>,
+    pub pull_request_comment_id: Option<i32>,
+    pub old_title: Option<String>,
+    pub new_title: Option<String>,
+    pub old_content: String,
+    pub new_content: String,
+    pub edited_by: i32,
+}
+
+#[derive(sqlx::FromRow, Debug)]
+pub struct Revision {
+    pub id: i32,
+    pub issue_comment_id: Option<Uuid>,
+    pub pull_request_comment_id: Option<i32>,
+    pub old_title: Option<String>,
+    pub new_title: Option<String>,
+    pub old_content: String,
+    pub new_content: String,
+    pub editor_name: String,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+#[async_trait]
+impl Insert for CreateRevision {
+    type Output = ();
+
+    async fn insert(self, db: &PgPool) -> sqlx::Result<Self::Output> {
+        sqlx::query!(
+            // language=PostgreSQL
+            r#"
+            INSERT INTO revision (repository_id, number, issue_comment_id, pull_request_comment_id,
+                                  old_title, new_title, old_content, new_content, edited_by)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            "#,
+            self.repository_id,
+            self.number,
+            self.issue_comment_id,
+            self.pull_request_comment_id,
+            self.old_title,
+            self.new_title,
+            self.old_content,
+            self.new_content,
+            self.edited_by,
+        )
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+}
+
+impl Revision {
+    /// Revisions of issue or pull request `number` and of its comments, oldest first
+    pub async fn for_item(
+        repository_id: i32,
+        number: i32,
+        db: &PgPool,
+    ) -> sqlx::Result<Vec<Revision>> {
+        let revisions = sqlx::query_as!(
+            Revision,
+            // language=PostgreSQL
+            r#"
+            SELECT r.id,
+                   r.issue_comment_id,
+                   r.pull_request_comment_id,
+                   r.old_title,
+                   r.new_title,
+                   r.old_content,
+                   r.new_content,
+                   u.username as editor_name,
+                   r.created_at
+            FROM revision r
+            JOIN users u ON u.id = r.edited_by
+            WHERE r.repository_id = $1 AND r.number = $2
+            ORDER BY r.created_at, r.id
+            "#,
+            repository_id,
+            number,
+        )
+        .fetch_all(db)
+        .await?;
+
+        Ok(revisions)
+    }
+
+    pub async fn by_id(repository_id: i32, id: i32, db: &PgPool) -> sqlx::Result<Revision> {
+        let revision = sqlx::query_as!(
+            Revision,
+            // language=PostgreSQL
+            r#"
+            SELECT r.id,
+                   r.issue_comment_id,
+                   r.pull_request_comment_id,
+                   r.old_title,
+                   r.new_title,
+                   r.old_content,
+                   r.new_content,
+                   u.username as editor_name,
+                   r.created_at
+            FROM revision r
+            JOIN users u ON u.id = r.edited_by
+            WHERE r.repository_id = $1 AND r.id = $2
+            "#,
+            repository_id,
+            id,
+        )
+        .fetch_one(db)
+        .await?;
+
+        Ok(revision)
+    }
+}
@@ -9,6 +9,7 @@ impl Repository {
             r#"
             INSERT INTO repository_star (repository_id, starred_by)
             VALUES ($1, $2)
+            ON CONFLICT DO NOTHING
             "#,
             self.id,
             starred_by
@@ -33,15 +34,47 @@ impl Repository {
                 followers_url, is_local, activity_pub_id
                 FROM repository_star s
                 JOIN users u ON s.starred_by = u.id
+                WHERE s.repository_id = $3
                 LIMIT $1
                 OFFSET $2
             "#,
             limit,
-            offset
+            offset,
+            self.id
         )
             .fetch_all(db)
             .await?;
 
         Ok(stars)
     }
+
+    pub async fn remove_star(&self, starred_by: i32, db: &PgPool) -> sqlx::Result<()> {
+        sqlx::query!(
+            // language=PostgreSQL
+            r#"DELETE FROM repository_star WHERE repository_id = $1 AND starred_by = $2"#,
+            self.id,
+            starred_by
+        )
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn is_starred_by(&self, user_id: i32, db: &PgPool) -> sqlx::Result<bool> {
+        let starred = sqlx::query_scalar!(
+            // language=PostgreSQL
+            r#"
+            SELECT EXISTS(
+                SELECT 1 FROM repository_star WHERE repository_id = $1 AND starred_by = $2
+            ) as "starred!"
+            "#,
+            self.id,
+            user_id
+        )
+        .fetch_one(db)
+        .await?;
+
+        Ok(starred)
+    }
 }
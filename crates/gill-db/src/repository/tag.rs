@@ -0,0 +1,50 @@
+use crate::repository::Repository;
+use sqlx::PgPool;
+
+#[derive(sqlx::FromRow, Debug)]
+pub struct Tag {
+    pub name: String,
+    pub repository_id: i32,
+    pub target: String,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+impl Repository {
+    pub async fn list_tags(&self, limit: i64, offset: i64, db: &PgPool) -> sqlx::Result<Vec<Tag>> {
+        let tags = sqlx::query_as!(
+            Tag,
+            // language=PostgreSQL
+            r#"
+                SELECT name, repository_id, target, created_at FROM tag
+                WHERE repository_id = $1
+                ORDER BY created_at DESC, name DESC
+                LIMIT $2
+                OFFSET $3
+            "#,
+            self.id,
+            limit,
+            offset
+        )
+        .fetch_all(db)
+        .await?;
+
+        Ok(tags)
+    }
+
+    pub async fn get_tag(&self, name: &str, db: &PgPool) -> sqlx::Result<Tag> {
+        let tag = sqlx::query_as!(
+            Tag,
+            // language=PostgreSQL
+            r#"
+                SELECT name, repository_id, target, created_at FROM tag
+                WHERE repository_id = $1 AND name = $2
+            "#,
+            self.id,
+            name
+        )
+        .fetch_one(db)
+        .await?;
+
+        Ok(tag)
+    }
+}
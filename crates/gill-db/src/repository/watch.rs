@@ -9,6 +9,7 @@ impl Repository {
             r#"
             INSERT INTO repository_watch (repository_id, watched_by)
             VALUES ($1, $2)
+            ON CONFLICT DO NOTHING
             "#,
             self.id,
             watcher_id
@@ -33,15 +34,59 @@ impl Repository {
                 followers_url, is_local, activity_pub_id
                 FROM repository_watch w
                 JOIN users u ON w.watched_by = u.id
+                WHERE w.repository_id = $3
                 LIMIT $1
                 OFFSET $2
             "#,
             limit,
-            offset
+            offset,
+            self.id
         )
             .fetch_all(db)
             .await?;
 
         Ok(watchers)
     }
+
+    pub async fn count_watchers(&self, db: &PgPool) -> sqlx::Result<i64> {
+        let count = sqlx::query_scalar!(
+            // language=PostgreSQL
+            r#"SELECT count(*) as "count!" FROM repository_watch WHERE repository_id = $1"#,
+            self.id
+        )
+        .fetch_one(db)
+        .await?;
+
+        Ok(count)
+    }
+
+    pub async fn remove_watcher(&self, watcher_id: i32, db: &PgPool) -> sqlx::Result<()> {
+        sqlx::query!(
+            // language=PostgreSQL
+            r#"DELETE FROM repository_watch WHERE repository_id = $1 AND watched_by = $2"#,
+            self.id,
+            watcher_id
+        )
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn is_watched_by(&self, user_id: i32, db: &PgPool) -> sqlx::Result<bool> {
+        let watched = sqlx::query_scalar!(
+            // language=PostgreSQL
+            r#"
+            SELECT EXISTS(
+                SELECT 1 FROM repository_watch WHERE repository_id = $1 AND watched_by = $2
+            ) as "watched!"
+            "#,
+            self.id,
+            user_id
+        )
+        .fetch_one(db)
+        .await?;
+
+        Ok(watched)
+    }
 }
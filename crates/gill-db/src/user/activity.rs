@@ -0,0 +1,129 @@
+use crate::Insert;
+use async_trait::async_trait;
+use sqlx::{PgExecutor, PgPool};
+
+#[derive(sqlx::Type, Debug, Copy, Clone, Eq, PartialEq)]
+#[sqlx(type_name = "user_activity_kind")]
+pub enum UserActivityKind {
+    Push,
+    OpenIssue,
+    CloseIssue,
+    ReopenIssue,
+    CommentIssue,
+    OpenPullRequest,
+    MergePullRequest,
+    ClosePullRequest,
+    CommentPullRequest,
+    Star,
+    Fork,
+}
+
+#[derive(Debug)]
+pub struct CreateUserActivity {
+    pub actor_id: i32,
+    pub repository_id: i32,
+    pub kind: UserActivityKind,
+    pub number: Option<i32>,
+    pub summary: Option<String>,
+}
+
+/// An activity log entry along with its actor and repository names
+#[derive(sqlx::FromRow, Debug)]
+pub struct UserActivity {
+    pub id: i32,
+    pub kind: UserActivityKind,
+    pub number: Option<i32>,
+    pub summary: Option<String>,
+    pub published: chrono::NaiveDateTime,
+    pub actor_name: String,
+    pub actor_domain: String,
+    pub actor_is_local: bool,
+    pub repository_owner: String,
+    pub repository_name: String,
+    pub repository_domain: String,
+    pub repository_is_local: bool,
+}
+
+#[async_trait]
+impl Insert for CreateUserActivity {
+    type Output = ();
+
+    async fn insert(self, db: &PgPool) -> sqlx::Result<Self::Output> {
+        self.insert_in(db).await
+    }
+}
+
+impl CreateUserActivity {
+    /// Insert with any executor, so pushes can log activities in their own transaction
+    pub(crate) async fn insert_in<'e, E>(self, executor: E) -> sqlx::Result<()>
+    where
+        E: PgExecutor<'e>,
+    {
+        sqlx::query!(
+            // language=PostgreSQL
+            r#"
+            INSERT INTO user_activity (actor_id, repository_id, kind, number, summary)
+            VALUES ($1, $2, $3, $4, $5)
+            "#,
+            self.actor_id,
+            self.repository_id,
+            self.kind as UserActivityKind,
+            self.number,
+            self.summary
+        )
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
+}
+
+impl UserActivity {
+    /// Activities of the users `user_id` follows and on the repositories they watch,
+    /// their own activities and the ones from silenced or blocked domains are left out
+    pub async fn dashboard(
+        user_id: i32,
+        limit: i64,
+        offset: i64,
+        db: &PgPool,
+    ) -> sqlx::Result<Vec<UserActivity>> {
+        let activities = sqlx::query_as!(
+            UserActivity,
+            // language=PostgreSQL
+            r#"
+            SELECT ua.id,
+                   ua.kind as "kind: UserActivityKind",
+                   ua.number,
+                   ua.summary,
+                   ua.published,
+                   actor.username as actor_name,
+                   actor.domain as actor_domain,
+                   actor.is_local as actor_is_local,
+                   owner.username as repository_owner,
+                   r.name as repository_name,
+                   r.domain as repository_domain,
+                   r.is_local as repository_is_local
+            FROM user_activity ua
+                     JOIN users actor ON actor.id = ua.actor_id
+                     JOIN repository r ON r.id = ua.repository_id
+                     JOIN users owner ON owner.activity_pub_id = r.attributed_to
+            WHERE ua.actor_id <> $1
+              AND NOT r.private
+              AND (ua.actor_id IN (SELECT user_id FROM user_follow WHERE follower_id = $1)
+                OR ua.repository_id IN (SELECT repository_id FROM repository_watch WHERE watched_by = $1))
+              AND NOT EXISTS(SELECT 1
+                             FROM domain_rule dr
+                             WHERE dr.domain = actor.domain AND dr.policy IN ('Silenced', 'Blocked'))
+            ORDER BY ua.published DESC, ua.id DESC
+            LIMIT $2 OFFSET $3
+            "#,
+            user_id,
+            limit,
+            offset
+        )
+        .fetch_all(db)
+        .await?;
+
+        Ok(activities)
+    }
+}
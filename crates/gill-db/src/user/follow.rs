@@ -2,6 +2,36 @@ use crate::user::User;
 use sqlx::PgPool;
 
 impl User {
+    pub async fn remove_follower(&self, follower_id: i32, db: &PgPool) -> sqlx::Result<()> {
+        sqlx::query!(
+            // language=PostgreSQL
+            r#"DELETE FROM user_follow WHERE user_id = $1 AND follower_id = $2"#,
+            self.id,
+            follower_id
+        )
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn is_followed_by(&self, follower_id: i32, db: &PgPool) -> sqlx::Result<bool> {
+        let followed = sqlx::query_scalar!(
+            // language=PostgreSQL
+            r#"
+            SELECT EXISTS(
+                SELECT 1 FROM user_follow WHERE user_id = $1 AND follower_id = $2
+            ) as "followed!"
+            "#,
+            self.id,
+            follower_id
+        )
+        .fetch_one(db)
+        .await?;
+
+        Ok(followed)
+    }
+
     pub async fn add_follower(&self, follower_id: i32, db: &PgPool) -> sqlx::Result<()> {
         sqlx::query!(
             // language=PostgreSQL
@@ -10,6 +40,7 @@ impl User {
                                 user_id,
                                 follower_id)
             values ($1, $2)
+            on conflict do nothing
             "#,
             self.id,
             follower_id
@@ -33,16 +64,80 @@ impl User {
                 SELECT u.id, username, domain, email, public_key, private_key, inbox_url, outbox_url,
                 followers_url, is_local, activity_pub_id
                 FROM user_follow f
-                JOIN users u ON f.user_id = u.id
+                JOIN users u ON f.follower_id = u.id
+                WHERE f.user_id = $3
+                ORDER BY f.id
                 LIMIT $1
                 OFFSET $2
             "#,
             limit,
-            offset
+            offset,
+            self.id
         )
             .fetch_all(db)
             .await?;
 
         Ok(followers)
     }
+
+    pub async fn count_followers(&self, db: &PgPool) -> sqlx::Result<i64> {
+        let count = sqlx::query_scalar!(
+            // language=PostgreSQL
+            r#"SELECT count(*) as "count!" FROM user_follow WHERE user_id = $1"#,
+            self.id
+        )
+        .fetch_one(db)
+        .await?;
+
+        Ok(count)
+    }
+
+    /// ActivityPub ids of the users followed and the repositories watched by this user
+    pub async fn get_following_ids(
+        &self,
+        limit: i64,
+        offset: i64,
+        db: &PgPool,
+    ) -> sqlx::Result<Vec<String>> {
+        let following = sqlx::query_scalar!(
+            // language=PostgreSQL
+            r#"
+                SELECT following.activity_pub_id as "activity_pub_id!" FROM (
+                    SELECT u.activity_pub_id FROM user_follow f
+                    JOIN users u ON f.user_id = u.id
+                    WHERE f.follower_id = $3
+                    UNION ALL
+                    SELECT r.activity_pub_id FROM repository_watch w
+                    JOIN repository r ON w.repository_id = r.id
+                    WHERE w.watched_by = $3
+                ) following
+                ORDER BY following.activity_pub_id
+                LIMIT $1
+                OFFSET $2
+            "#,
+            limit,
+            offset,
+            self.id
+        )
+        .fetch_all(db)
+        .await?;
+
+        Ok(following)
+    }
+
+    pub async fn count_following(&self, db: &PgPool) -> sqlx::Result<i64> {
+        let count = sqlx::query_scalar!(
+            // language=PostgreSQL
+            r#"
+                SELECT (SELECT count(*) FROM user_follow WHERE follower_id = $1)
+                     + (SELECT count(*) FROM repository_watch WHERE watched_by = $1)
+                     as "count!"
+            "#,
+            self.id
+        )
+        .fetch_one(db)
+        .await?;
+
+        Ok(count)
+    }
 }
@@ -4,7 +4,9 @@ use crate::Insert;
 use async_trait::async_trait;
 use sqlx::{FromRow, PgPool};
 
+pub mod activity;
 pub mod follow;
+pub mod notification;
 pub mod ssh_keys;
 
 pub struct CreateSSHKey {
@@ -148,6 +150,27 @@ impl User {
         Ok(user)
     }
 
+    pub async fn by_user_name_and_domain(
+        username: &str,
+        domain: &str,
+        pool: &PgPool,
+    ) -> sqlx::Result<User> {
+        let user = sqlx::query_as!(
+            User,
+            // language=PostgreSQL
+            r#"
+            select * from users
+            where username = $1 and domain = $2
+            "#,
+            username,
+            domain,
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(user)
+    }
+
     pub async fn get_local_repository_by_name(
         &self,
         repo_name: &str,
@@ -178,9 +201,10 @@ impl User {
                 r.item_count
             FROM users u
             JOIN repository r ON u.activity_pub_id = r.attributed_to
-            WHERE r.name = $1 AND r.is_local
+            WHERE r.name = $1 AND u.id = $2 AND r.is_local
             "#,
             repo_name,
+            self.id,
         )
         .fetch_one(db)
         .await?;
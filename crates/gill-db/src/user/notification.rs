@@ -0,0 +1,409 @@
+use sqlx::PgPool;
+
+#[derive(sqlx::Type, Debug, Copy, Clone, Eq, PartialEq)]
+#[sqlx(type_name = "notification_level")]
+pub enum NotificationLevel {
+    Watching,
+    Participating,
+    MentionsOnly,
+}
+
+#[derive(sqlx::Type, Debug, Copy, Clone, Eq, PartialEq)]
+#[sqlx(type_name = "notification_reason")]
+pub enum NotificationReason {
+    Watching,
+    Participating,
+    Mention,
+}
+
+#[derive(sqlx::Type, Debug, Copy, Clone, Eq, PartialEq)]
+#[sqlx(type_name = "notification_subject")]
+pub enum NotificationSubject {
+    Issue,
+    PullRequest,
+}
+
+#[derive(sqlx::FromRow, Debug, Clone)]
+pub struct NotificationSettings {
+    pub user_id: i32,
+    pub level: NotificationLevel,
+    pub email_digest: bool,
+}
+
+/// Something happened on an issue or a pull request, every local user
+/// concerned by it according to their settings gets a notification
+#[derive(Debug)]
+pub struct CreateNotifications {
+    pub actor_id: i32,
+    pub repository_id: i32,
+    pub subject: NotificationSubject,
+    pub number: i32,
+    pub title: String,
+    pub action: String,
+    /// Usernames mentioned in the content, unknown or remote ones are ignored
+    pub mentioned: Vec<String>,
+}
+
+#[derive(sqlx::FromRow, Debug)]
+pub struct Notification {
+    pub id: i32,
+    pub reason: NotificationReason,
+    pub subject: NotificationSubject,
+    pub number: i32,
+    pub title: String,
+    pub action: String,
+    pub created_at: chrono::NaiveDateTime,
+    pub read_at: Option<chrono::NaiveDateTime>,
+    pub actor_name: String,
+    pub actor_domain: String,
+    pub actor_is_local: bool,
+    pub repository_owner: String,
+    pub repository_name: String,
+}
+
+#[derive(sqlx::FromRow, Debug)]
+pub struct DigestRecipient {
+    pub user_id: i32,
+    pub username: String,
+    pub email: String,
+    /// Digests which failed to be emailed in a row
+    pub digest_failures: i32,
+}
+
+impl NotificationSettings {
+    pub async fn for_user(user_id: i32, db: &PgPool) -> sqlx::Result<NotificationSettings> {
+        let settings = sqlx::query_as!(
+            NotificationSettings,
+            // language=PostgreSQL
+            r#"
+            SELECT user_id, level as "level: NotificationLevel", email_digest
+            FROM notification_settings
+            WHERE user_id = $1
+            "#,
+            user_id
+        )
+        .fetch_optional(db)
+        .await?;
+
+        Ok(settings.unwrap_or(NotificationSettings {
+            user_id,
+            level: NotificationLevel::Participating,
+            email_digest: false,
+        }))
+    }
+
+    pub async fn save(&self, db: &PgPool) -> sqlx::Result<()> {
+        sqlx::query!(
+            // language=PostgreSQL
+            r#"
+            INSERT INTO notification_settings (user_id, level, email_digest)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (user_id) DO UPDATE SET level = $2, email_digest = $3
+            "#,
+            self.user_id,
+            self.level as NotificationLevel,
+            self.email_digest
+        )
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+}
+
+impl CreateNotifications {
+    /// Notify mentioned users, participants and watchers, a user concerned for several
+    /// reasons gets a single notification with the most specific one.
+    /// Returns the number of notifications created.
+    pub async fn fan_out(self, db: &PgPool) -> sqlx::Result<u64> {
+        let result = sqlx::query!(
+            // language=PostgreSQL
+            r#"
+            WITH candidate AS (
+                SELECT u.id AS recipient_id, 'Mention'::notification_reason AS reason, 0 AS priority
+                FROM users u
+                WHERE u.is_local AND u.username = ANY($7)
+                UNION ALL
+                SELECT s.subscriber, 'Participating'::notification_reason, 1
+                FROM issue_subscriber s
+                WHERE $3 = 'Issue'::notification_subject AND s.repository_id = $2 AND s.number = $4
+                UNION ALL
+                SELECT p.opened_by, 'Participating'::notification_reason, 1
+                FROM pull_request p
+                WHERE $3 = 'PullRequest'::notification_subject AND p.repository_id = $2 AND p.number = $4
+                UNION ALL
+                SELECT c.created_by, 'Participating'::notification_reason, 1
+                FROM pull_request_comment c
+                WHERE $3 = 'PullRequest'::notification_subject AND c.repository_id = $2 AND c.number = $4
+                UNION ALL
+                SELECT rw.watched_by, 'Watching'::notification_reason, 2
+                FROM repository_watch rw
+                WHERE rw.repository_id = $2
+            )
+            INSERT INTO notification (recipient_id, actor_id, repository_id, reason, subject, number, title, action)
+            SELECT DISTINCT ON (c.recipient_id) c.recipient_id, $1, $2, c.reason, $3, $4, $5, $6
+            FROM candidate c
+                     JOIN users u ON u.id = c.recipient_id AND u.is_local
+                     LEFT JOIN notification_settings ns ON ns.user_id = c.recipient_id
+            WHERE c.recipient_id <> $1
+              AND (c.reason = 'Mention'
+                OR (c.reason = 'Participating' AND coalesce(ns.level, 'Participating') <> 'MentionsOnly')
+                OR (c.reason = 'Watching' AND ns.level = 'Watching'))
+            ORDER BY c.recipient_id, c.priority
+            "#,
+            self.actor_id,
+            self.repository_id,
+            self.subject as NotificationSubject,
+            self.number,
+            self.title,
+            self.action,
+            &self.mentioned
+        )
+        .execute(db)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
+
+impl Notification {
+    pub async fn list(
+        recipient_id: i32,
+        limit: i64,
+        offset: i64,
+        db: &PgPool,
+    ) -> sqlx::Result<Vec<Notification>> {
+        let notifications = sqlx::query_as!(
+            Notification,
+            // language=PostgreSQL
+            r#"
+            SELECT n.id,
+                   n.reason as "reason: NotificationReason",
+                   n.subject as "subject: NotificationSubject",
+                   n.number,
+                   n.title,
+                   n.action,
+                   n.created_at,
+                   n.read_at,
+                   actor.username as actor_name,
+                   actor.domain as actor_domain,
+                   actor.is_local as actor_is_local,
+                   owner.username as repository_owner,
+                   r.name as repository_name
+            FROM notification n
+                     JOIN users actor ON actor.id = n.actor_id
+                     JOIN repository r ON r.id = n.repository_id
+                     JOIN users owner ON owner.activity_pub_id = r.attributed_to
+            WHERE n.recipient_id = $1
+            ORDER BY n.read_at IS NOT NULL, n.created_at DESC, n.id DESC
+            LIMIT $2 OFFSET $3
+            "#,
+            recipient_id,
+            limit,
+            offset
+        )
+        .fetch_all(db)
+        .await?;
+
+        Ok(notifications)
+    }
+
+    pub async fn by_id(id: i32, recipient_id: i32, db: &PgPool) -> sqlx::Result<Notification> {
+        let notification = sqlx::query_as!(
+            Notification,
+            // language=PostgreSQL
+            r#"
+            SELECT n.id,
+                   n.reason as "reason: NotificationReason",
+                   n.subject as "subject: NotificationSubject",
+                   n.number,
+                   n.title,
+                   n.action,
+                   n.created_at,
+                   n.read_at,
+                   actor.username as actor_name,
+                   actor.domain as actor_domain,
+                   actor.is_local as actor_is_local,
+                   owner.username as repository_owner,
+                   r.name as repository_name
+            FROM notification n
+                     JOIN users actor ON actor.id = n.actor_id
+                     JOIN repository r ON r.id = n.repository_id
+                     JOIN users owner ON owner.activity_pub_id = r.attributed_to
+            WHERE n.id = $1 AND n.recipient_id = $2
+            "#,
+            id,
+            recipient_id
+        )
+        .fetch_one(db)
+        .await?;
+
+        Ok(notification)
+    }
+
+    pub async fn unread_count(recipient_id: i32, db: &PgPool) -> sqlx::Result<i64> {
+        let count = sqlx::query_scalar!(
+            // language=PostgreSQL
+            r#"
+            SELECT count(*) as "count!"
+            FROM notification
+            WHERE recipient_id = $1 AND read_at IS NULL
+            "#,
+            recipient_id
+        )
+        .fetch_one(db)
+        .await?;
+
+        Ok(count)
+    }
+
+    /// Mark a notification as read, returns false when it does not belong to `recipient_id`
+    pub async fn mark_read(id: i32, recipient_id: i32, db: &PgPool) -> sqlx::Result<bool> {
+        let result = sqlx::query!(
+            // language=PostgreSQL
+            r#"
+            UPDATE notification
+            SET read_at = coalesce(read_at, now())
+            WHERE id = $1 AND recipient_id = $2
+            "#,
+            id,
+            recipient_id
+        )
+        .execute(db)
+        .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    pub async fn mark_all_read(recipient_id: i32, db: &PgPool) -> sqlx::Result<()> {
+        sqlx::query!(
+            // language=PostgreSQL
+            r#"
+            UPDATE notification
+            SET read_at = now()
+            WHERE recipient_id = $1 AND read_at IS NULL
+            "#,
+            recipient_id
+        )
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Local users with email digests enabled and unread notifications not emailed yet,
+    /// the ones waiting the longest first. Users whose last digest failed are skipped until
+    /// their retry is due.
+    pub async fn digest_recipients(limit: i64, db: &PgPool) -> sqlx::Result<Vec<DigestRecipient>> {
+        let recipients = sqlx::query_as!(
+            DigestRecipient,
+            // language=PostgreSQL
+            r#"
+            SELECT u.id as user_id, u.username, u.email as "email!", ns.digest_failures
+            FROM users u
+                     JOIN notification_settings ns ON ns.user_id = u.id AND ns.email_digest
+                     JOIN notification n
+                          ON n.recipient_id = u.id AND n.read_at IS NULL AND n.emailed_at IS NULL
+            WHERE u.is_local
+              AND u.email IS NOT NULL
+              AND (ns.digest_retry_at IS NULL OR ns.digest_retry_at <= now())
+            GROUP BY u.id, u.username, u.email, ns.digest_failures
+            ORDER BY min(n.created_at), u.id
+            LIMIT $1
+            "#,
+            limit
+        )
+        .fetch_all(db)
+        .await?;
+
+        Ok(recipients)
+    }
+
+    pub async fn digest_sent(recipient_id: i32, db: &PgPool) -> sqlx::Result<()> {
+        sqlx::query!(
+            // language=PostgreSQL
+            r#"
+            UPDATE notification_settings
+            SET digest_failures = 0, digest_retry_at = NULL
+            WHERE user_id = $1
+            "#,
+            recipient_id
+        )
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Postpone the digests of `recipient_id` until `retry_at`
+    pub async fn digest_failed(
+        recipient_id: i32,
+        retry_at: chrono::NaiveDateTime,
+        db: &PgPool,
+    ) -> sqlx::Result<()> {
+        sqlx::query!(
+            // language=PostgreSQL
+            r#"
+            UPDATE notification_settings
+            SET digest_failures = digest_failures + 1, digest_retry_at = $2
+            WHERE user_id = $1
+            "#,
+            recipient_id,
+            retry_at
+        )
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Unread notifications of `recipient_id` which were not part of a digest yet
+    pub async fn pending_digest(recipient_id: i32, db: &PgPool) -> sqlx::Result<Vec<Notification>> {
+        let notifications = sqlx::query_as!(
+            Notification,
+            // language=PostgreSQL
+            r#"
+            SELECT n.id,
+                   n.reason as "reason: NotificationReason",
+                   n.subject as "subject: NotificationSubject",
+                   n.number,
+                   n.title,
+                   n.action,
+                   n.created_at,
+                   n.read_at,
+                   actor.username as actor_name,
+                   actor.domain as actor_domain,
+                   actor.is_local as actor_is_local,
+                   owner.username as repository_owner,
+                   r.name as repository_name
+            FROM notification n
+                     JOIN users actor ON actor.id = n.actor_id
+                     JOIN repository r ON r.id = n.repository_id
+                     JOIN users owner ON owner.activity_pub_id = r.attributed_to
+            WHERE n.recipient_id = $1 AND n.read_at IS NULL AND n.emailed_at IS NULL
+            ORDER BY n.created_at, n.id
+            "#,
+            recipient_id
+        )
+        .fetch_all(db)
+        .await?;
+
+        Ok(notifications)
+    }
+
+    pub async fn mark_emailed(ids: &[i32], db: &PgPool) -> sqlx::Result<()> {
+        sqlx::query!(
+            // language=PostgreSQL
+            r#"
+            UPDATE notification
+            SET emailed_at = now()
+            WHERE id = ANY($1)
+            "#,
+            ids
+        )
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -1 +1,4 @@
+pub mod ref_update;
 
+/// Environment variable used to pass the pushing user id down to the post-receive hook
+pub const PUSHED_BY_ENV: &str = "GILL_PUSHED_BY";
@@ -1,6 +1,7 @@
 use gill_db::repository::Repository;
 use gill_db::user::User;
 use gill_db::PgPoolOptions;
+use gill_git_server::PUSHED_BY_ENV;
 use gill_settings::SETTINGS;
 use std::env;
 use std::fs::OpenOptions;
@@ -67,6 +68,7 @@ async fn main() -> anyhow::Result<()> {
             Command::new(verb)
                 .current_dir("/home/git")
                 .env("HOME", "/home/git")
+                .env(PUSHED_BY_ENV, user_id.to_string())
                 .stdout(Stdio::inherit())
                 .stdin(Stdio::inherit())
                 .stderr(Stdio::inherit())
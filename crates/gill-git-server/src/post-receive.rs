@@ -1,10 +1,12 @@
+use gill_db::repository::push_mirror::PushMirror;
 use gill_db::repository::Repository;
 use gill_db::PgPoolOptions;
+use gill_git_server::ref_update::parse_ref_updates;
+use gill_git_server::PUSHED_BY_ENV;
 use gill_settings::SETTINGS;
 use std::env;
 use std::fs::OpenOptions;
-use std::io::Write;
-use std::io::{stdin, BufRead};
+use std::io::{stdin, Read, Write};
 use std::path::PathBuf;
 use std::time::Duration;
 
@@ -25,14 +27,15 @@ async fn main() -> anyhow::Result<()> {
 
     let git_dir = env::var("GIT_DIR")?;
 
-    // Post receive hooks args are received via stdin
-    let args: String = stdin().lock().lines().filter_map(Result::ok).collect();
+    // Set by pack-serve, absent when the hook is triggered by a local push
+    let pushed_by = env::var(PUSHED_BY_ENV)
+        .ok()
+        .and_then(|user_id| user_id.parse().ok());
 
-    let args: Vec<&str> = args.split(' ').collect();
-
-    let [_, _sha, git_ref] = args.as_slice() else {
-        panic!("Unhandled post-receive hook arguments {args:?}");
-    };
+    // Post receive hooks args are received via stdin, one line per updated ref
+    let mut input = String::new();
+    stdin().read_to_string(&mut input)?;
+    let updates = parse_ref_updates(&input)?;
 
     let git_dir = PathBuf::from(git_dir).canonicalize()?;
 
@@ -52,25 +55,20 @@ async fn main() -> anyhow::Result<()> {
         .strip_suffix(".git")
         .expect("Invalid repo path, expected '.git' suffix");
 
-    match git_ref.strip_prefix("refs/heads/") {
-        Some(branch) => {
-            let repo = Repository::by_namespace(&repository_owner, repository_name, &db).await?;
-            let branches = repo.list_branches(i64::MAX, 0, &db).await?;
-            let branches: Vec<&str> = branches.iter().map(|branch| branch.name.as_str()).collect();
+    let repo = Repository::by_namespace(&repository_owner, repository_name, &db).await?;
+    let events = repo.apply_push(pushed_by, &updates, &db).await?;
+
+    // Mirrors are pushed asynchronously by gill, we only flag them here
+    if !updates.is_empty() {
+        PushMirror::schedule_for_repository(repo.id, &db).await?;
+    }
 
-            if branches.is_empty() {
-                repo.set_default_branch(branch, &db).await?;
-                writeln!(
-                    log_file,
-                    "default branch {branch} set for {repository_owner}/{repository_name}"
-                )?;
-            } else if !branches.contains(&branch) {
-                repo.create_branch(branch, &db).await?;
-            } else {
-                writeln!(log_file, "existing branch")?;
-            }
-        }
-        None => writeln!(log_file, "branch not found")?,
+    for event in events {
+        writeln!(
+            log_file,
+            "{repository_owner}/{repository_name}: {} {} -> {}",
+            event.ref_name, event.before, event.after
+        )?;
     }
 
     log_file.flush()?;
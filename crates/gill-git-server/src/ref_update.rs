@@ -0,0 +1,85 @@
+use anyhow::{anyhow, bail};
+use gill_db::repository::push::RefUpdate;
+
+/// Parse the `post-receive` hook input, git sends one `<old-sha> <new-sha> <ref-name>`
+/// line per updated ref.
+pub fn parse_ref_updates(input: &str) -> anyhow::Result<Vec<RefUpdate>> {
+    input
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(parse_line)
+        .collect()
+}
+
+fn parse_line(line: &str) -> anyhow::Result<RefUpdate> {
+    let mut parts = line.split_whitespace();
+    let (Some(before), Some(after), Some(ref_name), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        bail!("Invalid ref update line: '{line}'");
+    };
+
+    for sha in [before, after] {
+        if sha.len() != 40 || !sha.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(anyhow!("Invalid sha '{sha}' in '{line}'"));
+        }
+    }
+
+    Ok(RefUpdate {
+        before: before.to_string(),
+        after: after.to_string(),
+        ref_name: ref_name.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ref_update::parse_ref_updates;
+    use gill_db::repository::push::{RefTarget, RefUpdateKind};
+    use speculoos::prelude::*;
+
+    const NULL: &str = "0000000000000000000000000000000000000000";
+    const A: &str = "5e2d3a1b9f0c4e6a7b8c9d0e1f2a3b4c5d6e7f80";
+    const B: &str = "a94a8fe5ccb19ba61c4c0873d391e987982fbbd3";
+
+    #[test]
+    fn should_parse_batched_ref_updates() -> anyhow::Result<()> {
+        // Arrange
+        let input = format!(
+            "{NULL} {A} refs/heads/main\n{A} {B} refs/heads/feat/a\n{B} {NULL} refs/heads/old\n{NULL} {A} refs/tags/v1.0\n"
+        );
+
+        // Act
+        let updates = parse_ref_updates(&input)?;
+
+        // Assert
+        let kinds: Vec<_> = updates.iter().map(|update| update.kind()).collect();
+        let targets: Vec<_> = updates.iter().map(|update| update.target()).collect();
+        assert_that!(kinds).is_equal_to(vec![
+            RefUpdateKind::Create,
+            RefUpdateKind::Update,
+            RefUpdateKind::Delete,
+            RefUpdateKind::Create,
+        ]);
+        assert_that!(targets).is_equal_to(vec![
+            RefTarget::Branch("main"),
+            RefTarget::Branch("feat/a"),
+            RefTarget::Branch("old"),
+            RefTarget::Tag("v1.0"),
+        ]);
+        Ok(())
+    }
+
+    #[test]
+    fn should_reject_malformed_lines() {
+        assert_that!(parse_ref_updates(&format!("{A} refs/heads/main"))).is_err();
+        assert_that!(parse_ref_updates(&format!("{A} {B} refs/heads/main extra"))).is_err();
+        assert_that!(parse_ref_updates(&format!("{A} not-a-sha refs/heads/main"))).is_err();
+    }
+
+    #[test]
+    fn should_ignore_empty_input() -> anyhow::Result<()> {
+        assert_that!(parse_ref_updates("\n")?).is_empty();
+        Ok(())
+    }
+}
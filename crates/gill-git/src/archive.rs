@@ -0,0 +1,419 @@
+use crate::GitRepository;
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdout, Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::SystemTime;
+
+const ARCHIVE_CACHE_DIR: &str = "archive-cache";
+
+/// Size of the tag archive cache of a repository, the least recently used archives are
+/// evicted above it
+const ARCHIVE_CACHE_MAX_BYTES: u64 = 256 * 1024 * 1024;
+
+/// Distinguish the temporary files of tag archives being cached concurrently
+static PENDING_CACHE: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ArchiveFormat {
+    TarGz,
+    Zip,
+}
+
+impl ArchiveFormat {
+    /// Split a requested file name like `v1.0.tar.gz` into the reference and archive format
+    pub fn split(filename: &str) -> Option<(&str, ArchiveFormat)> {
+        if let Some(reference) = filename.strip_suffix(".tar.gz") {
+            Some((reference, ArchiveFormat::TarGz))
+        } else {
+            filename
+                .strip_suffix(".zip")
+                .map(|reference| (reference, ArchiveFormat::Zip))
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ArchiveFormat::TarGz => "tar.gz",
+            ArchiveFormat::Zip => "zip",
+        }
+    }
+
+    pub fn mime(&self) -> &'static str {
+        match self {
+            ArchiveFormat::TarGz => "application/gzip",
+            ArchiveFormat::Zip => "application/zip",
+        }
+    }
+}
+
+/// An archive read as it is produced by `git archive`, or from the tag archive cache
+#[derive(Debug)]
+pub struct Archive {
+    source: ArchiveSource,
+    cache: Option<CacheFile>,
+}
+
+#[derive(Debug)]
+enum ArchiveSource {
+    Cached(File),
+    Git { child: Child, stdout: ChildStdout },
+}
+
+/// A tag archive being written to the cache, it only replaces `path` once complete
+#[derive(Debug)]
+struct CacheFile {
+    file: File,
+    tmp: PathBuf,
+    path: PathBuf,
+}
+
+impl GitRepository {
+    /// Archive the tree at `reference` (a branch, a tag or a commit sha).
+    /// Every entry is placed under a `{repository}-{reference}/` directory and
+    /// timestamped with the commit time, so the same commit always produces the same bytes.
+    /// Tag archives are cached in the repository directory once read to the end.
+    pub fn archive(&self, reference: &str, format: ArchiveFormat) -> anyhow::Result<Archive> {
+        let (commit, is_tag) = self.resolve_archive_ref(reference)?;
+        let prefix = format!("{}-{}", self.name(), reference.replace('/', "-"));
+        let cache_dir = self.path().join(ARCHIVE_CACHE_DIR);
+        let cache = cache_dir.join(format!("{prefix}-{commit}.{}", format.extension()));
+
+        if is_tag && cache.exists() {
+            let file = File::open(&cache)?;
+            // Keep recently downloaded archives out of the eviction
+            let _ = file.set_modified(SystemTime::now());
+            return Ok(Archive {
+                source: ArchiveSource::Cached(file),
+                cache: None,
+            });
+        }
+
+        let mut child = Command::new("git")
+            .arg("--git-dir")
+            .arg(self.path())
+            // 644 and 755 file permissions, whatever the git config says
+            .args(["-c", "tar.umask=022", "archive"])
+            .arg(format!("--format={}", format.extension()))
+            .arg(format!("--prefix={prefix}/"))
+            .arg(commit.to_string())
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+
+        let stdout = child.stdout.take().expect("piped stdout");
+        let cache = if is_tag {
+            fs::create_dir_all(&cache_dir)?;
+            let pending = PENDING_CACHE.fetch_add(1, Ordering::Relaxed);
+            let tmp = cache_dir.join(format!(".{}-{pending}.tmp", std::process::id()));
+            Some(CacheFile {
+                file: File::create(&tmp)?,
+                tmp,
+                path: cache,
+            })
+        } else {
+            None
+        };
+
+        Ok(Archive {
+            source: ArchiveSource::Git { child, stdout },
+            cache,
+        })
+    }
+}
+
+impl Read for Archive {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = match &mut self.source {
+            ArchiveSource::Cached(file) => return file.read(buf),
+            ArchiveSource::Git { child, stdout } => {
+                let read = stdout.read(buf)?;
+                if read == 0 {
+                    let status = child.wait()?;
+                    if !status.success() {
+                        self.cache = None;
+                        return Err(io::Error::other(format!("git archive failed: {status}")));
+                    }
+                }
+
+                read
+            }
+        };
+
+        // A cache failure should not fail the download, the next one will try again
+        if let Some(mut cache) = self.cache.take() {
+            let written = if read == 0 {
+                cache.persist()
+            } else {
+                cache.file.write_all(&buf[..read]).map(|_| cache)
+            };
+
+            match written {
+                Ok(cache) => self.cache = Some(cache),
+                Err(err) => tracing::warn!("Failed to cache archive: {err}"),
+            }
+        }
+
+        Ok(read)
+    }
+}
+
+impl Drop for Archive {
+    fn drop(&mut self) {
+        // The download was interrupted, don't leave git running
+        if let ArchiveSource::Git { child, .. } = &mut self.source {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+impl CacheFile {
+    fn persist(mut self) -> io::Result<CacheFile> {
+        self.file.flush()?;
+        fs::rename(&self.tmp, &self.path)?;
+        self.tmp = self.path.clone();
+        if let Some(dir) = self.path.parent() {
+            prune_cache(dir, ARCHIVE_CACHE_MAX_BYTES)?;
+        }
+
+        Ok(self)
+    }
+}
+
+/// Remove the least recently used archives of `dir` until it holds at most `max_bytes`,
+/// archives being written are left alone
+fn prune_cache(dir: &Path, max_bytes: u64) -> io::Result<()> {
+    let mut archives = vec![];
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+
+        let metadata = entry.metadata()?;
+        archives.push((metadata.modified()?, metadata.len(), entry.path()));
+    }
+
+    let mut size: u64 = archives.iter().map(|(_, len, _)| len).sum();
+    archives.sort();
+    for (_, len, path) in archives {
+        if size <= max_bytes {
+            break;
+        }
+
+        fs::remove_file(path)?;
+        size -= len;
+    }
+
+    Ok(())
+}
+
+impl Drop for CacheFile {
+    fn drop(&mut self) {
+        if self.tmp != self.path {
+            let _ = fs::remove_file(&self.tmp);
+        }
+    }
+}
+
+mod imp {
+    use crate::GitRepository;
+    use anyhow::anyhow;
+    use git_repository::ObjectId;
+
+    impl GitRepository {
+        /// The commit `reference` points to and whether it is a tag
+        pub(super) fn resolve_archive_ref(
+            &self,
+            reference: &str,
+        ) -> anyhow::Result<(ObjectId, bool)> {
+            if let Ok(mut tag) = self.inner.find_reference(&format!("refs/tags/{reference}")) {
+                let commit = tag.peel_to_id_in_place()?.object()?.try_into_commit()?;
+                return Ok((commit.id, true));
+            }
+
+            if let Ok(mut branch) = self
+                .inner
+                .find_reference(&format!("refs/heads/{reference}"))
+            {
+                let commit = branch.peel_to_id_in_place()?.object()?.try_into_commit()?;
+                return Ok((commit.id, false));
+            }
+
+            let id = ObjectId::from_hex(reference.as_bytes())
+                .map_err(|_| anyhow!("reference not found: {reference}"))?;
+            let commit = self.inner.find_object(id)?.try_into_commit()?;
+            Ok((commit.id, false))
+        }
+
+        pub(super) fn name(&self) -> String {
+            let path = self.path();
+            let path = if self.inner.is_bare() {
+                path.as_path()
+            } else {
+                path.parent().unwrap_or(&path)
+            };
+
+            let name = path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default();
+
+            name.strip_suffix(".git")
+                .map(ToString::to_string)
+                .unwrap_or(name)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::archive::{prune_cache, ArchiveFormat};
+    use crate::GitRepository;
+    use cmd_lib::{run_cmd, run_fun};
+    use sealed_test::prelude::*;
+    use speculoos::prelude::*;
+    use std::fs;
+    use std::io::Read;
+    use std::os::unix::fs::PermissionsExt;
+    use std::path::Path;
+    use std::time::{Duration, SystemTime};
+
+    fn init_repository() -> anyhow::Result<GitRepository> {
+        run_cmd!(
+            git init --bare repo.git;
+            git init work_repo;
+        )?;
+
+        fs::create_dir("work_repo/src")?;
+        fs::write("work_repo/README.md", "readme")?;
+        fs::write("work_repo/src/run.sh", "echo run")?;
+        fs::set_permissions("work_repo/src/run.sh", fs::Permissions::from_mode(0o755))?;
+
+        run_cmd!(
+            cd work_repo;
+            git add .;
+            git commit -m "first commit";
+            git tag -a v1.0 -m "first release";
+            git remote add origin ../repo.git;
+            git push -u origin master --tags;
+        )?;
+
+        Ok(GitRepository {
+            inner: git_repository::open("repo.git")?,
+        })
+    }
+
+    fn read_archive(
+        repo: &GitRepository,
+        reference: &str,
+        format: ArchiveFormat,
+    ) -> anyhow::Result<Vec<u8>> {
+        let mut archive = vec![];
+        repo.archive(reference, format)?.read_to_end(&mut archive)?;
+        Ok(archive)
+    }
+
+    #[test]
+    fn should_split_archive_filename() {
+        assert_that!(ArchiveFormat::split("v1.0.tar.gz"))
+            .is_equal_to(Some(("v1.0", ArchiveFormat::TarGz)));
+        assert_that!(ArchiveFormat::split("main.zip"))
+            .is_equal_to(Some(("main", ArchiveFormat::Zip)));
+        assert_that!(ArchiveFormat::split("main")).is_none();
+    }
+
+    #[sealed_test]
+    fn should_build_tar_gz_archive() -> anyhow::Result<()> {
+        // Arrange
+        let repo = init_repository()?;
+
+        // Act
+        let archive = read_archive(&repo, "master", ArchiveFormat::TarGz)?;
+
+        // Assert
+        fs::write("archive.tar.gz", archive)?;
+        let listing = run_fun!(tar -tvzf archive.tar.gz)?;
+        assert_that!(listing).contains("repo-master/README.md");
+        assert_that!(listing).contains("-rwxr-xr-x");
+        assert_that!(listing).contains("repo-master/src/run.sh");
+        Ok(())
+    }
+
+    #[sealed_test]
+    fn should_build_zip_archive() -> anyhow::Result<()> {
+        // Arrange
+        let repo = init_repository()?;
+
+        // Act
+        let archive = read_archive(&repo, "v1.0", ArchiveFormat::Zip)?;
+
+        // Assert
+        fs::write("archive.zip", archive)?;
+        let listing = run_fun!(unzip -l archive.zip)?;
+        assert_that!(listing).contains("repo-v1.0/README.md");
+        assert_that!(listing).contains("repo-v1.0/src/run.sh");
+        Ok(())
+    }
+
+    #[sealed_test]
+    fn should_cache_tag_archive() -> anyhow::Result<()> {
+        // Arrange
+        let repo = init_repository()?;
+
+        // Act
+        let first = read_archive(&repo, "v1.0", ArchiveFormat::TarGz)?;
+        let second = read_archive(&repo, "v1.0", ArchiveFormat::TarGz)?;
+
+        // Assert
+        assert_that!(first).is_equal_to(second);
+        assert_that!(fs::read_dir("repo.git/archive-cache")?.count()).is_equal_to(1);
+        Ok(())
+    }
+
+    #[sealed_test]
+    fn should_not_cache_interrupted_archive() -> anyhow::Result<()> {
+        // Arrange
+        let repo = init_repository()?;
+
+        // Act
+        let mut archive = repo.archive("v1.0", ArchiveFormat::TarGz)?;
+        archive.read_exact(&mut [0; 8])?;
+        drop(archive);
+
+        // Assert
+        assert_that!(fs::read_dir("repo.git/archive-cache")?.count()).is_equal_to(0);
+        Ok(())
+    }
+
+    #[sealed_test]
+    fn should_evict_least_recently_used_archives() -> anyhow::Result<()> {
+        // Arrange
+        fs::create_dir("cache")?;
+        let now = SystemTime::now();
+        for (name, age) in [("old.zip", 30), ("recent.zip", 10), ("new.zip", 0)] {
+            let file = fs::File::create(Path::new("cache").join(name))?;
+            file.set_len(10)?;
+            file.set_modified(now - Duration::from_secs(age))?;
+        }
+        fs::write("cache/.pending.tmp", [0; 10])?;
+
+        // Act
+        prune_cache(Path::new("cache"), 20)?;
+
+        // Assert
+        let mut remaining: Vec<String> = fs::read_dir("cache")?
+            .map(|entry| entry.map(|entry| entry.file_name().to_string_lossy().to_string()))
+            .collect::<Result<_, _>>()?;
+        remaining.sort();
+        assert_that!(remaining).is_equal_to(vec![
+            ".pending.tmp".to_string(),
+            "new.zip".to_string(),
+            "recent.zip".to_string(),
+        ]);
+        Ok(())
+    }
+}
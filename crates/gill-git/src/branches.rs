@@ -0,0 +1,218 @@
+use crate::commits::OwnedCommit;
+use crate::GitRepository;
+use anyhow::anyhow;
+
+/// Number of commits a branch has that its base does not (`ahead`) and the other way around (`behind`)
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct AheadBehind {
+    pub ahead: usize,
+    pub behind: usize,
+}
+
+impl GitRepository {
+    /// Last commit of a local branch
+    pub fn branch_head(&self, branch: &str) -> anyhow::Result<OwnedCommit> {
+        let mut reference = self.inner.find_reference(&format!("refs/heads/{branch}"))?;
+        let commit = reference
+            .peel_to_id_in_place()?
+            .object()?
+            .try_into_commit()?;
+        OwnedCommit::try_from(&commit)
+    }
+
+    pub fn ahead_behind(&self, base: &str, branch: &str) -> anyhow::Result<AheadBehind> {
+        let range = format!("refs/heads/{base}...refs/heads/{branch}");
+        let output = self.git(&["rev-list", "--left-right", "--count", &range])?;
+        let output = String::from_utf8(output)?;
+        let counts = output.split_whitespace().collect::<Vec<_>>();
+        let [behind, ahead] = counts.as_slice() else {
+            return Err(anyhow!("unexpected rev-list output: {output}"));
+        };
+
+        Ok(AheadBehind {
+            ahead: ahead.parse()?,
+            behind: behind.parse()?,
+        })
+    }
+
+    /// Whether every commit of `branch` is reachable from `base`
+    pub fn is_merged(&self, base: &str, branch: &str) -> anyhow::Result<bool> {
+        Ok(self.ahead_behind(base, branch)?.ahead == 0)
+    }
+
+    /// Whether `after` descends from `before`, i.e. moving a branch from `before` to `after`
+    /// did not rewrite its history
+    pub fn is_fast_forward(&self, before: &str, after: &str) -> anyhow::Result<bool> {
+        let range = format!("{after}..{before}");
+        let output = self.git(&["rev-list", "--count", &range])?;
+        Ok(String::from_utf8(output)?.trim() == "0")
+    }
+
+    /// Create `branch` pointing at `from`, which can be a branch, a tag or a commit sha
+    pub fn create_branch(&self, branch: &str, from: &str) -> anyhow::Result<()> {
+        self.check_branch_name(branch)?;
+        let target = self.rev_parse(from)?;
+        self.git(&["branch", "--", branch, &target])?;
+        Ok(())
+    }
+
+    pub fn delete_branch(&self, branch: &str) -> anyhow::Result<()> {
+        self.check_branch_name(branch)?;
+        self.git(&["branch", "-D", "--", branch])?;
+        Ok(())
+    }
+
+    pub fn rename_branch(&self, branch: &str, new_name: &str) -> anyhow::Result<()> {
+        self.check_branch_name(branch)?;
+        self.check_branch_name(new_name)?;
+        self.git(&["branch", "-m", "--", branch, new_name])?;
+        Ok(())
+    }
+
+    /// Branch HEAD points to
+    pub fn default_branch(&self) -> anyhow::Result<String> {
+        let output = self.git(&["symbolic-ref", "--short", "HEAD"])?;
+        Ok(String::from_utf8(output)?.trim().to_string())
+    }
+
+    /// Point HEAD to `branch`, this is the branch checked out by `git clone`
+    pub fn set_head(&self, branch: &str) -> anyhow::Result<()> {
+        self.check_branch_name(branch)?;
+        self.git(&["symbolic-ref", "HEAD", &format!("refs/heads/{branch}")])?;
+        Ok(())
+    }
+}
+
+mod imp {
+    use crate::GitRepository;
+    use anyhow::anyhow;
+
+    impl GitRepository {
+        pub(super) fn check_branch_name(&self, branch: &str) -> anyhow::Result<()> {
+            if branch.starts_with('-') {
+                return Err(anyhow!("invalid branch name: {branch}"));
+            }
+
+            self.git(&["check-ref-format", "--branch", branch])
+                .map(|_| ())
+                .map_err(|_| anyhow!("invalid branch name: {branch}"))
+        }
+
+        pub(super) fn rev_parse(&self, reference: &str) -> anyhow::Result<String> {
+            if reference.starts_with('-') {
+                return Err(anyhow!("invalid reference: {reference}"));
+            }
+
+            let reference = format!("{reference}^{{commit}}");
+            let sha = self.git(&["rev-parse", "--verify", "--quiet", &reference])?;
+            Ok(String::from_utf8(sha)?.trim().to_string())
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::branches::AheadBehind;
+    use crate::GitRepository;
+    use cmd_lib::{run_cmd, run_fun};
+    use sealed_test::prelude::*;
+    use speculoos::prelude::*;
+
+    fn init_repository() -> anyhow::Result<GitRepository> {
+        run_cmd!(
+            git init --bare repo.git;
+            git init work_repo;
+            cd work_repo;
+            git commit --allow-empty -m "first commit";
+            git checkout -b feature;
+            git commit --allow-empty -m "feature commit";
+            git checkout master;
+            git commit --allow-empty -m "second commit";
+            git commit --allow-empty -m "third commit";
+            git remote add origin ../repo.git;
+            git push origin master feature;
+        )?;
+
+        Ok(GitRepository {
+            inner: git_repository::open("repo.git")?,
+        })
+    }
+
+    #[sealed_test]
+    fn should_count_ahead_behind() -> anyhow::Result<()> {
+        // Arrange
+        let repo = init_repository()?;
+
+        // Act
+        let ahead_behind = repo.ahead_behind("master", "feature")?;
+
+        // Assert
+        assert_that!(ahead_behind).is_equal_to(AheadBehind {
+            ahead: 1,
+            behind: 2,
+        });
+        assert_that!(repo.is_merged("master", "feature")?).is_false();
+        Ok(())
+    }
+
+    #[sealed_test]
+    fn should_detect_fast_forward() -> anyhow::Result<()> {
+        // Arrange
+        let repo = init_repository()?;
+
+        // Act
+        let fast_forward = repo.is_fast_forward("master~2", "master")?;
+        let rewritten = repo.is_fast_forward("feature", "master")?;
+
+        // Assert
+        assert_that!(fast_forward).is_true();
+        assert_that!(rewritten).is_false();
+        Ok(())
+    }
+
+    #[sealed_test]
+    fn should_create_rename_and_delete_branch() -> anyhow::Result<()> {
+        // Arrange
+        let repo = init_repository()?;
+
+        // Act
+        repo.create_branch("release", "master")?;
+        repo.rename_branch("release", "stable")?;
+        repo.delete_branch("feature")?;
+
+        // Assert
+        assert_that!(repo.branch_head("stable")?.summary).is_equal_to("third commit".to_string());
+        let mut branches = repo.list_branches()?;
+        branches.sort();
+        assert_that!(branches).is_equal_to(vec!["master".to_string(), "stable".to_string()]);
+        Ok(())
+    }
+
+    #[sealed_test]
+    fn should_set_head() -> anyhow::Result<()> {
+        // Arrange
+        let repo = init_repository()?;
+
+        // Act
+        repo.set_head("feature")?;
+
+        // Assert
+        let head = run_fun!(git --git-dir repo.git symbolic-ref HEAD)?;
+        assert_that!(head).is_equal_to("refs/heads/feature".to_string());
+        Ok(())
+    }
+
+    #[sealed_test]
+    fn should_reject_invalid_branch_name() -> anyhow::Result<()> {
+        // Arrange
+        let repo = init_repository()?;
+
+        // Act
+        let result = repo.create_branch("--force", "master");
+
+        // Assert
+        assert_that!(result).is_err();
+        assert_that!(repo.create_branch("bad..name", "master")).is_err();
+        Ok(())
+    }
+}
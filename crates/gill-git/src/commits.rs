@@ -13,6 +13,17 @@ impl GitRepository {
     pub fn history_between(&self, base: &str, compare: &str) -> anyhow::Result<Vec<OwnedCommit>> {
         self.list_commits_between_ref(base, compare)
     }
+
+    /// At most `limit` commits reachable from `after` down to `before` excluded, newest first.
+    /// Without `before`, e.g. when a branch is created, the whole history is walked.
+    pub fn history_range(
+        &self,
+        before: Option<&str>,
+        after: &str,
+        limit: usize,
+    ) -> anyhow::Result<Vec<OwnedCommit>> {
+        self.list_commits_between_shas(before, after, limit)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -112,6 +123,31 @@ mod imp {
 
             Ok(commits)
         }
+
+        pub fn list_commits_between_shas(
+            &self,
+            before: Option<&str>,
+            after: &str,
+            limit: usize,
+        ) -> Result<Vec<OwnedCommit>> {
+            let before = before
+                .map(|sha| ObjectId::from_hex(sha.as_bytes()))
+                .transpose()?;
+            let after = ObjectId::from_hex(after.as_bytes())?;
+            let head = self.inner.find_object(after)?.try_into_commit()?;
+            let mut commits = vec![];
+            for commit in head.ancestors().all()?.take(limit) {
+                let id = commit?;
+                if before.map_or(false, |before| id == before) {
+                    break;
+                }
+                let commit = id.object()?.try_into_commit()?;
+                let commit = OwnedCommit::try_from(&commit)?;
+                commits.push(commit);
+            }
+
+            Ok(commits)
+        }
     }
 }
 
@@ -182,6 +218,30 @@ mod test {
         Ok(())
     }
 
+    #[sealed_test]
+    fn list_commits_between_shas() -> anyhow::Result<()> {
+        // Arrange
+        run_cmd!(git init;)?;
+        let before = git_commit("one")?;
+        git_commit("two")?;
+        let after = git_commit("three")?;
+
+        let repo = GitRepository {
+            inner: git_repository::open(".")?,
+        };
+
+        // Act
+        let commits = repo.list_commits_between_shas(Some(&before), &after, 10)?;
+        let limited = repo.list_commits_between_shas(None, &after, 2)?;
+
+        // Assert
+        assert_that!(commits).has_length(2);
+        assert_that!(commits[0].summary).is_equal_to(&"three".to_string());
+        assert_that!(commits[1].summary).is_equal_to(&"two".to_string());
+        assert_that!(limited).has_length(2);
+        Ok(())
+    }
+
     #[sealed_test]
     fn find_commit_ok() -> anyhow::Result<()> {
         // Arrange
@@ -8,6 +8,7 @@ use imara_diff::intern::InternedInput;
 use imara_diff::{Algorithm, UnifiedDiffBuilder};
 
 pub mod commit;
+pub mod patch;
 pub mod tree;
 
 #[derive(Debug, Default)]
@@ -21,11 +22,13 @@ pub enum Diff {
         id: String,
         file_path: String,
         hunk: Option<String>,
+        executable: bool,
     },
     Deletion {
         id: String,
         file_path: String,
         hunk: Option<String>,
+        executable: bool,
     },
     Changes {
         previous_id: String,
@@ -67,23 +70,31 @@ impl DiffBuilder {
         });
     }
 
-    fn addition(&mut self, file_path: &str, id: &Id, hunk: Option<String>) {
+    fn addition(&mut self, file_path: &str, id: &Id, hunk: Option<String>, mode: EntryMode) {
         let id = id.to_string();
         let file_path = file_path.to_owned();
         self.out.push(Diff::Addition {
             id,
             file_path,
             hunk,
+            executable: mode == EntryMode::BlobExecutable,
         });
     }
 
-    fn deletion(&mut self, file_path: &str, previous_id: &Id, hunk: Option<String>) {
+    fn deletion(
+        &mut self,
+        file_path: &str,
+        previous_id: &Id,
+        hunk: Option<String>,
+        mode: EntryMode,
+    ) {
         let id = previous_id.to_string();
         let file_path = file_path.to_owned();
         self.out.push(Diff::Deletion {
             id,
             file_path,
             hunk,
+            executable: mode == EntryMode::BlobExecutable,
         });
     }
 }
@@ -151,7 +162,7 @@ impl GitRepository {
                                         UnifiedDiffBuilder::new(&input),
                                     )
                                 });
-                            diff_builder.addition(location, &id, hunk);
+                            diff_builder.addition(location, &id, hunk, entry_mode);
                         }
                         EntryMode::Link => {}
                         EntryMode::Commit => {}
@@ -174,7 +185,7 @@ impl GitRepository {
                                     )
                                 });
 
-                            diff_builder.deletion(location, &id, hunk);
+                            diff_builder.deletion(location, &id, hunk, entry_mode);
                         }
                         EntryMode::Link => {}
                         EntryMode::Commit => {}
@@ -0,0 +1,378 @@
+use crate::commits::OwnedCommit;
+use crate::diffs::Diff;
+use crate::{ref_to_tree, GitRepository};
+use chrono::{TimeZone, Utc};
+use git_repository::ObjectId;
+
+impl GitRepository {
+    /// Mbox formatted patch for a single commit against its first parent, as produced
+    /// by `git format-patch`.
+    pub fn commit_patch(&self, sha: &str) -> anyhow::Result<Vec<u8>> {
+        let commit = self.commit_by_sha(sha)?;
+        let diffs = self.commit_diff(sha)?;
+        Ok(mbox_entry(&commit, &diffs, None).into_bytes())
+    }
+
+    /// Raw unified diff of a commit against its first parent.
+    pub fn commit_raw_diff(&self, sha: &str) -> anyhow::Result<Vec<u8>> {
+        let diffs = self.commit_diff(sha)?;
+        Ok(unified_diff(&diffs).into_bytes())
+    }
+
+    /// Mbox formatted patch series for the commits in `compare` which are not in `base`.
+    pub fn format_patch(&self, base: &str, compare: &str) -> anyhow::Result<Vec<u8>> {
+        let mut commits = self.history_between(base, compare)?;
+        commits.reverse();
+
+        let total = commits.len();
+        let mut series = String::new();
+        for (index, commit) in commits.iter().enumerate() {
+            let diffs = self.commit_diff(&commit.id)?;
+            series.push_str(&mbox_entry(commit, &diffs, Some((index + 1, total))));
+        }
+
+        Ok(series.into_bytes())
+    }
+
+    /// Raw unified diff between the heads of `base` and `compare`, a `base..compare` range.
+    pub fn raw_diff(&self, base: &str, compare: &str) -> anyhow::Result<Vec<u8>> {
+        let diffs = self.diff(base, compare)?;
+        Ok(unified_diff(&diffs).into_bytes())
+    }
+
+    /// Raw unified diff between `compare` and its merge base with `base`, a `base...compare`
+    /// range.
+    pub fn raw_diff_from_merge_base(&self, base: &str, compare: &str) -> anyhow::Result<Vec<u8>> {
+        let base_ref = format!("refs/heads/{base}");
+        let compare_ref = format!("refs/heads/{compare}");
+        let merge_base = self.git(&["merge-base", &base_ref, &compare_ref])?;
+        let merge_base = ObjectId::from_hex(String::from_utf8(merge_base)?.trim().as_bytes())?;
+        let merge_base = self
+            .inner
+            .find_object(merge_base)?
+            .try_into_commit()?
+            .tree()?;
+        let compare = ref_to_tree(Some(&format!("heads/{compare}")), &self.inner)?;
+        let diffs = self.diff_tree_to_tree(merge_base, compare)?;
+        Ok(unified_diff(&diffs).into_bytes())
+    }
+}
+
+/// One `git format-patch` mail, `number` is the position in the series and its length
+fn mbox_entry(commit: &OwnedCommit, diffs: &[Diff], number: Option<(usize, usize)>) -> String {
+    let date = Utc
+        .timestamp_opt(i64::from(commit.authored_at), 0)
+        .single()
+        .unwrap_or_default()
+        .to_rfc2822();
+    let subject = match number {
+        Some((index, total)) if total > 1 => format!("[PATCH {index}/{total}]"),
+        _ => "[PATCH]".to_string(),
+    };
+
+    let mut entry = format!(
+        "From {} Mon Sep 17 00:00:00 2001\nFrom: {} <{}>\nDate: {date}\nSubject: {subject} {}\n\n",
+        commit.id, commit.author, commit.email, commit.summary
+    );
+
+    if let Some(body) = commit
+        .body
+        .as_deref()
+        .map(str::trim)
+        .filter(|body| !body.is_empty())
+    {
+        entry.push_str(body);
+        entry.push('\n');
+    }
+
+    entry.push_str("---\n");
+    entry.push_str(&unified_diff(diffs));
+    entry.push_str("-- \ngill\n\n");
+    entry
+}
+
+/// Diffs in the `git diff` format, suitable for `git apply`
+fn unified_diff(diffs: &[Diff]) -> String {
+    let mut out = String::new();
+    for diff in diffs {
+        let path = diff.path();
+        let (old_path, new_path, index) = match diff {
+            Diff::Addition { id, .. } => (
+                "/dev/null".to_string(),
+                format!("b/{path}"),
+                format!("0000000..{}", short(id)),
+            ),
+            Diff::Deletion { id, .. } => (
+                format!("a/{path}"),
+                "/dev/null".to_string(),
+                format!("{}..0000000", short(id)),
+            ),
+            Diff::Changes {
+                previous_id, id, ..
+            } => (
+                format!("a/{path}"),
+                format!("b/{path}"),
+                format!("{}..{}", short(previous_id), short(id)),
+            ),
+        };
+
+        let hunk = diff.hunk();
+        // Mode only changes are not tracked by `Diff`
+        if hunk == Some("") {
+            continue;
+        }
+
+        out.push_str(&format!("diff --git a/{path} b/{path}\n"));
+        match diff {
+            Diff::Addition { executable, .. } => {
+                out.push_str(&format!("new file mode {}\n", file_mode(*executable)))
+            }
+            Diff::Deletion { executable, .. } => {
+                out.push_str(&format!("deleted file mode {}\n", file_mode(*executable)))
+            }
+            Diff::Changes { .. } => {}
+        }
+
+        out.push_str(&format!("index {index}\n"));
+        match hunk {
+            Some(hunk) => {
+                out.push_str(&format!("--- {old_path}\n+++ {new_path}\n"));
+                out.push_str(hunk);
+                if !hunk.ends_with('\n') {
+                    out.push_str("\n\\ No newline at end of file\n");
+                }
+            }
+            None => out.push_str(&format!("Binary files {old_path} and {new_path} differ\n")),
+        }
+    }
+
+    out
+}
+
+fn file_mode(executable: bool) -> &'static str {
+    if executable {
+        "100755"
+    } else {
+        "100644"
+    }
+}
+
+fn short(id: &str) -> &str {
+    &id[..7.min(id.len())]
+}
+
+#[cfg(test)]
+mod test {
+    use crate::GitRepository;
+    use anyhow::{anyhow, Result};
+    use cmd_lib::{run_cmd, run_fun};
+    use sealed_test::prelude::*;
+    use speculoos::prelude::*;
+    use std::fs;
+
+    // Helper function to create a commit and get its sha1
+    fn git_commit(message: &str) -> Result<String> {
+        run_fun!(
+            git commit -q -m $message;
+            git log --format=%H -n 1;
+        )
+        .map_err(|e| anyhow!(e))
+    }
+
+    #[sealed_test]
+    fn should_format_commit_patch() -> Result<()> {
+        // Arrange
+        run_cmd!(git init;)?;
+        fs::write("file", "changes")?;
+        run_cmd!(git add .;)?;
+        let sha = git_commit("first commit")?;
+
+        let repo = GitRepository {
+            inner: git_repository::open(".")?,
+        };
+
+        // Act
+        let patch = String::from_utf8(repo.commit_patch(&sha)?)?;
+
+        // Assert
+        assert_that!(patch.starts_with(&format!("From {sha} "))).is_true();
+        assert_that!(patch).contains("Subject: [PATCH] first commit");
+        assert_that!(patch).contains("diff --git a/file b/file");
+
+        Ok(())
+    }
+
+    #[sealed_test]
+    fn should_get_commit_raw_diff() -> Result<()> {
+        // Arrange
+        run_cmd!(git init;)?;
+        fs::write("file", "changes")?;
+        run_cmd!(git add .;)?;
+        git_commit("first commit")?;
+        fs::write("file2", "changes")?;
+        run_cmd!(git add .;)?;
+        let sha = git_commit("second commit")?;
+
+        let repo = GitRepository {
+            inner: git_repository::open(".")?,
+        };
+
+        // Act
+        let diff = String::from_utf8(repo.commit_raw_diff(&sha)?)?;
+
+        // Assert
+        assert_that!(diff.starts_with("diff --git a/file2 b/file2")).is_true();
+        assert_that!(diff).does_not_contain("a/file ");
+
+        Ok(())
+    }
+
+    #[sealed_test]
+    fn should_format_patch_series_between_branches() -> Result<()> {
+        // Arrange
+        run_cmd!(
+            git init;
+            git commit --allow-empty -m "first commit";
+            git checkout -b other;
+        )?;
+        fs::write("file", "changes")?;
+        run_cmd!(git add .;)?;
+        git_commit("commit a")?;
+        fs::write("file2", "changes")?;
+        run_cmd!(git add .;)?;
+        git_commit("commit b")?;
+
+        let repo = GitRepository {
+            inner: git_repository::open(".")?,
+        };
+
+        // Act
+        let patch = String::from_utf8(repo.format_patch("master", "other")?)?;
+        let diff = String::from_utf8(repo.raw_diff("master", "other")?)?;
+
+        // Assert
+        assert_that!(patch).contains("Subject: [PATCH 1/2] commit a");
+        assert_that!(patch).contains("Subject: [PATCH 2/2] commit b");
+        assert_that!(diff).contains("diff --git a/file b/file");
+        assert_that!(diff).contains("diff --git a/file2 b/file2");
+
+        Ok(())
+    }
+
+    #[sealed_test]
+    fn should_format_merge_commit_patch_against_first_parent() -> Result<()> {
+        // Arrange
+        run_cmd!(
+            git init;
+            git commit --allow-empty -q -m "first commit";
+            git checkout -q -b other;
+        )?;
+        fs::write("file", "changes\n")?;
+        run_cmd!(git add .;)?;
+        git_commit("commit a")?;
+        run_cmd!(
+            git checkout -q master;
+            git merge -q --no-ff other -m "merge other";
+        )?;
+        let sha = run_fun!(git log --format=%H -n 1;)?;
+
+        let repo = GitRepository {
+            inner: git_repository::open(".")?,
+        };
+
+        // Act
+        let patch = String::from_utf8(repo.commit_patch(&sha)?)?;
+
+        // Assert
+        assert_that!(patch).contains("Subject: [PATCH] merge other");
+        assert_that!(patch).contains("diff --git a/file b/file");
+        assert_that!(patch).contains("+changes");
+
+        Ok(())
+    }
+
+    #[sealed_test]
+    fn should_diff_two_dot_and_three_dot_ranges() -> Result<()> {
+        // Arrange
+        run_cmd!(
+            git init;
+            git commit --allow-empty -q -m "first commit";
+            git checkout -q -b other;
+        )?;
+        fs::write("file", "changes\n")?;
+        run_cmd!(git add .;)?;
+        git_commit("commit a")?;
+        run_cmd!(git checkout -q master;)?;
+        fs::write("file2", "changes\n")?;
+        run_cmd!(git add .;)?;
+        git_commit("commit b")?;
+
+        let repo = GitRepository {
+            inner: git_repository::open(".")?,
+        };
+
+        // Act
+        let two_dot = String::from_utf8(repo.raw_diff("master", "other")?)?;
+        let three_dot = String::from_utf8(repo.raw_diff_from_merge_base("master", "other")?)?;
+
+        // Assert
+        assert_that!(two_dot).contains("--- a/file2\n+++ /dev/null");
+        assert_that!(two_dot).contains("--- /dev/null\n+++ b/file");
+        assert_that!(three_dot).does_not_contain("file2");
+        assert_that!(three_dot).contains("--- /dev/null\n+++ b/file");
+
+        Ok(())
+    }
+
+    #[sealed_test]
+    fn should_apply_patch_series() -> Result<()> {
+        // Arrange
+        run_cmd!(
+            git init;
+            git commit --allow-empty -q -m "first commit";
+            git checkout -q -b other;
+        )?;
+        fs::write("file", "one\ntwo\n")?;
+        run_cmd!(git add .;)?;
+        git_commit("commit a")?;
+        fs::write("file", "one\nthree\n")?;
+        run_cmd!(git add .;)?;
+        git_commit("commit b")?;
+
+        let repo = GitRepository {
+            inner: git_repository::open(".")?,
+        };
+        let patch = repo.format_patch("master", "other")?;
+        fs::write("series.patch", patch)?;
+
+        // Act
+        run_cmd!(
+            git checkout -q master;
+            git am -q series.patch;
+        )?;
+
+        // Assert
+        assert_that!(fs::read_to_string("file")?).is_equal_to("one\nthree\n".to_string());
+        assert_that!(run_fun!(git log --format=%s -n 1;)?).is_equal_to("commit b".to_string());
+
+        Ok(())
+    }
+
+    #[sealed_test]
+    fn should_reject_invalid_sha() -> Result<()> {
+        // Arrange
+        run_cmd!(git init;)?;
+
+        let repo = GitRepository {
+            inner: git_repository::open(".")?,
+        };
+
+        // Act
+        let patch = repo.commit_patch("--output=/tmp/pwned");
+
+        // Assert
+        assert_that!(patch).is_err();
+
+        Ok(())
+    }
+}
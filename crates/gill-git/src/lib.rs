@@ -1,11 +1,16 @@
+use anyhow::anyhow;
 use git_repository::{Commit, Id, Repository, Tree};
 use std::path::PathBuf;
+use std::process::Command;
 
+pub mod archive;
+pub mod branches;
 pub mod clone;
 pub mod commits;
 pub mod diffs;
 pub mod init;
 pub mod merge;
+pub mod mirror;
 pub mod ssh;
 pub mod traversal;
 
@@ -30,6 +35,28 @@ impl GitRepository {
         self.inner.path().to_path_buf()
     }
 
+    /// Directory where uploaded release assets are stored, next to the git objects
+    pub fn release_assets_path(&self) -> PathBuf {
+        self.path().join("release-assets")
+    }
+
+    // TODO: format-patch and ref manipulation are not available in git-oxide,
+    //  this should be replaced when ready
+    pub(crate) fn git(&self, args: &[&str]) -> anyhow::Result<Vec<u8>> {
+        let output = Command::new("git")
+            .arg("--git-dir")
+            .arg(self.path())
+            .args(args)
+            .output()?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(anyhow!("git {} failed: {stderr}", args.join(" ")));
+        }
+
+        Ok(output.stdout)
+    }
+
     pub(crate) fn non_bare_path(&self) -> PathBuf {
         let mut path = self.inner.path().to_path_buf();
         if !self.inner.is_bare() {
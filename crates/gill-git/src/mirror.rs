@@ -0,0 +1,380 @@
+use crate::{GitRepository, REPO_DIR};
+
+use std::fs;
+use std::path::PathBuf;
+
+/// Transports a mirror is allowed to fetch from, this keeps `file://` and `ext::` out of reach
+const MIRROR_PROTOCOLS: &str = "http:https:git:ssh";
+
+/// Create a bare repository fetching every ref of `remote_url`
+pub fn init_mirror(namespace: &str, name: &str, remote_url: &str) -> anyhow::Result<GitRepository> {
+    let path = PathBuf::from(REPO_DIR).join(namespace);
+
+    if !path.exists() {
+        fs::create_dir_all(&path)?;
+    }
+
+    GitRepository::init_mirror(path, name, remote_url)
+}
+
+/// Credential used to authenticate against a push mirror remote
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum PushCredential<'a> {
+    None,
+    /// Access token sent as basic auth password over http(s)
+    Token(&'a str),
+    /// Private ssh key
+    DeployKey(&'a str),
+}
+
+impl GitRepository {
+    /// Fetch the mirror remote, pruning deleted refs, and point HEAD to the remote default branch
+    pub fn sync_mirror(&self) -> anyhow::Result<()> {
+        self.fetch_mirror(MIRROR_PROTOCOLS)
+    }
+
+    /// Fetch every branch and tag of `remote_url` once and use its default branch as HEAD
+    pub fn import_from(&self, remote_url: &str) -> anyhow::Result<()> {
+        self.fetch_import(MIRROR_PROTOCOLS, remote_url)
+    }
+
+    /// Push every ref to `remote_url`, deleting remote refs which do not exist locally
+    pub fn push_mirror(&self, remote_url: &str, credential: PushCredential) -> anyhow::Result<()> {
+        self.push_to_mirror(MIRROR_PROTOCOLS, remote_url, credential)
+    }
+}
+
+mod imp {
+    use super::PushCredential;
+    use crate::GitRepository;
+    use anyhow::anyhow;
+    use base64::engine::general_purpose;
+    use base64::Engine;
+    use std::fs;
+    use std::io::Write;
+    use std::os::unix::fs::OpenOptionsExt;
+    use std::path::PathBuf;
+    use std::process::Command;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    const SSH_COMMAND: &str = "ssh -o BatchMode=yes -o StrictHostKeyChecking=accept-new";
+
+    impl GitRepository {
+        pub(crate) fn init_mirror(
+            base: PathBuf,
+            name: &str,
+            remote_url: &str,
+        ) -> anyhow::Result<GitRepository> {
+            if remote_url.starts_with('-') {
+                return Err(anyhow!("invalid mirror url: {remote_url}"));
+            }
+
+            let path = base.join(format!("{name}.git"));
+            tracing::debug!("Initializing mirror {:?} of {remote_url}", path);
+            let repository = GitRepository {
+                inner: git_repository::init_bare(path)?,
+            };
+
+            repository.git(&["remote", "add", "--mirror=fetch", "origin", remote_url])?;
+            Ok(repository)
+        }
+
+        pub(crate) fn fetch_mirror(&self, protocols: &str) -> anyhow::Result<()> {
+            self.remote_git(protocols, &[], &["fetch", "--prune", "--quiet", "origin"])?;
+            let head =
+                self.remote_git(protocols, &[], &["ls-remote", "--symref", "origin", "HEAD"])?;
+            let head = String::from_utf8(head)?;
+            if let Some(branch) = remote_head(&head) {
+                self.set_head(branch)?;
+            }
+
+            Ok(())
+        }
+
+        pub(crate) fn fetch_import(&self, protocols: &str, remote_url: &str) -> anyhow::Result<()> {
+            if remote_url.starts_with('-') {
+                return Err(anyhow!("invalid import url: {remote_url}"));
+            }
+
+            self.remote_git(
+                protocols,
+                &[],
+                &[
+                    "fetch",
+                    "--quiet",
+                    remote_url,
+                    "+refs/heads/*:refs/heads/*",
+                    "+refs/tags/*:refs/tags/*",
+                ],
+            )?;
+            let head = self.remote_git(
+                protocols,
+                &[],
+                &["ls-remote", "--symref", remote_url, "HEAD"],
+            )?;
+            let head = String::from_utf8(head)?;
+            if let Some(branch) = remote_head(&head) {
+                self.set_head(branch)?;
+            }
+
+            Ok(())
+        }
+
+        pub(crate) fn push_to_mirror(
+            &self,
+            protocols: &str,
+            remote_url: &str,
+            credential: PushCredential,
+        ) -> anyhow::Result<()> {
+            if remote_url.starts_with('-') {
+                return Err(anyhow!("invalid mirror url: {remote_url}"));
+            }
+
+            let args = ["push", "--mirror", "--quiet", remote_url];
+            match credential {
+                PushCredential::None => {
+                    self.remote_git(protocols, &[], &args)?;
+                }
+                PushCredential::Token(token) => {
+                    let header = format!("Authorization: Basic {}", basic_auth(token));
+                    // Passed through the environment so the token never shows up in the process list
+                    let env = [
+                        ("GIT_CONFIG_COUNT", "1"),
+                        ("GIT_CONFIG_KEY_0", "http.extraHeader"),
+                        ("GIT_CONFIG_VALUE_0", header.as_str()),
+                    ];
+                    self.remote_git(protocols, &env, &args)?;
+                }
+                PushCredential::DeployKey(key) => {
+                    let key_path = self.write_deploy_key(key)?;
+                    let ssh_command = format!(
+                        "{SSH_COMMAND} -o IdentitiesOnly=yes -i '{}'",
+                        key_path.display()
+                    );
+                    let outcome =
+                        self.remote_git(protocols, &[("GIT_SSH_COMMAND", &ssh_command)], &args);
+                    fs::remove_file(&key_path)?;
+                    outcome?;
+                }
+            }
+
+            Ok(())
+        }
+
+        // Ssh refuses keys readable by others, the key is written with 0600 permissions
+        fn write_deploy_key(&self, key: &str) -> anyhow::Result<PathBuf> {
+            let nanos = SystemTime::now().duration_since(UNIX_EPOCH)?.as_nanos();
+            let path = self.path().join(format!("push-mirror-key-{nanos}"));
+            let mut file = fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .mode(0o600)
+                .open(&path)?;
+
+            file.write_all(key.trim_end().as_bytes())?;
+            file.write_all(b"\n")?;
+            Ok(path)
+        }
+
+        // Same as `git` but never prompts for credentials and only allows `protocols`
+        fn remote_git(
+            &self,
+            protocols: &str,
+            env: &[(&str, &str)],
+            args: &[&str],
+        ) -> anyhow::Result<Vec<u8>> {
+            let output = Command::new("git")
+                .env("GIT_ALLOW_PROTOCOL", protocols)
+                .env("GIT_TERMINAL_PROMPT", "0")
+                .env("GIT_SSH_COMMAND", SSH_COMMAND)
+                .envs(env.iter().copied())
+                .arg("--git-dir")
+                .arg(self.path())
+                .args(args)
+                .output()?;
+
+            if !output.status.success() {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                return Err(anyhow!("git {} failed: {stderr}", args.join(" ")));
+            }
+
+            Ok(output.stdout)
+        }
+    }
+
+    // Forges ignore the username when a token is used as password
+    fn basic_auth(token: &str) -> String {
+        general_purpose::STANDARD.encode(format!("gill:{token}"))
+    }
+
+    /// Default branch from `git ls-remote --symref origin HEAD` output
+    pub(super) fn remote_head(ls_remote: &str) -> Option<&str> {
+        ls_remote.lines().find_map(|line| {
+            line.strip_prefix("ref: refs/heads/")?
+                .strip_suffix("\tHEAD")
+        })
+    }
+
+    #[cfg(test)]
+    mod test {
+        use super::remote_head;
+        use crate::mirror::PushCredential;
+        use crate::GitRepository;
+        use cmd_lib::run_cmd;
+        use sealed_test::prelude::*;
+        use speculoos::prelude::*;
+        use std::path::PathBuf;
+
+        #[test]
+        fn should_parse_remote_head() {
+            let output =
+                "ref: refs/heads/main\tHEAD\n3f1c2a9e7d1b4c2f8a0e6d5b4c3a2f1e0d9c8b7a\tHEAD\n";
+
+            assert_that!(remote_head(output)).is_equal_to(Some("main"));
+        }
+
+        #[test]
+        fn should_not_parse_detached_remote_head() {
+            let output = "3f1c2a9e7d1b4c2f8a0e6d5b4c3a2f1e0d9c8b7a\tHEAD\n";
+
+            assert_that!(remote_head(output)).is_none();
+        }
+
+        #[sealed_test]
+        fn should_fetch_mirror() -> anyhow::Result<()> {
+            // Arrange
+            run_cmd!(
+                git init remote;
+                cd remote;
+                git checkout -b main;
+                git commit --allow-empty -m "First commit";
+                git checkout -b feature;
+                git commit --allow-empty -m "Feature commit";
+                git checkout main;
+            )?;
+
+            let remote = std::env::current_dir()?.join("remote");
+            let remote = remote.to_string_lossy();
+            let mirror = GitRepository::init_mirror(PathBuf::from("."), "mirror", &remote)?;
+
+            // Act
+            mirror.fetch_mirror("file")?;
+
+            // Assert
+            assert_that!(mirror.list_branches())
+                .is_ok()
+                .contains_all_of(&[&"main".to_string(), &"feature".to_string()]);
+            assert_that!(mirror.default_branch())
+                .is_ok()
+                .is_equal_to("main".to_string());
+            Ok(())
+        }
+
+        #[sealed_test]
+        fn should_prune_deleted_branches() -> anyhow::Result<()> {
+            // Arrange
+            run_cmd!(
+                git init remote;
+                cd remote;
+                git checkout -b main;
+                git commit --allow-empty -m "First commit";
+                git branch feature;
+            )?;
+
+            let remote = std::env::current_dir()?.join("remote");
+            let remote = remote.to_string_lossy();
+            let mirror = GitRepository::init_mirror(PathBuf::from("."), "mirror", &remote)?;
+            mirror.fetch_mirror("file")?;
+            run_cmd!(cd remote; git branch -D feature;)?;
+
+            // Act
+            mirror.fetch_mirror("file")?;
+
+            // Assert
+            assert_that!(mirror.list_branches())
+                .is_ok()
+                .is_equal_to(vec!["main".to_string()]);
+            Ok(())
+        }
+
+        #[sealed_test]
+        fn should_import_branches_and_tags() -> anyhow::Result<()> {
+            // Arrange
+            run_cmd!(
+                git init --bare repository.git;
+                git init remote;
+                cd remote;
+                git checkout -b trunk;
+                git commit --allow-empty -m "First commit";
+                git tag v1.0.0;
+                git branch feature;
+            )?;
+
+            let repository = GitRepository {
+                inner: git_repository::open("repository.git")?,
+            };
+            let remote = std::env::current_dir()?.join("remote");
+
+            // Act
+            repository.fetch_import("file", &remote.to_string_lossy())?;
+
+            // Assert
+            assert_that!(repository.list_branches())
+                .is_ok()
+                .contains_all_of(&[&"trunk".to_string(), &"feature".to_string()]);
+            assert_that!(repository.default_branch())
+                .is_ok()
+                .is_equal_to("trunk".to_string());
+            run_cmd!(git --git-dir repository.git rev-parse --verify v1.0.0)?;
+            Ok(())
+        }
+
+        #[sealed_test]
+        fn should_push_mirror() -> anyhow::Result<()> {
+            // Arrange
+            run_cmd!(
+                git init --bare backup.git;
+                git init --bare repository.git;
+                git init work;
+                cd work;
+                git checkout -b main;
+                git commit --allow-empty -m "First commit";
+                git branch feature;
+                git push ../repository.git main feature;
+            )?;
+
+            let repository = GitRepository {
+                inner: git_repository::open("repository.git")?,
+            };
+            let backup = std::env::current_dir()?.join("backup.git");
+
+            // Act
+            repository.push_to_mirror("file", &backup.to_string_lossy(), PushCredential::None)?;
+
+            // Assert
+            let backup = GitRepository {
+                inner: git_repository::open("backup.git")?,
+            };
+            assert_that!(backup.list_branches())
+                .is_ok()
+                .contains_all_of(&[&"main".to_string(), &"feature".to_string()]);
+            Ok(())
+        }
+
+        #[sealed_test]
+        fn should_reject_file_protocol() -> anyhow::Result<()> {
+            run_cmd!(
+                git init remote;
+                cd remote;
+                git commit --allow-empty -m "First commit";
+            )?;
+
+            let remote = std::env::current_dir()?.join("remote");
+            let remote = remote.to_string_lossy();
+            let mirror = GitRepository::init_mirror(PathBuf::from("."), "mirror", &remote)?;
+
+            assert_that!(mirror.sync_mirror()).is_err();
+            Ok(())
+        }
+    }
+}
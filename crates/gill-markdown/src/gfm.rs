@@ -0,0 +1,194 @@
+use std::collections::{HashMap, VecDeque};
+use std::ops::Range;
+
+use pulldown_cmark::{CodeBlockKind, CowStr, Event, Tag};
+
+use crate::sanitize::USER_CONTENT_PREFIX;
+
+/// Alert markers and their titles, e.g. `> [!NOTE]`
+const ALERTS: [(&str, &str); 5] = [
+    ("NOTE", "Note"),
+    ("TIP", "Tip"),
+    ("IMPORTANT", "Important"),
+    ("WARNING", "Warning"),
+    ("CAUTION", "Caution"),
+];
+
+/// A task list checkbox. `data-task-offset` is the UTF-16 offset of the checkbox state
+/// character in the markdown source, so the page can toggle it in the source it holds.
+/// `markdown` line breaks must be normalised to `\n`, like a textarea value.
+pub(crate) fn task_checkbox<'a>(checked: bool, markdown: &str, range: Range<usize>) -> Event<'a> {
+    let offset = markdown
+        .get(range.clone())
+        .and_then(|marker| marker.find('['))
+        .map(|bracket| markdown[..range.start + bracket + 1].encode_utf16().count());
+
+    let offset = match offset {
+        Some(offset) => format!(r#" data-task-offset="{offset}""#),
+        None => String::new(),
+    };
+
+    let checked = if checked { r#" checked="""# } else { "" };
+    Event::Html(CowStr::from(format!(
+        r#"<input type="checkbox" class="task-list-item-checkbox"{offset} disabled=""{checked}/>"#
+    )))
+}
+
+/// Heading anchors, alert blocks and highlighted fenced code blocks
+pub(crate) fn extend<'a>(events: impl IntoIterator<Item = Event<'a>>) -> Vec<Event<'a>> {
+    let mut events: VecDeque<Event<'a>> = events.into_iter().collect();
+    let mut out = Vec::with_capacity(events.len());
+    let mut anchors = HashMap::new();
+    // Whether each open block quote is an alert
+    let mut block_quotes = vec![];
+    while let Some(event) = events.pop_front() {
+        match event {
+            Event::Start(Tag::Heading(level, id, _)) => {
+                let (content, _) = take_until_end(&mut events);
+                let text = id
+                    .map(str::to_string)
+                    .unwrap_or_else(|| plain_text(&content));
+                let anchor = unique_anchor(&text, &mut anchors);
+                out.push(Event::Html(CowStr::from(format!(
+                    r##"<{level} id="{anchor}"><a class="anchor" href="#{anchor}" aria-hidden="true">#</a>"##
+                ))));
+                out.extend(content);
+                out.push(Event::Html(CowStr::from(format!("</{level}>\n"))));
+            }
+            Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(info))) => {
+                let (content, end) = take_until_end(&mut events);
+                let token = info
+                    .split(|c: char| c.is_whitespace() || c == ',')
+                    .next()
+                    .unwrap_or_default();
+
+                match highlight(&plain_text(&content), token) {
+                    Some(html) => out.push(Event::Html(CowStr::from(html))),
+                    None => {
+                        out.push(Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(info))));
+                        out.extend(content);
+                        out.extend(end);
+                    }
+                }
+            }
+            Event::Start(Tag::BlockQuote) => match take_alert_marker(&mut events) {
+                Some((kind, title)) => {
+                    block_quotes.push(true);
+                    out.push(Event::Html(CowStr::from(format!(
+                        "<div class=\"markdown-alert markdown-alert-{kind}\">\n<p class=\"markdown-alert-title\">{title}</p>\n",
+                        kind = kind.to_lowercase()
+                    ))));
+                }
+                None => {
+                    block_quotes.push(false);
+                    out.push(Event::Start(Tag::BlockQuote));
+                }
+            },
+            Event::End(Tag::BlockQuote) => match block_quotes.pop() {
+                Some(true) => out.push(Event::Html(CowStr::from("</div>\n"))),
+                _ => out.push(Event::End(Tag::BlockQuote)),
+            },
+            event => out.push(event),
+        }
+    }
+
+    out
+}
+
+/// Events up to the end of the current heading or code block, and the end event itself
+fn take_until_end<'a>(events: &mut VecDeque<Event<'a>>) -> (Vec<Event<'a>>, Option<Event<'a>>) {
+    let mut content = vec![];
+    while let Some(event) = events.pop_front() {
+        match event {
+            Event::End(Tag::Heading(..) | Tag::CodeBlock(_)) => return (content, Some(event)),
+            event => content.push(event),
+        }
+    }
+
+    (content, None)
+}
+
+/// Remove the `[!KIND]` line opening a block quote, the marker can be split across text events
+fn take_alert_marker(events: &mut VecDeque<Event>) -> Option<(&'static str, &'static str)> {
+    if !matches!(events.front(), Some(Event::Start(Tag::Paragraph))) {
+        return None;
+    }
+
+    let mut marker = String::new();
+    let mut len = 0;
+    while let Some(Event::Text(text)) = events.get(len + 1) {
+        marker.push_str(text);
+        len += 1;
+    }
+
+    let marker = marker.trim();
+    let alert = ALERTS.into_iter().find(|(kind, _)| {
+        marker
+            .strip_prefix("[!")
+            .and_then(|marker| marker.strip_suffix(']'))
+            .map(|marker| marker.eq_ignore_ascii_case(kind))
+            .unwrap_or(false)
+    })?;
+
+    match events.get(len + 1) {
+        // The alert content starts on the next line, keep its paragraph
+        Some(Event::SoftBreak | Event::HardBreak) => {
+            events.drain(1..len + 2);
+        }
+        // The alert content is in the next paragraphs
+        Some(Event::End(Tag::Paragraph)) => {
+            events.drain(..len + 2);
+        }
+        _ => return None,
+    }
+
+    Some(alert)
+}
+
+fn plain_text(events: &[Event]) -> String {
+    events
+        .iter()
+        .filter_map(|event| match event {
+            Event::Text(text) | Event::Code(text) => Some(text.as_ref()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// A GitHub like heading anchor, duplicates get a numbered suffix
+fn unique_anchor(text: &str, anchors: &mut HashMap<String, usize>) -> String {
+    let slug: String = text
+        .trim()
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            c if c.is_alphanumeric() || c == '-' || c == '_' => Some(c),
+            _ => None,
+        })
+        .collect();
+
+    let count = anchors.entry(slug.clone()).or_insert(0);
+    let anchor = if *count == 0 {
+        slug
+    } else {
+        format!("{slug}-{count}")
+    };
+
+    *count += 1;
+    format!("{USER_CONTENT_PREFIX}{anchor}")
+}
+
+#[cfg(feature = "highlight")]
+fn highlight(code: &str, token: &str) -> Option<String> {
+    if token.is_empty() {
+        return None;
+    }
+
+    gill_syntax::highlight::highlight_code_block(code, token)
+}
+
+#[cfg(not(feature = "highlight"))]
+fn highlight(_code: &str, _token: &str) -> Option<String> {
+    None
+}
@@ -1,116 +1,171 @@
-use std::io::Cursor;
+use pulldown_cmark::escape::escape_html;
+use pulldown_cmark::{html, CowStr, Event, Options, Parser, Tag};
 
-use pulldown_cmark::{html, Options, Parser};
-use quick_xml::events::attributes::Attribute;
-use quick_xml::events::{BytesStart, Event as HtmlEvent};
-use quick_xml::name::QName;
-use quick_xml::reader::Reader;
-use quick_xml::Writer;
+use crate::sanitize::Sanitizer;
+pub use location::Location;
+pub use reference::Reference;
 
+mod gfm;
+mod location;
+mod reference;
+mod sanitize;
+
+/// Render `markdown_input` to html, relative links and images point to files of `repository`.
+/// Raw html goes through an allowlist, scripts, event handlers and `javascript:` urls are removed.
 pub fn render(markdown_input: &str, owner: &str, repository: &str) -> String {
-    let parser = Parser::new_ext(markdown_input, Options::all());
+    render_at(markdown_input, Location::repository(owner, repository))
+}
+
+/// Render a repository file, relative links and images are resolved from its `location`
+pub fn render_at(markdown_input: &str, location: Location) -> String {
+    // Browsers normalise textarea line breaks, task offsets must be computed the same way
+    let markdown_input = &normalize_line_breaks(markdown_input);
+    let mut sanitizer = Sanitizer::new(location);
+    let events = Parser::new_ext(markdown_input, Options::all())
+        .into_offset_iter()
+        .filter_map(|(event, range)| match event {
+            Event::TaskListMarker(checked) => {
+                Some(gfm::task_checkbox(checked, markdown_input, range))
+            }
+            event => sanitizer.sanitize(event),
+        });
+
+    let events = gfm::extend(events);
+    let events = link_references(events, location.owner, location.repository);
     let mut out = String::new();
-    html::push_html(&mut out, parser);
-    let out = render_html(&out, owner, repository).expect("Valid html");
+    html::push_html(&mut out, events.into_iter());
     out
 }
 
-fn render_html(
-    html_input: &str,
+fn normalize_line_breaks(markdown_input: &str) -> String {
+    markdown_input.replace("\r\n", "\n").replace('\r', "\n")
+}
+
+/// Mentions, issue references and commit shas found in `markdown_input`,
+/// code blocks, inline code and links are left out.
+pub fn references(markdown_input: &str) -> Vec<Reference> {
+    let parser = Parser::new_ext(markdown_input, Options::all());
+    let mut references = vec![];
+    for_each_linkable_text(parser, |text| {
+        references.extend(
+            reference::tokenize(text)
+                .into_iter()
+                .filter_map(|token| match token {
+                    reference::Token::Reference(_, reference) => Some(reference),
+                    reference::Token::Text(_) => None,
+                }),
+        );
+    });
+
+    references
+}
+
+/// Issue references closed by `text`, e.g. "fixes #12" or "closes owner/repository#3".
+/// Commit messages are not markdown, so `text` is searched as is.
+pub fn closing_references(text: &str) -> Vec<Reference> {
+    reference::closing_references(text)
+}
+
+/// Replace references in text nodes with links to their targets
+fn link_references<'a>(
+    events: impl IntoIterator<Item = Event<'a>>,
     owner: &str,
     repository: &str,
-) -> Result<String, Box<dyn std::error::Error>> {
-    let mut reader = Reader::from_str(html_input);
-    reader.check_end_names(false);
-    let mut out = vec![];
-    let mut writer = Writer::new(Cursor::new(&mut out));
-    loop {
-        match reader.read_event() {
-            Ok(HtmlEvent::Start(img)) if img.name().as_ref() == b"img" => {
-                match img.try_get_attribute("src")? {
-                    Some(src) => {
-                        let link = &src.value;
-                        let link = String::from_utf8_lossy(link);
-                        let link = link.as_ref();
-                        if is_relative(link) {
-                            let link = prepend_namespace(link, owner, repository);
-                            let src = Attribute::from(("src", link.as_str()));
-                            let mut new_img = BytesStart::new("img");
-                            let attributes: Vec<Attribute> = img
-                                .attributes()
-                                .map(|attr| attr.expect("Valid attribute"))
-                                .filter(|attr| attr.key != QName(b"src"))
-                                .collect();
-
-                            new_img.extend_attributes(attributes);
-                            new_img.push_attribute(src);
-                            writer.write_event(HtmlEvent::Start(new_img))?
-                        } else {
-                            writer.write_event(HtmlEvent::Start(img))?
-                        }
-                    }
-                    None => writer.write_event(HtmlEvent::Start(img))?,
-                };
-            }
-            Ok(HtmlEvent::Empty(img)) if img.name().as_ref() == b"img" => {
-                match img.try_get_attribute("src")? {
-                    Some(src) => {
-                        let link = &src.value;
-                        let link = String::from_utf8_lossy(link);
-                        let link = link.as_ref();
-                        if is_relative(link) {
-                            let link = prepend_namespace(link, owner, repository);
-                            let src = Attribute::from(("src", link.as_str()));
-                            let mut new_img = BytesStart::new("img");
-                            let attributes: Vec<Attribute> = img
-                                .attributes()
-                                .map(|attr| attr.expect("Valid attribute"))
-                                .filter(|attr| attr.key != QName(b"src"))
-                                .collect();
-
-                            new_img.extend_attributes(attributes);
-                            new_img.push_attribute(src);
-                            writer.write_event(HtmlEvent::Empty(new_img))?
-                        } else {
-                            writer.write_event(HtmlEvent::Empty(img))?
+) -> Vec<Event<'a>> {
+    let mut depth = 0;
+    events
+        .into_iter()
+        .map(|event| {
+            match &event {
+                Event::Start(tag) if is_unlinkable(tag) => depth += 1,
+                Event::End(tag) if is_unlinkable(tag) => depth -= 1,
+                Event::Text(text) if depth == 0 => {
+                    let tokens = reference::tokenize(text);
+                    let has_reference = tokens
+                        .iter()
+                        .any(|token| matches!(token, reference::Token::Reference(..)));
+
+                    if has_reference {
+                        let mut html = String::new();
+                        for token in tokens {
+                            match token {
+                                reference::Token::Text(text) => {
+                                    escape_html(&mut html, text).expect("write to string")
+                                }
+                                reference::Token::Reference(text, reference) => {
+                                    html.push_str(&reference.to_html(text, owner, repository))
+                                }
+                            }
                         }
-                    }
-                    None => writer.write_event(HtmlEvent::Empty(img))?,
-                };
-            }
-            Ok(HtmlEvent::Eof) => break,
-            Ok(e) => writer.write_event(e)?,
-            Err(err) => {
-                println!("{err}");
-            }
-        }
-    }
-    Ok(String::from_utf8_lossy(&out).to_string())
-}
 
-fn is_relative(link: &str) -> bool {
-    if link.starts_with("https://") || link.starts_with("http://") {
-        return false;
-    };
+                        return Event::Html(CowStr::from(html));
+                    }
+                }
+                _ => {}
+            };
 
-    true
+            event
+        })
+        .collect()
 }
 
-fn prepend_namespace(link: &str, owner: &str, repository: &str) -> String {
-    if link.starts_with('/') {
-        format!("/{owner}/{repository}{link}")
-    } else if let Some(link) = link.strip_prefix("./") {
-        format!("/{owner}/{repository}/{link}")
-    } else {
-        format!("/{owner}/{repository}/{link}")
+fn for_each_linkable_text(parser: Parser<'_, '_>, mut f: impl FnMut(&str)) {
+    let mut depth = 0;
+    for event in parser {
+        match event {
+            Event::Start(tag) if is_unlinkable(&tag) => depth += 1,
+            Event::End(tag) if is_unlinkable(&tag) => depth -= 1,
+            Event::Text(text) if depth == 0 => f(&text),
+            _ => {}
+        }
     }
 }
 
+/// References are not linked inside code and existing links
+fn is_unlinkable(tag: &Tag) -> bool {
+    matches!(tag, Tag::CodeBlock(_) | Tag::Link(..) | Tag::Image(..))
+}
+
 #[cfg(test)]
 mod test {
-    use crate::render;
+    use crate::{references, render, Reference};
     use speculoos::prelude::*;
 
+    #[test]
+    fn should_link_references() {
+        let markdown = "@alice fixed #12 in 3f2a9c1d";
+        let html = render(markdown, "oknozor", "gill");
+        assert_that!(html).is_equal_to(
+            &r#"<p><a class="mention" href="/alice">@alice</a> fixed <a class="issue-reference" href="/oknozor/gill/issues/12">#12</a> in <a class="commit-reference" href="/oknozor/gill/commit/3f2a9c1d">3f2a9c1</a></p>
+"#
+            .to_owned(),
+        );
+    }
+
+    #[test]
+    fn should_not_link_references_in_code() {
+        let markdown = "`#12` and\n\n```\n@alice\n```";
+        let html = render(markdown, "oknozor", "gill");
+        assert_that!(html).is_equal_to(
+            &"<p><code>#12</code> and</p>\n<pre><code>@alice\n</code></pre>\n".to_owned(),
+        );
+    }
+
+    #[test]
+    fn should_collect_references() {
+        let references = references("cc @bob, see oknozor/gill#3 but not `#4`");
+        assert_that!(references).is_equal_to(vec![
+            Reference::Mention {
+                username: "bob".to_string(),
+                domain: None,
+            },
+            Reference::Item {
+                namespace: Some(("oknozor".to_string(), "gill".to_string())),
+                number: 3,
+            },
+        ]);
+    }
+
     #[test]
     fn should_canonicalize_image_link() {
         let markdown = r#"<img src="/docs/assets/img.png" alt="image" />"#;
@@ -137,4 +192,76 @@ mod test {
             &r#"<img alt="image" src="/oknozor/gill/docs/assets/img.png"/>"#.to_owned(),
         );
     }
+
+    #[test]
+    fn should_remove_scripts_and_event_handlers() {
+        let markdown = "<script>alert('xss')</script>\n\n<img src=\"https://gill.example/a.png\" onerror=\"alert(1)\">";
+        let html = render(markdown, "oknozor", "gill");
+        assert_that!(html).does_not_contain("script");
+        assert_that!(html).does_not_contain("alert");
+        assert_that!(html).contains(r#"<img src="https://gill.example/a.png">"#);
+    }
+
+    #[test]
+    fn should_remove_javascript_links() {
+        let markdown =
+            r#"[click](javascript:alert(1)) <a href="JaVaScRiPt:alert(1)" title="me">me</a>"#;
+        let html = render(markdown, "oknozor", "gill");
+        assert_that!(html).contains(r##"<a href="#">click</a>"##);
+        assert_that!(html).contains(r#"<a title="me">me</a>"#);
+        assert_that!(html).does_not_contain("alert");
+    }
+
+    #[test]
+    fn should_canonicalize_links() {
+        let markdown = "[docs](docs/README.md) and [usage](#usage)";
+        let html = render(markdown, "oknozor", "gill");
+        assert_that!(html).is_equal_to(
+            &r##"<p><a href="/oknozor/gill/docs/README.md">docs</a> and <a href="#user-content-usage">usage</a></p>
+"##
+            .to_owned(),
+        );
+    }
+
+    #[test]
+    fn should_add_heading_anchors() {
+        let markdown = "# Getting started\n## Getting started";
+        let html = render(markdown, "oknozor", "gill");
+        assert_that!(html).is_equal_to(
+            &r##"<h1 id="user-content-getting-started"><a class="anchor" href="#user-content-getting-started" aria-hidden="true">#</a>Getting started</h1>
+<h2 id="user-content-getting-started-1"><a class="anchor" href="#user-content-getting-started-1" aria-hidden="true">#</a>Getting started</h2>
+"##
+            .to_owned(),
+        );
+    }
+
+    #[test]
+    fn should_render_task_list_with_source_offsets() {
+        let markdown = "- [ ] todo\n- [x] done";
+        let html = render(markdown, "oknozor", "gill");
+        assert_that!(html).contains(r#"data-task-offset="3" disabled=""/>"#);
+        assert_that!(html).contains(r#"data-task-offset="14" disabled="" checked=""/>"#);
+    }
+
+    #[test]
+    fn should_compute_task_offsets_on_normalised_line_breaks() {
+        let markdown = "intro\r\n\r\n- [ ] todo\r\n- [x] done";
+        let html = render(markdown, "oknozor", "gill");
+        assert_that!(html).contains(r#"data-task-offset="10" disabled=""/>"#);
+        assert_that!(html).contains(r#"data-task-offset="21" disabled="" checked=""/>"#);
+    }
+
+    #[test]
+    fn should_render_alerts() {
+        let markdown = "> [!WARNING]\n> Back up your data first";
+        let html = render(markdown, "oknozor", "gill");
+        assert_that!(html).is_equal_to(
+            &r#"<div class="markdown-alert markdown-alert-warning">
+<p class="markdown-alert-title">Warning</p>
+<p>Back up your data first</p>
+</div>
+"#
+            .to_owned(),
+        );
+    }
 }
@@ -0,0 +1,175 @@
+use crate::sanitize::USER_CONTENT_PREFIX;
+
+/// Where a rendered document lives, relative links and images are resolved against it
+#[derive(Debug, Clone, Copy)]
+pub struct Location<'a> {
+    pub owner: &'a str,
+    pub repository: &'a str,
+    /// Branch the document was read from, relative links point to the file browser when known
+    pub branch: Option<&'a str>,
+    /// Directory of the document, relative to the repository root
+    pub directory: &'a str,
+}
+
+impl<'a> Location<'a> {
+    /// A document which is not a repository file, such as an issue or a release note
+    pub fn repository(owner: &'a str, repository: &'a str) -> Self {
+        Self {
+            owner,
+            repository,
+            branch: None,
+            directory: "",
+        }
+    }
+
+    /// A file in `directory` of `branch`
+    pub fn file(owner: &'a str, repository: &'a str, branch: &'a str, directory: &'a str) -> Self {
+        Self {
+            owner,
+            repository,
+            branch: Some(branch),
+            directory,
+        }
+    }
+
+    /// Images are served from the document branch, or the default branch when unknown
+    pub(crate) fn image_url(&self, path: &str) -> String {
+        let (owner, repository) = (self.owner, self.repository);
+        let resolved = self.resolve(path);
+        match self.branch.map(encode_segment) {
+            Some(branch) => format!("/{owner}/{repository}/raw/{branch}/{resolved}"),
+            None => format!("/{owner}/{repository}/{resolved}"),
+        }
+    }
+
+    pub(crate) fn link_url(&self, link: &str) -> String {
+        let (owner, repository) = (self.owner, self.repository);
+        let (path, suffix) = link.split_at(link.find(['?', '#']).unwrap_or(link.len()));
+        let resolved = self.resolve(path);
+        // Branch names may contain slashes, they are encoded as a single segment
+        let url = match self.branch.map(encode_segment) {
+            Some(branch) if resolved.is_empty() => format!("/{owner}/{repository}/tree/{branch}"),
+            Some(branch) if path.ends_with('/') => {
+                format!("/{owner}/{repository}/tree/{branch}/{resolved}")
+            }
+            Some(branch) => format!("/{owner}/{repository}/blob/{branch}/{resolved}"),
+            None => format!("/{owner}/{repository}/{resolved}"),
+        };
+
+        match suffix.split_once('#') {
+            Some((query, fragment)) if !fragment.is_empty() => {
+                format!("{url}{query}#{USER_CONTENT_PREFIX}{fragment}")
+            }
+            _ => format!("{url}{suffix}"),
+        }
+    }
+
+    /// `path` relative to the repository root with its segments percent encoded,
+    /// `..` can't go above it
+    fn resolve(&self, path: &str) -> String {
+        let directory = if path.starts_with('/') {
+            ""
+        } else {
+            self.directory
+        };
+
+        let mut segments = vec![];
+        for segment in directory.split('/').chain(path.split('/')) {
+            match segment {
+                "" | "." => {}
+                ".." => {
+                    segments.pop();
+                }
+                segment => segments.push(segment),
+            }
+        }
+
+        segments
+            .into_iter()
+            .map(|segment| encode_segment(&decode_segment(segment)))
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+}
+
+/// Percent encode everything but unreserved characters, slashes included
+fn encode_segment(segment: &str) -> String {
+    let mut encoded = String::with_capacity(segment.len());
+    for byte in segment.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                encoded.push(byte as char)
+            }
+            byte => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+
+    encoded
+}
+
+/// Links written in documents may already be percent encoded, decode them so they are not
+/// encoded twice
+fn decode_segment(segment: &str) -> String {
+    let bytes = segment.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::Location;
+    use speculoos::prelude::*;
+
+    #[test]
+    fn should_resolve_links_from_document_directory() {
+        let location = Location::file("oknozor", "gill", "main", "docs/guide");
+
+        assert_that!(location.link_url("install.md"))
+            .is_equal_to("/oknozor/gill/blob/main/docs/guide/install.md".to_string());
+        assert_that!(location.link_url("../../README.md#usage"))
+            .is_equal_to("/oknozor/gill/blob/main/README.md#user-content-usage".to_string());
+        assert_that!(location.link_url("/crates/"))
+            .is_equal_to("/oknozor/gill/tree/main/crates".to_string());
+        assert_that!(location.image_url("./img/logo.png"))
+            .is_equal_to("/oknozor/gill/raw/main/docs/guide/img/logo.png".to_string());
+    }
+
+    #[test]
+    fn should_not_resolve_above_repository_root() {
+        let location = Location::repository("oknozor", "gill");
+
+        assert_that!(location.image_url("../../../etc/passwd"))
+            .is_equal_to("/oknozor/gill/etc/passwd".to_string());
+    }
+
+    #[test]
+    fn should_percent_encode_branch_and_path_segments() {
+        let location = Location::file("oknozor", "gill", "feature/docs", "my docs");
+
+        assert_that!(location.image_url("logo #1.png"))
+            .is_equal_to("/oknozor/gill/raw/feature%2Fdocs/my%20docs/logo%20%231.png".to_string());
+        assert_that!(location.link_url("caf%C3%A9.md#usage")).is_equal_to(
+            "/oknozor/gill/blob/feature%2Fdocs/my%20docs/caf%C3%A9.md#user-content-usage"
+                .to_string(),
+        );
+    }
+}
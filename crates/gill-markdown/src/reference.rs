@@ -0,0 +1,346 @@
+use pulldown_cmark::escape::escape_html;
+
+/// Words closing the issue reference following them, e.g. "fixes #12"
+const CLOSING_KEYWORDS: [&str; 9] = [
+    "close", "closes", "closed", "fix", "fixes", "fixed", "resolve", "resolves", "resolved",
+];
+
+const MIN_SHA_LEN: usize = 7;
+const MAX_SHA_LEN: usize = 40;
+
+/// Something a piece of markdown points to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reference {
+    /// `@user` or `@user@remote.instance`
+    Mention {
+        username: String,
+        domain: Option<String>,
+    },
+    /// `#123` or `owner/repository#123`, an issue or a pull request
+    Item {
+        namespace: Option<(String, String)>,
+        number: i32,
+    },
+    /// A bare commit sha, at least 7 hex characters
+    Commit(String),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum Token<'a> {
+    Text(&'a str),
+    Reference(&'a str, Reference),
+}
+
+impl Reference {
+    /// Html link to the reference, `owner` and `repository` are the ones the markdown belongs to
+    pub(crate) fn to_html(&self, text: &str, owner: &str, repository: &str) -> String {
+        let (href, label) = match self {
+            Reference::Mention {
+                username,
+                domain: None,
+            } => (format!("/{username}"), text.to_string()),
+            Reference::Mention {
+                username,
+                domain: Some(domain),
+            } => (
+                format!("/follow_user?follow={username}@{domain}"),
+                text.to_string(),
+            ),
+            Reference::Item { namespace, number } => {
+                let (owner, repository) = namespace
+                    .as_ref()
+                    .map(|(owner, repository)| (owner.as_str(), repository.as_str()))
+                    .unwrap_or((owner, repository));
+                (
+                    format!("/{owner}/{repository}/issues/{number}"),
+                    text.to_string(),
+                )
+            }
+            Reference::Commit(sha) => (
+                format!("/{owner}/{repository}/commit/{sha}"),
+                sha[..MIN_SHA_LEN].to_string(),
+            ),
+        };
+
+        let class = match self {
+            Reference::Mention { .. } => "mention",
+            Reference::Item { .. } => "issue-reference",
+            Reference::Commit(_) => "commit-reference",
+        };
+
+        let mut html = String::new();
+        html.push_str(&format!("<a class=\"{class}\" href=\""));
+        escape_html(&mut html, &href).expect("write to string");
+        html.push_str("\">");
+        escape_html(&mut html, &label).expect("write to string");
+        html.push_str("</a>");
+        html
+    }
+}
+
+/// Split a text node into plain text and references
+pub(crate) fn tokenize(text: &str) -> Vec<Token<'_>> {
+    let mut tokens = vec![];
+    let mut pending = 0;
+    let mut idx = 0;
+
+    while idx < text.len() {
+        let at_boundary = text[..idx].chars().next_back().map_or(true, is_boundary);
+        if at_boundary {
+            if let Some((len, reference)) = parse_reference(&text[idx..]) {
+                if pending < idx {
+                    tokens.push(Token::Text(&text[pending..idx]));
+                }
+
+                tokens.push(Token::Reference(&text[idx..idx + len], reference));
+                idx += len;
+                pending = idx;
+                continue;
+            }
+        }
+
+        idx += text[idx..].chars().next().map_or(1, char::len_utf8);
+    }
+
+    if pending < text.len() {
+        tokens.push(Token::Text(&text[pending..]));
+    }
+
+    tokens
+}
+
+/// Issue references preceded by a closing keyword in `text`
+pub(crate) fn closing_references(text: &str) -> Vec<Reference> {
+    let mut previous_text = "";
+    let mut references = vec![];
+
+    for token in tokenize(text) {
+        match token {
+            Token::Text(text) => previous_text = text,
+            Token::Reference(_, reference @ Reference::Item { .. }) => {
+                let keyword = previous_text
+                    .trim_end()
+                    .trim_end_matches(':')
+                    .rsplit(|c: char| !c.is_alphabetic())
+                    .next()
+                    .unwrap_or_default()
+                    .to_lowercase();
+
+                if CLOSING_KEYWORDS.contains(&keyword.as_str()) {
+                    references.push(reference);
+                }
+
+                previous_text = "";
+            }
+            Token::Reference(..) => previous_text = "",
+        }
+    }
+
+    references
+}
+
+/// References can only start after a character which can't be part of a word, an url or an email
+fn is_boundary(previous: char) -> bool {
+    !(previous.is_alphanumeric() || matches!(previous, '_' | '-' | '.' | '/' | '@' | '#' | '&'))
+}
+
+fn is_username_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '-'
+}
+
+fn is_namespace_char(c: char) -> bool {
+    is_username_char(c) || c == '.'
+}
+
+fn is_domain_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '-' | '.' | ':')
+}
+
+/// The reference starting `input` if any, along with its length
+fn parse_reference(input: &str) -> Option<(usize, Reference)> {
+    if let Some(rest) = input.strip_prefix('@') {
+        return parse_mention(rest).map(|(len, reference)| (len + 1, reference));
+    }
+
+    if let Some(rest) = input.strip_prefix('#') {
+        return parse_number(rest).map(|(len, number)| {
+            let reference = Reference::Item {
+                namespace: None,
+                number,
+            };
+            (len + 1, reference)
+        });
+    }
+
+    parse_namespaced_item(input).or_else(|| parse_sha(input))
+}
+
+fn parse_mention(input: &str) -> Option<(usize, Reference)> {
+    let username = take_while(input, is_username_char);
+    if username.is_empty() {
+        return None;
+    }
+
+    let domain = input[username.len()..]
+        .strip_prefix('@')
+        .map(|rest| take_while(rest, is_domain_char).trim_end_matches(['.', ':', '-']))
+        .filter(|domain| !domain.is_empty());
+
+    let len = username.len() + domain.map_or(0, |domain| domain.len() + 1);
+    let reference = Reference::Mention {
+        username: username.to_string(),
+        domain: domain.map(ToString::to_string),
+    };
+
+    Some((len, reference))
+}
+
+fn parse_number(input: &str) -> Option<(usize, i32)> {
+    let digits = take_while(input, |c| c.is_ascii_digit());
+    let ends_word = input[digits.len()..]
+        .chars()
+        .next()
+        .map_or(true, |c| !is_username_char(c));
+
+    if digits.is_empty() || !ends_word {
+        return None;
+    }
+
+    digits.parse().ok().map(|number| (digits.len(), number))
+}
+
+fn parse_namespaced_item(input: &str) -> Option<(usize, Reference)> {
+    let owner = take_while(input, is_namespace_char);
+    let rest = input[owner.len()..].strip_prefix('/')?;
+    let repository = take_while(rest, is_namespace_char);
+    let rest = rest[repository.len()..].strip_prefix('#')?;
+    let (number_len, number) = parse_number(rest)?;
+
+    if owner.is_empty() || repository.is_empty() {
+        return None;
+    }
+
+    let reference = Reference::Item {
+        namespace: Some((owner.to_string(), repository.to_string())),
+        number,
+    };
+
+    Some((owner.len() + repository.len() + number_len + 2, reference))
+}
+
+fn parse_sha(input: &str) -> Option<(usize, Reference)> {
+    let sha = take_while(input, |c| c.is_ascii_digit() || ('a'..='f').contains(&c));
+    let ends_word = input[sha.len()..]
+        .chars()
+        .next()
+        .map_or(true, |c| !(c.is_alphanumeric() || c == '_'));
+
+    // Plain numbers and words like "defaced" are not commits
+    let has_digit = sha.chars().any(|c| c.is_ascii_digit());
+    let has_letter = sha.chars().any(|c| c.is_ascii_alphabetic());
+
+    if (MIN_SHA_LEN..=MAX_SHA_LEN).contains(&sha.len()) && ends_word && has_digit && has_letter {
+        Some((sha.len(), Reference::Commit(sha.to_string())))
+    } else {
+        None
+    }
+}
+
+fn take_while(input: &str, predicate: impl Fn(char) -> bool) -> &str {
+    let end = input
+        .char_indices()
+        .find(|(_, c)| !predicate(*c))
+        .map_or(input.len(), |(idx, _)| idx);
+
+    &input[..end]
+}
+
+#[cfg(test)]
+mod test {
+    use crate::reference::{closing_references, tokenize, Reference, Token};
+    use speculoos::prelude::*;
+
+    fn references(text: &str) -> Vec<Reference> {
+        tokenize(text)
+            .into_iter()
+            .filter_map(|token| match token {
+                Token::Reference(_, reference) => Some(reference),
+                Token::Text(_) => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn should_parse_mentions() {
+        assert_that!(references("thanks @alice and @bob@gill.example.org:3000.")).is_equal_to(
+            vec![
+                Reference::Mention {
+                    username: "alice".to_string(),
+                    domain: None,
+                },
+                Reference::Mention {
+                    username: "bob".to_string(),
+                    domain: Some("gill.example.org:3000".to_string()),
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn should_parse_items() {
+        assert_that!(references("fixed in #12 and oknozor/gill#3, not in a#4")).is_equal_to(vec![
+            Reference::Item {
+                namespace: None,
+                number: 12,
+            },
+            Reference::Item {
+                namespace: Some(("oknozor".to_string(), "gill".to_string())),
+                number: 3,
+            },
+        ]);
+    }
+
+    #[test]
+    fn should_parse_commit_sha() {
+        assert_that!(references("reverted by 3f2a9c1d, see 1234567 and defaced"))
+            .is_equal_to(vec![Reference::Commit("3f2a9c1d".to_string())]);
+    }
+
+    #[test]
+    fn should_ignore_emails_and_urls() {
+        assert_that!(references(
+            "alice@example.org https://example.org/a/b#12 &#39;"
+        ))
+        .is_empty();
+    }
+
+    #[test]
+    fn should_find_closing_references() {
+        let text = "Fixes #12, closes: oknozor/gill#3 and relates to #4\n\nresolved#5";
+        assert_that!(closing_references(text)).is_equal_to(vec![
+            Reference::Item {
+                namespace: None,
+                number: 12,
+            },
+            Reference::Item {
+                namespace: Some(("oknozor".to_string(), "gill".to_string())),
+                number: 3,
+            },
+        ]);
+    }
+
+    #[test]
+    fn should_keep_surrounding_text() {
+        assert_that!(tokenize("(#1)")).is_equal_to(vec![
+            Token::Text("("),
+            Token::Reference(
+                "#1",
+                Reference::Item {
+                    namespace: None,
+                    number: 1,
+                },
+            ),
+            Token::Text(")"),
+        ]);
+    }
+}
@@ -0,0 +1,278 @@
+use std::io::Cursor;
+
+use pulldown_cmark::escape::escape_html;
+use pulldown_cmark::{CowStr, Event, LinkType, Tag};
+use quick_xml::events::attributes::Attribute;
+use quick_xml::events::{BytesEnd, BytesStart, Event as HtmlEvent};
+use quick_xml::reader::Reader;
+use quick_xml::Writer;
+
+use crate::Location;
+
+/// Prefix of the ids generated from user content, so they can't clobber the page ones
+pub(crate) const USER_CONTENT_PREFIX: &str = "user-content-";
+
+/// Elements removed along with their content
+const DROPPED_WITH_CONTENT: &[&str] = &[
+    "script", "style", "iframe", "frame", "frameset", "object", "embed", "applet", "noscript",
+    "noembed", "template", "textarea", "select", "title", "xmp", "svg", "math",
+];
+
+/// Attributes allowed on each allowed element, other elements are removed but their content is
+/// kept
+fn allowed_attributes(tag: &str) -> Option<&'static [&'static str]> {
+    let attributes: &[&str] = match tag {
+        "a" => &["href", "title"],
+        "img" => &["src", "alt", "title", "width", "height", "align"],
+        "td" | "th" => &["align", "colspan", "rowspan"],
+        "ol" => &["start"],
+        "input" => &["type", "checked"],
+        "details" => &["open"],
+        "abbr" => &["title"],
+        "p" | "div" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => &["align"],
+        "b" | "i" | "u" | "s" | "em" | "strong" | "del" | "ins" | "strike" | "mark" | "sub"
+        | "sup" | "small" | "kbd" | "samp" | "var" | "cite" | "q" | "code" | "pre" | "br"
+        | "hr" | "blockquote" | "ul" | "li" | "dl" | "dt" | "dd" | "table" | "thead" | "tbody"
+        | "tfoot" | "tr" | "caption" | "summary" | "span" | "picture" => &[],
+        _ => return None,
+    };
+
+    Some(attributes)
+}
+
+/// Sanitize the raw html found in markdown and the urls of its links and images.
+/// Only the html written by the author goes through the allowlist, the html generated
+/// afterward for references, headings, task lists and code blocks is trusted.
+pub(crate) struct Sanitizer<'a> {
+    location: Location<'a>,
+    /// Element whose content is being removed
+    dropped: Option<String>,
+}
+
+impl<'a> Sanitizer<'a> {
+    pub(crate) fn new(location: Location<'a>) -> Self {
+        Self {
+            location,
+            dropped: None,
+        }
+    }
+
+    /// `None` if the event is part of a removed element content
+    pub(crate) fn sanitize<'e>(&mut self, event: Event<'e>) -> Option<Event<'e>> {
+        match event {
+            Event::Html(html) => Some(Event::Html(CowStr::from(self.sanitize_html(&html)))),
+            Event::Text(_) | Event::Code(_) | Event::SoftBreak | Event::HardBreak
+                if self.dropped.is_some() =>
+            {
+                None
+            }
+            // Rendered with a `mailto:` prefix
+            event @ Event::Start(Tag::Link(LinkType::Email, ..)) => Some(event),
+            Event::Start(Tag::Link(kind, dest, title)) => {
+                let dest = self.link_url(&dest).unwrap_or_else(|| "#".to_string());
+                Some(Event::Start(Tag::Link(kind, CowStr::from(dest), title)))
+            }
+            Event::Start(Tag::Image(kind, dest, title)) => {
+                let dest = self.image_url(&dest).unwrap_or_default();
+                Some(Event::Start(Tag::Image(kind, CowStr::from(dest), title)))
+            }
+            event => Some(event),
+        }
+    }
+
+    /// Markdown html blocks and inline html come in fragments, an element dropped in one
+    /// fragment can end in a later one
+    fn sanitize_html(&mut self, html: &str) -> String {
+        self.try_sanitize_html(html).unwrap_or_else(|_| {
+            // Not something we can make sense of, show it as text
+            let mut escaped = String::new();
+            escape_html(&mut escaped, html).expect("write to string");
+            escaped
+        })
+    }
+
+    fn try_sanitize_html(&mut self, html: &str) -> Result<String, quick_xml::Error> {
+        let mut reader = Reader::from_str(html);
+        reader.check_end_names(false);
+        let mut out = vec![];
+        let mut writer = Writer::new(Cursor::new(&mut out));
+        loop {
+            let event = reader.read_event()?;
+            if let Some(dropped) = self.dropped.clone() {
+                match event {
+                    HtmlEvent::End(tag) if tag_name(tag.name().as_ref()) == dropped => {
+                        self.dropped = None
+                    }
+                    HtmlEvent::Eof => break,
+                    _ => {}
+                }
+
+                continue;
+            }
+
+            match event {
+                HtmlEvent::Start(tag) => {
+                    let name = tag_name(tag.name().as_ref());
+                    if DROPPED_WITH_CONTENT.contains(&name.as_str()) {
+                        self.dropped = Some(name);
+                    } else if let Some(tag) = self.sanitize_tag(&name, &tag) {
+                        writer.write_event(HtmlEvent::Start(tag))?;
+                    }
+                }
+                HtmlEvent::Empty(tag) => {
+                    let name = tag_name(tag.name().as_ref());
+                    if let Some(tag) = self.sanitize_tag(&name, &tag) {
+                        writer.write_event(HtmlEvent::Empty(tag))?;
+                    }
+                }
+                HtmlEvent::End(tag) => {
+                    let name = tag_name(tag.name().as_ref());
+                    if allowed_attributes(&name).is_some() {
+                        writer.write_event(HtmlEvent::End(BytesEnd::new(name)))?;
+                    }
+                }
+                HtmlEvent::Text(text) => writer.write_event(HtmlEvent::Text(text))?,
+                HtmlEvent::Eof => break,
+                // Comments, CDATA, doctypes and processing instructions
+                _ => {}
+            }
+        }
+
+        Ok(String::from_utf8_lossy(&out).to_string())
+    }
+
+    /// A copy of `tag` with its allowed attributes only, `None` if the element is not allowed
+    fn sanitize_tag(&self, name: &str, tag: &BytesStart) -> Option<BytesStart<'static>> {
+        let allowed = allowed_attributes(name)?;
+        let mut attributes = vec![];
+        // Rewritten urls go last
+        let mut urls = vec![];
+        for attribute in tag.html_attributes() {
+            // Skip the remaining attributes of a malformed tag
+            let Ok(attribute) = attribute else {
+                break;
+            };
+
+            let key = tag_name(attribute.key.as_ref());
+            if !allowed.contains(&key.as_str()) {
+                continue;
+            }
+
+            // Html entities unknown to xml are kept as is, the value is escaped again on write
+            let value = attribute
+                .unescape_value()
+                .unwrap_or_else(|_| String::from_utf8_lossy(&attribute.value).to_string().into());
+
+            match (name, key.as_str()) {
+                ("a", "href") => match self.link_url(&value) {
+                    Some(url) if url != value => urls.push((key, url)),
+                    Some(url) => attributes.push((key, url)),
+                    None => {}
+                },
+                ("img", "src") => match self.image_url(&value) {
+                    Some(url) if url != value => urls.push((key, url)),
+                    Some(url) => attributes.push((key, url)),
+                    None => {}
+                },
+                _ => attributes.push((key, value.to_string())),
+            }
+        }
+
+        if name == "input" {
+            let is_checkbox = attributes
+                .iter()
+                .any(|(key, value)| key == "type" && value.eq_ignore_ascii_case("checkbox"));
+            if !is_checkbox {
+                return None;
+            }
+
+            attributes.push(("disabled".to_string(), String::new()));
+        }
+
+        let mut sanitized = BytesStart::new(name.to_string());
+        for (key, value) in attributes.iter().chain(urls.iter()) {
+            sanitized.push_attribute(Attribute::from((key.as_str(), value.as_str())));
+        }
+
+        Some(sanitized)
+    }
+
+    /// Links to anchors point to the prefixed heading ids, relative links to the repository
+    fn link_url(&self, url: &str) -> Option<String> {
+        match url.strip_prefix('#') {
+            Some(fragment)
+                if !fragment.is_empty() && !fragment.starts_with(USER_CONTENT_PREFIX) =>
+            {
+                Some(format!("#{USER_CONTENT_PREFIX}{fragment}"))
+            }
+            Some(_) => Some(url.to_string()),
+            None => match url_kind(url) {
+                UrlKind::Absolute => Some(url.to_string()),
+                UrlKind::Relative => Some(self.location.link_url(url)),
+                UrlKind::Unsafe => None,
+            },
+        }
+    }
+
+    fn image_url(&self, url: &str) -> Option<String> {
+        match url_kind(url) {
+            UrlKind::Absolute => Some(url.to_string()),
+            UrlKind::Relative => Some(self.location.image_url(url)),
+            UrlKind::Unsafe => None,
+        }
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+enum UrlKind {
+    Absolute,
+    Relative,
+    /// `javascript:`, `data:` and any other scheme we don't want to link to
+    Unsafe,
+}
+
+fn url_kind(url: &str) -> UrlKind {
+    // Browsers ignore whitespaces and control characters in urls, "java\tscript:" is a
+    // javascript url
+    let url: String = url
+        .chars()
+        .filter(|c| !c.is_ascii_whitespace() && !c.is_control())
+        .collect::<String>()
+        .to_ascii_lowercase();
+
+    if url.starts_with("//") {
+        return UrlKind::Absolute;
+    }
+
+    let scheme_end = url.find([':', '/', '?', '#']);
+    match scheme_end {
+        Some(end) if url[end..].starts_with(':') => match &url[..end] {
+            "http" | "https" | "mailto" => UrlKind::Absolute,
+            _ => UrlKind::Unsafe,
+        },
+        _ => UrlKind::Relative,
+    }
+}
+
+/// Html names are case insensitive
+fn tag_name(name: &[u8]) -> String {
+    String::from_utf8_lossy(name).to_ascii_lowercase()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::sanitize::{url_kind, UrlKind};
+    use speculoos::prelude::*;
+
+    #[test]
+    fn should_classify_urls() {
+        assert_that!(url_kind("https://gill.example")).is_equal_to(UrlKind::Absolute);
+        assert_that!(url_kind("mailto:alice@gill.example")).is_equal_to(UrlKind::Absolute);
+        assert_that!(url_kind("//gill.example/image.png")).is_equal_to(UrlKind::Absolute);
+        assert_that!(url_kind("docs/img.png")).is_equal_to(UrlKind::Relative);
+        assert_that!(url_kind("/docs/a:b.png")).is_equal_to(UrlKind::Relative);
+        assert_that!(url_kind("JavaScript:alert(1)")).is_equal_to(UrlKind::Unsafe);
+        assert_that!(url_kind("java\tscript:alert(1)")).is_equal_to(UrlKind::Unsafe);
+        assert_that!(url_kind("data:text/html;base64,PHNjcmlwdD4=")).is_equal_to(UrlKind::Unsafe);
+    }
+}
@@ -22,11 +22,22 @@ const DB_PASSWORD: &str = "GILL_DB_PASSWORD";
 const PORT: &str = "GILL_PORT";
 const SSH_PORT: &str = "GILL_SSH_PORT";
 const DEBUG: &str = "GILL_DEBUG";
+const ADMINS: &str = "GILL_ADMINS";
+const SECRET_KEY: &str = "GILL_SECRET_KEY";
+const SMTP_HOST: &str = "GILL_SMTP_HOST";
+const SMTP_PORT: &str = "GILL_SMTP_PORT";
+const SMTP_USER: &str = "GILL_SMTP_USER";
+const SMTP_PASSWORD: &str = "GILL_SMTP_PASSWORD";
+const SMTP_FROM: &str = "GILL_SMTP_FROM";
 
 trait DefaultSettings {
     fn default_ssh_port() -> u16 {
         22
     }
+
+    fn default_smtp_port() -> u16 {
+        587
+    }
 }
 
 impl DefaultSettings for Settings {}
@@ -40,6 +51,28 @@ pub struct Settings {
     pub database: DbSettings,
     #[serde(default = "Settings::default_ssh_port")]
     pub ssh_port: u16,
+    /// Usernames of the local users allowed to moderate the instance
+    #[serde(default)]
+    pub admins: Vec<String>,
+    /// Used to encrypt the credentials gill stores, such as push mirror tokens
+    #[serde(default)]
+    pub secret_key: Option<String>,
+    /// Mail server used to send notification digests, emails are only logged when missing
+    #[serde(default)]
+    pub smtp: Option<SmtpSettings>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SmtpSettings {
+    pub host: String,
+    #[serde(default = "Settings::default_smtp_port")]
+    pub port: u16,
+    #[serde(default)]
+    pub user: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+    /// Sender address of the notification emails
+    pub from: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -95,6 +128,10 @@ impl Settings {
         )
     }
 
+    pub fn is_admin(&self, username: &str) -> bool {
+        self.admins.iter().any(|admin| admin == username)
+    }
+
     pub fn domain_url(&self) -> Result<Url, ParseError> {
         let url = format!("{}://{}", self.protocol(), self.domain);
         Url::parse(&url)
@@ -119,14 +156,14 @@ impl Settings {
                 .build()?
                 .try_deserialize()?;
 
-            Ok(config.override_with_env())
+            config.override_with_env()
         } else {
             tracing::warn!("{config_path:?}, not found");
-            Ok(Settings::from_env())
+            Settings::from_env()
         }
     }
 
-    fn override_with_env(mut self) -> Self {
+    fn override_with_env(mut self) -> Result<Self, config::ConfigError> {
         if let Ok(client_id) = env::var(OAUTH_CLIENT_ID) {
             self.oauth_provider.client_id = client_id;
         }
@@ -183,11 +220,23 @@ impl Settings {
             self.debug = debug.parse().expect("Expected a bool");
         }
 
-        self
+        if let Ok(admins) = env::var(ADMINS) {
+            self.admins = parse_admins(&admins);
+        }
+
+        if let Ok(secret_key) = env::var(SECRET_KEY) {
+            self.secret_key = Some(secret_key);
+        }
+
+        if let Some(smtp) = smtp_from_env()? {
+            self.smtp = Some(smtp);
+        }
+
+        Ok(self)
     }
 
-    fn from_env() -> Self {
-        Settings {
+    fn from_env() -> Result<Self, config::ConfigError> {
+        Ok(Settings {
             domain: env::var(DOMAIN).expect("Missing env var 'GILL_DOMAIN'"),
             debug: env::var(DEBUG)
                 .expect(DEBUG)
@@ -219,6 +268,45 @@ impl Settings {
                 .expect(SSH_PORT)
                 .parse()
                 .expect("GILL_SSH_PORT must be an integer"),
-        }
+            admins: env::var(ADMINS)
+                .map(|admins| parse_admins(&admins))
+                .unwrap_or_default(),
+            secret_key: env::var(SECRET_KEY).ok(),
+            smtp: smtp_from_env()?,
+        })
     }
 }
+
+fn smtp_from_env() -> Result<Option<SmtpSettings>, config::ConfigError> {
+    let Ok(host) = env::var(SMTP_HOST) else {
+        return Ok(None);
+    };
+
+    let port = match env::var(SMTP_PORT) {
+        Ok(port) => port
+            .parse()
+            .map_err(|_| config::ConfigError::Message(format!("{SMTP_PORT} must be an integer")))?,
+        Err(_) => Settings::default_smtp_port(),
+    };
+
+    let from = env::var(SMTP_FROM).map_err(|_| {
+        config::ConfigError::Message(format!("{SMTP_FROM} is required when {SMTP_HOST} is set"))
+    })?;
+
+    Ok(Some(SmtpSettings {
+        host,
+        port,
+        user: env::var(SMTP_USER).ok(),
+        password: env::var(SMTP_PASSWORD).ok(),
+        from,
+    }))
+}
+
+fn parse_admins(admins: &str) -> Vec<String> {
+    admins
+        .split(',')
+        .map(str::trim)
+        .filter(|admin| !admin.is_empty())
+        .map(ToString::to_string)
+        .collect()
+}
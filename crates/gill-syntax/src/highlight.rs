@@ -1,5 +1,6 @@
 use anyhow::anyhow;
 
+use syntect::easy::HighlightLines;
 use syntect::highlighting::{Color, Theme};
 
 use crate::{highlighter_for_extension, SYNTAX_SET, THEME};
@@ -34,6 +35,31 @@ pub fn highlight_blob(content: &str, extension: &str) -> anyhow::Result<String>
     Ok(output)
 }
 
+/// Highlight a fenced markdown code block, `token` is the language name or extension
+/// written after the fence. `None` if the language is unknown.
+pub fn highlight_code_block(code: &str, token: &str) -> Option<String> {
+    let syntax = SYNTAX_SET.find_syntax_by_token(token)?;
+    let mut highlighter = HighlightLines::new(syntax, &THEME);
+    let bg = THEME.settings.background.unwrap_or(Color::WHITE);
+    let mut output = format!(
+        "<pre class=\"highlight\" style=\"background-color:#{:02x}{:02x}{:02x};\"><code>",
+        bg.r, bg.g, bg.b
+    );
+
+    for line in LinesWithEndings::from(code) {
+        let regions = highlighter.highlight_line(line, &SYNTAX_SET).ok()?;
+        append_highlighted_html_for_styled_line(
+            &regions[..],
+            IncludeBackground::IfDifferent(bg),
+            &mut output,
+        )
+        .ok()?;
+    }
+
+    output.push_str("</code></pre>\n");
+    Some(output)
+}
+
 pub fn start_highlighted_html(t: &Theme) -> (String, Color) {
     let c = t.settings.background.unwrap_or(Color::WHITE);
     (